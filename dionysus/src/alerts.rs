@@ -0,0 +1,96 @@
+use crate::finance::Token;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertDirection {
+    /// Triggers when the price reaches the alert price from below.
+    Above,
+    /// Triggers when the price reaches the alert price from above.
+    Below,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceAlert {
+    pub id: usize,
+    pub token: Token,
+    pub price: f64,
+    pub direction: AlertDirection,
+}
+
+/// Price alerts waiting to be triggered.
+#[derive(Debug, Default)]
+pub struct Alerts {
+    alerts: Vec<PriceAlert>,
+    next_id: usize,
+}
+
+impl Alerts {
+    /// Adds an alert at `price`, the direction is given by the `current` price.
+    pub fn add(&mut self, token: &Token, price: f64, current: f64) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.alerts.push(PriceAlert {
+            id,
+            token: token.clone(),
+            price,
+            direction: if price >= current {
+                AlertDirection::Above
+            } else {
+                AlertDirection::Below
+            },
+        });
+        id
+    }
+
+    pub fn remove(&mut self, id: usize) -> bool {
+        let n = self.alerts.len();
+        self.alerts.retain(|a| a.id != id);
+        self.alerts.len() != n
+    }
+
+    pub fn all(&self) -> &[PriceAlert] {
+        &self.alerts
+    }
+
+    pub fn active(&self, token: &Token) -> Vec<&PriceAlert> {
+        self.alerts.iter().filter(|a| a.token == *token).collect()
+    }
+
+    /// Removes and returns the alerts of `token` reached by a price range.
+    pub fn check(&mut self, token: &Token, low: f64, high: f64) -> Vec<PriceAlert> {
+        let (triggered, pending): (Vec<PriceAlert>, Vec<PriceAlert>) =
+            self.alerts.drain(..).partition(|a| {
+                a.token == *token
+                    && match a.direction {
+                        AlertDirection::Above => high >= a.price,
+                        AlertDirection::Below => low <= a.price,
+                    }
+            });
+        self.alerts = pending;
+        triggered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AlertDirection, Alerts};
+    use crate::finance::Token;
+
+    #[test]
+    fn test_alerts() {
+        let token = Token::pair("BTC", "USDT");
+        let mut alerts = Alerts::default();
+        let above = alerts.add(&token, 110.0, 100.0);
+        alerts.add(&token, 90.0, 100.0);
+        assert_eq!(alerts.all()[1].direction, AlertDirection::Below);
+        assert!(alerts.check(&token, 95.0, 105.0).is_empty());
+        let triggered = alerts.check(&token, 100.0, 111.0);
+        assert_eq!(triggered.len(), 1);
+        assert_eq!(triggered[0].id, above);
+        assert_eq!(alerts.active(&token).len(), 1);
+        assert!(alerts
+            .check(&Token::pair("ETH", "USDT"), 1.0, 1.0)
+            .is_empty());
+        assert!(alerts.remove(1));
+        assert!(alerts.all().is_empty());
+    }
+}
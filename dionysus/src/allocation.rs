@@ -0,0 +1,427 @@
+use crate::analytics::returns;
+use crate::finance::{Order, OrderType, Sample, Side, TimeInForce, Token, QUOTE_CURRENCIES};
+use crate::time::{Date, TimeUnit};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Target weights of a portfolio. Weights are non-negative and sum to 1
+/// (or are all zero when nothing could be estimated).
+#[derive(Default, Clone, Debug)]
+pub struct Allocation {
+    pub tokens: Vec<Token>,
+    /// Mean return per sample of each token.
+    pub expected_returns: Vec<f64>,
+    pub weights: Vec<f64>,
+}
+
+impl Allocation {
+    pub fn weight(&self, token: &Token) -> f64 {
+        match self.tokens.iter().position(|t| t == token) {
+            Some(i) => self.weights[i],
+            None => 0.0,
+        }
+    }
+}
+
+/// Returns of every series over the last `window` timestamps shared by all of them.
+pub fn aligned_returns_matrix(series: &[(Token, &[Sample])], window: usize) -> Vec<Vec<f64>> {
+    if series.is_empty() {
+        return Vec::new();
+    }
+    let mut shared: HashSet<u64> = series[0].1.iter().map(|s| s.timestamp).collect();
+    for (_, samples) in &series[1..] {
+        let timestamps: HashSet<u64> = samples.iter().map(|s| s.timestamp).collect();
+        shared.retain(|t| timestamps.contains(t));
+    }
+    series
+        .iter()
+        .map(|(_, samples)| {
+            let aligned: Vec<Sample> = samples
+                .iter()
+                .filter(|s| shared.contains(&s.timestamp))
+                .cloned()
+                .collect();
+            let start = aligned.len().saturating_sub(window + 1);
+            returns(&aligned[start..])
+        })
+        .collect()
+}
+
+pub fn expected_returns(returns: &[Vec<f64>]) -> Vec<f64> {
+    returns
+        .iter()
+        .map(|r| {
+            if r.is_empty() {
+                0.0
+            } else {
+                r.iter().sum::<f64>() / r.len() as f64
+            }
+        })
+        .collect()
+}
+
+/// Sample covariance matrix of series of same length.
+pub fn covariance_matrix(returns: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let n = returns.len();
+    let m = returns.iter().map(|r| r.len()).min().unwrap_or(0);
+    let mut cov = vec![vec![0.0; n]; n];
+    if m < 2 {
+        return cov;
+    }
+    let means: Vec<f64> = returns
+        .iter()
+        .map(|r| r[..m].iter().sum::<f64>() / m as f64)
+        .collect();
+    for i in 0..n {
+        for j in i..n {
+            let mut s = 0.0;
+            for k in 0..m {
+                s += (returns[i][k] - means[i]) * (returns[j][k] - means[j]);
+            }
+            cov[i][j] = s / (m - 1) as f64;
+            cov[j][i] = cov[i][j];
+        }
+    }
+    cov
+}
+
+/// Solves `a x = b` by gaussian elimination with partial pivoting.
+fn solve(a: &[Vec<f64>], b: &[f64]) -> Option<Vec<f64>> {
+    let n = b.len();
+    let mut m: Vec<Vec<f64>> = a
+        .iter()
+        .zip(b.iter())
+        .map(|(row, v)| {
+            let mut r = row.clone();
+            r.push(*v);
+            r
+        })
+        .collect();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|x, y| m[*x][col].abs().total_cmp(&m[*y][col].abs()))?;
+        if m[pivot][col].abs() < 1e-15 {
+            return None;
+        }
+        m.swap(col, pivot);
+        for row in 0..n {
+            if row != col {
+                let f = m[row][col] / m[col][col];
+                for k in col..=n {
+                    m[row][k] -= f * m[col][k];
+                }
+            }
+        }
+    }
+    Some((0..n).map(|i| m[i][n] / m[i][i]).collect())
+}
+
+/// Long-only mean-variance weights. The unconstrained optimum
+/// `inv(cov) * mu / risk_aversion` is clipped at zero and normalized to sum 1.
+/// A small ridge keeps the covariance invertible for collinear series.
+pub fn mean_variance_weights(mu: &[f64], cov: &[Vec<f64>], risk_aversion: f64) -> Vec<f64> {
+    let n = mu.len();
+    let trace = (0..n).map(|i| cov[i][i]).sum::<f64>();
+    let ridge = 1e-6 * trace.max(1e-12) / n.max(1) as f64;
+    let regularized: Vec<Vec<f64>> = (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| cov[i][j] + if i == j { ridge } else { 0.0 })
+                .collect()
+        })
+        .collect();
+    let raw = match solve(&regularized, mu) {
+        Some(x) => x,
+        None => return vec![0.0; n],
+    };
+    let clipped: Vec<f64> = raw
+        .iter()
+        .map(|w| (w / risk_aversion.max(f64::EPSILON)).max(0.0))
+        .collect();
+    let total = clipped.iter().sum::<f64>();
+    if total <= 0.0 || !total.is_finite() {
+        return vec![0.0; n];
+    }
+    clipped.iter().map(|w| w / total).collect()
+}
+
+/// Mean-variance allocation over the last `window` shared returns of each series.
+pub fn allocate(series: &[(Token, &[Sample])], window: usize, risk_aversion: f64) -> Allocation {
+    let returns = aligned_returns_matrix(series, window);
+    let mu = expected_returns(&returns);
+    let cov = covariance_matrix(&returns);
+    Allocation {
+        tokens: series.iter().map(|(token, _)| token.clone()).collect(),
+        weights: mean_variance_weights(&mu, &cov, risk_aversion),
+        expected_returns: mu,
+    }
+}
+
+/// Market orders moving the current `holdings` (quantities) towards the target
+/// weights. Portfolio value includes `cash`; trades smaller than `min_notional`
+/// are skipped.
+pub fn rebalance_orders(
+    allocation: &Allocation,
+    holdings: &HashMap<Token, f64>,
+    prices: &HashMap<Token, f64>,
+    cash: f64,
+    min_notional: f64,
+) -> Vec<Order> {
+    let mut total = cash;
+    for token in &allocation.tokens {
+        if let (Some(q), Some(p)) = (holdings.get(token), prices.get(token)) {
+            total += q * p;
+        }
+    }
+    let mut orders: Vec<Order> = Vec::new();
+    for (i, token) in allocation.tokens.iter().enumerate() {
+        let price = match prices.get(token) {
+            Some(p) if *p > 0.0 => *p,
+            _ => continue,
+        };
+        let held = *holdings.get(token).unwrap_or(&0.0);
+        let delta = allocation.weights[i] * total - held * price;
+        if delta.abs() < min_notional {
+            continue;
+        }
+        orders.push(Order {
+            index: orders.len(),
+            position_index: None,
+            id: None,
+            token: token.clone(),
+            date: Date::now(),
+            side: if delta > 0.0 { Side::Buy } else { Side::Sell },
+            quantity: delta.abs() / price,
+            price,
+            stop_price: None,
+            order_type: OrderType::Market,
+            tif: TimeInForce::default(),
+        });
+    }
+    // sells first so that buys can use the released cash
+    orders.sort_by_key(|o| if o.side == Side::Sell { 0 } else { 1 });
+    orders
+}
+
+/// Minimum number of seconds between two drift triggered rebalances, so that
+/// orders are not repeated while the wallet catches up.
+const REBALANCE_COOLDOWN: i64 = 60;
+
+/// Holds fixed target percentages of a portfolio. Targets are symbols (ex: BTC)
+/// and the part not assigned to them is kept in `currency`. Orders are emitted
+/// when any weight drifts more than `threshold` from its target or, when an
+/// `interval` is given, once per interval.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Rebalancer {
+    pub targets: Vec<(Token, f64)>,
+    pub currency: String,
+    pub threshold: f64,
+    pub interval: Option<TimeUnit>,
+    pub last_rebalance: i64,
+}
+
+impl Rebalancer {
+    /// Parses `<symbol> <pct> ... [drift <pct>] [every <resolution>]`,
+    /// ex: `BTC 50 ETH 30 USDT 20 drift 5 every 1d`. When the percentages add up
+    /// to 100 and the last symbol is a quote currency, it is the cash currency
+    /// (USDT otherwise).
+    pub fn from_text(words: &[&str]) -> Option<Rebalancer> {
+        let mut rebalancer = Rebalancer {
+            currency: String::from("USDT"),
+            threshold: 0.05,
+            ..Default::default()
+        };
+        let mut i = 0;
+        while i + 1 < words.len() {
+            match words[i].to_uppercase().as_str() {
+                "DRIFT" => rebalancer.threshold = words[i + 1].parse::<f64>().ok()? / 100.0,
+                "EVERY" => match TimeUnit::from_name(words[i + 1]) {
+                    TimeUnit::Unit(_) => return None,
+                    unit => rebalancer.interval = Some(unit),
+                },
+                symbol => {
+                    let pct = words[i + 1].parse::<f64>().ok()? / 100.0;
+                    rebalancer
+                        .targets
+                        .push((Token::Symbol(symbol.to_string()), pct));
+                }
+            }
+            i += 2;
+        }
+        let total = rebalancer.targets.iter().map(|(_, p)| p).sum::<f64>();
+        if let Some((last, _)) = rebalancer.targets.last() {
+            let symbol = last.get_symbol();
+            if (total - 1.0).abs() < 1e-9 && QUOTE_CURRENCIES.contains(&symbol.as_str()) {
+                rebalancer.currency = symbol;
+                rebalancer.targets.pop();
+            }
+        }
+        if rebalancer.targets.is_empty() || total > 1.0 + 1e-9 {
+            return None;
+        }
+        Some(rebalancer)
+    }
+
+    fn pair(&self, symbol: &Token) -> Token {
+        Token::Pair((symbol.get_symbol(), self.currency.clone()))
+    }
+
+    /// Current weights of the targets, given `holdings` by symbol and `prices`
+    /// by pair.
+    pub fn weights(
+        &self,
+        holdings: &HashMap<Token, f64>,
+        prices: &HashMap<Token, f64>,
+    ) -> Vec<f64> {
+        let values: Vec<f64> = self
+            .targets
+            .iter()
+            .map(|(symbol, _)| {
+                holdings.get(symbol).unwrap_or(&0.0)
+                    * prices.get(&self.pair(symbol)).unwrap_or(&0.0)
+            })
+            .collect();
+        let total = values.iter().sum::<f64>()
+            + holdings
+                .get(&Token::Symbol(self.currency.clone()))
+                .unwrap_or(&0.0);
+        if total <= 0.0 {
+            return vec![0.0; values.len()];
+        }
+        values.iter().map(|v| v / total).collect()
+    }
+
+    /// Largest absolute difference between current and target weights.
+    pub fn drift(&self, holdings: &HashMap<Token, f64>, prices: &HashMap<Token, f64>) -> f64 {
+        self.weights(holdings, prices)
+            .iter()
+            .zip(self.targets.iter())
+            .map(|(w, (_, target))| (w - target).abs())
+            .fold(0.0, f64::max)
+    }
+
+    pub fn is_due(
+        &self,
+        now: i64,
+        holdings: &HashMap<Token, f64>,
+        prices: &HashMap<Token, f64>,
+    ) -> bool {
+        if let Some(interval) = &self.interval {
+            if now - self.last_rebalance >= interval.num_seconds() {
+                return true;
+            }
+        }
+        now - self.last_rebalance >= REBALANCE_COOLDOWN
+            && self.drift(holdings, prices) > self.threshold
+    }
+
+    /// Orders bringing the portfolio back to its targets, or nothing if the
+    /// rebalance is not due. `now` is a timestamp in seconds.
+    pub fn orders(
+        &mut self,
+        now: i64,
+        holdings: &HashMap<Token, f64>,
+        prices: &HashMap<Token, f64>,
+        min_notional: f64,
+    ) -> Vec<Order> {
+        if !self.is_due(now, holdings, prices) {
+            return Vec::new();
+        }
+        self.last_rebalance = now;
+        let allocation = Allocation {
+            tokens: self.targets.iter().map(|(t, _)| self.pair(t)).collect(),
+            expected_returns: vec![0.0; self.targets.len()],
+            weights: self.targets.iter().map(|(_, p)| *p).collect(),
+        };
+        let pair_holdings: HashMap<Token, f64> = self
+            .targets
+            .iter()
+            .filter_map(|(t, _)| holdings.get(t).map(|q| (self.pair(t), *q)))
+            .collect();
+        let cash = *holdings
+            .get(&Token::Symbol(self.currency.clone()))
+            .unwrap_or(&0.0);
+        rebalance_orders(&allocation, &pair_holdings, prices, cash, min_notional)
+    }
+
+    pub fn name(&self) -> String {
+        let mut txt: Vec<String> = self
+            .targets
+            .iter()
+            .map(|(t, p)| format!("{} {:.0}%", t.get_symbol(), p * 100.0))
+            .collect();
+        let rest = 1.0 - self.targets.iter().map(|(_, p)| p).sum::<f64>();
+        txt.push(format!("{} {:.0}%", self.currency, rest * 100.0));
+        txt.join(" / ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mean_variance_weights, rebalance_orders, Allocation, Rebalancer};
+    use crate::finance::{Side, Token};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_mean_variance_weights() {
+        let cov = vec![vec![0.04, 0.0], vec![0.0, 0.01]];
+        let w = mean_variance_weights(&[0.01, 0.01], &cov, 2.0);
+        assert!((w[0] - 0.2).abs() < 1e-4);
+        assert!((w[1] - 0.8).abs() < 1e-4);
+        let w = mean_variance_weights(&[-0.01, 0.01], &cov, 2.0);
+        assert!((w[0]).abs() < 1e-12);
+        assert!((w[1] - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_rebalance_orders() {
+        let a = Token::Symbol(String::from("A"));
+        let b = Token::Symbol(String::from("B"));
+        let allocation = Allocation {
+            tokens: vec![a.clone(), b.clone()],
+            expected_returns: vec![0.0, 0.0],
+            weights: vec![0.5, 0.5],
+        };
+        let holdings = HashMap::from([(a.clone(), 10.0)]);
+        let prices = HashMap::from([(a.clone(), 10.0), (b.clone(), 5.0)]);
+        let orders = rebalance_orders(&allocation, &holdings, &prices, 0.0, 1.0);
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[0].side, Side::Sell);
+        assert!((orders[0].quantity - 5.0).abs() < 1e-12);
+        assert_eq!(orders[1].token, b);
+        assert!((orders[1].quantity - 10.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_rebalancer() {
+        let mut rebalancer =
+            Rebalancer::from_text(&["BTC", "50", "ETH", "30", "USDT", "20", "drift", "5"]).unwrap();
+        assert_eq!(rebalancer.currency, "USDT");
+        assert_eq!(rebalancer.targets.len(), 2);
+        let btc = Token::Symbol(String::from("BTC"));
+        let eth = Token::Symbol(String::from("ETH"));
+        let usdt = Token::Symbol(String::from("USDT"));
+        let prices = HashMap::from([
+            (
+                Token::Pair((String::from("BTC"), String::from("USDT"))),
+                100.0,
+            ),
+            (
+                Token::Pair((String::from("ETH"), String::from("USDT"))),
+                10.0,
+            ),
+        ]);
+        // 50 / 30 / 20
+        let holdings = HashMap::from([
+            (btc.clone(), 5.0),
+            (eth.clone(), 30.0),
+            (usdt.clone(), 200.0),
+        ]);
+        assert!(rebalancer.drift(&holdings, &prices) < 1e-12);
+        assert!(rebalancer.orders(0, &holdings, &prices, 1.0).is_empty());
+        // 60 / 24 / 16
+        let holdings = HashMap::from([(btc, 7.5), (eth, 30.0), (usdt, 200.0)]);
+        let orders = rebalancer.orders(100, &holdings, &prices, 1.0);
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[0].side, Side::Sell);
+    }
+}
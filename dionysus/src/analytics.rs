@@ -0,0 +1,685 @@
+use crate::finance::{Sample, Token};
+use crate::time::{Date, TimeUnit};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Simple close-to-close returns. The output has one element less than the input.
+pub fn returns(samples: &[Sample]) -> Vec<f64> {
+    samples
+        .windows(2)
+        .map(|w| {
+            if w[0].close != 0.0 {
+                w[1].close / w[0].close - 1.0
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// Pearson correlation between two series of same length.
+pub fn correlation(a: &[f64], b: &[f64]) -> Option<f64> {
+    let n = a.len().min(b.len());
+    if n < 2 {
+        return None;
+    }
+    let mean_a = a[..n].iter().sum::<f64>() / n as f64;
+    let mean_b = b[..n].iter().sum::<f64>() / n as f64;
+    let mut cov = 0.0;
+    let mut var_a = 0.0;
+    let mut var_b = 0.0;
+    for i in 0..n {
+        let da = a[i] - mean_a;
+        let db = b[i] - mean_b;
+        cov += da * db;
+        var_a += da * da;
+        var_b += db * db;
+    }
+    if var_a == 0.0 || var_b == 0.0 {
+        return None;
+    }
+    Some(cov / (var_a.sqrt() * var_b.sqrt()))
+}
+
+/// Samples of both series restricted to the timestamps they share.
+pub fn aligned_samples(a: &[Sample], b: &[Sample]) -> (Vec<Sample>, Vec<Sample>) {
+    let b_index: HashMap<u64, usize> = b
+        .iter()
+        .enumerate()
+        .map(|(i, sample)| (sample.timestamp, i))
+        .collect();
+    let mut aa: Vec<Sample> = Vec::new();
+    let mut bb: Vec<Sample> = Vec::new();
+    for sample in a {
+        if let Some(i) = b_index.get(&sample.timestamp) {
+            aa.push(sample.clone());
+            bb.push(b[*i].clone());
+        }
+    }
+    (aa, bb)
+}
+
+/// Returns of both series computed only over the timestamps they share.
+pub fn aligned_returns(a: &[Sample], b: &[Sample]) -> (Vec<f64>, Vec<f64>) {
+    let (aa, bb) = aligned_samples(a, b);
+    (returns(&aa[..]), returns(&bb[..]))
+}
+
+/// Log-price spread `ln(a) - beta * ln(b)` over the last `n` shared samples, where
+/// beta is the least squares hedge ratio. Returns (z-score of the last spread, beta).
+pub fn spread_zscore(a: &[Sample], b: &[Sample], n: usize) -> Option<(f64, f64)> {
+    let (aa, bb) = aligned_samples(a, b);
+    let start = aa.len().saturating_sub(n);
+    let la: Vec<f64> = aa[start..].iter().map(|s| s.close.ln()).collect();
+    let lb: Vec<f64> = bb[start..].iter().map(|s| s.close.ln()).collect();
+    if la.len() < 2 || la.iter().chain(lb.iter()).any(|x| !x.is_finite()) {
+        return None;
+    }
+    let m = la.len() as f64;
+    let mean_a = la.iter().sum::<f64>() / m;
+    let mean_b = lb.iter().sum::<f64>() / m;
+    let mut cov = 0.0;
+    let mut var_b = 0.0;
+    for i in 0..la.len() {
+        cov += (la[i] - mean_a) * (lb[i] - mean_b);
+        var_b += (lb[i] - mean_b).powi(2);
+    }
+    if var_b == 0.0 {
+        return None;
+    }
+    let beta = cov / var_b;
+    let spread: Vec<f64> = la
+        .iter()
+        .zip(lb.iter())
+        .map(|(x, y)| x - beta * y)
+        .collect();
+    let mean = spread.iter().sum::<f64>() / m;
+    let sd = (spread.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / m).sqrt();
+    if sd == 0.0 {
+        return None;
+    }
+    Some(((spread.last().unwrap() - mean) / sd, beta))
+}
+
+/// Correlation of returns over a rolling window. Entries without enough
+/// data are NaN.
+pub fn rolling_correlation(a: &[Sample], b: &[Sample], window: usize) -> Vec<f64> {
+    let (ra, rb) = aligned_returns(a, b);
+    let mut r = vec![f64::NAN; ra.len()];
+    if window < 2 {
+        return r;
+    }
+    for i in window.saturating_sub(1)..ra.len() {
+        let start = i + 1 - window;
+        r[i] = correlation(&ra[start..i + 1], &rb[start..i + 1]).unwrap_or(f64::NAN);
+    }
+    r
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct CorrelationMatrix {
+    pub tokens: Vec<Token>,
+    /// values[i][j] holds the correlation between tokens i and j (NaN when unknown).
+    pub values: Vec<Vec<f64>>,
+}
+
+/// Correlation of returns over the last `window` shared samples of each pair of series.
+pub fn correlation_matrix(series: &[(Token, &[Sample])], window: usize) -> CorrelationMatrix {
+    let n = series.len();
+    let mut matrix = CorrelationMatrix {
+        tokens: series.iter().map(|(token, _)| token.clone()).collect(),
+        values: vec![vec![f64::NAN; n]; n],
+    };
+    for i in 0..n {
+        matrix.values[i][i] = 1.0;
+        for j in i + 1..n {
+            let (ra, rb) = aligned_returns(series[i].1, series[j].1);
+            let start = ra.len().saturating_sub(window);
+            let c = correlation(&ra[start..], &rb[start..]).unwrap_or(f64::NAN);
+            matrix.values[i][j] = c;
+            matrix.values[j][i] = c;
+        }
+    }
+    matrix
+}
+
+/// Realized volatility estimators.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VolatilityEstimator {
+    /// Standard deviation of log close-to-close returns.
+    #[default]
+    CloseToClose,
+    /// High-low range based estimator.
+    Parkinson,
+    /// Open-high-low-close based estimator.
+    GarmanKlass,
+}
+
+impl VolatilityEstimator {
+    pub fn from_name(name: &str) -> Option<VolatilityEstimator> {
+        match name.to_uppercase().as_str() {
+            "CC" => Some(VolatilityEstimator::CloseToClose),
+            "PK" => Some(VolatilityEstimator::Parkinson),
+            "GK" => Some(VolatilityEstimator::GarmanKlass),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            VolatilityEstimator::CloseToClose => String::from("cc"),
+            VolatilityEstimator::Parkinson => String::from("pk"),
+            VolatilityEstimator::GarmanKlass => String::from("gk"),
+        }
+    }
+}
+
+/// Per-bar volatility of the given samples (not annualized).
+pub fn volatility(estimator: &VolatilityEstimator, samples: &[Sample]) -> Option<f64> {
+    match estimator {
+        VolatilityEstimator::CloseToClose => {
+            let r: Vec<f64> = samples
+                .windows(2)
+                .filter(|w| w[0].close > 0.0 && w[1].close > 0.0)
+                .map(|w| (w[1].close / w[0].close).ln())
+                .collect();
+            if r.len() < 2 {
+                return None;
+            }
+            let mean = r.iter().sum::<f64>() / r.len() as f64;
+            let var = r.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (r.len() - 1) as f64;
+            Some(var.sqrt())
+        }
+        VolatilityEstimator::Parkinson => {
+            let hl: Vec<f64> = samples
+                .iter()
+                .filter(|s| s.low > 0.0 && s.high > 0.0)
+                .map(|s| (s.high / s.low).ln().powi(2))
+                .collect();
+            if hl.is_empty() {
+                return None;
+            }
+            Some((hl.iter().sum::<f64>() / (4.0 * hl.len() as f64 * 2f64.ln())).sqrt())
+        }
+        VolatilityEstimator::GarmanKlass => {
+            let v: Vec<f64> = samples
+                .iter()
+                .filter(|s| s.low > 0.0 && s.high > 0.0 && s.open > 0.0 && s.close > 0.0)
+                .map(|s| {
+                    0.5 * (s.high / s.low).ln().powi(2)
+                        - (2.0 * 2f64.ln() - 1.0) * (s.close / s.open).ln().powi(2)
+                })
+                .collect();
+            if v.is_empty() {
+                return None;
+            }
+            Some((v.iter().sum::<f64>() / v.len() as f64).max(0.0).sqrt())
+        }
+    }
+}
+
+/// Per-bar volatility over a rolling window. Entries without enough data are NaN.
+pub fn rolling_volatility(
+    estimator: &VolatilityEstimator,
+    window: usize,
+    samples: &[Sample],
+) -> Vec<f64> {
+    let mut r = vec![f64::NAN; samples.len()];
+    if window == 0 {
+        return r;
+    }
+    for i in window.saturating_sub(1)..samples.len() {
+        r[i] = volatility(estimator, &samples[i + 1 - window..i + 1]).unwrap_or(f64::NAN);
+    }
+    r
+}
+
+/// Annualized volatility of one lookback of a volatility cone: the latest value
+/// against the distribution of its rolling history.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConeLevel {
+    /// Lookback in bars.
+    pub window: usize,
+    pub current: f64,
+    pub min: f64,
+    pub p25: f64,
+    pub median: f64,
+    pub p75: f64,
+    pub max: f64,
+    /// Percent of the history at or below the current value.
+    pub rank: f64,
+}
+
+/// Linearly interpolated `pct` percentile of non empty sorted values.
+fn percentile(sorted: &[f64], pct: f64) -> f64 {
+    let rank = pct / 100.0 * (sorted.len() - 1) as f64;
+    let (lo, hi) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64)
+}
+
+/// Volatility cone of `samples`: for each lookback of `windows` bars, the
+/// current rolling volatility against all its past values, scaled by
+/// `annualization`. Lookbacks with less than two values are left out.
+pub fn volatility_cone(
+    estimator: &VolatilityEstimator,
+    samples: &[Sample],
+    windows: &[usize],
+    annualization: f64,
+) -> Vec<ConeLevel> {
+    windows
+        .iter()
+        .filter_map(|&window| {
+            let mut values: Vec<f64> = rolling_volatility(estimator, window, samples)
+                .into_iter()
+                .filter(|v| v.is_finite())
+                .map(|v| v * annualization)
+                .collect();
+            if values.len() < 2 {
+                return None;
+            }
+            let current = values[values.len() - 1];
+            let rank = values.iter().filter(|v| **v <= current).count() as f64 * 100.0
+                / values.len() as f64;
+            values.sort_by(|a, b| a.total_cmp(b));
+            Some(ConeLevel {
+                window,
+                current,
+                min: values[0],
+                p25: percentile(&values, 25.0),
+                median: percentile(&values, 50.0),
+                p75: percentile(&values, 75.0),
+                max: values[values.len() - 1],
+                rank,
+            })
+        })
+        .collect()
+}
+
+/// Factor converting per-bar volatility of the given resolution into yearly volatility.
+pub fn annualization_factor(resolution: &TimeUnit) -> f64 {
+    let seconds = resolution.num_seconds();
+    if seconds <= 0 {
+        return 1.0;
+    }
+    (TimeUnit::Year(1).num_seconds() as f64 / seconds as f64).sqrt()
+}
+
+#[derive(Default, Clone, Copy, Debug)]
+pub struct SeasonalityBucket {
+    pub sum: f64,
+    pub count: usize,
+}
+
+impl SeasonalityBucket {
+    pub fn add(&mut self, value: f64) {
+        self.sum += value;
+        self.count += 1;
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        if self.count == 0 {
+            None
+        } else {
+            Some(self.sum / self.count as f64)
+        }
+    }
+}
+
+/// Bar returns (in percent) aggregated by UTC hour of day and day of week.
+#[derive(Clone, Debug)]
+pub struct Seasonality {
+    pub by_hour: [SeasonalityBucket; 24],
+    pub by_weekday: [SeasonalityBucket; 7],
+    /// by_weekday_hour[weekday][hour]
+    pub by_weekday_hour: [[SeasonalityBucket; 24]; 7],
+}
+
+impl Default for Seasonality {
+    fn default() -> Self {
+        Seasonality {
+            by_hour: [SeasonalityBucket::default(); 24],
+            by_weekday: [SeasonalityBucket::default(); 7],
+            by_weekday_hour: [[SeasonalityBucket::default(); 24]; 7],
+        }
+    }
+}
+
+pub fn seasonality(samples: &[Sample]) -> Seasonality {
+    let mut s = Seasonality::default();
+    for sample in samples {
+        if sample.open == 0.0 {
+            continue;
+        }
+        let r = (sample.close / sample.open - 1.0) * 100.0;
+        let date = Date::from_timestamp(sample.seconds());
+        let hour = date.hour() as usize;
+        let weekday = date.weekday() as usize;
+        s.by_hour[hour].add(r);
+        s.by_weekday[weekday].add(r);
+        s.by_weekday_hour[weekday][hour].add(r);
+    }
+    s
+}
+
+/// Traded volume by price level, in `bins` of equal height from `low`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VolumeProfile {
+    pub low: f64,
+    pub bin_size: f64,
+    pub volumes: Vec<f64>,
+}
+
+impl VolumeProfile {
+    /// Price at the middle of bin `i`.
+    pub fn price(&self, i: usize) -> f64 {
+        self.low + (i as f64 + 0.5) * self.bin_size
+    }
+
+    /// Bin with the most volume (point of control).
+    pub fn poc(&self) -> Option<usize> {
+        self.volumes
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| **v > 0.0)
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(i, _)| i)
+    }
+
+    pub fn max_volume(&self) -> f64 {
+        self.volumes.iter().cloned().fold(0.0, f64::max)
+    }
+}
+
+/// Spreads the volume of each bar evenly over its low..high range, bars
+/// without range put all their volume in the bin of their close.
+pub fn volume_profile(samples: &[Sample], bins: usize) -> VolumeProfile {
+    let low = samples.iter().map(|s| s.low).fold(f64::MAX, f64::min);
+    let high = samples.iter().map(|s| s.high).fold(f64::MIN, f64::max);
+    if samples.is_empty() || bins == 0 || high <= low {
+        return VolumeProfile::default();
+    }
+    let bin_size = (high - low) / bins as f64;
+    let bin = |price: f64| (((price - low) / bin_size) as usize).min(bins - 1);
+    let mut volumes = vec![0.0; bins];
+    for s in samples {
+        let volume = s.volume as f64;
+        if s.high <= s.low {
+            volumes[bin(s.close)] += volume;
+            continue;
+        }
+        for (i, v) in volumes
+            .iter_mut()
+            .enumerate()
+            .take(bin(s.high) + 1)
+            .skip(bin(s.low))
+        {
+            let bottom = low + i as f64 * bin_size;
+            let overlap = s.high.min(bottom + bin_size) - s.low.max(bottom);
+            *v += volume * overlap.max(0.0) / (s.high - s.low);
+        }
+    }
+    VolumeProfile {
+        low,
+        bin_size,
+        volumes,
+    }
+}
+
+/// Risk adjusted performance of an equity curve. Sharpe and alpha are
+/// annualized, alpha in percent; alpha and beta are relative to a benchmark.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RiskMetrics {
+    pub sharpe: Option<f64>,
+    pub alpha: Option<f64>,
+    pub beta: Option<f64>,
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Periods per year of a series sampled at the median spacing of `timestamps`
+/// (seconds or ms).
+fn periods_per_year(timestamps: &[u64]) -> Option<f64> {
+    let mut steps: Vec<u64> = timestamps
+        .windows(2)
+        .map(|w| w[1].saturating_sub(w[0]))
+        .collect();
+    if steps.is_empty() {
+        return None;
+    }
+    steps.sort();
+    let mut step = steps[steps.len() / 2] as f64;
+    if timestamps[0] > 100_000_000_000 {
+        step /= 1000.0;
+    }
+    if step <= 0.0 {
+        return None;
+    }
+    Some(TimeUnit::Year(1).num_seconds() as f64 / step)
+}
+
+/// Sharpe ratio of `equity` (timestamp, value) over a yearly risk-free rate
+/// (percent), with alpha and beta against the closes of `benchmark` at the same
+/// timestamps.
+pub fn risk_metrics(
+    equity: &[(u64, f64)],
+    benchmark: Option<&[Sample]>,
+    risk_free_pct: f64,
+) -> RiskMetrics {
+    let mut metrics = RiskMetrics::default();
+    let timestamps: Vec<u64> = equity.iter().map(|(t, _)| *t).collect();
+    let periods = match periods_per_year(&timestamps) {
+        Some(periods) => periods,
+        None => return metrics,
+    };
+    let risk_free = (1.0 + risk_free_pct / 100.0).powf(1.0 / periods) - 1.0;
+    let excess: Vec<f64> = equity
+        .windows(2)
+        .filter(|w| w[0].1 > 0.0)
+        .map(|w| w[1].1 / w[0].1 - 1.0 - risk_free)
+        .collect();
+    if excess.len() >= 2 {
+        let m = mean(&excess);
+        let var = excess.iter().map(|x| (x - m).powi(2)).sum::<f64>() / (excess.len() - 1) as f64;
+        if var > 0.0 {
+            metrics.sharpe = Some(m / var.sqrt() * periods.sqrt());
+        }
+    }
+    if let Some(benchmark) = benchmark {
+        let closes: HashMap<u64, f64> = benchmark.iter().map(|s| (s.timestamp, s.close)).collect();
+        let shared: Vec<(f64, f64)> = equity
+            .iter()
+            .filter_map(|(t, v)| closes.get(t).map(|close| (*v, *close)))
+            .collect();
+        let (rp, rb): (Vec<f64>, Vec<f64>) = shared
+            .windows(2)
+            .filter(|w| w[0].0 > 0.0 && w[0].1 > 0.0)
+            .map(|w| (w[1].0 / w[0].0 - 1.0, w[1].1 / w[0].1 - 1.0))
+            .unzip();
+        if rp.len() >= 2 {
+            let (mp, mb) = (mean(&rp), mean(&rb));
+            let cov: f64 = rp.iter().zip(&rb).map(|(p, b)| (p - mp) * (b - mb)).sum();
+            let var_b: f64 = rb.iter().map(|b| (b - mb).powi(2)).sum();
+            if var_b > 0.0 {
+                let beta = cov / var_b;
+                metrics.beta = Some(beta);
+                metrics.alpha =
+                    Some(((mp - risk_free) - beta * (mb - risk_free)) * periods * 100.0);
+            }
+        }
+    }
+    metrics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        correlation, correlation_matrix, percentile, risk_metrics, seasonality, spread_zscore,
+        volatility, volatility_cone, VolatilityEstimator,
+    };
+    use crate::finance::{Sample, Token};
+
+    fn samples(closes: &[f64]) -> Vec<Sample> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, c)| Sample {
+                timestamp: i as u64,
+                close: *c,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_correlation() {
+        let a = vec![1.0, 2.0, 3.0, 4.0];
+        let b = vec![2.0, 4.0, 6.0, 8.0];
+        let c = vec![4.0, 3.0, 2.0, 1.0];
+        assert!((correlation(&a, &b).unwrap() - 1.0).abs() < 1e-12);
+        assert!((correlation(&a, &c).unwrap() + 1.0).abs() < 1e-12);
+        assert_eq!(correlation(&a, &[1.0, 1.0, 1.0, 1.0]), None);
+    }
+
+    #[test]
+    fn test_spread_zscore() {
+        let b = vec![10.0, 11.0, 12.0, 11.5, 13.0, 12.5, 14.0, 13.5];
+        let mut a: Vec<f64> = b.iter().map(|x| x * x).collect();
+        *a.last_mut().unwrap() *= 0.8;
+        let (z, beta) = spread_zscore(&samples(&a), &samples(&b), 8).unwrap();
+        assert!(beta > 1.0);
+        assert!(z < -2.0);
+        assert_eq!(spread_zscore(&samples(&a), &samples(&b), 1), None);
+    }
+
+    #[test]
+    fn test_correlation_matrix() {
+        let a = samples(&[10.0, 11.0, 10.5, 12.0, 11.0]);
+        let b = samples(&[20.0, 22.0, 21.0, 24.0, 22.0]);
+        let m = correlation_matrix(
+            &[
+                (Token::pair("A", "USDT"), &a[..]),
+                (Token::pair("B", "USDT"), &b[..]),
+            ],
+            10,
+        );
+        assert_eq!(m.values[0][0], 1.0);
+        assert!((m.values[0][1] - 1.0).abs() < 1e-9);
+        assert_eq!(m.values[0][1], m.values[1][0]);
+    }
+
+    #[test]
+    fn test_volatility() {
+        let flat = samples(&[10.0, 10.0, 10.0, 10.0]);
+        assert_eq!(
+            volatility(&VolatilityEstimator::CloseToClose, &flat[..]),
+            Some(0.0)
+        );
+        let ranged: Vec<Sample> = (0..4)
+            .map(|i| Sample {
+                timestamp: i,
+                open: 10.0,
+                high: 11.0,
+                low: 10.0,
+                close: 10.0,
+                ..Default::default()
+            })
+            .collect();
+        let pk = volatility(&VolatilityEstimator::Parkinson, &ranged[..]).unwrap();
+        let expected = ((1.1f64).ln().powi(2) / (4.0 * 2f64.ln())).sqrt();
+        assert!((pk - expected).abs() < 1e-12);
+        let gk = volatility(&VolatilityEstimator::GarmanKlass, &ranged[..]).unwrap();
+        assert!((gk - (0.5 * (1.1f64).ln().powi(2)).sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_volatility_cone() {
+        assert_eq!(percentile(&[1.0, 2.0, 3.0, 4.0], 50.0), 2.5);
+        assert_eq!(percentile(&[1.0, 2.0, 3.0, 4.0], 100.0), 4.0);
+        // calm history, then wide swings
+        let mut closes: Vec<f64> = (0..20).map(|i| 10.0 + (i % 2) as f64 * 0.1).collect();
+        closes.extend([10.0, 12.0, 10.0, 12.0, 10.0]);
+        let estimator = VolatilityEstimator::CloseToClose;
+        let cone = volatility_cone(&estimator, &samples(&closes), &[4, 100], 1.0);
+        assert_eq!(cone.len(), 1);
+        let level = &cone[0];
+        assert_eq!((level.window, level.rank), (4, 100.0));
+        assert_eq!(level.current, level.max);
+        assert!(level.min <= level.p25 && level.p25 <= level.median);
+        assert!(level.median <= level.p75 && level.p75 <= level.max);
+        let scaled = volatility_cone(&estimator, &samples(&closes), &[4], 2.0);
+        assert!((scaled[0].current - 2.0 * level.current).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_volume_profile() {
+        let bar = |low: f64, high: f64, volume: u64| Sample {
+            low,
+            high,
+            close: high,
+            volume,
+            ..Default::default()
+        };
+        let profile = volume_profile(&[bar(10.0, 14.0, 40), bar(12.0, 13.0, 30)], 4);
+        assert_eq!(profile.low, 10.0);
+        assert_eq!(profile.bin_size, 1.0);
+        assert_eq!(profile.volumes, vec![10.0, 10.0, 40.0, 10.0]);
+        assert_eq!(profile.poc(), Some(2));
+        assert_eq!(profile.price(2), 12.5);
+        assert_eq!(
+            volume_profile(&[bar(1.0, 1.0, 5)], 4),
+            VolumeProfile::default()
+        );
+    }
+
+    #[test]
+    fn test_seasonality() {
+        // 2024-01-01 00:00:00 UTC is a Monday
+        let samples: Vec<Sample> = (0..48)
+            .map(|i| Sample {
+                timestamp: 1704067200 + i * 3600,
+                open: 100.0,
+                close: if i % 24 == 5 { 101.0 } else { 100.0 },
+                ..Default::default()
+            })
+            .collect();
+        let s = seasonality(&samples[..]);
+        assert_eq!(s.by_hour[5].count, 2);
+        assert!((s.by_hour[5].mean().unwrap() - 1.0).abs() < 1e-12);
+        assert_eq!(s.by_hour[6].mean(), Some(0.0));
+        assert_eq!(s.by_weekday[0].count, 24);
+        assert_eq!(s.by_weekday[1].count, 24);
+        assert_eq!(s.by_weekday[2].mean(), None);
+        assert_eq!(s.by_weekday_hour[1][5].count, 1);
+    }
+
+    #[test]
+    fn test_risk_metrics() {
+        let day = 86400;
+        let benchmark: Vec<Sample> = [100.0, 102.0, 101.0, 104.0, 103.0]
+            .iter()
+            .enumerate()
+            .map(|(i, c)| Sample {
+                timestamp: i as u64 * day,
+                close: *c,
+                ..Default::default()
+            })
+            .collect();
+        // twice the benchmark moves
+        let mut value = 1000.0;
+        let mut equity = vec![(0, value)];
+        for w in benchmark.windows(2) {
+            value *= 1.0 + 2.0 * (w[1].close / w[0].close - 1.0);
+            equity.push((w[1].timestamp, value));
+        }
+        let metrics = risk_metrics(&equity, Some(&benchmark), 0.0);
+        assert!((metrics.beta.unwrap() - 2.0).abs() < 1e-9);
+        assert!(metrics.alpha.unwrap().abs() < 1e-6);
+        assert!(metrics.sharpe.unwrap() > 0.0);
+        let with_rate = risk_metrics(&equity, None, 5.0);
+        assert!(with_rate.sharpe.unwrap() < metrics.sharpe.unwrap());
+        assert_eq!(with_rate.beta, None);
+        assert_eq!(risk_metrics(&equity[..1], None, 0.0), Default::default());
+    }
+}
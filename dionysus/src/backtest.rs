@@ -1,6 +1,10 @@
+use std::collections::HashMap;
+
 use crate::{
-    finance::{Book, BookLine, DiError, Order, Sample, Token},
+    finance::{Book, BookLine, DiError, Order, Sample, Side, Token},
+    futures::Perpetual,
     historical_data::HistoricalData,
+    signal_log::SignalLog,
     strategy::Chrysus,
     time::{Date, TimeWindow},
     utils::compute_change_pct,
@@ -13,26 +17,107 @@ pub struct Backtest {
     pub period: TimeWindow,
     pub currency_balance: f64,
     pub symbol_balance: f64,
+    pub signals: SignalLog,
+    /// Leveraged amount, included in the currency balance.
+    pub borrowed: f64,
+    /// (sample timestamp, funding paid) of perpetual futures, negative when received.
+    pub funding: Vec<(u64, f64)>,
+    /// Fee rate charged on the value of each order.
+    pub fee_rate: f64,
+    pub fees: f64,
+}
+
+/// Buy and the sell closing it. Timestamps are those of the backtest orders.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RoundTrip {
+    pub entry_timestamp: u64,
+    pub entry_price: f64,
+    pub exit_timestamp: u64,
+    pub exit_price: f64,
+    pub quantity: f64,
+}
+
+impl RoundTrip {
+    pub fn profit(&self) -> f64 {
+        (self.exit_price - self.entry_price) * self.quantity
+    }
+}
+
+/// Buy left open after matching, (timestamp, price, quantity).
+pub type OpenLot = (u64, f64, f64);
+
+/// Round trips of orders of a single token, sells close the oldest open buys
+/// first. Also returns the buys still open.
+pub fn match_round_trips<'a>(
+    orders: impl Iterator<Item = &'a Order>,
+) -> (Vec<RoundTrip>, Vec<OpenLot>) {
+    let mut trips: Vec<RoundTrip> = Vec::new();
+    let mut open: Vec<OpenLot> = Vec::new();
+    for order in orders {
+        let timestamp = order.date.timestamp() as u64;
+        match order.side {
+            Side::Buy => open.push((timestamp, order.price, order.quantity)),
+            Side::Sell => {
+                let mut quantity = order.quantity;
+                while quantity > 1e-12 && !open.is_empty() {
+                    let filled = quantity.min(open[0].2);
+                    trips.push(RoundTrip {
+                        entry_timestamp: open[0].0,
+                        entry_price: open[0].1,
+                        exit_timestamp: timestamp,
+                        exit_price: order.price,
+                        quantity: filled,
+                    });
+                    quantity -= filled;
+                    open[0].2 -= filled;
+                    if open[0].2 <= 1e-12 {
+                        open.remove(0);
+                    }
+                }
+            }
+        }
+    }
+    (trips, open)
+}
+
+/// Costs and instrument settings of a backtest.
+#[derive(Debug, Clone, Default)]
+pub struct BacktestSettings {
+    /// Fraction of the order value, backtest orders are taken at market.
+    pub fee_rate: f64,
+    pub perpetual: Perpetual,
 }
 
 impl Backtest {
     pub fn compute_profit(&self, tick: f64) -> f64 {
         compute_change_pct(
             self.initial_capital,
-            self.currency_balance + tick * self.symbol_balance,
+            self.currency_balance + tick * self.symbol_balance - self.borrowed,
         )
     }
+
+    pub fn total_funding(&self) -> f64 {
+        self.funding.iter().map(|(_, paid)| paid).sum()
+    }
+
+    /// Round trips of `token`, sells close the oldest open buys first. A sell
+    /// spanning several buys gives one round trip per buy.
+    pub fn round_trips(&self, token: &Token) -> Vec<RoundTrip> {
+        match_round_trips(self.orders.iter().filter(|o| o.token == *token)).0
+    }
 }
 
 struct BacktestData<'a> {
     samples: &'a [Sample],
+    legs: &'a HashMap<Token, &'a [Sample]>,
     pub sample_index: usize,
 }
 
 impl<'a> BacktestData<'a> {
-    pub fn new(samples: &'a [Sample]) -> BacktestData<'a> {
+    pub fn new(samples: &'a [Sample], legs: &'a HashMap<Token, &'a [Sample]>) -> BacktestData<'a> {
         BacktestData {
             samples,
+            legs,
             sample_index: 0,
         }
     }
@@ -47,26 +132,69 @@ impl<'a> HistoricalData for BacktestData<'a> {
         Err(DiError::NotImplemented)
     }
 
-    fn get_last(&self, _: &Token, duration: &TimeWindow) -> Result<&[Sample], DiError> {
+    fn get_last(&self, token: &Token, duration: &TimeWindow) -> Result<&[Sample], DiError> {
+        if let Some(leg) = self.legs.get(token) {
+            // leg samples up to the last sample visible in the main history
+            let last_index = match self.sample_index.checked_sub(1) {
+                Some(i) => leg.partition_point(|s| s.timestamp <= self.samples[i].timestamp),
+                None => 0,
+            };
+            let first_index = last_index.saturating_sub(duration.count as usize);
+            return Ok(&leg[first_index..last_index]);
+        }
         let first_index = self.sample_index.saturating_sub(duration.count as usize);
         Ok(&self.samples[first_index..self.sample_index])
     }
 }
 
 pub fn backtest(chrysus: &Chrysus, history: &[Sample]) -> Backtest {
+    backtest_with_legs(chrysus, history, &HashMap::new())
+}
+
+/// Backtest of a strategy that also trades other tokens (legs). Hedge holdings
+/// left at the end are valued at the last price of their leg.
+pub fn backtest_with_legs(
+    chrysus: &Chrysus,
+    history: &[Sample],
+    legs: &HashMap<Token, &[Sample]>,
+) -> Backtest {
+    backtest_with_settings(chrysus, history, legs, &BacktestSettings::default())
+}
+
+/// Backtest charging fees on every order. Perpetual futures trade with
+/// leveraged capital and funding is paid (or received) on the held position at
+/// each funding time.
+pub fn backtest_with_settings(
+    chrysus: &Chrysus,
+    history: &[Sample],
+    legs: &HashMap<Token, &[Sample]>,
+    settings: &BacktestSettings,
+) -> Backtest {
+    let perpetual = &settings.perpetual;
     let capital = 1000.0;
+    let borrowed = perpetual.borrowed(capital);
     let mut c: Chrysus = chrysus.clone();
-    c.capital = capital;
+    c.capital = capital + borrowed;
     let mut backtest_result = Backtest::default();
     backtest_result.initial_capital = capital;
+    backtest_result.borrowed = borrowed;
+    backtest_result.fee_rate = settings.fee_rate;
     backtest_result.period = TimeWindow {
         resolution: history[0].resolution,
         count: history.len() as i64,
     };
-    let mut backtest_data = BacktestData::new(history);
+    let mut backtest_data = BacktestData::new(history, legs);
     let offset = chrysus.strategy.required_history_size();
     for i in offset..history.len() {
         backtest_data.sample_index = i;
+        if i > 0 && c.balance != 0.0 {
+            let from = history[i - 1].seconds();
+            for funding in perpetual.funding_between(from, history[i].seconds()) {
+                let paid = c.balance * history[i].close * funding.rate;
+                c.capital -= paid;
+                backtest_result.funding.push((history[i].timestamp, paid));
+            }
+        }
         let book = Book {
             token: chrysus.token.clone(),
             bids: vec![BookLine {
@@ -81,11 +209,74 @@ pub fn backtest(chrysus: &Chrysus, history: &[Sample]) -> Backtest {
         let mut orders = c.decide(book, &backtest_data);
         for order in &mut orders {
             order.date = Date::from_timestamp(history[i].timestamp);
+            if order.token != chrysus.token {
+                if let Some(price) = leg_price(legs, &order.token, history[i].timestamp) {
+                    order.price = price;
+                }
+            }
             c.realize(&order);
+            let fee = order.quantity * order.price * settings.fee_rate;
+            c.capital -= fee;
+            backtest_result.fees += fee;
             backtest_result.orders.push(order.clone());
         }
     }
     backtest_result.currency_balance = c.capital;
+    for (token, held) in &c.hedge_balance {
+        if let Some(price) = legs.get(token).and_then(|leg| leg.last()).map(|s| s.close) {
+            backtest_result.currency_balance += held * price;
+        }
+    }
     backtest_result.symbol_balance = c.balance;
+    backtest_result.signals = c.signals.clone();
     backtest_result
 }
+
+fn leg_price(legs: &HashMap<Token, &[Sample]>, token: &Token, timestamp: u64) -> Option<f64> {
+    let leg = legs.get(token)?;
+    let index = leg.partition_point(|s| s.timestamp <= timestamp);
+    leg[..index].last().map(|s| s.close)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Backtest;
+    use crate::finance::{Order, OrderType, Side, TimeInForce, Token};
+    use crate::time::Date;
+
+    #[test]
+    fn test_round_trips() {
+        let token = Token::pair("BTC", "USDT");
+        let order = |timestamp: u64, side: Side, quantity: f64, price: f64| Order {
+            index: 0,
+            position_index: None,
+            id: None,
+            token: token.clone(),
+            date: Date::from_timestamp(timestamp),
+            side,
+            quantity,
+            price,
+            stop_price: None,
+            order_type: OrderType::Limit,
+            tif: TimeInForce::default(),
+        };
+        let mut backtest = Backtest::default();
+        backtest.orders = vec![
+            order(1, Side::Buy, 1.0, 10.0),
+            order(2, Side::Buy, 2.0, 20.0),
+            order(3, Side::Sell, 2.0, 30.0),
+            Order {
+                token: Token::pair("ETH", "USDT"),
+                ..order(4, Side::Sell, 5.0, 1.0)
+            },
+            order(5, Side::Sell, 1.0, 15.0),
+        ];
+        let trips = backtest.round_trips(&token);
+        assert_eq!(trips.len(), 3);
+        assert_eq!((trips[0].entry_timestamp, trips[0].exit_timestamp), (1, 3));
+        assert_eq!(trips[0].profit(), 20.0);
+        assert_eq!((trips[1].entry_price, trips[1].quantity), (20.0, 1.0));
+        assert_eq!(trips[1].profit(), 10.0);
+        assert_eq!((trips[2].exit_timestamp, trips[2].profit()), (5, -5.0));
+    }
+}
@@ -0,0 +1,72 @@
+use crate::finance::Token;
+use crate::time::TimeUnit;
+use std::collections::HashMap;
+
+/// Tells when candle periods end. Bars are followed from their klines and
+/// closed by the clock at the end of their period, or earlier by a kline of a
+/// later period when the clock lags the exchange. Each bar closes once, late
+/// updates of closed bars are ignored.
+#[derive(Debug, Default)]
+pub struct BarClock {
+    /// Open time (seconds) of the forming bar.
+    bars: HashMap<(Token, TimeUnit), u64>,
+}
+
+impl BarClock {
+    /// Follows the bar opened at `open` (seconds). Returns the open time of
+    /// the bar it closes, if it was formed before.
+    pub fn observe(&mut self, token: &Token, resolution: &TimeUnit, open: u64) -> Option<u64> {
+        match self.bars.get_mut(&(token.clone(), *resolution)) {
+            Some(forming) if open > *forming => Some(std::mem::replace(forming, open)),
+            Some(_) => None,
+            None => {
+                self.bars.insert((token.clone(), *resolution), open);
+                None
+            }
+        }
+    }
+
+    /// Bars whose period ended by `now` (seconds, server time), with their
+    /// open time. Periods without klines are skipped, each bar is reported once.
+    pub fn poll(&mut self, now: u64) -> Vec<(Token, TimeUnit, u64)> {
+        let mut closed = Vec::new();
+        for ((token, resolution), forming) in self.bars.iter_mut() {
+            let length = resolution.num_seconds().max(1) as u64;
+            if now >= *forming + length {
+                closed.push((token.clone(), *resolution, *forming));
+                *forming += (now - *forming) / length * length;
+            }
+        }
+        closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BarClock;
+    use crate::finance::Token;
+    use crate::time::TimeUnit;
+
+    #[test]
+    fn test_bar_clock() {
+        let btc = Token::pair("BTC", "USDT");
+        let m1 = TimeUnit::Min(1);
+        let mut clock = BarClock::default();
+        assert!(clock.poll(1000).is_empty());
+        assert_eq!(clock.observe(&btc, &m1, 960), None);
+        assert_eq!(clock.observe(&btc, &m1, 960), None);
+        assert!(clock.poll(1019).is_empty());
+        assert_eq!(clock.poll(1020), vec![(btc.clone(), m1, 960)]);
+        assert!(clock.poll(1021).is_empty());
+        // last update of the closed bar and the first of the next one
+        assert_eq!(clock.observe(&btc, &m1, 960), None);
+        assert_eq!(clock.observe(&btc, &m1, 1020), None);
+        // the exchange is ahead of the clock
+        assert_eq!(clock.observe(&btc, &m1, 1080), Some(1020));
+        assert!(clock.poll(1100).is_empty());
+        // no klines for a few periods
+        assert_eq!(clock.poll(1300), vec![(btc.clone(), m1, 1080)]);
+        assert!(clock.poll(1319).is_empty());
+        assert_eq!(clock.poll(1320), vec![(btc, m1, 1260)]);
+    }
+}
@@ -1,6 +1,10 @@
 use crate::cache::Cache;
-use crate::finance::{Book, BookLine, DiError, MarketEvent, MarketTick, Sample, Token};
-use crate::time::TimeUnit;
+use crate::finance::{
+    Book, BookLine, DiError, Fill, MarketEvent, MarketTick, RestError, Sample, Side, Token,
+};
+use crate::retry::{Failure, RetryPolicy};
+use crate::stream_stats::StreamMonitor;
+use crate::time::{Date, TimeUnit};
 use crate::{ERROR, INFO};
 use binance;
 use binance::config::Config;
@@ -9,7 +13,7 @@ use slog::{self, slog_error, slog_info};
 use std::collections::HashMap;
 use std::fs::read_to_string;
 use std::sync::{
-    atomic::AtomicBool,
+    atomic::{AtomicBool, Ordering},
     mpsc::{self, Receiver, Sender},
     Arc, Mutex,
 };
@@ -31,8 +35,170 @@ pub fn binance_error(e: binance::errors::ErrorKind) -> String {
     }
 }
 
+/// Binance error codes worth retrying: disconnected, too many requests, timeout.
+const TRANSIENT_CODES: [i16; 3] = [-1001, -1003, -1007];
+/// Timestamp outside of the receive window.
+const CLOCK_DRIFT_CODE: i16 = -1021;
+
+pub fn classify(e: &binance::errors::Error) -> Failure {
+    match &e.0 {
+        binance::errors::ErrorKind::BinanceError(response) => {
+            if response.code == CLOCK_DRIFT_CODE {
+                Failure::ClockDrift
+            } else if TRANSIENT_CODES.contains(&response.code) {
+                Failure::Transient
+            } else {
+                Failure::Permanent
+            }
+        }
+        binance::errors::ErrorKind::ReqError(e) => {
+            if e.is_timeout() || e.is_connect() || e.status().map_or(false, |s| s.is_server_error())
+            {
+                Failure::Transient
+            } else {
+                Failure::Permanent
+            }
+        }
+        binance::errors::ErrorKind::IoError(_) => Failure::Transient,
+        // 5xx responses are reported as plain messages
+        binance::errors::ErrorKind::Msg(msg) => {
+            if msg.contains("Internal Server Error")
+                || msg.contains("Service Unavailable")
+                || msg.starts_with("Received response: 5")
+            {
+                Failure::Transient
+            } else {
+                Failure::Permanent
+            }
+        }
+        _ => Failure::Permanent,
+    }
+}
+
+pub fn rest_error(e: binance::errors::Error, failure: Failure) -> DiError {
+    DiError::Rest(match (failure, e.0) {
+        (Failure::ClockDrift, _) => RestError::ClockDrift,
+        (_, binance::errors::ErrorKind::BinanceError(response))
+            if response.code == CLOCK_DRIFT_CODE =>
+        {
+            RestError::ClockDrift
+        }
+        (_, binance::errors::ErrorKind::BinanceError(response)) => RestError::Rejected {
+            code: response.code,
+            msg: response.msg,
+        },
+        (Failure::Transient, kind) => RestError::Unavailable(format!("{}", kind)),
+        (_, kind) => RestError::Failed(binance_error(kind)),
+    })
+}
+
+/// Difference between the server and the local clock, in ms.
+pub fn clock_drift() -> Result<i64, DiError> {
+    let general: binance::general::General = binance::api::Binance::new(None, None);
+    let server_time = general
+        .get_server_time()
+        .map_err(|e| {
+            let failure = classify(&e);
+            rest_error(e, failure)
+        })?
+        .server_time;
+    let local_time = chrono::Utc::now().timestamp_millis();
+    Ok(server_time as i64 - local_time)
+}
+
+/// REST round trip and clock drift from one server time request.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ClockSample {
+    /// Round trip, in ms.
+    pub latency: i64,
+    /// Server minus local clock at the middle of the request, in ms.
+    pub drift: i64,
+}
+
+pub fn measure_clock() -> Result<ClockSample, DiError> {
+    let general: binance::general::General = binance::api::Binance::new(None, None);
+    let sent = chrono::Utc::now().timestamp_millis();
+    let server_time = general
+        .get_server_time()
+        .map_err(|e| {
+            let failure = classify(&e);
+            rest_error(e, failure)
+        })?
+        .server_time as i64;
+    let received = chrono::Utc::now().timestamp_millis();
+    Ok(ClockSample {
+        latency: received - sent,
+        drift: server_time - (sent + received) / 2,
+    })
+}
+
+/// Measures latency and drift in the background.
+pub struct ClockMonitor {
+    channel: (
+        Sender<Result<ClockSample, DiError>>,
+        Receiver<Result<ClockSample, DiError>>,
+    ),
+    /// Latest successful measure.
+    pub last: Option<ClockSample>,
+}
+
+impl Default for ClockMonitor {
+    fn default() -> Self {
+        Self {
+            channel: mpsc::channel(),
+            last: None,
+        }
+    }
+}
+
+impl ClockMonitor {
+    /// Measures every `interval` until the monitor is dropped.
+    pub fn start(&self, interval: std::time::Duration) {
+        let sender = self.channel.0.clone();
+        std::thread::spawn(move || loop {
+            if sender.send(measure_clock()).is_err() {
+                break;
+            }
+            std::thread::sleep(interval);
+        });
+    }
+
+    /// New measure since the last call, errors are logged.
+    pub fn poll(&mut self) -> Option<ClockSample> {
+        let mut latest = None;
+        for result in self.channel.1.try_iter() {
+            match result {
+                Ok(sample) => latest = Some(sample),
+                Err(e) => ERROR!("clock monitor {:?}", e),
+            }
+        }
+        if latest.is_some() {
+            self.last = latest;
+        }
+        latest
+    }
+}
+
+/// Runs a Binance REST call under the default retry policy. A resync can't
+/// change the timestamp of the call, so clock drift failures are not retried,
+/// signed calls go through `wallet::with_signed_retry`.
+/// Only for idempotent requests, orders are never retried.
+pub fn with_retry<T>(call: impl FnMut() -> binance::errors::Result<T>) -> Result<T, DiError> {
+    RetryPolicy::default()
+        .run(
+            call,
+            |e| match classify(e) {
+                Failure::ClockDrift => Failure::Permanent,
+                failure => failure,
+            },
+            || (),
+        )
+        .map_err(|(e, failure)| rest_error(e, failure))
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct ExchangeSymbolInfo {
+    pub exchange: String,
     pub min_qty: f64,
 
     pub symbol: String,
@@ -50,10 +216,18 @@ pub struct ExchangeSymbolInfo {
     pub order_types: Vec<String>,
 
     pub lot_min_qty: f64,
+    /// Price increment, 0 when unknown.
+    pub tick_size: f64,
+    /// Quantity increment, 0 when unknown.
+    pub step_size: f64,
+    /// Minimum order value in the quote asset.
+    pub min_notional: f64,
 }
 
 pub struct BinanceExchange {
     pub server_time: u64,
+    /// Server minus local clock, in ms, measured at start.
+    pub clock_offset: i64,
 
     general: binance::general::General,
     symbols: HashMap<Token, ExchangeSymbolInfo>,
@@ -67,9 +241,12 @@ pub struct BinanceStream {
 pub struct BinanceMarket {
     pub market: binance::market::Market,
     pub cache: Cache,
+    /// Message and connection statistics of the streams.
+    pub streams: StreamMonitor,
     pool: ThreadPool,
     event_channel: (Sender<MarketEvent>, Receiver<MarketEvent>),
-    thread_control: Arc<Mutex<HashMap<String, bool>>>,
+    /// Keeps the event loop of each registered service running.
+    thread_control: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
 }
 
 impl Default for BinanceExchange {
@@ -77,9 +254,16 @@ impl Default for BinanceExchange {
         let mut be = BinanceExchange {
             general: binance::api::Binance::new(None, None),
             server_time: 0,
+            clock_offset: 0,
             symbols: HashMap::new(),
         };
-        be.server_time = be.general.get_server_time().unwrap().server_time;
+        match with_retry(|| be.general.get_server_time()) {
+            Ok(time) => {
+                be.server_time = time.server_time;
+                be.clock_offset = time.server_time as i64 - chrono::Utc::now().timestamp_millis();
+            }
+            Err(e) => ERROR!("server time {:?}", e),
+        }
         be
     }
 }
@@ -106,12 +290,28 @@ impl BinanceStream {
         }
     }
 
-    pub fn start(&mut self) -> Result<(), DiError> {
+    /// Runs the user stream until stopped, order executions are sent as fills.
+    pub fn start(&mut self, events: Sender<MarketEvent>) -> Result<(), DiError> {
         if let Ok(answer) = self.stream.start() {
             let listen_key = answer.listen_key;
 
             let mut web_socket = WebSockets::new(|event: WebsocketEvent| {
                 match event {
+                    WebsocketEvent::OrderTrade(trade) if trade.execution_type == "TRADE" => {
+                        let parse = |value: &str| value.parse::<f64>().unwrap_or_default();
+                        // the receiver maps the symbol to its token, the
+                        // callback can't wait on exchange info requests
+                        let fill = Fill {
+                            id: trade.order_id as i64,
+                            token: Token::Symbol(trade.symbol.clone()),
+                            side: Side::from_string(&trade.side),
+                            quantity: parse(&trade.qty_last_filled_trade),
+                            price: parse(&trade.price_last_filled_trade),
+                            remaining: parse(&trade.qty)
+                                - parse(&trade.accumulated_qty_filled_trades),
+                        };
+                        let _ = events.send(MarketEvent::Fill(fill));
+                    }
                     WebsocketEvent::BalanceUpdate(account_update) => {
                         for balance in &account_update.balance {
                             println!(
@@ -145,6 +345,60 @@ impl BinanceStream {
     }
 }
 
+/// Stream of the 24h tickers of all symbols.
+pub const TICKER_STREAM: &str = "!ticker@arr";
+
+pub fn kline_stream(token: &Token, resolution: &TimeUnit) -> String {
+    format!(
+        "{}@kline_{}",
+        token.to_string().to_lowercase(),
+        resolution.name()
+    )
+}
+
+pub fn depth_stream(token: &Token) -> String {
+    format!("{}@depth@100ms", token.to_string().to_lowercase())
+}
+
+/// Stream an event came from, None for account events.
+fn stream_name(event: &MarketEvent) -> Option<String> {
+    match event {
+        MarketEvent::KLine((token, sample)) => Some(kline_stream(token, &sample.resolution)),
+        MarketEvent::OrderBook(book) => Some(depth_stream(&book.token)),
+        MarketEvent::Ticks(_) => Some(String::from(TICKER_STREAM)),
+        MarketEvent::Fill(_) => None,
+    }
+}
+
+/// Connects `web_socket` to `stream`, recording the connection.
+fn connect(web_socket: &mut WebSockets, stream: &str, streams: &StreamMonitor) -> bool {
+    match web_socket.connect(stream) {
+        Ok(()) => {
+            streams.connection(stream, Date::now().timestamp_millis(), true, None);
+            true
+        }
+        Err(e) => {
+            ERROR!("{} connection error: {:?}", stream, e);
+            streams.connection(
+                stream,
+                Date::now().timestamp_millis(),
+                false,
+                Some(format!("{:?}", e)),
+            );
+            false
+        }
+    }
+}
+
+fn disconnected(streams: &StreamMonitor, stream: &str, result: binance::errors::Result<()>) {
+    streams.connection(
+        stream,
+        Date::now().timestamp_millis(),
+        false,
+        result.err().map(|e| format!("{:?}", e)),
+    );
+}
+
 impl BinanceMarket {
     pub fn new(use_test_api: bool) -> Self {
         if use_test_api {
@@ -152,6 +406,7 @@ impl BinanceMarket {
             Self {
                 market: binance::api::Binance::new_with_config(None, None, &config),
                 cache: Cache::default(),
+                streams: StreamMonitor::default(),
                 pool: ThreadPool::new(MAX_CONCURRENT_THREADS),
                 event_channel: mpsc::channel(),
                 thread_control: Arc::new(Mutex::new(HashMap::new())),
@@ -160,6 +415,7 @@ impl BinanceMarket {
             Self {
                 market: binance::api::Binance::new(None, None),
                 cache: Cache::default(),
+                streams: StreamMonitor::default(),
                 pool: ThreadPool::new(MAX_CONCURRENT_THREADS),
                 event_channel: mpsc::channel(),
                 thread_control: Arc::new(Mutex::new(HashMap::new())),
@@ -167,37 +423,81 @@ impl BinanceMarket {
         }
     }
 
+    /// Fills of the account orders, from the user stream of `keys_file`.
+    pub fn user_stream_service(&mut self, keys_file: &str) {
+        if self.register_service("user_stream").is_some() {
+            let tx = self.event_channel.0.clone();
+            let keys_file = keys_file.to_string();
+            self.pool.execute(move || {
+                let mut stream = BinanceStream::new(&keys_file, false);
+                if let Err(e) = stream.start(tx) {
+                    ERROR!("user stream {:?}", e);
+                }
+            });
+        }
+    }
+
+    /// Pending events. Updates of a stream superseded by a later one in the
+    /// same batch are counted as coalesced.
     pub fn get_events(&self) -> Vec<MarketEvent> {
         let mut events: Vec<MarketEvent> = Vec::new();
+        let mut counts: HashMap<String, u64> = HashMap::new();
         for event in self.event_channel.1.try_iter() {
+            if let Some(stream) = stream_name(&event) {
+                *counts.entry(stream).or_default() += 1;
+            }
             events.push(event);
         }
+        for (stream, count) in counts {
+            if count > 1 {
+                self.streams.coalesced(&stream, count - 1);
+            }
+        }
         events
     }
 
-    fn register_service(&mut self, key: &str) -> bool {
+    /// Run flag of a new service, None when `key` already runs.
+    fn register_service(&mut self, key: &str) -> Option<Arc<AtomicBool>> {
         let mut control = self.thread_control.lock().unwrap();
 
         if control.contains_key(key) {
-            return false;
+            return None;
         }
         // TODO check max number of threads
-        control.insert(String::from(key), true);
-        true
+        let keep_running = Arc::new(AtomicBool::new(true));
+        control.insert(String::from(key), Arc::clone(&keep_running));
+        Some(keep_running)
+    }
+
+    /// Stops the service of stream `key`, so it can be started again. A dead
+    /// websocket may block its thread until the connection times out, the
+    /// events it still delivers are dropped.
+    pub fn stop_service(&mut self, key: &str) -> bool {
+        match self.thread_control.lock().unwrap().remove(key) {
+            Some(keep_running) => {
+                keep_running.store(false, Ordering::Relaxed);
+                self.streams.restarted(key);
+                true
+            }
+            None => false,
+        }
     }
 
     pub fn order_book_service(&mut self, token: &Token) {
-        let key = format!("{}@depth@100ms", token.to_string().to_lowercase());
+        let key = depth_stream(token);
 
-        if self.register_service(key.as_str()) {
-            let _control = Arc::clone(&self.thread_control);
+        if let Some(keep_running) = self.register_service(key.as_str()) {
             let tx = self.event_channel.0.clone();
+            let streams = self.streams.clone();
             let tk = token.clone();
             self.pool.execute(move || {
-                let keep_running = AtomicBool::new(true);
                 let mut web_socket = WebSockets::new(|event: WebsocketEvent| {
+                    if !keep_running.load(Ordering::Relaxed) {
+                        return Ok(());
+                    }
                     if let WebsocketEvent::DepthOrderBook(depth_order_book) = event {
-                        tx.send(MarketEvent::OrderBook(Book {
+                        streams.message(&key, Date::now().timestamp_millis());
+                        let sent = tx.send(MarketEvent::OrderBook(Book {
                             token: tk.clone(),
                             bids: depth_order_book
                                 .bids
@@ -215,49 +515,60 @@ impl BinanceMarket {
                                     quantity: b.qty,
                                 })
                                 .collect(),
-                        }))
-                        .unwrap();
+                        }));
+                        if sent.is_err() {
+                            streams.dropped(&key);
+                        }
                     }
 
                     Ok(())
                 });
 
                 INFO!("order-book service: {:?}", key);
-                web_socket.connect(&key).unwrap(); // check error
-                if let Err(e) = web_socket.event_loop(&keep_running) {
-                    match e {
-                        err => {
-                            ERROR!("order-book service error {:?}: {:?}", key, err);
-                        }
-                    }
+                if !connect(&mut web_socket, &key, &streams) {
+                    return;
+                }
+                let result = web_socket.event_loop(&keep_running);
+                if let Err(e) = &result {
+                    ERROR!("order-book service error {:?}: {:?}", key, e);
+                }
+                // a stopped service was replaced, its stream lives on
+                if keep_running.load(Ordering::Relaxed) {
+                    disconnected(&streams, &key, result);
                 }
-                web_socket.disconnect().unwrap();
+                let _ = web_socket.disconnect();
             });
         }
     }
 
     pub fn day_ticker_all_service(&mut self, currency: &str) {
-        let key = format!("!ticker@arr");
-        if self.register_service(key.as_str()) {
-            let _control = Arc::clone(&self.thread_control);
+        let key = String::from(TICKER_STREAM);
+        if let Some(keep_running) = self.register_service(key.as_str()) {
             let curr = String::from(currency);
             let tx = self.event_channel.0.clone();
+            let streams = self.streams.clone();
             self.pool.execute(move || {
-                let keep_running = AtomicBool::new(true); // Used to control the event loop
-                let agg_trade = format!("!ticker@arr"); // All Symbols
+                let agg_trade = String::from(TICKER_STREAM); // All Symbols
                 let mut web_socket = WebSockets::new(|event: WebsocketEvent| {
                     match event {
                         // 24hr rolling window ticker statistics for all symbols that changed in an array.
                         WebsocketEvent::DayTickerAll(ticker_events) => {
+                            streams.message(&agg_trade, Date::now().timestamp_millis());
                             let mut ticks: Vec<MarketTick> = Vec::new();
                             for tick_event in ticker_events {
-                                if tick_event.symbol.contains(curr.as_str()) {
+                                // pairs quoted in `currency`, plus `currency` quoted
+                                // in others (e.g. USDTBRL) for cross rates
+                                let token = Token::parse_pair(&tick_event.symbol, &[curr.as_str()])
+                                    .or_else(|| {
+                                        tick_event
+                                            .symbol
+                                            .strip_prefix(curr.as_str())
+                                            .filter(|quote| !quote.is_empty())
+                                            .map(|quote| Token::pair(curr.as_str(), quote))
+                                    });
+                                if let Some(token) = token {
                                     ticks.push(MarketTick {
-                                        token: Token::pair(
-                                            &tick_event.symbol
-                                                [..tick_event.symbol.len() - curr.len()],
-                                            curr.as_str(),
-                                        ),
+                                        token,
                                         price: tick_event.current_close.parse::<f64>().unwrap(),
                                         change_pct: tick_event
                                             .price_change_percent
@@ -266,8 +577,8 @@ impl BinanceMarket {
                                     });
                                 }
                             }
-                            if !ticks.is_empty() {
-                                tx.send(MarketEvent::Ticks(ticks)).unwrap();
+                            if !ticks.is_empty() && tx.send(MarketEvent::Ticks(ticks)).is_err() {
+                                streams.dropped(&agg_trade);
                             }
                         }
                         _ => (),
@@ -277,36 +588,35 @@ impl BinanceMarket {
                 });
 
                 INFO!("all-ticker service: {:?}", agg_trade);
-                web_socket.connect(&agg_trade).unwrap(); // check error
-                if let Err(e) = web_socket.event_loop(&keep_running) {
-                    match e {
-                        err => {
-                            ERROR!("all-ticker service error {:?}: {:?}", agg_trade, err);
-                        }
-                    }
+                if !connect(&mut web_socket, &agg_trade, &streams) {
+                    return;
+                }
+                let result = web_socket.event_loop(&keep_running);
+                if let Err(e) = &result {
+                    ERROR!("all-ticker service error {:?}: {:?}", agg_trade, e);
                 }
+                disconnected(&streams, &agg_trade, result);
             });
         }
     }
 
     pub fn kline_service(&mut self, token: &Token, resolution: &TimeUnit) {
-        let kline_key = format!(
-            "{}@kline_{}",
-            token.to_string().to_lowercase(),
-            resolution.name()
-        );
-        if self.register_service(kline_key.as_str()) {
-            let _control = Arc::clone(&self.thread_control);
+        let kline_key = kline_stream(token, resolution);
+        if let Some(keep_running) = self.register_service(kline_key.as_str()) {
             let tx = self.event_channel.0.clone();
+            let streams = self.streams.clone();
             let res = resolution.clone();
             let tk = token.clone();
 
             self.pool.execute(move || {
-                let keep_running = AtomicBool::new(true);
                 let mut web_socket = WebSockets::new(|event: WebsocketEvent| {
+                    if !keep_running.load(Ordering::Relaxed) {
+                        return Ok(());
+                    }
                     match event {
                         WebsocketEvent::Kline(kline_event) => {
-                            tx.send(MarketEvent::KLine((
+                            streams.message(&kline_key, Date::now().timestamp_millis());
+                            let sent = tx.send(MarketEvent::KLine((
                                 tk.clone(),
                                 Sample {
                                     resolution: res.clone(),
@@ -317,8 +627,10 @@ impl BinanceMarket {
                                     close: kline_event.kline.close.parse::<f64>().unwrap(),
                                     volume: kline_event.kline.volume.parse::<f64>().unwrap() as u64,
                                 },
-                            )))
-                            .unwrap();
+                            )));
+                            if sent.is_err() {
+                                streams.dropped(&kline_key);
+                            }
                         }
                         _ => (),
                     };
@@ -327,15 +639,18 @@ impl BinanceMarket {
 
                 INFO!("kline service: {:?}", kline_key);
 
-                web_socket.connect(&kline_key).unwrap(); // check error
-                if let Err(e) = web_socket.event_loop(&keep_running) {
-                    match e {
-                        err => {
-                            ERROR!("kline service error {:?}: {:?}", kline_key, err);
-                        }
-                    }
+                if !connect(&mut web_socket, &kline_key, &streams) {
+                    return;
+                }
+                let result = web_socket.event_loop(&keep_running);
+                if let Err(e) = &result {
+                    ERROR!("kline service error {:?}: {:?}", kline_key, e);
                 }
-                web_socket.disconnect().unwrap();
+                // a stopped service was replaced, its stream lives on
+                if keep_running.load(Ordering::Relaxed) {
+                    disconnected(&streams, &kline_key, result);
+                }
+                let _ = web_socket.disconnect();
             });
         }
     }
@@ -344,6 +659,7 @@ impl BinanceMarket {
 impl ExchangeSymbolInfo {
     pub fn new(info: binance::model::Symbol) -> Self {
         let mut esi = ExchangeSymbolInfo::default();
+        esi.exchange = String::from("binance");
         esi.symbol = info.symbol;
         esi.status = info.status;
         esi.base_asset = info.base_asset;
@@ -363,7 +679,7 @@ impl ExchangeSymbolInfo {
                     min_price,
                     max_price,
                     tick_size,
-                } => (),
+                } => esi.tick_size = tick_size.parse::<f64>().unwrap_or_default(),
                 binance::model::Filters::PercentPrice {
                     multiplier_up,
                     multiplier_down,
@@ -373,13 +689,20 @@ impl ExchangeSymbolInfo {
                     min_qty,
                     max_qty,
                     step_size,
-                } => esi.lot_min_qty = min_qty.parse::<f64>().unwrap(),
+                } => {
+                    esi.lot_min_qty = min_qty.parse::<f64>().unwrap();
+                    esi.step_size = step_size.parse::<f64>().unwrap_or_default();
+                }
                 binance::model::Filters::MinNotional {
                     notional,
                     min_notional,
                     apply_to_market,
                     avg_price_mins,
-                } => (),
+                } => {
+                    if let Some(value) = min_notional.or(notional) {
+                        esi.min_notional = value.parse::<f64>().unwrap_or_default();
+                    }
+                }
                 binance::model::Filters::IcebergParts { limit } => {
                     assert_eq!(limit.unwrap(), 10);
                 }
@@ -399,14 +722,199 @@ impl ExchangeSymbolInfo {
     }
 }
 
+impl ExchangeSymbolInfo {
+    pub fn token(&self) -> Token {
+        Token::pair(&self.base_asset, &self.quote_asset)
+    }
+
+    pub fn is_trading(&self) -> bool {
+        self.status == "TRADING"
+    }
+
+    /// Rounds down to the quantity step.
+    pub fn round_quantity(&self, quantity: f64) -> f64 {
+        round_to_step(quantity, self.step_size, f64::floor)
+    }
+
+    /// Rounds to the nearest price tick.
+    pub fn round_price(&self, price: f64) -> f64 {
+        round_to_step(price, self.tick_size, f64::round)
+    }
+
+    /// Decimal places of the price tick.
+    pub fn price_decimals(&self) -> usize {
+        step_decimals(self.tick_size)
+    }
+
+    /// Decimal places of the quantity step.
+    pub fn quantity_decimals(&self) -> usize {
+        step_decimals(self.step_size)
+    }
+
+    /// Why an order would be rejected by the symbol filters, if it would.
+    pub fn check_order(&self, price: f64, quantity: f64) -> Result<(), DiError> {
+        if !self.is_trading() {
+            return Err(DiError::Message(format!(
+                "{} is not trading ({})",
+                self.symbol, self.status
+            )));
+        }
+        if quantity < self.lot_min_qty {
+            return Err(DiError::Message(format!(
+                "{} min quantity is {}",
+                self.symbol, self.lot_min_qty
+            )));
+        }
+        if price * quantity < self.min_notional {
+            return Err(DiError::Message(format!(
+                "{} min order value is {}",
+                self.symbol, self.min_notional
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn step_decimals(step: f64) -> usize {
+    if step <= 0.0 {
+        return 8;
+    }
+    ((-step.log10() - 1e-9).ceil().max(0.0) as usize).min(16)
+}
+
+fn round_to_step(value: f64, step: f64, round: fn(f64) -> f64) -> f64 {
+    if step <= 0.0 {
+        return value;
+    }
+    // the epsilon keeps exact multiples from falling one step down
+    let steps = round(value / step + 1e-9);
+    let scale = 10f64.powi(step_decimals(step) as i32);
+    (steps * step * scale).round() / scale
+}
+
+/// Samples of exchange klines, timestamps in ms.
+pub fn samples_from_klines(
+    klines: binance::model::KlineSummaries,
+    resolution: &TimeUnit,
+) -> Vec<Sample> {
+    match klines {
+        binance::model::KlineSummaries::AllKlineSummaries(klines) => klines
+            .into_iter()
+            .map(|kline| Sample {
+                resolution: resolution.clone(),
+                timestamp: kline.open_time as u64,
+                open: kline.open.parse::<f64>().unwrap(),
+                high: kline.high.parse::<f64>().unwrap(),
+                low: kline.low.parse::<f64>().unwrap(),
+                close: kline.close.parse::<f64>().unwrap(),
+                volume: kline.number_of_trades as u64,
+            })
+            .collect(),
+    }
+}
+
+/// Last `count` klines of `token`, without going through a market cache.
+pub fn fetch_klines(
+    token: &Token,
+    resolution: &TimeUnit,
+    count: u16,
+) -> Result<Vec<Sample>, DiError> {
+    let market: binance::market::Market = binance::api::Binance::new(None, None);
+    let klines = with_retry(|| {
+        market.get_klines(
+            token.to_string().as_str(),
+            resolution.name(),
+            count,
+            None,
+            None,
+        )
+    })?;
+    Ok(samples_from_klines(klines, resolution))
+}
+
+/// Symbol info of every symbol listed on the exchange.
+pub fn fetch_exchange_info() -> Result<Vec<ExchangeSymbolInfo>, DiError> {
+    let general: binance::general::General = binance::api::Binance::new(None, None);
+    let info = with_retry(|| general.exchange_info())?;
+    Ok(info
+        .symbols
+        .into_iter()
+        .map(ExchangeSymbolInfo::new)
+        .collect())
+}
+
 impl BinanceExchange {
+    /// Current server time in seconds, from the local clock and the offset.
+    pub fn server_now(&self) -> u64 {
+        ((chrono::Utc::now().timestamp_millis() + self.clock_offset) / 1000) as u64
+    }
+
+    /// Maps an exchange symbol (ex: DOGEUSDT) to its token using the base/quote
+    /// assets from exchange info, falling back to the static quote currency list.
+    pub fn token_from_symbol(&mut self, symbol: &str) -> Token {
+        for info in self.symbols.values() {
+            if info.symbol == symbol {
+                return info.token();
+            }
+        }
+        match with_retry(|| self.general.get_symbol_info(symbol)) {
+            Ok(symbol_info) => {
+                let info = ExchangeSymbolInfo::new(symbol_info);
+                let token = info.token();
+                self.symbols.insert(token.clone(), info);
+                token
+            }
+            Err(_) => Token::from_string(&String::from(symbol)),
+        }
+    }
+
     pub fn get(&mut self, token: &Token) -> ExchangeSymbolInfo {
         if let Some(info) = self.symbols.get(token) {
             return info.clone();
         }
-        let symbol = self.general.get_symbol_info(token.to_string()).unwrap();
-        let info = ExchangeSymbolInfo::new(symbol);
-        self.symbols.insert(token.clone(), info.clone());
-        return info;
+        match with_retry(|| self.general.get_symbol_info(token.to_string())) {
+            Ok(symbol) => {
+                let info = ExchangeSymbolInfo::new(symbol);
+                self.symbols.insert(token.clone(), info.clone());
+                info
+            }
+            Err(e) => {
+                ERROR!("exchange info {} {:?}", token.to_string(), e);
+                ExchangeSymbolInfo::default()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BinanceExchange, ExchangeSymbolInfo};
+    use crate::finance::Token;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_token_from_symbol() {
+        let mut exchange = BinanceExchange {
+            server_time: 0,
+            clock_offset: 0,
+            general: binance::api::Binance::new(None, None),
+            symbols: HashMap::new(),
+        };
+        let info = ExchangeSymbolInfo {
+            symbol: String::from("ETHRUB"),
+            base_asset: String::from("ETH"),
+            quote_asset: String::from("RUB"),
+            ..Default::default()
+        };
+        exchange.symbols.insert(info.token(), info);
+        // RUB is not a known quote currency
+        assert_eq!(
+            Token::from_string(&String::from("ETHRUB")),
+            Token::Symbol(String::from("ETHRUB"))
+        );
+        assert_eq!(
+            exchange.token_from_symbol("ETHRUB"),
+            Token::pair("ETH", "RUB")
+        );
     }
 }
@@ -0,0 +1,145 @@
+use crate::finance::{Book, DiError, Token};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+
+/// Order book of a token at a point in time.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BookRecord {
+    /// Milliseconds
+    pub timestamp: u64,
+    pub book: Book,
+}
+
+/// File holding the recorded books of `token`.
+pub fn book_file(dir: &str, token: &Token) -> String {
+    format!("{}/{}.jsonl", dir, token.to_string())
+}
+
+/// Persists depth snapshots to disk, one json per line and one file per token.
+pub struct BookRecorder {
+    pub dir: String,
+    /// Minimum time between snapshots of a token (ms).
+    pub interval: u64,
+    /// Levels kept on each side.
+    pub depth: usize,
+    last: HashMap<Token, u64>,
+}
+
+impl BookRecorder {
+    pub fn new(dir: &str, interval: u64, depth: usize) -> Self {
+        Self {
+            dir: dir.to_string(),
+            interval,
+            depth,
+            last: HashMap::new(),
+        }
+    }
+
+    /// Appends the book unless the last snapshot of its token is too recent.
+    /// Returns whether it was written.
+    pub fn record(&mut self, timestamp: u64, book: &Book) -> Result<bool, DiError> {
+        if let Some(last) = self.last.get(&book.token) {
+            if timestamp < last + self.interval {
+                return Ok(false);
+            }
+        }
+        let mut book = book.clone();
+        book.bids.truncate(self.depth);
+        book.asks.truncate(self.depth);
+        let line = serde_json::to_string(&BookRecord {
+            timestamp,
+            book: book.clone(),
+        })
+        .map_err(|e| DiError::Message(e.to_string()))?;
+        std::fs::create_dir_all(&self.dir).map_err(|e| DiError::Message(e.to_string()))?;
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(book_file(&self.dir, &book.token))
+            .and_then(|mut file| writeln!(file, "{}", line))
+            .map_err(|e| DiError::Message(e.to_string()))?;
+        self.last.insert(book.token, timestamp);
+        Ok(true)
+    }
+}
+
+/// Recorded books of a token, sorted by time.
+#[derive(Default)]
+pub struct BookReplay {
+    pub records: Vec<BookRecord>,
+}
+
+impl BookReplay {
+    /// Invalid lines are skipped.
+    pub fn load(dir: &str, token: &Token) -> Result<BookReplay, DiError> {
+        let path = book_file(dir, token);
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| DiError::Message(format!("{}: {}", path, e)))?;
+        Ok(Self::from_records(
+            text.lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect(),
+        ))
+    }
+
+    pub fn from_records(mut records: Vec<BookRecord>) -> BookReplay {
+        records.sort_by_key(|r| r.timestamp);
+        BookReplay { records }
+    }
+
+    /// Records with `from <= timestamp < to`.
+    pub fn between(&self, from: u64, to: u64) -> &[BookRecord] {
+        let start = self.records.partition_point(|r| r.timestamp < from);
+        let end = self.records.partition_point(|r| r.timestamp < to);
+        &self.records[start..end.max(start)]
+    }
+
+    /// Latest book at or before `timestamp`.
+    pub fn at(&self, timestamp: u64) -> Option<&Book> {
+        match self.records.partition_point(|r| r.timestamp <= timestamp) {
+            0 => None,
+            i => Some(&self.records[i - 1].book),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BookRecorder, BookReplay};
+    use crate::finance::{Book, BookLine, Token};
+
+    #[test]
+    fn test_record_and_replay() {
+        let dir = std::env::temp_dir().join(format!("books_{}", std::process::id()));
+        let dir = dir.to_str().unwrap();
+        let token = Token::pair("BTC", "USDT");
+        let book = |price: f64| Book {
+            token: token.clone(),
+            bids: vec![
+                BookLine {
+                    price,
+                    quantity: 1.0,
+                },
+                BookLine {
+                    price: price - 1.0,
+                    quantity: 2.0,
+                },
+            ],
+            asks: Vec::new(),
+        };
+        let mut recorder = BookRecorder::new(dir, 1000, 1);
+        assert!(recorder.record(1000, &book(10.0)).unwrap());
+        assert!(!recorder.record(1500, &book(11.0)).unwrap());
+        assert!(recorder.record(2000, &book(12.0)).unwrap());
+        assert!(recorder.record(3500, &book(13.0)).unwrap());
+
+        let replay = BookReplay::load(dir, &token).unwrap();
+        let _ = std::fs::remove_dir_all(dir);
+        assert_eq!(replay.records.len(), 3);
+        assert_eq!(replay.records[0].book.bids.len(), 1);
+        assert_eq!(replay.between(1000, 3500).len(), 2);
+        assert!(replay.at(999).is_none());
+        assert_eq!(replay.at(2999).unwrap().bids[0].price, 12.0);
+    }
+}
@@ -0,0 +1,368 @@
+use crate::counselor::Advice;
+use crate::finance::{DiError, Order, OrderStatus, OrderType, Side, TimeInForce, Token};
+use crate::time::Date;
+use crate::trader::Trader;
+use serde::{Deserialize, Serialize};
+use ta::{indicators::AverageTrueRange, Next};
+
+/// Distance of an exit from the entry price.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BracketOffset {
+    /// Percent of the entry price.
+    Percent(f64),
+    /// Multiple of the ATR.
+    Atr(f64),
+}
+
+impl BracketOffset {
+    /// "2%" or "1.5atr"
+    pub fn from_text(text: &str) -> Option<BracketOffset> {
+        let text = text.to_lowercase();
+        if let Some(value) = text.strip_suffix('%') {
+            return value.parse::<f64>().ok().map(BracketOffset::Percent);
+        }
+        text.strip_suffix("atr")
+            .and_then(|value| value.parse::<f64>().ok())
+            .map(BracketOffset::Atr)
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            BracketOffset::Percent(p) => format!("{}%", p),
+            BracketOffset::Atr(k) => format!("{}atr", k),
+        }
+    }
+
+    /// None for ATR offsets without an ATR.
+    pub fn distance(&self, price: f64, atr: Option<f64>) -> Option<f64> {
+        match self {
+            BracketOffset::Percent(p) => Some(price * p / 100.0),
+            BracketOffset::Atr(k) => atr.map(|atr| atr * k),
+        }
+    }
+}
+
+/// Stop loss and take profit placed around an entry.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BracketTemplate {
+    pub stop_loss: BracketOffset,
+    pub take_profit: BracketOffset,
+}
+
+impl BracketTemplate {
+    /// (stop loss, take profit) prices of an entry on `side`.
+    pub fn prices(&self, side: &Side, entry: f64, atr: Option<f64>) -> Option<(f64, f64)> {
+        let stop = self.stop_loss.distance(entry, atr)?;
+        let take = self.take_profit.distance(entry, atr)?;
+        match side {
+            Side::Buy => Some((entry - stop, entry + take)),
+            Side::Sell => Some((entry + stop, entry - take)),
+        }
+    }
+}
+
+/// Last ATR of the samples.
+pub fn last_atr(period: usize, samples: &[crate::finance::Sample]) -> Option<f64> {
+    let mut atr = AverageTrueRange::new(period.max(1)).ok()?;
+    samples.iter().map(|s| atr.next(s)).last()
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum BracketState {
+    /// Waiting for the entry to fill.
+    #[default]
+    Pending,
+    /// Entry filled, stop loss resting.
+    Open,
+    /// Stop loss filled or take profit placed.
+    Closed,
+    /// Rejected or cancelled.
+    Failed,
+}
+
+/// Entry with linked stop loss and take profit orders, tracked as one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bracket {
+    pub entry: Order,
+    pub stop_loss: f64,
+    pub take_profit: f64,
+    pub state: BracketState,
+    pub entry_id: Option<u64>,
+    pub stop_id: Option<u64>,
+    pub take_id: Option<u64>,
+}
+
+impl Bracket {
+    pub fn new(entry: Order, stop_loss: f64, take_profit: f64) -> Self {
+        Self {
+            entry,
+            stop_loss,
+            take_profit,
+            state: BracketState::Pending,
+            entry_id: None,
+            stop_id: None,
+            take_id: None,
+        }
+    }
+
+    pub fn from_template(
+        entry: Order,
+        template: &BracketTemplate,
+        atr: Option<f64>,
+    ) -> Result<Self, DiError> {
+        match template.prices(&entry.side, entry.price, atr) {
+            Some((stop_loss, take_profit)) => Ok(Bracket::new(entry, stop_loss, take_profit)),
+            None => Err(DiError::Message(String::from("bracket needs the ATR"))),
+        }
+    }
+
+    /// Exits from the stop loss and take profit of the advice.
+    pub fn from_advice(entry: Order, advice: &Advice) -> Result<Self, DiError> {
+        if advice.stop_loss == 0.0 || advice.take_profit == 0.0 {
+            return Err(DiError::Message(String::from(
+                "advice without stop loss or take profit",
+            )));
+        }
+        Ok(Bracket::new(entry, advice.stop_loss, advice.take_profit))
+    }
+
+    /// Stop limit and limit orders closing the entry.
+    pub fn exit_orders(&self) -> (Order, Order) {
+        let side = match self.entry.side {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        };
+        let exit = |order_type: OrderType, price: f64, stop_price: Option<f64>| Order {
+            index: self.entry.index,
+            position_index: self.entry.position_index,
+            id: None,
+            token: self.entry.token.clone(),
+            date: Date::now(),
+            side: side.clone(),
+            quantity: self.entry.quantity,
+            price,
+            stop_price,
+            order_type,
+            tif: TimeInForce::GTC,
+        };
+        (
+            exit(OrderType::StopLimit, self.stop_loss, Some(self.stop_loss)),
+            exit(OrderType::Limit, self.take_profit, None),
+        )
+    }
+
+    pub fn is_done(&self) -> bool {
+        matches!(self.state, BracketState::Closed | BracketState::Failed)
+    }
+}
+
+/// Brackets placed through a trader. Balances can't back two resting exits, so
+/// only the stop loss rests on the exchange: once the price reaches the take
+/// profit the stop is cancelled and the take profit placed in its place.
+#[derive(Debug, Default)]
+pub struct BracketTracker {
+    pub brackets: Vec<Bracket>,
+}
+
+impl BracketTracker {
+    pub fn submit(&mut self, trader: &impl Trader, mut bracket: Bracket) -> Result<(), DiError> {
+        bracket.entry_id = Some(trader.create_order(&bracket.entry)?);
+        self.brackets.push(bracket);
+        Ok(())
+    }
+
+    /// Advances the brackets given the open orders of the account and the
+    /// last prices, returning the errors of the orders placed or cancelled.
+    /// An entry missing from the open orders is taken as filled.
+    pub fn update(
+        &mut self,
+        trader: &impl Trader,
+        open: &[OrderStatus],
+        price: impl Fn(&Token) -> Option<f64>,
+    ) -> Vec<DiError> {
+        let is_open = |id: Option<u64>| {
+            id.map_or(false, |id| {
+                open.iter().any(|status| status.order.id == Some(id as i64))
+            })
+        };
+        let mut errors = Vec::new();
+        for bracket in self.brackets.iter_mut() {
+            match bracket.state {
+                BracketState::Pending if !is_open(bracket.entry_id) => {
+                    let (stop, _) = bracket.exit_orders();
+                    match trader.create_order(&stop) {
+                        Ok(id) => {
+                            bracket.stop_id = Some(id);
+                            bracket.state = BracketState::Open;
+                        }
+                        Err(e) => {
+                            errors.push(e);
+                            bracket.state = BracketState::Failed;
+                        }
+                    }
+                }
+                BracketState::Open if !is_open(bracket.stop_id) => {
+                    bracket.state = BracketState::Closed;
+                }
+                BracketState::Open => {
+                    let reached = match (price(&bracket.entry.token), &bracket.entry.side) {
+                        (Some(price), Side::Buy) => price >= bracket.take_profit,
+                        (Some(price), Side::Sell) => price <= bracket.take_profit,
+                        (None, _) => false,
+                    };
+                    if !reached {
+                        continue;
+                    }
+                    if let Some(id) = bracket.stop_id {
+                        if let Err(e) = trader.cancel_order(&bracket.entry.token, id) {
+                            // the stop may have filled meanwhile
+                            errors.push(e);
+                            continue;
+                        }
+                    }
+                    let (_, take) = bracket.exit_orders();
+                    match trader.create_order(&take) {
+                        Ok(id) => {
+                            bracket.take_id = Some(id);
+                            bracket.state = BracketState::Closed;
+                        }
+                        Err(e) => {
+                            errors.push(e);
+                            bracket.state = BracketState::Failed;
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+        errors
+    }
+
+    /// Brackets still pending or open.
+    pub fn active(&self) -> impl Iterator<Item = &Bracket> {
+        self.brackets.iter().filter(|b| !b.is_done())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bracket, BracketOffset, BracketState, BracketTemplate, BracketTracker};
+    use crate::finance::{DiError, Order, OrderStatus, OrderType, Side, TimeInForce, Token};
+    use crate::time::Date;
+    use crate::trader::Trader;
+    use binance::model::Transaction;
+    use std::cell::RefCell;
+
+    /// Keeps placed orders open until cancelled.
+    #[derive(Default)]
+    struct PaperTrader {
+        open: RefCell<Vec<Order>>,
+        next_id: RefCell<u64>,
+    }
+
+    impl Trader for PaperTrader {
+        fn buy_order(&self, _: &Order) -> Result<Transaction, DiError> {
+            Err(DiError::NotImplemented)
+        }
+        fn sell_order(&self, _: &Order) -> Result<Transaction, DiError> {
+            Err(DiError::NotImplemented)
+        }
+        fn create_order(&self, order: &Order) -> Result<u64, DiError> {
+            *self.next_id.borrow_mut() += 1;
+            let id = *self.next_id.borrow();
+            let mut order = order.clone();
+            order.id = Some(id as i64);
+            self.open.borrow_mut().push(order);
+            Ok(id)
+        }
+        fn get_all_open_orders(&self) -> Result<Vec<OrderStatus>, DiError> {
+            Ok(self
+                .open
+                .borrow()
+                .iter()
+                .map(|order| OrderStatus {
+                    order: order.clone(),
+                    executed_qty: 0.0,
+                    status: String::from("NEW"),
+                    update_time: Date::now(),
+                    is_working: true,
+                })
+                .collect())
+        }
+        fn cancel_order(&self, _: &Token, id: u64) -> Result<(), DiError> {
+            self.open.borrow_mut().retain(|o| o.id != Some(id as i64));
+            Ok(())
+        }
+    }
+
+    impl PaperTrader {
+        fn fill(&self, id: u64) {
+            self.open.borrow_mut().retain(|o| o.id != Some(id as i64));
+        }
+    }
+
+    #[test]
+    fn test_bracket() {
+        assert_eq!(
+            BracketOffset::from_text("2%"),
+            Some(BracketOffset::Percent(2.0))
+        );
+        assert_eq!(
+            BracketOffset::from_text("1.5ATR"),
+            Some(BracketOffset::Atr(1.5))
+        );
+        assert_eq!(BracketOffset::from_text("2"), None);
+        let template = BracketTemplate {
+            stop_loss: BracketOffset::Percent(2.0),
+            take_profit: BracketOffset::Atr(2.0),
+        };
+        assert_eq!(template.prices(&Side::Buy, 100.0, None), None);
+        assert_eq!(
+            template.prices(&Side::Buy, 100.0, Some(3.0)),
+            Some((98.0, 106.0))
+        );
+        assert_eq!(
+            template.prices(&Side::Sell, 100.0, Some(3.0)),
+            Some((102.0, 94.0))
+        );
+
+        let entry = Order {
+            index: 0,
+            position_index: None,
+            id: None,
+            token: Token::pair("BTC", "USDT"),
+            date: Date::now(),
+            side: Side::Buy,
+            quantity: 1.0,
+            price: 100.0,
+            stop_price: None,
+            order_type: OrderType::Limit,
+            tif: TimeInForce::default(),
+        };
+        let trader = PaperTrader::default();
+        let mut tracker = BracketTracker::default();
+        let bracket = Bracket::from_template(entry, &template, Some(3.0)).unwrap();
+        tracker.submit(&trader, bracket).unwrap();
+        let update = |tracker: &mut BracketTracker, price: f64| {
+            let open = trader.get_all_open_orders().unwrap();
+            assert!(tracker.update(&trader, &open, |_| Some(price)).is_empty());
+        };
+        update(&mut tracker, 100.0);
+        assert_eq!(tracker.brackets[0].state, BracketState::Pending);
+        trader.fill(1);
+        update(&mut tracker, 100.0);
+        assert_eq!(tracker.brackets[0].state, BracketState::Open);
+        let open = trader.open.borrow().clone();
+        assert_eq!(open.len(), 1);
+        assert_eq!((open[0].side.clone(), open[0].price), (Side::Sell, 98.0));
+        update(&mut tracker, 105.0);
+        assert_eq!(tracker.brackets[0].state, BracketState::Open);
+        // the take profit replaces the stop loss
+        update(&mut tracker, 106.5);
+        assert_eq!(tracker.brackets[0].state, BracketState::Closed);
+        let open = trader.open.borrow().clone();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].price, 106.0);
+        assert_eq!(tracker.active().count(), 0);
+    }
+}
@@ -1,8 +1,115 @@
 use crate::cache::Cache;
-use crate::finance::{Quote, Token};
-use crate::time::{Date, TimeWindow};
-use rand::thread_rng;
-use rand_distr::{Distribution, Normal};
+use crate::finance::{Book, BookLine, MarketEvent, MarketTick, Quote, Sample, Token};
+use crate::time::{Date, TimeUnit, TimeWindow};
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
+use rand_distr::{Distribution, Normal, Poisson};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Stochastic process used to generate synthetic prices. Parameters are
+/// expressed over the whole generated period (time horizon 1).
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum PriceModel {
+    /// Geometric brownian motion.
+    #[default]
+    GeometricBrownian,
+    /// Mean-reverting Ornstein-Uhlenbeck process on the log price, pulled
+    /// towards `mean` with speed `theta`.
+    OrnsteinUhlenbeck { theta: f64, mean: f64 },
+    /// Merton jump diffusion: geometric brownian motion plus `lambda` jumps
+    /// (on average) of log size N(`jump_mu`, `jump_sigma`).
+    JumpDiffusion {
+        lambda: f64,
+        jump_mu: f64,
+        jump_sigma: f64,
+    },
+}
+
+impl PriceModel {
+    /// Model with default parameters: gbm, ou or jump.
+    pub fn from_name(name: &str) -> Option<PriceModel> {
+        match name.to_lowercase().as_str() {
+            "gbm" | "brownian" => Some(PriceModel::GeometricBrownian),
+            "ou" | "ornstein-uhlenbeck" => Some(PriceModel::OrnsteinUhlenbeck {
+                theta: 20.0,
+                mean: INITIAL_PRICE,
+            }),
+            "jump" | "merton" => Some(PriceModel::JumpDiffusion {
+                lambda: 10.0,
+                jump_mu: -0.05,
+                jump_sigma: 0.1,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            PriceModel::GeometricBrownian => String::from("gbm"),
+            PriceModel::OrnsteinUhlenbeck { .. } => String::from("ou"),
+            PriceModel::JumpDiffusion { .. } => String::from("jump"),
+        }
+    }
+}
+
+const INITIAL_PRICE: f64 = 500.0;
+/// Maximum number of simulated minutes produced by a single get_events call.
+const MAX_STREAM_STEPS: usize = 60;
+
+/// Shape of the synthetic order book around the simulated price.
+#[derive(Debug, Clone)]
+pub struct BookModel {
+    /// Distance between best bid and best ask, as a fraction of the price.
+    pub spread: f64,
+    pub levels: usize,
+    /// Distance between consecutive levels, as a fraction of the price.
+    pub step: f64,
+    /// Average quantity of the first level, deeper levels hold more.
+    pub quantity: f64,
+}
+
+impl Default for BookModel {
+    fn default() -> Self {
+        BookModel {
+            spread: 0.001,
+            levels: 10,
+            step: 0.0005,
+            quantity: 1.0,
+        }
+    }
+}
+
+pub fn generate_book(token: &Token, price: f64, model: &BookModel, rng: &mut impl Rng) -> Book {
+    let mut bids = Vec::with_capacity(model.levels);
+    let mut asks = Vec::with_capacity(model.levels);
+    let half_spread = 0.5 * model.spread * price;
+    for i in 0..model.levels {
+        let offset = half_spread + i as f64 * model.step * price;
+        let depth = model.quantity * (i + 1) as f64;
+        bids.push(BookLine {
+            price: price - offset,
+            quantity: depth * rng.gen_range(0.5..1.5),
+        });
+        asks.push(BookLine {
+            price: price + offset,
+            quantity: depth * rng.gen_range(0.5..1.5),
+        });
+    }
+    Book {
+        token: token.clone(),
+        bids,
+        asks,
+    }
+}
+
+/// Live simulation of a token.
+struct SimStream {
+    resolution: TimeUnit,
+    book: bool,
+    price: f64,
+    open_price: f64,
+    date: Date,
+}
 
 pub struct BrownianMotionMarket {
     // - Drift (mu): 0.2
@@ -11,7 +118,21 @@ pub struct BrownianMotionMarket {
     pub sigma: f64,
     // - Time horizon: 1.0
     pub time_horizon: f64,
+    /// First price of generated series and streams without history.
+    pub start_price: f64,
+    pub model: PriceModel,
+    /// Fixed seed makes every generated series identical across runs.
+    pub seed: Option<u64>,
     pub cache: Cache,
+    pub book_model: BookModel,
+    /// Wall clock time corresponding to one simulated minute of the live stream.
+    pub tick_interval: Duration,
+    /// Time step of the live stream (fraction of the time horizon), by default
+    /// one minute of a year.
+    pub stream_dt: f64,
+    streams: HashMap<Token, SimStream>,
+    rng: StdRng,
+    last_tick: Instant,
 }
 
 impl Default for BrownianMotionMarket {
@@ -20,30 +141,289 @@ impl Default for BrownianMotionMarket {
             mu: 0.2,
             sigma: 0.4,
             time_horizon: 1.0,
+            start_price: INITIAL_PRICE,
+            model: PriceModel::default(),
+            seed: None,
             cache: Cache::default(),
+            book_model: BookModel::default(),
+            tick_interval: Duration::from_secs(1),
+            stream_dt: 1.0 / (365.0 * 24.0 * 60.0),
+            streams: HashMap::new(),
+            rng: StdRng::from_entropy(),
+            last_tick: Instant::now(),
+        }
+    }
+}
+
+impl BrownianMotionMarket {
+    pub fn new(mu: f64, sigma: f64, start_price: f64) -> Self {
+        BrownianMotionMarket {
+            mu,
+            sigma,
+            start_price,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        BrownianMotionMarket {
+            seed: Some(seed),
+            rng: StdRng::seed_from_u64(seed),
+            ..Default::default()
+        }
+    }
+
+    fn stream(&mut self, token: &Token, resolution: &TimeUnit) -> &mut SimStream {
+        let last = self
+            .cache
+            .read(
+                token,
+                &TimeWindow {
+                    resolution: *resolution,
+                    count: 1,
+                },
+            )
+            .ok()
+            .and_then(|samples| samples.last().cloned());
+        let start_price = self.start_price;
+        self.streams.entry(token.clone()).or_insert_with(|| {
+            let (price, date) = match &last {
+                Some(sample) => (sample.close, Date::from_timestamp(sample.timestamp)),
+                None => (start_price, Date::now()),
+            };
+            SimStream {
+                resolution: *resolution,
+                book: false,
+                price,
+                open_price: price,
+                date,
+            }
+        })
+    }
+
+    /// Streams klines and ticks of `token`, continuing its cached history.
+    pub fn kline_service(&mut self, token: &Token, resolution: &TimeUnit) {
+        self.stream(token, resolution).resolution = *resolution;
+    }
+
+    /// Also streams a synthetic order book of `token`.
+    pub fn order_book_service(&mut self, token: &Token) {
+        let resolution = match self.streams.get(token) {
+            Some(stream) => stream.resolution,
+            None => TimeUnit::Min(1),
+        };
+        self.stream(token, &resolution).book = true;
+    }
+
+    /// Merges a new price into the last sample of the stream resolution.
+    fn update_sample(
+        &mut self,
+        token: &Token,
+        resolution: &TimeUnit,
+        date: &Date,
+        price: f64,
+    ) -> Sample {
+        let timestamp = date.timestamp() as u64;
+        let window = TimeWindow {
+            resolution: *resolution,
+            count: 1,
+        };
+        let last = self
+            .cache
+            .read(token, &window)
+            .ok()
+            .and_then(|samples| samples.last().cloned());
+        let sample = match last {
+            Some(mut sample)
+                if (timestamp as i64 - sample.timestamp as i64) < resolution.num_seconds() =>
+            {
+                sample.high = sample.high.max(price);
+                sample.low = sample.low.min(price);
+                sample.close = price;
+                sample.volume += 1;
+                sample
+            }
+            _ => Sample {
+                resolution: *resolution,
+                timestamp,
+                open: price,
+                high: price,
+                low: price,
+                close: price,
+                volume: 1,
+            },
+        };
+        let _ = self.cache.write(token, &[sample.clone()]);
+        sample
+    }
+
+    /// Advances every stream by `steps` simulated minutes.
+    pub fn advance(&mut self, steps: usize) -> Vec<MarketEvent> {
+        let mut events: Vec<MarketEvent> = Vec::new();
+        let mut ticks: Vec<MarketTick> = Vec::new();
+        let tokens: Vec<Token> = self.streams.keys().cloned().collect();
+        for token in tokens {
+            let stream = self.streams.get_mut(&token).unwrap();
+            for _ in 0..steps {
+                stream.price = step_price(
+                    &self.model,
+                    self.mu,
+                    self.sigma,
+                    self.stream_dt,
+                    stream.price,
+                    &mut self.rng,
+                );
+                stream.date += TimeWindow::minutes(1);
+            }
+            let (resolution, date, price, open_price, book) = (
+                stream.resolution,
+                stream.date,
+                stream.price,
+                stream.open_price,
+                stream.book,
+            );
+            let sample = self.update_sample(&token, &resolution, &date, price);
+            events.push(MarketEvent::KLine((token.clone(), sample)));
+            ticks.push(MarketTick {
+                token: token.clone(),
+                price,
+                change_pct: (price / open_price - 1.0) * 100.0,
+            });
+            if book {
+                events.push(MarketEvent::OrderBook(generate_book(
+                    &token,
+                    price,
+                    &self.book_model,
+                    &mut self.rng,
+                )));
+            }
+        }
+        if !ticks.is_empty() {
+            events.push(MarketEvent::Ticks(ticks));
         }
+        events
+    }
+
+    /// Events of the simulated minutes elapsed since the last call, in the
+    /// same form as the live exchange streams.
+    pub fn get_events(&mut self) -> Vec<MarketEvent> {
+        let interval = self.tick_interval.as_secs_f64();
+        if interval <= 0.0 {
+            return Vec::new();
+        }
+        let steps = (self.last_tick.elapsed().as_secs_f64() / interval) as usize;
+        if steps == 0 {
+            return Vec::new();
+        }
+        self.last_tick = Instant::now();
+        self.advance(steps.min(MAX_STREAM_STEPS))
     }
 }
 
-pub fn generate_brownian_data(mu: f64, sigma: f64, duration: &TimeWindow) -> Vec<Quote> {
+/// Price after a step of `dt` (fraction of the time horizon) following `model`.
+pub fn step_price(
+    model: &PriceModel,
+    mu: f64,
+    sigma: f64,
+    dt: f64,
+    price: f64,
+    rng: &mut impl Rng,
+) -> f64 {
+    let z: f64 = rng.sample(rand_distr::StandardNormal);
+    match model {
+        PriceModel::GeometricBrownian => {
+            // from https://github.com/nzengi/stochastic-gbm/blob/master/src/gbm.rs
+            let drift = (mu - 0.5 * sigma.powi(2)) * dt;
+            let vol_sqrt_dt = sigma * dt.sqrt();
+            price * (drift + vol_sqrt_dt * z).exp()
+        }
+        PriceModel::OrnsteinUhlenbeck { theta, mean } => {
+            // exact discretization of dx = theta (m - x) dt + sigma dW, x = ln(price)
+            let m = mean.max(f64::MIN_POSITIVE).ln();
+            let decay = (-theta * dt).exp();
+            let sd = if *theta > 0.0 {
+                sigma * ((1.0 - decay * decay) / (2.0 * theta)).sqrt()
+            } else {
+                sigma * dt.sqrt()
+            };
+            (m + (price.ln() - m) * decay + sd * z).exp()
+        }
+        PriceModel::JumpDiffusion {
+            lambda,
+            jump_mu,
+            jump_sigma,
+        } => {
+            // drift is compensated so that the expected return stays mu
+            let k = (jump_mu + 0.5 * jump_sigma.powi(2)).exp() - 1.0;
+            let drift = (mu - 0.5 * sigma.powi(2) - lambda * k) * dt;
+            let mut log_return = drift + sigma * dt.sqrt() * z;
+            if let Ok(jumps) = Poisson::new(lambda * dt) {
+                let count: f64 = jumps.sample(rng);
+                let jump_size = Normal::new(*jump_mu, jump_sigma.abs()).unwrap();
+                for _ in 0..count as usize {
+                    log_return += jump_size.sample(rng);
+                }
+            }
+            price * log_return.exp()
+        }
+    }
+}
+
+/// Price path of `n` steps following `model`, starting at `start_price`.
+pub fn generate_prices(
+    model: &PriceModel,
+    mu: f64,
+    sigma: f64,
+    start_price: f64,
+    n: usize,
+    rng: &mut impl Rng,
+) -> Vec<f64> {
+    let mut prices = Vec::with_capacity(n);
+    let dt = 1.0 / n.max(1) as f64;
+    let mut price = start_price;
+    for _ in 0..n {
+        price = step_price(model, mu, sigma, dt, price, rng);
+        prices.push(price);
+    }
+    prices
+}
+
+/// Minute resolution quotes covering `duration`, following `model`. Prices
+/// are reproducible when a `seed` is given.
+pub fn generate_model_data(
+    model: &PriceModel,
+    mu: f64,
+    sigma: f64,
+    start_price: f64,
+    duration: &TimeWindow,
+    seed: Option<u64>,
+) -> Vec<Quote> {
     // generate data in minute resolution, then sample
     let total_minutes = duration.num_minutes() as usize;
     let time_increment = TimeWindow::minutes(1);
 
+    let prices = match seed {
+        Some(seed) => generate_prices(
+            model,
+            mu,
+            sigma,
+            start_price,
+            total_minutes,
+            &mut StdRng::seed_from_u64(seed),
+        ),
+        None => generate_prices(
+            model,
+            mu,
+            sigma,
+            start_price,
+            total_minutes,
+            &mut thread_rng(),
+        ),
+    };
+
     let mut quotes = Vec::with_capacity(total_minutes);
-    let normal = Normal::new(0.0, 1.0).unwrap();
-
-    // from https://github.com/nzengi/stochastic-gbm/blob/master/src/gbm.rs
-    let dt = 1.0 / total_minutes as f64;
-    let drift = (mu - 0.5 * sigma.powi(2)) * dt;
-    let vol_sqrt_dt = sigma * dt.sqrt();
-    let mut old_price = 500.0;
-    let mut rng = thread_rng();
     let mut quote_date = Date::now();
-    for _ in 0..total_minutes {
-        let z = normal.sample(&mut rng);
-        let price = old_price * (drift + vol_sqrt_dt * z).exp();
-        old_price = price;
+    for price in prices {
         let quote = Quote {
             token: Token::Symbol("brownian".to_string()),
             biddate: quote_date.clone(),
@@ -57,3 +437,89 @@ pub fn generate_brownian_data(mu: f64, sigma: f64, duration: &TimeWindow) -> Vec
 
     quotes
 }
+
+pub fn generate_brownian_data(
+    mu: f64,
+    sigma: f64,
+    duration: &TimeWindow,
+    seed: Option<u64>,
+) -> Vec<Quote> {
+    generate_model_data(
+        &PriceModel::GeometricBrownian,
+        mu,
+        sigma,
+        INITIAL_PRICE,
+        duration,
+        seed,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_prices, BrownianMotionMarket, PriceModel};
+    use crate::finance::{MarketEvent, Order, OrderType, Side, TimeInForce, Token};
+    use crate::historical_data::HistoricalData;
+    use crate::time::{Date, TimeUnit, TimeWindow};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_seeded_prices() {
+        for name in ["gbm", "ou", "jump"] {
+            let model = PriceModel::from_name(name).unwrap();
+            let a = generate_prices(&model, 0.2, 0.4, 500.0, 500, &mut StdRng::seed_from_u64(7));
+            let b = generate_prices(&model, 0.2, 0.4, 500.0, 500, &mut StdRng::seed_from_u64(7));
+            let c = generate_prices(&model, 0.2, 0.4, 500.0, 500, &mut StdRng::seed_from_u64(8));
+            assert_eq!(a, b);
+            assert_ne!(a, c);
+            assert!(a.iter().all(|p| p.is_finite() && *p > 0.0));
+        }
+    }
+
+    #[test]
+    fn test_stream() {
+        let token = Token::Symbol(String::from("sim"));
+        let mut market = BrownianMotionMarket::with_seed(3);
+        let duration = TimeWindow {
+            resolution: TimeUnit::Hour(1),
+            count: 10,
+        };
+        market.fetch_last(&token, &duration).unwrap();
+        market.kline_service(&token, &TimeUnit::Hour(1));
+        market.order_book_service(&token);
+        let events = market.advance(3);
+        assert_eq!(events.len(), 3);
+        let mut book = None;
+        for event in events {
+            match event {
+                MarketEvent::KLine((t, sample)) => {
+                    assert_eq!(t, token);
+                    assert!(sample.low <= sample.close && sample.close <= sample.high);
+                }
+                MarketEvent::OrderBook(b) => book = Some(b),
+                MarketEvent::Ticks(ticks) => assert_eq!(ticks.len(), 1),
+                MarketEvent::Fill(_) => panic!("simulations don't trade"),
+            }
+        }
+        let book = book.unwrap();
+        assert!(book.best_bid().unwrap() < book.best_ask().unwrap());
+        let order = Order {
+            index: 0,
+            position_index: None,
+            id: None,
+            token: token.clone(),
+            date: Date::now(),
+            side: Side::Buy,
+            quantity: 1.0,
+            price: 0.0,
+            stop_price: None,
+            order_type: OrderType::Market,
+            tif: TimeInForce::default(),
+        };
+        assert_eq!(book.fill_price(&order), book.best_ask());
+        let limit = Order {
+            order_type: OrderType::Limit,
+            ..order
+        };
+        assert_eq!(book.fill_price(&limit), None);
+    }
+}
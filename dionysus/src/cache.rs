@@ -1,5 +1,6 @@
 use crate::finance::{DiError, Sample, Token};
 use crate::time::{Period, TimeUnit, TimeWindow};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 pub type SampleCache = HashMap<TimeUnit, Vec<Sample>>;
@@ -22,6 +23,14 @@ pub struct Cache {
     data: SymbolCache,
 }
 
+/// Samples of one token and resolution, as stored in snapshot files.
+#[derive(Serialize, Deserialize)]
+struct SnapshotEntry {
+    token: String,
+    resolution: TimeUnit,
+    samples: Vec<Sample>,
+}
+
 impl Cache {
     pub fn contains(&self, token: &Token, period: &Period) -> bool {
         if let Some(unit_cache) = self.data.get(KEY_STR!(token)) {
@@ -92,4 +101,93 @@ impl Cache {
         }
         Ok(())
     }
+
+    /// Number of samples to fetch to bring `duration` up to date at `now`
+    /// (seconds), counting the one still forming. None if the cache doesn't
+    /// hold the whole window.
+    pub fn missing(&self, token: &Token, duration: &TimeWindow, now: u64) -> Option<i64> {
+        let samples = self
+            .data
+            .get(KEY_STR!(token))
+            .and_then(|unit_cache| unit_cache.get(&duration.resolution))?;
+        if (samples.len() as i64) < duration.count {
+            return None;
+        }
+        let elapsed = now.saturating_sub(samples.last()?.seconds()) as i64;
+        let bars = elapsed / duration.resolution.num_seconds().max(1) + 1;
+        Some(bars.min(duration.count))
+    }
+
+    /// Writes all tokens and resolutions to `path`.
+    pub fn save(&self, path: &str) -> Result<(), DiError> {
+        let mut entries: Vec<SnapshotEntry> = Vec::new();
+        for (token, unit_cache) in &self.data {
+            for (resolution, samples) in unit_cache {
+                entries.push(SnapshotEntry {
+                    token: token.clone(),
+                    resolution: *resolution,
+                    samples: samples.clone(),
+                });
+            }
+        }
+        let file = std::fs::File::create(path).map_err(|e| DiError::Message(e.to_string()))?;
+        serde_json::to_writer(file, &entries).map_err(|e| DiError::Message(e.to_string()))
+    }
+
+    /// Reads a cache written by `save`.
+    pub fn load(path: &str) -> Result<Cache, DiError> {
+        let data = std::fs::read_to_string(path).map_err(|e| DiError::Message(e.to_string()))?;
+        let entries: Vec<SnapshotEntry> =
+            serde_json::from_str(&data).map_err(|e| DiError::Message(e.to_string()))?;
+        let mut cache = Cache::default();
+        for entry in entries {
+            cache
+                .data
+                .entry(entry.token)
+                .or_default()
+                .insert(entry.resolution, entry.samples);
+        }
+        Ok(cache)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cache;
+    use crate::finance::{Sample, Token};
+    use crate::time::{TimeUnit, TimeWindow};
+
+    #[test]
+    fn test_cache_snapshot() {
+        let token = Token::pair("BTC", "USDT");
+        let samples: Vec<Sample> = (0..10)
+            .map(|i| Sample {
+                resolution: TimeUnit::Min(1),
+                timestamp: 60 * i,
+                close: i as f64,
+                ..Default::default()
+            })
+            .collect();
+        let mut cache = Cache::default();
+        cache.write(&token, &samples).unwrap();
+        let path = std::env::temp_dir().join("dionysus_cache_snapshot.json");
+        let path = path.to_str().unwrap();
+        cache.save(path).unwrap();
+        let restored = Cache::load(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        let window = TimeWindow {
+            resolution: TimeUnit::Min(1),
+            count: 5,
+        };
+        assert_eq!(restored.read(&token, &window).unwrap().len(), 5);
+        assert_eq!(restored.missing(&token, &window, 9 * 60 + 30), Some(1));
+        assert_eq!(restored.missing(&token, &window, 12 * 60), Some(4));
+        assert_eq!(restored.missing(&token, &window, 100 * 60), Some(5));
+        let long = TimeWindow {
+            resolution: TimeUnit::Min(1),
+            count: 50,
+        };
+        assert_eq!(restored.missing(&token, &long, 9 * 60), None);
+    }
 }
@@ -1,13 +1,16 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    analytics::spread_zscore,
+    bracket::last_atr,
     finance::{DiError, OrderType, Quote, Sample, TimeInForce, Token, F64},
-    indicators::{Indicator, IndicatorData},
-    time::Date,
+    indicators::{Indicator, IndicatorData, MovingAverageType},
+    time::{Date, TimeUnit},
     INFO,
 };
 use slog::slog_info;
 use std::cmp::Ordering;
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Crossover {
@@ -101,6 +104,15 @@ pub enum Signal {
     None,
 }
 
+/// Second leg of an advice. The hedge is traded on the opposite side of the
+/// signal, `ratio` units of the hedge token per unit of the advised token.
+#[derive(Default, Debug, Clone)]
+pub struct Hedge {
+    pub token: Token,
+    pub ratio: f64,
+    pub price: f64,
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct Advice {
     pub signal: Signal,
@@ -109,9 +121,87 @@ pub struct Advice {
     pub stop_loss: f64,
     pub take_profit: f64,
     pub tif: TimeInForce,
+    pub hedge: Option<Hedge>,
+    /// Price distance of a stop trailing the high of the bought position.
+    pub trailing: Option<f64>,
+}
+
+/// When the DCA counselor buys.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum DcaSchedule {
+    /// Every n bars, counted from the epoch so the schedule does not depend
+    /// on the loaded window.
+    Every(usize),
+    /// On the bar containing this UTC (hour, minute) of each day.
+    At((u32, u32)),
+}
+
+impl DcaSchedule {
+    pub fn from_words(words: &[&str]) -> Option<DcaSchedule> {
+        match (words.first()?.to_uppercase().as_str(), words.get(1)) {
+            ("EVERY", Some(n)) => match n.parse::<usize>() {
+                Ok(n) if n > 0 => Some(DcaSchedule::Every(n)),
+                _ => None,
+            },
+            ("AT", Some(time)) => {
+                let mut parts = time.split(':');
+                let hour = parts.next()?.parse::<u32>().ok()?;
+                let minute = match parts.next() {
+                    Some(minute) => minute.parse::<u32>().ok()?,
+                    None => 0,
+                };
+                match hour < 24 && minute < 60 {
+                    true => Some(DcaSchedule::At((hour, minute))),
+                    false => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            DcaSchedule::Every(n) => format!("every {}", n),
+            DcaSchedule::At((hour, minute)) => format!("at {:02}:{:02}", hour, minute),
+        }
+    }
+}
+
+/// Stop loss and take profit as multiples of the ATR of `period`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AtrExits {
+    pub period: usize,
+    pub stop_loss: f64,
+    pub take_profit: f64,
+}
+
+impl AtrExits {
+    /// (stop loss, take profit) of an entry at `price` following `signal`,
+    /// None without a signal.
+    pub fn prices(&self, signal: &Signal, price: f64, atr: f64) -> Option<(f64, f64)> {
+        let (stop, take) = (self.stop_loss * atr, self.take_profit * atr);
+        match signal {
+            Signal::Buy => Some((price - stop, price + take)),
+            Signal::Sell => Some((price + stop, price - take)),
+            Signal::None => None,
+        }
+    }
+}
+
+impl Advice {
+    /// Replaces the exits with ones `exits` ATRs away from the stop price,
+    /// keeping them when there is no signal or ATR.
+    pub fn set_atr_exits(&mut self, exits: &AtrExits, history: &[Sample]) {
+        let prices = last_atr(exits.period, history)
+            .and_then(|atr| exits.prices(&self.signal, self.stop_price, atr));
+        if let Some((stop_loss, take_profit)) = prices {
+            self.stop_loss = stop_loss;
+            self.take_profit = take_profit;
+        }
+    }
 }
 
-#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Counselor {
     #[default]
     Trace,
@@ -121,48 +211,80 @@ pub enum Counselor {
     EMACross((usize, usize)),
     RSI((usize, F64)),
     Tyche(usize),
+    /// (efficiency ratio period, fast period, slow period)
+    KAMASlope((usize, usize, usize)),
+    /// (hedge token, lookback, entry z-score, exit z-score)
+    PairsTrading((Token, usize, F64, F64)),
+    /// Buys on a schedule regardless of price (dollar-cost averaging).
+    DCA(DcaSchedule),
 }
 
 pub fn match_oracle_from_text(words: &[&str]) -> Option<Counselor> {
-    match words[0].to_uppercase().as_str() {
+    let arg = |i: usize| words.get(i).copied().unwrap_or("");
+    match arg(0).to_uppercase().as_str() {
         "MEAN-REVERSION" => {
-            if let (Ok(n), Ok(w)) = (words[1].parse::<usize>(), words[2].parse::<f64>()) {
+            if let (Ok(n), Ok(w)) = (arg(1).parse::<usize>(), arg(2).parse::<f64>()) {
                 return Some(Counselor::MeanReversion((n, w.into())));
             }
         }
         "MACD-CROSSOVER" => {
             if let (Ok(fp), Ok(sp), Ok(ss)) = (
-                words[1].parse::<usize>(),
-                words[2].parse::<usize>(),
-                words[3].parse::<usize>(),
+                arg(1).parse::<usize>(),
+                arg(2).parse::<usize>(),
+                arg(3).parse::<usize>(),
             ) {
                 return Some(Counselor::MACDCrossover((fp, sp, ss)));
             }
         }
         "MACD-ZERO-CROSS" => {
             if let (Ok(fp), Ok(sp), Ok(ss)) = (
-                words[1].parse::<usize>(),
-                words[2].parse::<usize>(),
-                words[3].parse::<usize>(),
+                arg(1).parse::<usize>(),
+                arg(2).parse::<usize>(),
+                arg(3).parse::<usize>(),
             ) {
                 return Some(Counselor::MACDZeroCross((fp, sp, ss)));
             }
         }
         "EMA-CROSS" => {
-            if let (Ok(fp), Ok(sp)) = (words[1].parse::<usize>(), words[2].parse::<usize>()) {
+            if let (Ok(fp), Ok(sp)) = (arg(1).parse::<usize>(), arg(2).parse::<usize>()) {
                 return Some(Counselor::EMACross((fp, sp)));
             }
         }
         "RSI" => {
-            if let (Ok(n), Ok(w)) = (words[1].parse::<usize>(), words[2].parse::<f64>()) {
+            if let (Ok(n), Ok(w)) = (arg(1).parse::<usize>(), arg(2).parse::<f64>()) {
                 return Some(Counselor::RSI((n, w.into())));
             }
         }
+        "KAMA-SLOPE" => {
+            if let (Ok(n), Ok(fast), Ok(slow)) = (
+                arg(1).parse::<usize>(),
+                arg(2).parse::<usize>(),
+                arg(3).parse::<usize>(),
+            ) {
+                return Some(Counselor::KAMASlope((n, fast, slow)));
+            }
+        }
         "TYCHE" => {
-            if let Ok(n) = words[1].parse::<usize>() {
+            if let Ok(n) = arg(1).parse::<usize>() {
                 return Some(Counselor::Tyche(n));
             }
         }
+        "PAIRS" => {
+            if let (Some(symbol), Some(n), Some(entry), Some(exit)) = (
+                words.get(1),
+                words.get(2).and_then(|w| w.parse::<usize>().ok()),
+                words.get(3).and_then(|w| w.parse::<f64>().ok()),
+                words.get(4).and_then(|w| w.parse::<f64>().ok()),
+            ) {
+                return Some(Counselor::PairsTrading((
+                    Token::from_string(&symbol.to_string()),
+                    n,
+                    entry.into(),
+                    exit.into(),
+                )));
+            }
+        }
+        "DCA" => return DcaSchedule::from_words(&words[1..]).map(Counselor::DCA),
         "TRACE" => return Some(Counselor::Trace),
         _ => (),
     };
@@ -179,6 +301,41 @@ impl Counselor {
             Counselor::EMACross((_, sp)) => *sp,
             Counselor::RSI((n, _)) => *n,
             Counselor::Tyche(n) => *n,
+            Counselor::KAMASlope((n, _, _)) => *n + 2,
+            Counselor::PairsTrading((_, n, _, _)) => *n,
+            Counselor::DCA(_) => 1,
+        }
+    }
+    /// Token whose history this counselor needs besides the traded one.
+    pub fn hedge_token(&self) -> Option<Token> {
+        match self {
+            Counselor::PairsTrading((token, _, _, _)) => Some(token.clone()),
+            _ => None,
+        }
+    }
+    /// Same as `run`, with access to the histories of other tokens (legs) and
+    /// to the hedge balances of the strategy (negative when short).
+    pub fn run_with(
+        &self,
+        quote: &Quote,
+        history: &[Sample],
+        legs: &HashMap<Token, &[Sample]>,
+        hedges: &HashMap<Token, f64>,
+    ) -> Result<Advice, DiError> {
+        match self {
+            Counselor::PairsTrading((token, n, entry, exit)) => match legs.get(token) {
+                Some(leg) => run_pairs_trading(
+                    token,
+                    *n,
+                    entry.value,
+                    exit.value,
+                    history,
+                    leg,
+                    *hedges.get(token).unwrap_or(&0.0),
+                ),
+                None => Err(DiError::NotFound),
+            },
+            _ => self.run(quote, history),
         }
     }
     pub fn run(&self, quote: &Quote, history: &[Sample]) -> Result<Advice, DiError> {
@@ -194,6 +351,10 @@ impl Counselor {
             Counselor::EMACross((fp, sp)) => run_ema_cross(*fp, *sp, quote, history),
             Counselor::RSI((n, w)) => run_rsi(*n, w.value, quote, history),
             Counselor::Tyche(n) => run_tyche(*n, quote, history),
+            Counselor::KAMASlope((n, fast, slow)) => run_kama_slope(*n, *fast, *slow, history),
+            Counselor::DCA(schedule) => run_dca(schedule, quote, history),
+            // the hedge leg is only available through run_with
+            Counselor::PairsTrading(_) => Ok(Advice::default()),
         }
     }
     pub fn run_series(&self, samples: &[Sample]) -> Result<Vec<Advice>, DiError> {
@@ -222,12 +383,18 @@ impl Counselor {
             }
             Counselor::MACDCrossover((fp, sp, ss)) => {
                 vec![Indicator::MovingAverageConvergenceDivergence((
-                    *fp, *sp, *ss,
+                    *fp,
+                    *sp,
+                    *ss,
+                    MovingAverageType::default(),
                 ))]
             }
             Counselor::MACDZeroCross((fp, sp, ss)) => {
                 vec![Indicator::MovingAverageConvergenceDivergence((
-                    *fp, *sp, *ss,
+                    *fp,
+                    *sp,
+                    *ss,
+                    MovingAverageType::default(),
                 ))]
             }
             Counselor::EMACross((fp, sp)) => {
@@ -242,9 +409,38 @@ impl Counselor {
             Counselor::Tyche(n) => {
                 vec![Indicator::ExponentialMovingAverage(*n)]
             }
+            Counselor::KAMASlope((n, fast, slow)) => {
+                vec![Indicator::KaufmanAdaptiveMovingAverage((*n, *fast, *slow))]
+            }
             _ => Vec::new(),
         }
     }
+    /// Text form accepted by match_oracle_from_text.
+    pub fn to_text(&self) -> String {
+        match &self {
+            Counselor::Trace => String::from("trace"),
+            Counselor::MeanReversion((n, w)) => format!("mean-reversion {} {}", n, w.value),
+            Counselor::MACDCrossover((fp, sp, ss)) => {
+                format!("macd-crossover {} {} {}", fp, sp, ss)
+            }
+            Counselor::MACDZeroCross((fp, sp, ss)) => {
+                format!("macd-zero-cross {} {} {}", fp, sp, ss)
+            }
+            Counselor::EMACross((fp, sp)) => format!("ema-cross {} {}", fp, sp),
+            Counselor::RSI((n, w)) => format!("rsi {} {}", n, w.value),
+            Counselor::Tyche(n) => format!("tyche {}", n),
+            Counselor::KAMASlope((n, fast, slow)) => format!("kama-slope {} {} {}", n, fast, slow),
+            Counselor::PairsTrading((token, n, entry, exit)) => format!(
+                "pairs {} {} {} {}",
+                token.to_string(),
+                n,
+                entry.value,
+                exit.value
+            ),
+            Counselor::DCA(schedule) => format!("dca {}", schedule.name()),
+        }
+    }
+
     pub fn name(&self) -> String {
         match &self {
             Counselor::Trace => format!("trace"),
@@ -260,6 +456,13 @@ impl Counselor {
             }
             Counselor::RSI((n, w)) => format!("rsi({:?}, {:?})", n, w),
             Counselor::Tyche(n) => format!("tyche({})", n),
+            Counselor::KAMASlope((n, fast, slow)) => {
+                format!("kama-slope({}, {}, {})", n, fast, slow)
+            }
+            Counselor::PairsTrading((token, n, entry, exit)) => {
+                format!("pairs({}, {}, {:?}, {:?})", token.name(), n, entry, exit)
+            }
+            Counselor::DCA(schedule) => format!("dca({})", schedule.name()),
         }
     }
 }
@@ -313,8 +516,12 @@ fn run_macd_crossover(
     _quote: &Quote,
     history: &[Sample],
 ) -> Result<Advice, DiError> {
-    let macd_i =
-        Indicator::MovingAverageConvergenceDivergence((fast_period, slow_period, signal_period));
+    let macd_i = Indicator::MovingAverageConvergenceDivergence((
+        fast_period,
+        slow_period,
+        signal_period,
+        MovingAverageType::default(),
+    ));
     let mut crossover = Crossover::Equal;
     if let Ok(IndicatorData::Matrix(macd)) = macd_i.compute_series(history) {
         crossover = compute_crossover(&macd[0][..], &macd[1][..], |a, b| {
@@ -350,8 +557,12 @@ fn run_macd_zero_cross(
     _quote: &Quote,
     history: &[Sample],
 ) -> Result<Advice, DiError> {
-    let macd_i =
-        Indicator::MovingAverageConvergenceDivergence((fast_period, slow_period, signal_period));
+    let macd_i = Indicator::MovingAverageConvergenceDivergence((
+        fast_period,
+        slow_period,
+        signal_period,
+        MovingAverageType::default(),
+    ));
     let mut crossover = Crossover::Equal;
     if let Ok(IndicatorData::Matrix(macd)) = macd_i.compute_series(history) {
         crossover = compute_zero_cross(&macd[0][..]);
@@ -472,11 +683,230 @@ fn run_tyche(n: usize, quote: &Quote, history: &[Sample]) -> Result<Advice, DiEr
     Ok(advice)
 }
 
+/// Buys when the KAMA turns up and sells when it turns down.
+fn run_kama_slope(
+    n: usize,
+    fast: usize,
+    slow: usize,
+    history: &[Sample],
+) -> Result<Advice, DiError> {
+    let kama =
+        match Indicator::KaufmanAdaptiveMovingAverage((n, fast, slow)).compute_series(history) {
+            Ok(IndicatorData::Vector(v)) => v,
+            Ok(_) => return Err(DiError::Error),
+            Err(e) => return Err(e),
+        };
+    let slope: Vec<f64> = kama.windows(2).map(|w| w[1] - w[0]).collect();
+    let last_sample = history.last().unwrap();
+
+    let mut advice = Advice::default();
+    if slope.len() < 2 {
+        return Ok(advice);
+    }
+    match compute_zero_cross(&slope[..]) {
+        Crossover::CrossingUpwards => {
+            advice.signal = Signal::Buy;
+            advice.stop_price = last_sample.high;
+            advice.stop_loss = last_sample.low;
+            advice.take_profit = advice.stop_price + (advice.stop_price - advice.stop_loss);
+        }
+        Crossover::CrossingDownwards => {
+            advice.signal = Signal::Sell;
+            advice.stop_price = last_sample.low;
+            advice.stop_loss = last_sample.high;
+            advice.take_profit = advice.stop_price - (advice.stop_loss - advice.stop_price);
+        }
+        _ => (),
+    }
+    Ok(advice)
+}
+
+/// Buys at the quote on the bars of the schedule, never sells.
+fn run_dca(schedule: &DcaSchedule, quote: &Quote, history: &[Sample]) -> Result<Advice, DiError> {
+    const DAY: u64 = 24 * 60 * 60;
+    let last_sample = history.last().ok_or(DiError::NotFound)?;
+    let start = last_sample.seconds();
+    let bar = last_sample.resolution.num_seconds().max(1) as u64;
+    let buy = match schedule {
+        DcaSchedule::Every(n) => (start / bar) % (*n).max(1) as u64 == 0,
+        DcaSchedule::At((hour, minute)) => {
+            let time = (*hour as u64 * 60 + *minute as u64) * 60;
+            let since_midnight = start - TimeUnit::Day(1).session_start(start);
+            // the bar may start the day before the time
+            (time + DAY - since_midnight) % DAY < bar
+        }
+    };
+    let mut advice = Advice::default();
+    if buy {
+        advice.signal = Signal::Buy;
+        advice.stop_price = quote.ask.unwrap_or(last_sample.close);
+    }
+    Ok(advice)
+}
+
+/// Long the advised token (short the hedge) when the log-price spread is
+/// `entry` deviations bellow its mean, and unwind once it recovers above `-exit`.
+/// The unwind is only advised while the hedge is short (`held` < 0).
+fn run_pairs_trading(
+    token: &Token,
+    n: usize,
+    entry: f64,
+    exit: f64,
+    history: &[Sample],
+    leg: &[Sample],
+    held: f64,
+) -> Result<Advice, DiError> {
+    let (z, beta) = match spread_zscore(history, leg, n) {
+        Some(r) => r,
+        None => return Err(DiError::NotFound),
+    };
+    let (last_sample, last_leg) = match (history.last(), leg.last()) {
+        (Some(a), Some(b)) => (a, b),
+        _ => return Err(DiError::NotFound),
+    };
+
+    let mut advice = Advice::default();
+    if z < -entry {
+        advice.signal = Signal::Buy;
+    } else if z > -exit && held < 0.0 {
+        advice.signal = Signal::Sell;
+    } else {
+        return Ok(advice);
+    }
+    advice.stop_price = last_sample.close;
+    advice.hedge = Some(Hedge {
+        token: token.clone(),
+        ratio: beta * last_sample.close / last_leg.close,
+        price: last_leg.close,
+    });
+    Ok(advice)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::counselor::Crossover;
+    use crate::counselor::{
+        match_oracle_from_text, Advice, AtrExits, Counselor, Crossover, DcaSchedule, Signal,
+    };
+    use crate::finance::{Sample, Token};
+    use crate::time::TimeUnit;
 
-    use super::compute_crossover_s;
+    use super::{compute_crossover_s, run_pairs_trading};
+
+    #[test]
+    fn test_pairs_trading() {
+        let token = Token::pair("ETH", "USDT");
+        let sample = |i: usize, close: f64| Sample {
+            resolution: TimeUnit::Hour(1),
+            timestamp: i as u64 * 3600,
+            close,
+            ..Default::default()
+        };
+        let leg: Vec<Sample> = (0..20)
+            .map(|i| sample(i, 100.0 + 3.0 * i as f64 + (i % 3) as f64))
+            .collect();
+        // spread alternating around its mean, back at it in the last bar
+        let history: Vec<Sample> = leg
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let e = match i {
+                    19 => 0.0,
+                    _ if i % 2 == 0 => 0.01,
+                    _ => -0.01,
+                };
+                sample(i, s.close * f64::exp(e))
+            })
+            .collect();
+        let advice = |held: f64| {
+            run_pairs_trading(&token, 20, 2.0, 0.5, &history, &leg, held)
+                .unwrap()
+                .signal
+        };
+        // nothing to unwind on a flat book
+        assert_eq!(advice(0.0), Signal::None);
+        assert_eq!(advice(-1.0), Signal::Sell);
+    }
+
+    #[test]
+    fn test_counselor_text() {
+        let counselors = vec![
+            Counselor::MeanReversion((20, 2.5.into())),
+            Counselor::MACDCrossover((12, 26, 9)),
+            Counselor::RSI((14, 30.0.into())),
+            Counselor::KAMASlope((10, 2, 30)),
+            Counselor::PairsTrading((Token::pair("ETH", "USDT"), 30, 2.0.into(), 0.5.into())),
+            Counselor::DCA(DcaSchedule::Every(24)),
+            Counselor::DCA(DcaSchedule::At((9, 5))),
+        ];
+        for counselor in counselors {
+            let text = counselor.to_text();
+            let words: Vec<&str> = text.split_whitespace().collect();
+            assert_eq!(match_oracle_from_text(&words), Some(counselor));
+        }
+        assert_eq!(match_oracle_from_text(&["rsi", "14"]), None);
+        assert_eq!(match_oracle_from_text(&[]), None);
+    }
+
+    #[test]
+    fn test_dca() {
+        // hourly bars from 2024-01-01 00:00:00 UTC
+        let samples: Vec<Sample> = (0..24)
+            .map(|i| Sample {
+                resolution: TimeUnit::Hour(1),
+                timestamp: 1704067200 + i * 3600,
+                close: 100.0,
+                ..Default::default()
+            })
+            .collect();
+        let buys = |schedule: DcaSchedule| -> Vec<usize> {
+            Counselor::DCA(schedule)
+                .run_series(&samples)
+                .unwrap()
+                .iter()
+                .enumerate()
+                .filter(|(_, advice)| advice.signal == Signal::Buy)
+                .map(|(i, _)| i)
+                .collect()
+        };
+        assert_eq!(buys(DcaSchedule::Every(4)), vec![4, 8, 12, 16, 20]);
+        assert_eq!(buys(DcaSchedule::At((2, 30))), vec![2]);
+        assert_eq!(buys(DcaSchedule::At((0, 0))), Vec::<usize>::new());
+        assert_eq!(match_oracle_from_text(&["dca", "at", "25:00"]), None);
+        assert_eq!(match_oracle_from_text(&["dca", "every", "0"]), None);
+    }
+
+    #[test]
+    fn test_atr_exits() {
+        // true range of 2 on every bar
+        let samples: Vec<Sample> = (0..20)
+            .map(|i| Sample {
+                timestamp: i,
+                high: 101.0,
+                low: 99.0,
+                close: 100.0,
+                ..Default::default()
+            })
+            .collect();
+        let exits = AtrExits {
+            period: 14,
+            stop_loss: 1.5,
+            take_profit: 3.0,
+        };
+        let exits_of = |signal: Signal| {
+            let mut advice = Advice {
+                signal,
+                stop_price: 100.0,
+                ..Default::default()
+            };
+            advice.set_atr_exits(&exits, &samples);
+            // rounded, the averaged true range may be off by a few ulps
+            let round = |v: f64| (v * 1e6).round() / 1e6;
+            (round(advice.stop_loss), round(advice.take_profit))
+        };
+        assert_eq!(exits_of(Signal::Buy), (97.0, 106.0));
+        assert_eq!(exits_of(Signal::Sell), (103.0, 94.0));
+        assert_eq!(exits_of(Signal::None), (0.0, 0.0));
+    }
 
     #[test]
     fn test_crossover() {
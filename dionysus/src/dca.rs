@@ -0,0 +1,154 @@
+use crate::finance::{Order, OrderType, Side, TimeInForce, Token};
+use crate::time::{Date, TimeUnit};
+
+/// Recurring market buy of `notional` (quote currency) of a pair.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DcaPlan {
+    pub id: usize,
+    pub token: Token,
+    pub notional: f64,
+    pub interval: TimeUnit,
+    /// Time (seconds) of the next purchase.
+    pub next: i64,
+}
+
+impl DcaPlan {
+    /// Market buy of the notional at `price`, `round` fits the quantity to the
+    /// exchange lot size.
+    pub fn order(&self, price: f64, round: impl Fn(f64) -> f64) -> Order {
+        Order {
+            index: 0,
+            position_index: None,
+            id: None,
+            token: self.token.clone(),
+            date: Date::now(),
+            side: Side::Buy,
+            quantity: round(self.notional / price),
+            price,
+            stop_price: None,
+            order_type: OrderType::Market,
+            tif: TimeInForce::default(),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        format!(
+            "#{} {} {} every {}",
+            self.id,
+            self.token.name(),
+            self.notional,
+            self.interval.name()
+        )
+    }
+}
+
+/// Purchase made, or attempted, by a plan.
+#[derive(Debug, Clone)]
+pub struct DcaPurchase {
+    pub plan: usize,
+    pub order: Order,
+    pub order_id: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// Plans buying at fixed wall clock times: every 1d buys at 00:00 UTC, every
+/// 4h at 00:00, 04:00 and so on.
+#[derive(Debug, Default)]
+pub struct DcaScheduler {
+    pub plans: Vec<DcaPlan>,
+    /// Past purchases, oldest first.
+    pub purchases: Vec<DcaPurchase>,
+    next_id: usize,
+}
+
+/// First multiple of `interval` after `now` (seconds).
+fn next_time(interval: &TimeUnit, now: i64) -> i64 {
+    let length = interval.num_seconds().max(1);
+    (now.div_euclid(length) + 1) * length
+}
+
+impl DcaScheduler {
+    /// Schedules a plan, returning its id. Plans start at the next interval.
+    pub fn add(&mut self, token: &Token, notional: f64, interval: TimeUnit, now: i64) -> usize {
+        self.next_id += 1;
+        self.plans.push(DcaPlan {
+            id: self.next_id,
+            token: token.clone(),
+            notional,
+            interval,
+            next: next_time(&interval, now),
+        });
+        self.next_id
+    }
+
+    pub fn remove(&mut self, id: usize) -> bool {
+        let count = self.plans.len();
+        self.plans.retain(|plan| plan.id != id);
+        self.plans.len() < count
+    }
+
+    /// Plans due at `now` (seconds), moved to their next purchase. Purchases
+    /// missed while offline are not made up.
+    pub fn due(&mut self, now: i64) -> Vec<DcaPlan> {
+        let mut due = Vec::new();
+        for plan in self.plans.iter_mut().filter(|plan| plan.next <= now) {
+            due.push(plan.clone());
+            plan.next = next_time(&plan.interval, now);
+        }
+        due
+    }
+
+    /// The next `count` purchases of all plans, as (time, plan), soonest first.
+    pub fn upcoming(&self, count: usize) -> Vec<(i64, &DcaPlan)> {
+        let mut upcoming: Vec<(i64, &DcaPlan)> = Vec::new();
+        for plan in &self.plans {
+            let length = plan.interval.num_seconds().max(1);
+            upcoming.extend((0..count as i64).map(|i| (plan.next + i * length, plan)));
+        }
+        upcoming.sort_by_key(|(time, plan)| (*time, plan.id));
+        upcoming.truncate(count);
+        upcoming
+    }
+
+    pub fn record(&mut self, purchase: DcaPurchase) {
+        self.purchases.push(purchase);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DcaScheduler;
+    use crate::finance::{OrderType, Token};
+    use crate::time::TimeUnit;
+
+    #[test]
+    fn test_dca_scheduler() {
+        let btc = Token::pair("BTC", "USDT");
+        let eth = Token::pair("ETH", "USDT");
+        let mut scheduler = DcaScheduler::default();
+        let day = scheduler.add(&btc, 25.0, TimeUnit::Day(1), 86400 + 100);
+        let hours = scheduler.add(&eth, 10.0, TimeUnit::Hour(4), 86400 + 100);
+        assert_eq!(scheduler.plans[0].next, 2 * 86400);
+        assert_eq!(scheduler.plans[1].next, 86400 + 4 * 3600);
+        let upcoming = scheduler.upcoming(3);
+        let ids: Vec<usize> = upcoming.iter().map(|(_, plan)| plan.id).collect();
+        assert_eq!(ids, vec![hours, hours, hours]);
+        assert_eq!(upcoming[2].0, 86400 + 12 * 3600);
+        assert!(scheduler.due(86400 + 4 * 3600 - 1).is_empty());
+        let due = scheduler.due(86400 + 4 * 3600);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].id, hours);
+        assert!(scheduler.due(86400 + 4 * 3600 + 1).is_empty());
+        // offline for two days, each plan buys once
+        let due = scheduler.due(4 * 86400 + 60);
+        assert_eq!(due.len(), 2);
+        assert_eq!(scheduler.plans[0].next, 5 * 86400);
+        assert_eq!(scheduler.plans[1].next, 4 * 86400 + 4 * 3600);
+        let order = due[0].order(50000.0, |q| (q * 1e5).floor() / 1e5);
+        assert!(matches!(order.order_type, OrderType::Market));
+        assert!((order.quantity - 0.0005).abs() < 1e-12);
+        assert!(scheduler.remove(day));
+        assert!(!scheduler.remove(day));
+        assert_eq!(scheduler.plans.len(), 1);
+    }
+}
@@ -0,0 +1,368 @@
+use crate::finance::{DiError, Sample, F64};
+use crate::indicators::{match_indicator_from_text, Indicator, IndicatorData, IndicatorDomain};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl Operator {
+    fn symbol(&self) -> char {
+        match self {
+            Operator::Add => '+',
+            Operator::Sub => '-',
+            Operator::Mul => '*',
+            Operator::Div => '/',
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Operator::Add | Operator::Sub => 0,
+            Operator::Mul | Operator::Div => 1,
+        }
+    }
+
+    /// Division by zero gives zero, so curves stay finite.
+    fn apply(&self, a: f64, b: f64) -> f64 {
+        match self {
+            Operator::Add => a + b,
+            Operator::Sub => a - b,
+            Operator::Mul => a * b,
+            Operator::Div if b == 0.0 => 0.0,
+            Operator::Div => a / b,
+        }
+    }
+}
+
+/// Arithmetic over indicator series, e.g. "ema(20)-ema(50)". Indicators are
+/// written as calls of their text form, "macd(12,26,9)" for "macd 12 26 9";
+/// indicators with several series contribute their first one.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Expression {
+    Number(F64),
+    Indicator(Box<Indicator>),
+    Neg(Box<Expression>),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+}
+
+#[derive(Clone)]
+enum Token {
+    Number(f64),
+    Indicator(Indicator),
+    Operator(Operator),
+    Open,
+    Close,
+}
+
+fn tokenize(text: &str) -> Option<Vec<Token>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            // quotes are allowed around the whole expression
+            _ if c.is_whitespace() || c == '"' => i += 1,
+            '+' | '-' | '*' | '/' => {
+                tokens.push(Token::Operator(match c {
+                    '+' => Operator::Add,
+                    '-' => Operator::Sub,
+                    '*' => Operator::Mul,
+                    _ => Operator::Div,
+                }));
+                i += 1;
+            }
+            '(' => {
+                tokens.push(Token::Open);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::Close);
+                i += 1;
+            }
+            _ if c.is_ascii_digit() || c == '.' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let number: String = chars[start..i].iter().collect();
+                tokens.push(Token::Number(number.parse::<f64>().ok()?));
+            }
+            _ if c.is_alphabetic() || c == '%' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '%') {
+                    i += 1;
+                }
+                let name: String = chars[start..i].iter().collect();
+                // the arguments run to the closing parenthesis
+                if chars.get(i) != Some(&'(') {
+                    return None;
+                }
+                let end = i + chars[i..].iter().position(|c| *c == ')')?;
+                let args: String = chars[i + 1..end].iter().collect();
+                let mut words = vec![name.as_str()];
+                words.extend(args.split(',').map(|a| a.trim()).filter(|a| !a.is_empty()));
+                tokens.push(Token::Indicator(match_indicator_from_text(&words)?));
+                i = end + 1;
+            }
+            _ => return None,
+        }
+    }
+    Some(tokens)
+}
+
+/// Recursive descent over the tokens, `pos` is the next token.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn operator(&self, operators: &[Operator]) -> Option<Operator> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Operator(op)) if operators.contains(op) => Some(*op),
+            _ => None,
+        }
+    }
+
+    /// term (('+'|'-') term)*
+    fn sum(&mut self) -> Option<Expression> {
+        let mut e = self.product()?;
+        while let Some(op) = self.operator(&[Operator::Add, Operator::Sub]) {
+            self.pos += 1;
+            e = Expression::Binary(op, Box::new(e), Box::new(self.product()?));
+        }
+        Some(e)
+    }
+
+    /// factor (('*'|'/') factor)*
+    fn product(&mut self) -> Option<Expression> {
+        let mut e = self.factor()?;
+        while let Some(op) = self.operator(&[Operator::Mul, Operator::Div]) {
+            self.pos += 1;
+            e = Expression::Binary(op, Box::new(e), Box::new(self.factor()?));
+        }
+        Some(e)
+    }
+
+    /// '-' factor | number | indicator | '(' sum ')'
+    fn factor(&mut self) -> Option<Expression> {
+        let token = self.tokens.get(self.pos)?.clone();
+        self.pos += 1;
+        match token {
+            Token::Operator(Operator::Sub) => Some(Expression::Neg(Box::new(self.factor()?))),
+            Token::Number(n) => Some(Expression::Number(n.into())),
+            Token::Indicator(i) => Some(Expression::Indicator(Box::new(i))),
+            Token::Open => {
+                let e = self.sum()?;
+                match self.tokens.get(self.pos) {
+                    Some(Token::Close) => {
+                        self.pos += 1;
+                        Some(e)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Value of a (sub)expression, series are aligned to the last sample.
+enum Value {
+    Scalar(f64),
+    Series(Vec<f64>),
+}
+
+impl Value {
+    fn combine(self, other: Value, op: Operator) -> Value {
+        match (self, other) {
+            (Value::Scalar(a), Value::Scalar(b)) => Value::Scalar(op.apply(a, b)),
+            (Value::Series(a), Value::Scalar(b)) => {
+                Value::Series(a.iter().map(|a| op.apply(*a, b)).collect())
+            }
+            (Value::Scalar(a), Value::Series(b)) => {
+                Value::Series(b.iter().map(|b| op.apply(a, *b)).collect())
+            }
+            (Value::Series(a), Value::Series(b)) => {
+                let n = a.len().min(b.len());
+                Value::Series(
+                    a[a.len() - n..]
+                        .iter()
+                        .zip(&b[b.len() - n..])
+                        .map(|(a, b)| op.apply(*a, *b))
+                        .collect(),
+                )
+            }
+        }
+    }
+}
+
+impl Expression {
+    pub fn parse(text: &str) -> Option<Expression> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            pos: 0,
+        };
+        let e = parser.sum()?;
+        match parser.pos == parser.tokens.len() {
+            true => Some(e),
+            false => None,
+        }
+    }
+
+    /// Text accepted by parse, without spaces.
+    pub fn to_text(&self) -> String {
+        match self {
+            Expression::Number(n) => format!("{}", n.value),
+            Expression::Indicator(i) => {
+                let text = i.to_text();
+                let mut words = text.split_whitespace();
+                let name = words.next().unwrap_or_default();
+                format!("{}({})", name, words.collect::<Vec<&str>>().join(","))
+            }
+            Expression::Neg(e) => match e.as_ref() {
+                Expression::Binary(..) => format!("-({})", e.to_text()),
+                _ => format!("-{}", e.to_text()),
+            },
+            Expression::Binary(op, a, b) => {
+                let wrap = |e: &Expression, right: bool| match e {
+                    Expression::Binary(child, ..)
+                        if child.precedence() < op.precedence()
+                            || (right && child.precedence() == op.precedence()) =>
+                    {
+                        format!("({})", e.to_text())
+                    }
+                    _ => e.to_text(),
+                };
+                format!("{}{}{}", wrap(a, false), op.symbol(), wrap(b, true))
+            }
+        }
+    }
+
+    /// Value of an expression made of numbers only.
+    fn constant(&self) -> Option<f64> {
+        match self {
+            Expression::Number(n) => Some(n.value),
+            Expression::Indicator(_) => None,
+            Expression::Neg(e) => e.constant().map(|v| -v),
+            Expression::Binary(op, a, b) => Some(op.apply(a.constant()?, b.constant()?)),
+        }
+    }
+
+    /// How the result scales with price series: 1 for a price indicator, 0
+    /// for a difference of two. None when other series are involved or when
+    /// two series are multiplied or divided.
+    fn price_weight(&self) -> Option<f64> {
+        match self {
+            Expression::Number(_) => Some(0.0),
+            Expression::Indicator(i) => match i.domain() {
+                IndicatorDomain::Price => Some(1.0),
+                _ => None,
+            },
+            Expression::Neg(e) => e.price_weight().map(|w| -w),
+            Expression::Binary(Operator::Add, a, b) => Some(a.price_weight()? + b.price_weight()?),
+            Expression::Binary(Operator::Sub, a, b) => Some(a.price_weight()? - b.price_weight()?),
+            Expression::Binary(Operator::Mul, a, b) => match (a.constant(), b.constant()) {
+                (Some(c), _) => b.price_weight().map(|w| w * c),
+                (_, Some(c)) => a.price_weight().map(|w| w * c),
+                _ => None,
+            },
+            Expression::Binary(Operator::Div, a, b) => match b.constant() {
+                Some(c) if c != 0.0 => a.price_weight().map(|w| w / c),
+                _ => None,
+            },
+        }
+    }
+
+    /// Price when the result moves with price (e.g. an average of averages),
+    /// otherwise an oscillator around zero.
+    pub fn domain(&self) -> IndicatorDomain {
+        match self.price_weight() {
+            Some(w) if w > 0.0 => IndicatorDomain::Price,
+            _ => IndicatorDomain::Cartesian,
+        }
+    }
+
+    fn evaluate(&self, samples: &[Sample]) -> Result<Value, DiError> {
+        match self {
+            Expression::Number(n) => Ok(Value::Scalar(n.value)),
+            Expression::Indicator(i) => match i.compute_series(samples)? {
+                IndicatorData::Scalar(s) => Ok(Value::Scalar(s)),
+                IndicatorData::Vector(v) => Ok(Value::Series(v)),
+                IndicatorData::Matrix(m) => match m.into_iter().next() {
+                    Some(v) => Ok(Value::Series(v)),
+                    None => Err(DiError::Message(format!("{} has no series", i.to_text()))),
+                },
+            },
+            Expression::Neg(e) => {
+                Ok(Value::Scalar(-1.0).combine(e.evaluate(samples)?, Operator::Mul))
+            }
+            Expression::Binary(op, a, b) => {
+                Ok(a.evaluate(samples)?.combine(b.evaluate(samples)?, *op))
+            }
+        }
+    }
+
+    pub fn compute_series(&self, samples: &[Sample]) -> Result<IndicatorData, DiError> {
+        match self.evaluate(samples)? {
+            Value::Scalar(s) => Ok(IndicatorData::Scalar(s)),
+            Value::Series(v) => Ok(IndicatorData::Vector(v)),
+        }
+    }
+
+    pub fn compute(&self, samples: &[Sample]) -> Result<IndicatorData, DiError> {
+        match self.evaluate(samples)? {
+            Value::Scalar(s) => Ok(IndicatorData::Scalar(s)),
+            Value::Series(v) => match v.last() {
+                Some(s) => Ok(IndicatorData::Scalar(*s)),
+                None => Err(DiError::Error),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Expression;
+    use crate::finance::Sample;
+    use crate::indicators::{IndicatorData, IndicatorDomain};
+
+    #[test]
+    fn test_expression() {
+        let e = Expression::parse("\"EMA(20) - EMA(50)\"").unwrap();
+        assert_eq!(e.to_text(), "ema(20)-ema(50)");
+        assert!(e.domain() == IndicatorDomain::Cartesian);
+        let e = Expression::parse("(sma(10)+sma(20))/2").unwrap();
+        assert_eq!(e.to_text(), "(sma(10)+sma(20))/2");
+        assert!(e.domain() == IndicatorDomain::Price);
+        assert_eq!(Expression::parse("1-(2-3)").unwrap().to_text(), "1-(2-3)");
+        assert!(Expression::parse("rsi(14)*2").unwrap().domain() == IndicatorDomain::Cartesian);
+        assert!(Expression::parse("ema(20)-").is_none());
+        assert!(Expression::parse("foo(1)").is_none());
+        assert!(Expression::parse("(1+2").is_none());
+
+        let samples: Vec<Sample> = (1..=5)
+            .map(|i| Sample {
+                timestamp: i,
+                close: i as f64,
+                ..Default::default()
+            })
+            .collect();
+        assert!(matches!(
+            Expression::parse("1+2*-3").unwrap().compute(&samples),
+            Ok(IndicatorData::Scalar(s)) if s == -5.0
+        ));
+        match Expression::parse("(sma(1)-sma(2))*2")
+            .unwrap()
+            .compute_series(&samples)
+        {
+            Ok(IndicatorData::Vector(v)) => assert_eq!(v, vec![0.0, 1.0, 1.0, 1.0, 1.0]),
+            _ => panic!("expected a series"),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::convert::From;
 use std::hash::Hash;
 
@@ -32,8 +33,29 @@ pub enum DiError {
     Error,
     OutOfBounds,
     None,
+    Rest(RestError),
 }
 
+/// Failure of an exchange REST call, after retries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RestError {
+    /// Network or server problems that persisted through all attempts.
+    Unavailable(String),
+    /// The exchange rejected the request.
+    Rejected { code: i16, msg: String },
+    /// Local clock still outside the receive window after resyncing.
+    ClockDrift,
+    /// Any other error, not retried.
+    Failed(String),
+}
+
+/// Quote currencies used to split exchange symbols (ex: DOGEUSDT) when no
+/// exchange information is available. Longer matches take precedence.
+pub const QUOTE_CURRENCIES: &[&str] = &[
+    "USDT", "FDUSD", "USDC", "TUSD", "BUSD", "USDP", "DAI", "BTC", "ETH", "BNB", "EUR", "BRL",
+    "TRY", "GBP", "AUD", "JPY", "UAH", "ZAR", "PLN", "RON", "ARS", "MXN", "COP", "IDR",
+];
+
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum Token {
     Symbol(String),
@@ -44,8 +66,24 @@ pub enum Token {
 }
 
 impl Token {
+    /// Parses an exchange symbol (ex: BTCUSDT, 1000SATSUSDT) into a pair using
+    /// the static list of quote currencies. Unknown symbols become a Symbol token.
     pub fn from_string(s: &String) -> Token {
-        Token::Pair((s[0..3].to_string(), s[3..6].to_string()))
+        match Token::parse_pair(s.as_str(), QUOTE_CURRENCIES) {
+            Some(token) => token,
+            None => Token::Symbol(s.to_uppercase()),
+        }
+    }
+
+    /// Splits an exchange symbol into a pair by matching the longest quote currency
+    /// suffix in `currencies`. The remaining base asset must not be empty.
+    pub fn parse_pair(s: &str, currencies: &[&str]) -> Option<Token> {
+        let symbol = s.to_uppercase();
+        currencies
+            .iter()
+            .filter(|currency| symbol.len() > currency.len() && symbol.ends_with(*currency))
+            .max_by_key(|currency| currency.len())
+            .map(|currency| Token::pair(&symbol[..symbol.len() - currency.len()], currency))
     }
 
     pub fn pair(symbol: &str, currency: &str) -> Token {
@@ -153,7 +191,7 @@ pub enum Side {
 
 impl Side {
     pub fn from_string(name: &String) -> Side {
-        if name == &String::from("Buy") {
+        if name.eq_ignore_ascii_case("buy") {
             Side::Buy
         } else {
             Side::Sell
@@ -219,7 +257,7 @@ pub struct Quote {
 }
 
 /// Summary of price movements of an asset over a time period.
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Sample {
     pub resolution: TimeUnit,
     pub timestamp: u64,
@@ -264,6 +302,16 @@ impl Sample {
     pub fn date(&self) -> Date {
         Date::from_timestamp(self.timestamp)
     }
+
+    /// Timestamp in seconds. Exchange klines carry milliseconds while the other
+    /// providers use seconds.
+    pub fn seconds(&self) -> u64 {
+        if self.timestamp > 100_000_000_000 {
+            self.timestamp / 1000
+        } else {
+            self.timestamp
+        }
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -309,6 +357,78 @@ impl Book {
             })
         }
     }
+
+    pub fn best_bid(&self) -> Option<f64> {
+        self.bids.iter().map(|l| l.price).reduce(f64::max)
+    }
+
+    pub fn best_ask(&self) -> Option<f64> {
+        self.asks.iter().map(|l| l.price).reduce(f64::min)
+    }
+
+    /// Levels grouped into price buckets `pct` percent of the mid price wide.
+    /// Bids are rounded down and asks up, so buckets never cross the spread.
+    /// The book is kept as is when `pct` is not positive.
+    pub fn bucketed(&self, pct: f64) -> Book {
+        let mid = match (self.best_bid(), self.best_ask()) {
+            (Some(bid), Some(ask)) => (bid + ask) / 2.0,
+            (Some(price), None) | (None, Some(price)) => price,
+            (None, None) => return self.clone(),
+        };
+        let width = mid * pct / 100.0;
+        if width <= 0.0 {
+            return self.clone();
+        }
+        let bucket = |lines: &[BookLine], round: fn(f64) -> f64| {
+            let mut buckets: BTreeMap<i64, f64> = BTreeMap::new();
+            for line in lines {
+                *buckets
+                    .entry(round(line.price / width) as i64)
+                    .or_insert(0.0) += line.quantity;
+            }
+            buckets
+                .into_iter()
+                .map(|(index, quantity)| BookLine {
+                    price: index as f64 * width,
+                    quantity,
+                })
+                .collect()
+        };
+        Book {
+            token: self.token.clone(),
+            bids: bucket(&self.bids, f64::floor),
+            asks: bucket(&self.asks, f64::ceil),
+        }
+    }
+
+    /// Price a paper order would be filled at against this book, if it fills.
+    /// Market orders take the best price of the opposite side, limit orders
+    /// fill when that price is at least as good as the limit and stop orders
+    /// once the stop price is touched.
+    pub fn fill_price(&self, order: &Order) -> Option<f64> {
+        let best = match order.side {
+            Side::Buy => self.best_ask()?,
+            Side::Sell => self.best_bid()?,
+        };
+        let better_or_equal = |limit: f64| match order.side {
+            Side::Buy => best <= limit,
+            Side::Sell => best >= limit,
+        };
+        let touched = |stop: f64| match order.side {
+            Side::Buy => best >= stop,
+            Side::Sell => best <= stop,
+        };
+        match order.order_type {
+            OrderType::Market => Some(best),
+            OrderType::Limit => better_or_equal(order.price).then_some(best),
+            OrderType::StopMarket => {
+                touched(order.stop_price.unwrap_or(order.price)).then_some(best)
+            }
+            OrderType::StopLimit => (touched(order.stop_price.unwrap_or(order.price))
+                && better_or_equal(order.price))
+            .then_some(best),
+        }
+    }
 }
 
 #[derive(Clone, Default, Debug)]
@@ -318,8 +438,78 @@ pub struct MarketTick {
     pub change_pct: f64,
 }
 
+/// Execution of (part of) an exchange order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fill {
+    /// Exchange order id.
+    pub id: i64,
+    pub token: Token,
+    pub side: Side,
+    /// Quantity of this execution.
+    pub quantity: f64,
+    pub price: f64,
+    /// Unfilled quantity of the order.
+    pub remaining: f64,
+}
+
 pub enum MarketEvent {
     KLine((Token, Sample)),
     Ticks(Vec<MarketTick>),
     OrderBook(Book),
+    Fill(Fill),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Book, BookLine, Token, QUOTE_CURRENCIES};
+
+    #[test]
+    fn test_token_from_string() {
+        assert_eq!(
+            Token::from_string(&String::from("BTCUSDT")),
+            Token::pair("BTC", "USDT")
+        );
+        assert_eq!(
+            Token::from_string(&String::from("DOGEUSDT")),
+            Token::pair("DOGE", "USDT")
+        );
+        assert_eq!(
+            Token::from_string(&String::from("1000SATSUSDT")),
+            Token::pair("1000SATS", "USDT")
+        );
+        assert_eq!(
+            Token::from_string(&String::from("ethbtc")),
+            Token::pair("ETH", "BTC")
+        );
+        assert_eq!(
+            Token::from_string(&String::from("BTCFDUSD")),
+            Token::pair("BTC", "FDUSD")
+        );
+        assert_eq!(
+            Token::from_string(&String::from("USDT")),
+            Token::Symbol(String::from("USDT"))
+        );
+        assert_eq!(Token::parse_pair("XYZABC", QUOTE_CURRENCIES), None);
+    }
+
+    #[test]
+    fn test_book_bucketed() {
+        let line = |price: f64, quantity: f64| BookLine { price, quantity };
+        let book = Book {
+            token: Token::pair("BTC", "USDT"),
+            bids: vec![line(99.9, 1.0), line(99.5, 2.0), line(98.7, 1.0)],
+            asks: vec![line(100.1, 1.0), line(100.9, 3.0)],
+        };
+        // mid 100, 1% buckets
+        let bucketed = book.bucketed(1.0);
+        let levels = |lines: &[BookLine]| -> Vec<(f64, f64)> {
+            lines
+                .iter()
+                .map(|l| ((l.price * 100.0).round() / 100.0, l.quantity))
+                .collect()
+        };
+        assert_eq!(levels(&bucketed.bids), vec![(98.0, 1.0), (99.0, 3.0)]);
+        assert_eq!(levels(&bucketed.asks), vec![(101.0, 4.0)]);
+        assert_eq!(book.bucketed(0.0).bids.len(), 3);
+    }
 }
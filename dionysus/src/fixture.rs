@@ -0,0 +1,170 @@
+use crate::cache::Cache;
+use crate::finance::{DiError, Sample, Token};
+use crate::historical_data::HistoricalData;
+use crate::time::{TimeUnit, TimeWindow};
+
+/// Bundled deterministic OHLCV series (1h resolution) covering different
+/// market regimes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fixture {
+    Trend,
+    Chop,
+    Crash,
+}
+
+impl Fixture {
+    pub fn all() -> [Fixture; 3] {
+        [Fixture::Trend, Fixture::Chop, Fixture::Crash]
+    }
+
+    pub fn from_name(name: &str) -> Option<Fixture> {
+        match name.to_lowercase().as_str() {
+            "trend" => Some(Fixture::Trend),
+            "chop" => Some(Fixture::Chop),
+            "crash" => Some(Fixture::Crash),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Fixture::Trend => String::from("trend"),
+            Fixture::Chop => String::from("chop"),
+            Fixture::Crash => String::from("crash"),
+        }
+    }
+
+    pub fn token(&self) -> Token {
+        Token::Symbol(self.name())
+    }
+
+    pub fn resolution() -> TimeUnit {
+        TimeUnit::Hour(1)
+    }
+
+    fn csv(&self) -> &'static str {
+        match self {
+            Fixture::Trend => include_str!("../fixtures/trend.csv"),
+            Fixture::Chop => include_str!("../fixtures/chop.csv"),
+            Fixture::Crash => include_str!("../fixtures/crash.csv"),
+        }
+    }
+
+    /// Parses the bundled csv (timestamp,open,high,low,close,volume).
+    pub fn samples(&self) -> Vec<Sample> {
+        let mut samples = Vec::new();
+        for line in self.csv().lines().skip(1) {
+            let columns: Vec<&str> = line.trim().split(',').collect();
+            if columns.len() != 6 {
+                continue;
+            }
+            samples.push(Sample {
+                resolution: Fixture::resolution(),
+                timestamp: columns[0].parse::<u64>().unwrap_or(0),
+                open: columns[1].parse::<f64>().unwrap_or(0.0),
+                high: columns[2].parse::<f64>().unwrap_or(0.0),
+                low: columns[3].parse::<f64>().unwrap_or(0.0),
+                close: columns[4].parse::<f64>().unwrap_or(0.0),
+                volume: columns[5].parse::<u64>().unwrap_or(0),
+            });
+        }
+        samples
+    }
+}
+
+/// Offline market serving the bundled fixtures. Each fixture is available
+/// through the token `Token::Symbol(<fixture name>)`.
+pub struct FixtureMarket {
+    pub cache: Cache,
+}
+
+impl Default for FixtureMarket {
+    fn default() -> Self {
+        let mut cache = Cache::default();
+        for fixture in Fixture::all() {
+            let _ = cache.write(&fixture.token(), &fixture.samples()[..]);
+        }
+        FixtureMarket { cache }
+    }
+}
+
+impl HistoricalData for FixtureMarket {
+    fn append(&mut self, token: &Token, sample: &Sample) -> Result<(), DiError> {
+        let v = vec![sample.clone()];
+        self.cache.write(token, &v[..])
+    }
+    fn fetch_last(&mut self, token: &Token, duration: &TimeWindow) -> Result<&[Sample], DiError> {
+        self.cache.read(token, duration)
+    }
+    fn get_last(&self, token: &Token, duration: &TimeWindow) -> Result<&[Sample], DiError> {
+        self.cache.read(token, duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Fixture;
+    use crate::counselor::{Counselor, Signal};
+
+    // Set DIONYSUS_UPDATE_GOLDEN=1 to regenerate fixtures/golden after an
+    // intended change of behavior.
+    fn counselors() -> Vec<(&'static str, Counselor)> {
+        vec![
+            ("mean-reversion", Counselor::MeanReversion((20, 2.0.into()))),
+            ("ema-cross", Counselor::EMACross((5, 20))),
+            ("macd-crossover", Counselor::MACDCrossover((12, 26, 9))),
+            ("macd-zero-cross", Counselor::MACDZeroCross((12, 26, 9))),
+            ("rsi", Counselor::RSI((14, 0.3.into()))),
+        ]
+    }
+
+    fn signals(counselor: &Counselor, fixture: &Fixture) -> String {
+        counselor
+            .run_series(&fixture.samples()[..])
+            .unwrap()
+            .iter()
+            .map(|advice| match advice.signal {
+                Signal::Buy => 'B',
+                Signal::Sell => 'S',
+                Signal::None => '.',
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fixture_samples() {
+        for fixture in Fixture::all() {
+            let samples = fixture.samples();
+            assert_eq!(samples.len(), 160);
+            for sample in &samples {
+                assert!(sample.low <= sample.open.min(sample.close));
+                assert!(sample.high >= sample.open.max(sample.close));
+            }
+        }
+    }
+
+    #[test]
+    fn test_counselor_signals() {
+        for fixture in Fixture::all() {
+            let path = format!(
+                "{}/fixtures/golden/{}.txt",
+                env!("CARGO_MANIFEST_DIR"),
+                fixture.name()
+            );
+            let computed: Vec<String> = counselors()
+                .iter()
+                .map(|(label, counselor)| format!("{} {}", label, signals(counselor, &fixture)))
+                .collect();
+            if std::env::var("DIONYSUS_UPDATE_GOLDEN").is_ok() {
+                std::fs::write(&path, computed.join("\n") + "\n").unwrap();
+                continue;
+            }
+            let golden = std::fs::read_to_string(&path).unwrap();
+            let expected: Vec<&str> = golden.lines().collect();
+            assert_eq!(expected.len(), computed.len());
+            for (e, c) in expected.iter().zip(computed.iter()) {
+                assert_eq!(*e, c.as_str(), "{} fixture", fixture.name());
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{
+    backtest::Backtest,
+    finance::{Book, Order, Sample, Side},
+    historical_data::HistoricalData,
+    strategy::Chrysus,
+    time::Date,
+    utils::compute_change_pct,
+};
+
+/// Paper trading of a strategy from the moment it was activated. Orders are
+/// realized at the price they were placed, the same way backtests do, so the
+/// result can be compared against the backtested expectation.
+#[derive(Clone)]
+pub struct ForwardTest {
+    pub chrysus: Chrysus,
+    pub started: Date,
+    pub initial_capital: f64,
+    pub orders: Vec<Order>,
+    pub last_price: f64,
+}
+
+impl ForwardTest {
+    pub fn new(chrysus: &Chrysus, capital: f64) -> ForwardTest {
+        let mut paper = Chrysus::new(&chrysus.token);
+        paper.strategy = chrysus.strategy.clone();
+        paper.capital = capital;
+        paper.active = true;
+        ForwardTest {
+            chrysus: paper,
+            started: Date::now(),
+            initial_capital: capital,
+            orders: Vec::new(),
+            last_price: 0.0,
+        }
+    }
+
+    /// Runs the strategy over the current book and realizes its orders.
+    pub fn step(&mut self, book: Book, history: &impl HistoricalData) -> Vec<Order> {
+        if let Some(quote) = book.quote() {
+            self.last_price = quote.bid.or(quote.ask).unwrap_or(self.last_price);
+        }
+        let orders = self.chrysus.decide(book, history);
+        for order in &orders {
+            self.chrysus.realize(order);
+            self.orders.push(order.clone());
+        }
+        orders
+    }
+
+    pub fn value(&self, price: f64) -> f64 {
+        self.chrysus.capital + self.chrysus.balance * price
+    }
+
+    /// Realized return (%) since activation.
+    pub fn profit(&self, price: f64) -> f64 {
+        compute_change_pct(self.initial_capital, self.value(price))
+    }
+
+    pub fn elapsed_seconds(&self, now: &Date) -> i64 {
+        (now.timestamp() - self.started.timestamp()).max(0)
+    }
+
+    /// Return (%) the backtest would have produced over the same elapsed time,
+    /// assuming its profit was spread evenly over the backtested period.
+    pub fn expected_profit(&self, backtest: &Backtest, price: f64, now: &Date) -> f64 {
+        let period = backtest.period.num_seconds();
+        if period <= 0 {
+            return 0.0;
+        }
+        backtest.compute_profit(price) * self.elapsed_seconds(now) as f64 / period as f64
+    }
+
+    /// Realized minus expected return (%).
+    pub fn drift(&self, backtest: &Backtest, price: f64, now: &Date) -> f64 {
+        self.profit(price) - self.expected_profit(backtest, price, now)
+    }
+
+    pub fn count(&self, side: Side) -> usize {
+        self.orders.iter().filter(|o| o.side == side).count()
+    }
+
+    /// Paper value at the close of each sample since activation.
+    pub fn equity(&self, samples: &[Sample]) -> Vec<(u64, f64)> {
+        let mut cash = self.initial_capital;
+        let mut quantity = 0.0;
+        let mut next = 0;
+        samples
+            .iter()
+            .filter(|sample| sample.seconds() as i64 >= self.started.timestamp())
+            .map(|sample| {
+                while next < self.orders.len()
+                    && self.orders[next].date.timestamp() <= sample.seconds() as i64
+                {
+                    let order = &self.orders[next];
+                    let q = if order.token == self.chrysus.token {
+                        order.quantity
+                    } else {
+                        0.0
+                    };
+                    match order.side {
+                        Side::Buy => {
+                            cash -= order.quantity * order.price;
+                            quantity += q;
+                        }
+                        Side::Sell => {
+                            cash += order.quantity * order.price;
+                            quantity -= q;
+                        }
+                    }
+                    next += 1;
+                }
+                (sample.timestamp, cash + quantity * sample.close)
+            })
+            .collect()
+    }
+}
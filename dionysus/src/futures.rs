@@ -0,0 +1,202 @@
+use crate::finance::{DiError, Sample};
+use serde::{Deserialize, Serialize};
+
+/// Funding rate charged at a funding time (seconds) of a perpetual future.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FundingRate {
+    pub timestamp: u64,
+    pub rate: f64,
+}
+
+/// Settings of a token traded as a perpetual future.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Perpetual {
+    pub leverage: f64,
+    /// Sorted by time.
+    pub funding: Vec<FundingRate>,
+}
+
+impl Default for Perpetual {
+    fn default() -> Self {
+        Self {
+            leverage: 1.0,
+            funding: Vec::new(),
+        }
+    }
+}
+
+impl Perpetual {
+    pub fn new(leverage: f64, mut funding: Vec<FundingRate>) -> Self {
+        funding.sort_by_key(|f| f.timestamp);
+        Self { leverage, funding }
+    }
+
+    /// Amount borrowed on top of `capital` to reach the leverage.
+    pub fn borrowed(&self, capital: f64) -> f64 {
+        capital * (self.leverage.max(1.0) - 1.0)
+    }
+
+    /// Funding events in the interval (from, to], in seconds.
+    pub fn funding_between(&self, from: u64, to: u64) -> &[FundingRate] {
+        let start = self.funding.partition_point(|f| f.timestamp <= from);
+        let end = self.funding.partition_point(|f| f.timestamp <= to);
+        &self.funding[start..end.max(start)]
+    }
+}
+
+/// Parses `timestamp,rate` lines, timestamps in seconds or ms. Headers and
+/// invalid lines are skipped.
+pub fn parse_funding_csv(text: &str) -> Vec<FundingRate> {
+    let mut rates = Vec::new();
+    for line in text.lines() {
+        let mut fields = line.split(',').map(|f| f.trim());
+        if let (Some(Ok(timestamp)), Some(Ok(rate))) = (
+            fields.next().map(|f| f.parse::<u64>()),
+            fields.next().map(|f| f.parse::<f64>()),
+        ) {
+            rates.push(FundingRate {
+                timestamp: if timestamp > 100_000_000_000 {
+                    timestamp / 1000
+                } else {
+                    timestamp
+                },
+                rate,
+            });
+        }
+    }
+    rates
+}
+
+pub fn load_funding(path: &str) -> Result<Vec<FundingRate>, DiError> {
+    let text = std::fs::read_to_string(path).map_err(|e| DiError::Message(e.to_string()))?;
+    Ok(parse_funding_csv(&text))
+}
+
+/// Margin kept by the exchange on top of the position loss, as a fraction.
+pub const MAINTENANCE_MARGIN: f64 = 0.005;
+
+/// Price band where leveraged positions are estimated to be liquidated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiquidationZone {
+    pub low: f64,
+    pub high: f64,
+    /// Longs are liquidated when price drops into the zone, shorts when it rises.
+    pub long: bool,
+    /// Relative to the largest zone, from 0 to 1.
+    pub weight: f64,
+}
+
+/// Estimates liquidation clusters of positions opened along `samples`.
+/// `open_interest` holds the interest opened at each sample (volume is a fair
+/// proxy without open interest history), split evenly between longs, shorts
+/// and `leverages`. Positions whose liquidation price was already reached are
+/// discarded.
+pub fn liquidation_zones(
+    samples: &[Sample],
+    open_interest: &[f64],
+    leverages: &[f64],
+    bins: usize,
+) -> Vec<LiquidationZone> {
+    let n = samples.len().min(open_interest.len());
+    if n == 0 || leverages.is_empty() || bins == 0 {
+        return Vec::new();
+    }
+    // lowest low and highest high after each sample
+    let mut future_low = vec![f64::MAX; n];
+    let mut future_high = vec![f64::MIN; n];
+    for i in (0..n - 1).rev() {
+        future_low[i] = future_low[i + 1].min(samples[i + 1].low);
+        future_high[i] = future_high[i + 1].max(samples[i + 1].high);
+    }
+    let mut levels: Vec<(f64, bool, f64)> = Vec::new();
+    for i in 0..n {
+        let entry = (samples[i].high + samples[i].low + samples[i].close) / 3.0;
+        let weight = open_interest[i] / (2 * leverages.len()) as f64;
+        for leverage in leverages.iter().filter(|l| **l > 1.0) {
+            let long = entry * (1.0 - 1.0 / leverage + MAINTENANCE_MARGIN);
+            if future_low[i] > long {
+                levels.push((long, true, weight));
+            }
+            let short = entry * (1.0 + 1.0 / leverage - MAINTENANCE_MARGIN);
+            if future_high[i] < short {
+                levels.push((short, false, weight));
+            }
+        }
+    }
+    let min = levels.iter().map(|l| l.0).fold(f64::MAX, f64::min);
+    let max = levels.iter().map(|l| l.0).fold(f64::MIN, f64::max);
+    if min > max {
+        return Vec::new();
+    }
+    let step = ((max - min) / bins as f64).max(f64::EPSILON);
+    let mut long_bins = vec![0.0; bins];
+    let mut short_bins = vec![0.0; bins];
+    for (price, long, weight) in levels {
+        let bin = (((price - min) / step) as usize).min(bins - 1);
+        match long {
+            true => long_bins[bin] += weight,
+            false => short_bins[bin] += weight,
+        }
+    }
+    let largest = long_bins
+        .iter()
+        .chain(short_bins.iter())
+        .cloned()
+        .fold(0.0, f64::max);
+    let mut zones = Vec::new();
+    for (long, bins) in [(true, &long_bins), (false, &short_bins)] {
+        for (i, weight) in bins.iter().enumerate() {
+            if *weight > 0.0 && largest > 0.0 {
+                zones.push(LiquidationZone {
+                    low: min + i as f64 * step,
+                    high: min + (i + 1) as f64 * step,
+                    long,
+                    weight: weight / largest,
+                });
+            }
+        }
+    }
+    zones
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{liquidation_zones, parse_funding_csv, Perpetual};
+    use crate::finance::Sample;
+
+    #[test]
+    fn test_funding() {
+        let rates = parse_funding_csv("fundingTime,fundingRate\n28800000,0.0001\n0,-0.0002\n");
+        assert_eq!(rates.len(), 2);
+        let perpetual = Perpetual::new(3.0, rates);
+        assert_eq!(perpetual.funding[0].timestamp, 0);
+        assert_eq!(perpetual.funding[1].timestamp, 28800);
+        assert_eq!(perpetual.funding_between(0, 28800).len(), 1);
+        assert_eq!(perpetual.funding_between(28800, 50000).len(), 0);
+        assert_eq!(perpetual.borrowed(100.0), 200.0);
+    }
+
+    #[test]
+    fn test_liquidation_zones() {
+        let sample = |price: f64| Sample {
+            high: price,
+            low: price,
+            close: price,
+            ..Default::default()
+        };
+        let samples = vec![sample(100.0), sample(100.0), sample(95.0)];
+        let zones = liquidation_zones(&samples, &[1.0, 1.0, 0.0], &[10.0], 10);
+        // longs from 100 at 10x liquidate at 90.5, shorts at 109.5
+        assert!(zones
+            .iter()
+            .any(|z| z.long && z.low <= 90.5 && z.high >= 90.5));
+        assert!(zones
+            .iter()
+            .any(|z| !z.long && z.low <= 109.5 && z.high >= 109.5));
+        assert!(zones.iter().all(|z| z.weight > 0.0 && z.weight <= 1.0));
+        // price reached the long liquidation
+        let samples = vec![sample(100.0), sample(90.0)];
+        let zones = liquidation_zones(&samples, &[1.0, 0.0], &[10.0], 10);
+        assert!(zones.iter().all(|z| !z.long));
+    }
+}
@@ -1,8 +1,8 @@
-use crate::binance::BinanceMarket;
-use crate::brownian::{generate_brownian_data, BrownianMotionMarket};
+use crate::binance::{samples_from_klines, with_retry, BinanceMarket};
+use crate::brownian::{generate_model_data, BrownianMotionMarket};
 use crate::finance::{DiError, Quote, Sample, Token};
-use crate::time::{TimeUnit, TimeWindow};
-use crate::yahoo::YahooMarket;
+use crate::time::{Date, Period, TimeUnit, TimeWindow};
+use crate::yahoo::{fetch_history, YahooMarket};
 use std::cmp::Ordering;
 
 pub fn sample_quotes(quotes: &[Quote], resolution: &TimeUnit) -> Vec<Sample> {
@@ -126,31 +126,21 @@ impl HistoricalData for BinanceMarket {
         self.cache.write(token, &v[..])
     }
     fn fetch_last(&mut self, token: &Token, duration: &TimeWindow) -> Result<&[Sample], DiError> {
-        let mut samples: Vec<Sample> = Vec::new();
-        match self.market.get_klines(
-            token.to_string().as_str(),
-            duration.resolution.name(),
-            duration.count as u16,
-            None,
-            None,
-        ) {
-            Ok(klines) => match klines {
-                binance::model::KlineSummaries::AllKlineSummaries(klines) => {
-                    for kline in klines {
-                        samples.push(Sample {
-                            resolution: duration.resolution.clone(),
-                            timestamp: kline.open_time as u64,
-                            open: kline.open.parse::<f64>().unwrap(),
-                            high: kline.high.parse::<f64>().unwrap(),
-                            low: kline.low.parse::<f64>().unwrap(),
-                            close: kline.close.parse::<f64>().unwrap(),
-                            volume: kline.number_of_trades as u64,
-                        });
-                    }
-                }
-            },
-            Err(e) => return Err(DiError::Message(format!("FETCH LAST {:?}", e))),
-        };
+        // a cache restored from a snapshot only needs the bars since then
+        let count = self
+            .cache
+            .missing(token, duration, Date::now().timestamp() as u64)
+            .unwrap_or(duration.count);
+        let klines = with_retry(|| {
+            self.market.get_klines(
+                token.to_string().as_str(),
+                duration.resolution.name(),
+                count as u16,
+                None,
+                None,
+            )
+        })?;
+        let samples = samples_from_klines(klines, &duration.resolution);
         if !samples.is_empty() {
             self.cache.write(token, &samples[..])?;
         }
@@ -163,33 +153,44 @@ impl HistoricalData for BinanceMarket {
 }
 
 impl HistoricalData for YahooMarket {
-    fn append(&mut self, _token: &Token, _sample: &Sample) -> Result<(), DiError> {
-        Err(DiError::NotImplemented)
+    fn append(&mut self, token: &Token, sample: &Sample) -> Result<(), DiError> {
+        self.cache.write(token, &[sample.clone()])
     }
-    fn fetch_last(&mut self, _token: &Token, _duration: &TimeWindow) -> Result<&[Sample], DiError> {
-        Err(DiError::NotImplemented)
+    fn fetch_last(&mut self, token: &Token, duration: &TimeWindow) -> Result<&[Sample], DiError> {
+        // exchanges close on weekends and holidays, twice the period covers
+        // the requested number of samples
+        let count = self
+            .cache
+            .missing(token, duration, Date::now().timestamp() as u64)
+            .unwrap_or(duration.count);
+        let period = Period::last(TimeWindow {
+            resolution: duration.resolution,
+            count: count * 2,
+        });
+        let samples = fetch_history(&token.get_symbol(), &period, self.adjustment())?;
+        if !samples.is_empty() {
+            self.cache.write(token, &samples[..])?;
+        }
+        self.cache.read(token, duration)
     }
-    fn get_last(&self, _token: &Token, _duration: &TimeWindow) -> Result<&[Sample], DiError> {
-        //let period_end = Date::now();
-        Err(DiError::NotImplemented)
+    fn get_last(&self, token: &Token, duration: &TimeWindow) -> Result<&[Sample], DiError> {
+        self.cache.read(token, duration)
     }
-    //fn fetch_one(&mut self, symbol: &str, period: &Period) -> Result<(), DiError> {
-    //    match fetch_history(symbol, &period) {
-    //        Ok(history) => {
-    //            self.cache.insert(symbol.to_string(), history);
-    //            Ok(())
-    //        }
-    //        Err(_) => Err(DiError::NotFound),
-    //    }
-    //}
 }
 
 impl HistoricalData for BrownianMotionMarket {
-    fn append(&mut self, _token: &Token, _sample: &Sample) -> Result<(), DiError> {
-        Err(DiError::NotImplemented)
+    fn append(&mut self, token: &Token, sample: &Sample) -> Result<(), DiError> {
+        self.cache.write(token, &[sample.clone()])
     }
     fn fetch_last(&mut self, token: &Token, duration: &TimeWindow) -> Result<&[Sample], DiError> {
-        let quotes = generate_brownian_data(self.mu, self.sigma, &duration);
+        let quotes = generate_model_data(
+            &self.model,
+            self.mu,
+            self.sigma,
+            self.start_price,
+            &duration,
+            self.seed,
+        );
         let samples = sample_quotes(&quotes[..], &duration.resolution);
         self.cache.write(token, &samples[..])?;
         self.cache.read(token, duration)
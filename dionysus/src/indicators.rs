@@ -1,6 +1,10 @@
+use crate::analytics::{annualization_factor, rolling_volatility, VolatilityEstimator};
+use crate::expression::Expression;
 use crate::finance::{DiError, Sample, F64};
+use crate::time::TimeUnit;
+use crate::utils::compute_change_pct;
 use ta::indicators::{
-    BollingerBands, ExponentialMovingAverage, MovingAverageConvergenceDivergence,
+    AverageTrueRange, BollingerBands, ExponentialMovingAverage, MovingAverageConvergenceDivergence,
     RelativeStrengthIndex, SimpleMovingAverage, StandardDeviation,
 };
 use ta::Next;
@@ -21,21 +25,124 @@ pub enum IndicatorDomain {
     Volume,
 }
 
+/// Moving average used by composite indicators.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MovingAverageType {
+    #[default]
+    Exponential,
+    Simple,
+    Weighted,
+    VolumeWeighted,
+}
+
+impl MovingAverageType {
+    pub fn from_name(name: &str) -> Option<MovingAverageType> {
+        match name.to_uppercase().as_str() {
+            "EMA" => Some(MovingAverageType::Exponential),
+            "SMA" => Some(MovingAverageType::Simple),
+            "WMA" => Some(MovingAverageType::Weighted),
+            "VWMA" => Some(MovingAverageType::VolumeWeighted),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            MovingAverageType::Exponential => String::from("ema"),
+            MovingAverageType::Simple => String::from("sma"),
+            MovingAverageType::Weighted => String::from("wma"),
+            MovingAverageType::VolumeWeighted => String::from("vwma"),
+        }
+    }
+
+    /// Average of `values` over the last `n` entries (fewer at the start).
+    /// Only the volume weighted average uses `volumes`.
+    pub fn series(&self, n: usize, values: &[f64], volumes: &[f64]) -> Vec<f64> {
+        let n = n.max(1);
+        match self {
+            MovingAverageType::Exponential => match ExponentialMovingAverage::new(n) {
+                Ok(mut f) => values.iter().map(|v| f.next(*v)).collect(),
+                Err(_) => Vec::new(),
+            },
+            MovingAverageType::Simple => match SimpleMovingAverage::new(n) {
+                Ok(mut f) => values.iter().map(|v| f.next(*v)).collect(),
+                Err(_) => Vec::new(),
+            },
+            MovingAverageType::Weighted => (0..values.len())
+                .map(|i| {
+                    let window = &values[(i + 1).saturating_sub(n)..i + 1];
+                    let weights = (window.len() * (window.len() + 1) / 2) as f64;
+                    window
+                        .iter()
+                        .enumerate()
+                        .map(|(j, v)| v * (j + 1) as f64)
+                        .sum::<f64>()
+                        / weights
+                })
+                .collect(),
+            MovingAverageType::VolumeWeighted => (0..values.len())
+                .map(|i| {
+                    let start = (i + 1).saturating_sub(n);
+                    let volume: f64 = volumes[start..i + 1].iter().sum();
+                    let window = &values[start..i + 1];
+                    if volume > 0.0 {
+                        window
+                            .iter()
+                            .zip(&volumes[start..i + 1])
+                            .map(|(v, w)| v * w)
+                            .sum::<f64>()
+                            / volume
+                    } else {
+                        window.iter().sum::<f64>() / window.len() as f64
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct Indicators {
     indicators: Vec<Indicator>,
 }
 
-#[derive(PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Indicator {
     ExponentialMovingAverage(usize),
     SimpleMovingAverage(usize),
     StandardDeviation(usize),
     RelativeStrengthIndex(usize),
+    AverageTrueRange(usize),
+    /// ADX with the +DI and -DI lines.
+    AverageDirectionalIndex(usize),
+    /// Commodity channel index of the typical price.
+    CCI(usize),
+    /// Close within the range of the lookback, -100 at the low and 0 at the high.
+    WilliamsPercentR(usize),
+    /// Floor pivots of the calendar sessions (days, weeks, months).
+    PivotPoints(TimeUnit),
+    /// Retracements of the move from one price to another, None retraces the
+    /// last swing.
+    FibonacciRetracement(Option<(F64, F64)>),
     BollingerBands((usize, F64)),
-    MovingAverageConvergenceDivergence((usize, usize, usize)),
+    /// Position of the close within the bands, 0 at the lower and 100 at the upper band.
+    BollingerPercentB((usize, F64)),
+    /// Width of the bands relative to the middle band.
+    BollingerBandwidth((usize, F64)),
+    MovingAverageConvergenceDivergence((usize, usize, usize, MovingAverageType)),
     SupportLines(F64),
     ResistanceLines(F64),
+    Volatility((usize, VolatilityEstimator)),
+    /// (efficiency ratio period, fast period, slow period)
+    KaufmanAdaptiveMovingAverage((usize, usize, usize)),
+    /// Volume weighted average price since the anchor timestamp.
+    AnchoredVolumeWeightedAveragePrice(u64),
+    /// Return (%) of the close since the anchor timestamp.
+    CumulativeReturn(u64),
+    /// (tenkan period, kijun period, senkou B period)
+    Ichimoku((usize, usize, usize)),
+    /// Arithmetic over other indicators, e.g. "ema(20)-ema(50)".
+    Expression(Expression),
 }
 
 impl Default for Indicator {
@@ -72,6 +179,7 @@ indicator_series_fn!(exponential_moving_average_s, ExponentialMovingAverage);
 indicator_series_fn!(simple_moving_average_s, SimpleMovingAverage);
 indicator_series_fn!(standard_deviation_s, StandardDeviation);
 indicator_series_fn!(relative_strength_index_s, RelativeStrengthIndex);
+indicator_series_fn!(average_true_range_s, AverageTrueRange);
 
 macro_rules! indicator_fn {
     ($name:tt, $func:ident) => {
@@ -94,6 +202,7 @@ indicator_fn!(exponential_moving_average, ExponentialMovingAverage);
 indicator_fn!(simple_moving_average, SimpleMovingAverage);
 indicator_fn!(standard_deviation, StandardDeviation);
 indicator_fn!(relative_strength_index, RelativeStrengthIndex);
+indicator_fn!(average_true_range, AverageTrueRange);
 
 macro_rules! match_indicator {
     ($func:ident, $words:expr) => {
@@ -130,6 +239,52 @@ pub fn bollinger_bands(n: usize, w: f64, samples: &[Sample]) -> Result<Indicator
     }
 }
 
+/// %B of each sample, from the band matrix.
+pub fn bollinger_percent_b_s(
+    n: usize,
+    w: f64,
+    samples: &[Sample],
+) -> Result<IndicatorData, DiError> {
+    match bollinger_bands_s(n, w, samples) {
+        Ok(IndicatorData::Matrix(r)) => Ok(IndicatorData::Vector(
+            samples
+                .iter()
+                .enumerate()
+                .map(|(i, sample)| {
+                    let width = r[2][i] - r[0][i];
+                    if width > 0.0 {
+                        (sample.close - r[0][i]) / width * 100.0
+                    } else {
+                        50.0
+                    }
+                })
+                .collect(),
+        )),
+        Ok(_) => Err(DiError::Error),
+        Err(e) => Err(e),
+    }
+}
+
+/// (upper - lower) / middle of each sample, from the band matrix.
+pub fn bollinger_bandwidth_s(
+    n: usize,
+    w: f64,
+    samples: &[Sample],
+) -> Result<IndicatorData, DiError> {
+    match bollinger_bands_s(n, w, samples) {
+        Ok(IndicatorData::Matrix(r)) => Ok(IndicatorData::Vector(
+            (0..samples.len())
+                .map(|i| match r[1][i] {
+                    m if m != 0.0 => (r[2][i] - r[0][i]) / m,
+                    _ => 0.0,
+                })
+                .collect(),
+        )),
+        Ok(_) => Err(DiError::Error),
+        Err(e) => Err(e),
+    }
+}
+
 fn _round(nums: (f64, f64, f64)) -> (f64, f64, f64) {
     let n0 = (nums.0 * 100.0).round() / 100.0;
     let n1 = (nums.1 * 100.0).round() / 100.0;
@@ -137,12 +292,24 @@ fn _round(nums: (f64, f64, f64)) -> (f64, f64, f64) {
     (n0, n1, n2)
 }
 
+/// MACD and signal lines. The signal line uses the same kind of average as
+/// the MACD line.
 pub fn macd_s(
     fast_period: usize,
     slow_period: usize,
     signal_period: usize,
+    ma: MovingAverageType,
     samples: &[Sample],
 ) -> Result<IndicatorData, DiError> {
+    if ma != MovingAverageType::Exponential {
+        let closes: Vec<f64> = samples.iter().map(|s| s.close).collect();
+        let volumes: Vec<f64> = samples.iter().map(|s| s.volume as f64).collect();
+        let fast = ma.series(fast_period, &closes, &volumes);
+        let slow = ma.series(slow_period, &closes, &volumes);
+        let line: Vec<f64> = fast.iter().zip(&slow).map(|(f, s)| f - s).collect();
+        let signal = ma.series(signal_period, &line, &volumes);
+        return Ok(IndicatorData::Matrix(vec![line, signal]));
+    }
     let mut r: Vec<Vec<f64>> = vec![Vec::new(), Vec::new()];
     let mut macd =
         MovingAverageConvergenceDivergence::new(fast_period, slow_period, signal_period).unwrap();
@@ -160,12 +327,14 @@ pub fn macd(
     fast_period: usize,
     slow_period: usize,
     signal_period: usize,
+    ma: MovingAverageType,
     samples: &[Sample],
 ) -> Result<IndicatorData, DiError> {
     match macd_s(
         fast_period,
         slow_period,
         signal_period,
+        ma,
         &samples[samples.len() - slow_period..],
     ) {
         Ok(IndicatorData::Matrix(r)) => Ok(IndicatorData::Matrix(vec![
@@ -234,7 +403,460 @@ pub fn resistance_lines(
     Ok(IndicatorData::Matrix(r))
 }
 
+/// Annualized realized volatility (in percent) over a rolling window.
+pub fn volatility_s(
+    n: usize,
+    estimator: &VolatilityEstimator,
+    samples: &[Sample],
+) -> Result<IndicatorData, DiError> {
+    if n == 0 || samples.len() < n {
+        return Err(DiError::NotFound);
+    }
+    let factor = annualization_factor(&samples[0].resolution) * 100.0;
+    let v: Vec<f64> = rolling_volatility(estimator, n, samples)[n - 1..]
+        .iter()
+        .map(|v| v * factor)
+        .collect();
+    Ok(IndicatorData::Vector(v))
+}
+
+pub fn volatility(
+    n: usize,
+    estimator: &VolatilityEstimator,
+    samples: &[Sample],
+) -> Result<IndicatorData, DiError> {
+    match volatility_s(n, estimator, &samples[samples.len().saturating_sub(n)..]) {
+        Ok(IndicatorData::Vector(v)) => Ok(IndicatorData::Scalar(*v.last().unwrap())),
+        Ok(_) => Err(DiError::Error),
+        Err(e) => Err(e),
+    }
+}
+
+/// Kaufman's adaptive moving average. The smoothing moves between the fast
+/// and slow EMA constants following the efficiency ratio of the last `n` closes.
+pub fn kama_s(
+    n: usize,
+    fast: usize,
+    slow: usize,
+    samples: &[Sample],
+) -> Result<IndicatorData, DiError> {
+    if n == 0 || samples.is_empty() {
+        return Err(DiError::NotFound);
+    }
+    let fast_sc = 2.0 / (fast as f64 + 1.0);
+    let slow_sc = 2.0 / (slow as f64 + 1.0);
+    let mut v: Vec<f64> = Vec::with_capacity(samples.len());
+    let mut kama = samples[0].close;
+    v.push(kama);
+    for i in 1..samples.len() {
+        let start = i.saturating_sub(n);
+        let change = (samples[i].close - samples[start].close).abs();
+        let path: f64 = samples[start..i + 1]
+            .windows(2)
+            .map(|w| (w[1].close - w[0].close).abs())
+            .sum();
+        let er = if path > 0.0 { change / path } else { 0.0 };
+        let sc = (er * (fast_sc - slow_sc) + slow_sc).powi(2);
+        kama += sc * (samples[i].close - kama);
+        v.push(kama);
+    }
+    Ok(IndicatorData::Vector(v))
+}
+
+/// Index of the first sample at or after `anchor`.
+fn anchor_index(anchor: u64, samples: &[Sample]) -> Result<usize, DiError> {
+    match samples.iter().position(|s| s.timestamp >= anchor) {
+        Some(i) => Ok(i),
+        None => Err(DiError::NotFound),
+    }
+}
+
+/// Cumulative typical price weighted by volume, from the anchor on.
+pub fn anchored_vwap_s(anchor: u64, samples: &[Sample]) -> Result<IndicatorData, DiError> {
+    let start = anchor_index(anchor, samples)?;
+    let mut pv = 0.0;
+    let mut volume = 0.0;
+    let mut v: Vec<f64> = Vec::new();
+    for sample in &samples[start..] {
+        let typical = (sample.high + sample.low + sample.close) / 3.0;
+        pv += typical * sample.volume as f64;
+        volume += sample.volume as f64;
+        v.push(if volume > 0.0 { pv / volume } else { typical });
+    }
+    Ok(IndicatorData::Vector(v))
+}
+
+/// Change (%) of each close relative to the close of the anchor bar.
+pub fn cumulative_return_s(anchor: u64, samples: &[Sample]) -> Result<IndicatorData, DiError> {
+    let start = anchor_index(anchor, samples)?;
+    let base = samples[start].close;
+    Ok(IndicatorData::Vector(
+        samples[start..]
+            .iter()
+            .map(|s| compute_change_pct(base, s.close))
+            .collect(),
+    ))
+}
+
+/// Wilder's smoothing, an average with weight 1/n (fewer at the start).
+fn wilder_s(n: usize, values: &[f64]) -> Vec<f64> {
+    let n = n.max(1) as f64;
+    let mut v: Vec<f64> = Vec::with_capacity(values.len());
+    for (i, x) in values.iter().enumerate() {
+        v.push(match v.last() {
+            Some(s) => s + (x - s) / n.min(i as f64 + 1.0),
+            None => *x,
+        });
+    }
+    v
+}
+
+/// ADX, +DI and -DI of each sample, in percent.
+pub fn adx_s(n: usize, samples: &[Sample]) -> Result<IndicatorData, DiError> {
+    if n == 0 || samples.is_empty() {
+        return Err(DiError::NotFound);
+    }
+    let mut tr: Vec<f64> = vec![samples[0].high - samples[0].low];
+    let mut plus_dm: Vec<f64> = vec![0.0];
+    let mut minus_dm: Vec<f64> = vec![0.0];
+    for w in samples.windows(2) {
+        let (prev, cur) = (&w[0], &w[1]);
+        tr.push(
+            (cur.high - cur.low)
+                .max((cur.high - prev.close).abs())
+                .max((cur.low - prev.close).abs()),
+        );
+        let up = cur.high - prev.high;
+        let down = prev.low - cur.low;
+        plus_dm.push(if up > down && up > 0.0 { up } else { 0.0 });
+        minus_dm.push(if down > up && down > 0.0 { down } else { 0.0 });
+    }
+    let tr = wilder_s(n, &tr);
+    let di = |dm: &[f64]| -> Vec<f64> {
+        wilder_s(n, dm)
+            .iter()
+            .zip(&tr)
+            .map(|(dm, tr)| if *tr > 0.0 { dm / tr * 100.0 } else { 0.0 })
+            .collect()
+    };
+    let plus_di = di(&plus_dm);
+    let minus_di = di(&minus_dm);
+    let dx: Vec<f64> = plus_di
+        .iter()
+        .zip(&minus_di)
+        .map(|(p, m)| match p + m {
+            sum if sum > 0.0 => (p - m).abs() / sum * 100.0,
+            _ => 0.0,
+        })
+        .collect();
+    Ok(IndicatorData::Matrix(vec![
+        wilder_s(n, &dx),
+        plus_di,
+        minus_di,
+    ]))
+}
+
+/// Deviation of the typical price from its `n` average, in units of 0.015
+/// mean absolute deviations (fewer samples at the start).
+pub fn cci_s(n: usize, samples: &[Sample]) -> Result<IndicatorData, DiError> {
+    if n == 0 || samples.is_empty() {
+        return Err(DiError::NotFound);
+    }
+    let typical: Vec<f64> = samples
+        .iter()
+        .map(|s| (s.high + s.low + s.close) / 3.0)
+        .collect();
+    Ok(IndicatorData::Vector(
+        (0..typical.len())
+            .map(|i| {
+                let window = &typical[(i + 1).saturating_sub(n)..i + 1];
+                let mean = window.iter().sum::<f64>() / window.len() as f64;
+                let deviation =
+                    window.iter().map(|t| (t - mean).abs()).sum::<f64>() / window.len() as f64;
+                if deviation > 0.0 {
+                    (typical[i] - mean) / (0.015 * deviation)
+                } else {
+                    0.0
+                }
+            })
+            .collect(),
+    ))
+}
+
+/// Williams %R of each close over the last `n` samples (fewer at the start).
+pub fn williams_r_s(n: usize, samples: &[Sample]) -> Result<IndicatorData, DiError> {
+    if n == 0 || samples.is_empty() {
+        return Err(DiError::NotFound);
+    }
+    Ok(IndicatorData::Vector(
+        (0..samples.len())
+            .map(|i| {
+                let window = &samples[(i + 1).saturating_sub(n)..i + 1];
+                let high = window.iter().map(|s| s.high).fold(f64::MIN, f64::max);
+                let low = window.iter().map(|s| s.low).fold(f64::MAX, f64::min);
+                if high > low {
+                    (high - samples[i].close) / (high - low) * -100.0
+                } else {
+                    -50.0
+                }
+            })
+            .collect(),
+    ))
+}
+
+/// R2, R1, P, S1 and S2 floor pivots of each sample, from the high, low and
+/// close of the previous `session`. Starts at the second session.
+pub fn pivot_points_s(session: &TimeUnit, samples: &[Sample]) -> Result<IndicatorData, DiError> {
+    let mut r: Vec<Vec<f64>> = vec![Vec::new(); 5];
+    // (session start, high, low, close) of the current and previous sessions
+    let mut current: Option<(u64, f64, f64, f64)> = None;
+    let mut previous: Option<(f64, f64, f64)> = None;
+    for sample in samples {
+        let start = session.session_start(sample.seconds());
+        current = match current {
+            Some((s, high, low, _)) if s == start => {
+                Some((s, high.max(sample.high), low.min(sample.low), sample.close))
+            }
+            last => {
+                previous = last.map(|(_, high, low, close)| (high, low, close));
+                Some((start, sample.high, sample.low, sample.close))
+            }
+        };
+        if let Some((high, low, close)) = previous {
+            let p = (high + low + close) / 3.0;
+            let levels = [
+                p + (high - low),
+                2.0 * p - low,
+                p,
+                2.0 * p - high,
+                p - (high - low),
+            ];
+            for (row, level) in r.iter_mut().zip(levels) {
+                row.push(level);
+            }
+        }
+    }
+    if r[0].is_empty() {
+        return Err(DiError::NotFound);
+    }
+    Ok(IndicatorData::Matrix(r))
+}
+
+/// Standard Fibonacci retracement ratios.
+pub const FIBONACCI_RATIOS: [f64; 7] = [0.0, 0.236, 0.382, 0.5, 0.618, 0.786, 1.0];
+/// Swings of the automatic retracement, in ATRs of the given period.
+const FIBONACCI_SWING: SwingThreshold = SwingThreshold::Atr(14, 3.0);
+
+/// Last swing of the samples, from its start to its end price. Falls back to
+/// the range of the samples without a swing.
+fn last_swing(samples: &[Sample]) -> Option<(f64, f64)> {
+    let pivots = zigzag(FIBONACCI_SWING, samples);
+    if let [.., from, to] = pivots.as_slice() {
+        return Some((from.price, to.price));
+    }
+    let high = samples.iter().map(|s| s.high).fold(f64::MIN, f64::max);
+    let low = samples.iter().map(|s| s.low).fold(f64::MAX, f64::min);
+    match samples.is_empty() {
+        true => None,
+        false => Some((low, high)),
+    }
+}
+
+/// Level of each retracement ratio of the move `from` -> `to`, across all
+/// samples. The 0 level is `to`, 1 is `from`.
+pub fn fibonacci_s(
+    range: Option<(f64, f64)>,
+    samples: &[Sample],
+) -> Result<IndicatorData, DiError> {
+    let (from, to) = match range.or_else(|| last_swing(samples)) {
+        Some(range) => range,
+        None => return Err(DiError::NotFound),
+    };
+    Ok(IndicatorData::Matrix(
+        FIBONACCI_RATIOS
+            .iter()
+            .map(|r| vec![to - (to - from) * r; samples.len()])
+            .collect(),
+    ))
+}
+
+fn last_of_matrix(data: Result<IndicatorData, DiError>) -> Result<IndicatorData, DiError> {
+    match data {
+        Ok(IndicatorData::Matrix(m)) => Ok(IndicatorData::Matrix(
+            m.iter().map(|r| vec![*r.last().unwrap()]).collect(),
+        )),
+        Ok(_) => Err(DiError::Error),
+        Err(e) => Err(e),
+    }
+}
+
+/// Middle of the highest high and the lowest low of the last `n` samples
+/// (fewer at the start).
+fn midpoint_s(n: usize, samples: &[Sample]) -> Vec<f64> {
+    let n = n.max(1);
+    (0..samples.len())
+        .map(|i| {
+            let window = &samples[(i + 1).saturating_sub(n)..i + 1];
+            let high = window.iter().map(|s| s.high).fold(f64::MIN, f64::max);
+            let low = window.iter().map(|s| s.low).fold(f64::MAX, f64::min);
+            (high + low) / 2.0
+        })
+        .collect()
+}
+
+/// Tenkan, kijun, senkou A, senkou B and chikou lines, one value per sample.
+/// The senkou spans are drawn `kijun` bars ahead of their sample and the
+/// chikou `kijun` bars behind, see `Indicator::shifts`.
+pub fn ichimoku_s(
+    tenkan: usize,
+    kijun: usize,
+    senkou: usize,
+    samples: &[Sample],
+) -> Result<IndicatorData, DiError> {
+    if samples.is_empty() {
+        return Err(DiError::NotFound);
+    }
+    let tenkan_line = midpoint_s(tenkan, samples);
+    let kijun_line = midpoint_s(kijun, samples);
+    let span_a: Vec<f64> = tenkan_line
+        .iter()
+        .zip(&kijun_line)
+        .map(|(t, k)| (t + k) / 2.0)
+        .collect();
+    let span_b = midpoint_s(senkou, samples);
+    let chikou: Vec<f64> = samples.iter().map(|s| s.close).collect();
+    Ok(IndicatorData::Matrix(vec![
+        tenkan_line,
+        kijun_line,
+        span_a,
+        span_b,
+        chikou,
+    ]))
+}
+
+/// Ichimoku lines at the last sample: the cloud over it was projected
+/// `kijun` bars ago and the chikou is the last close.
+pub fn ichimoku(
+    tenkan: usize,
+    kijun: usize,
+    senkou: usize,
+    samples: &[Sample],
+) -> Result<IndicatorData, DiError> {
+    match ichimoku_s(tenkan, kijun, senkou, samples) {
+        Ok(IndicatorData::Matrix(r)) => {
+            let last = samples.len() - 1;
+            let projected = last.saturating_sub(kijun);
+            Ok(IndicatorData::Matrix(vec![
+                vec![r[0][last]],
+                vec![r[1][last]],
+                vec![r[2][projected]],
+                vec![r[3][projected]],
+                vec![r[4][last]],
+            ]))
+        }
+        Ok(_) => Err(DiError::Error),
+        Err(e) => Err(e),
+    }
+}
+
+fn last_of(data: Result<IndicatorData, DiError>) -> Result<IndicatorData, DiError> {
+    match data {
+        Ok(IndicatorData::Vector(v)) => Ok(IndicatorData::Scalar(*v.last().unwrap())),
+        Ok(_) => Err(DiError::Error),
+        Err(e) => Err(e),
+    }
+}
+
+/// Minimum reversal for a new swing in the zigzag.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SwingThreshold {
+    /// Percent of the swing extreme.
+    Percent(f64),
+    /// Multiple of the average true range of the given period.
+    Atr(usize, f64),
+}
+
+/// Swing point of a zigzag.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pivot {
+    /// Index of the sample.
+    pub index: usize,
+    pub timestamp: u64,
+    pub price: f64,
+    /// Pivot high, otherwise pivot low.
+    pub high: bool,
+    /// False for the last extreme, which may still move.
+    pub confirmed: bool,
+}
+
+/// Alternating pivot highs and lows. A pivot is confirmed once price
+/// reverses from it by at least the threshold.
+pub fn zigzag(threshold: SwingThreshold, samples: &[Sample]) -> Vec<Pivot> {
+    let mut pivots: Vec<Pivot> = Vec::new();
+    if samples.is_empty() {
+        return pivots;
+    }
+    let atr: Vec<f64> = match threshold {
+        SwingThreshold::Atr(n, _) => match AverageTrueRange::new(n.max(1)) {
+            Ok(mut f) => samples.iter().map(|s| f.next(s)).collect(),
+            Err(_) => return pivots,
+        },
+        SwingThreshold::Percent(_) => Vec::new(),
+    };
+    let distance = |i: usize, price: f64| match threshold {
+        SwingThreshold::Percent(p) => price.abs() * p / 100.0,
+        SwingThreshold::Atr(_, k) => atr[i] * k,
+    };
+    let pivot = |i: usize, high: bool| Pivot {
+        index: i,
+        timestamp: samples[i].timestamp,
+        price: if high {
+            samples[i].high
+        } else {
+            samples[i].low
+        },
+        high,
+        confirmed: true,
+    };
+    // 1 tracking a high, -1 tracking a low, 0 until the first swing
+    let mut direction = 0;
+    let (mut hi, mut lo) = (0, 0);
+    for (i, sample) in samples.iter().enumerate().skip(1) {
+        if direction >= 0 && sample.high > samples[hi].high {
+            hi = i;
+        }
+        if direction <= 0 && sample.low < samples[lo].low {
+            lo = i;
+        }
+        if direction <= 0 && sample.high - samples[lo].low >= distance(i, samples[lo].low) {
+            pivots.push(pivot(lo, false));
+            direction = 1;
+            hi = i;
+        } else if direction >= 0 && samples[hi].high - sample.low >= distance(i, samples[hi].high) {
+            pivots.push(pivot(hi, true));
+            direction = -1;
+            lo = i;
+        }
+    }
+    match direction {
+        1 => pivots.push(Pivot {
+            confirmed: false,
+            ..pivot(hi, true)
+        }),
+        -1 => pivots.push(Pivot {
+            confirmed: false,
+            ..pivot(lo, false)
+        }),
+        _ => (),
+    }
+    pivots
+}
+
 pub fn match_indicator_from_text(words: &[&str]) -> Option<Indicator> {
+    // all indicators take at least one parameter
+    if words.len() < 2 {
+        return None;
+    }
     match words[0].to_uppercase().as_str() {
         "RSI" => {
             match_indicator!(RelativeStrengthIndex, words)
@@ -248,19 +870,63 @@ pub fn match_indicator_from_text(words: &[&str]) -> Option<Indicator> {
         "SDEV" => {
             match_indicator!(StandardDeviation, words)
         }
-        "MACD" => {
-            if let (Ok(fp), Ok(sp), Ok(ss)) = (
+        "ATR" => {
+            match_indicator!(AverageTrueRange, words)
+        }
+        "ADX" => {
+            match_indicator!(AverageDirectionalIndex, words)
+        }
+        "CCI" => {
+            match_indicator!(CCI, words)
+        }
+        "%R" | "WILLR" => {
+            match_indicator!(WilliamsPercentR, words)
+        }
+        "FIB" => match (words[1].to_uppercase().as_str(), words.get(2)) {
+            ("AUTO", None) => return Some(Indicator::FibonacciRetracement(None)),
+            (from, Some(to)) => {
+                if let (Ok(from), Ok(to)) = (from.parse::<f64>(), to.parse::<f64>()) {
+                    return Some(Indicator::FibonacciRetracement(Some((
+                        from.into(),
+                        to.into(),
+                    ))));
+                }
+            }
+            _ => (),
+        },
+        "PIVOTS" => match TimeUnit::from_name(words[1]) {
+            TimeUnit::Unit(_) => (),
+            session => return Some(Indicator::PivotPoints(session)),
+        },
+        "MACD" if words.len() > 3 => {
+            let ma = match words.get(4) {
+                Some(name) => MovingAverageType::from_name(name),
+                None => Some(MovingAverageType::default()),
+            };
+            if let (Ok(fp), Ok(sp), Ok(ss), Some(ma)) = (
                 words[1].parse::<usize>(),
                 words[2].parse::<usize>(),
                 words[3].parse::<usize>(),
+                ma,
             ) {
-                return Some(Indicator::MovingAverageConvergenceDivergence((fp, sp, ss)));
+                return Some(Indicator::MovingAverageConvergenceDivergence((
+                    fp, sp, ss, ma,
+                )));
+            }
+        }
+        "BBANDS" | "%B" | "BBW" => {
+            let w = match words.get(2) {
+                Some(w) => w.parse::<f64>().ok(),
+                None => Some(2.0),
+            };
+            if let (Ok(n), Some(w)) = (words[1].parse::<usize>(), w) {
+                return Some(match words[0].to_uppercase().as_str() {
+                    "%B" => Indicator::BollingerPercentB((n, w.into())),
+                    "BBW" => Indicator::BollingerBandwidth((n, w.into())),
+                    _ => Indicator::BollingerBands((n, w.into())),
+                });
             }
         }
-        "BBANDS" => match words[1].parse::<usize>() {
-            Ok(n) => return Some(Indicator::BollingerBands((n, 2.0.into()))),
-            Err(_) => (),
-        },
         "RL" => match words[1].parse::<f64>() {
             Ok(w) => return Some(Indicator::ResistanceLines(w.into())),
             Err(_) => (),
@@ -269,6 +935,44 @@ pub fn match_indicator_from_text(words: &[&str]) -> Option<Indicator> {
             Ok(w) => return Some(Indicator::SupportLines(w.into())),
             Err(_) => (),
         },
+        "AVWAP" => match words[1].parse::<u64>() {
+            Ok(t) => return Some(Indicator::AnchoredVolumeWeightedAveragePrice(t)),
+            Err(_) => (),
+        },
+        "CRET" => match words[1].parse::<u64>() {
+            Ok(t) => return Some(Indicator::CumulativeReturn(t)),
+            Err(_) => (),
+        },
+        "KAMA" => {
+            let fast = words.get(2).map_or(Ok(2), |w| w.parse::<usize>());
+            let slow = words.get(3).map_or(Ok(30), |w| w.parse::<usize>());
+            if let (Ok(n), Ok(fast), Ok(slow)) = (words[1].parse::<usize>(), fast, slow) {
+                return Some(Indicator::KaufmanAdaptiveMovingAverage((n, fast, slow)));
+            }
+        }
+        "ICHIMOKU" => {
+            let kijun = words.get(2).map_or(Ok(26), |w| w.parse::<usize>());
+            let senkou = words.get(3).map_or(Ok(52), |w| w.parse::<usize>());
+            if let (Ok(tenkan), Ok(kijun), Ok(senkou)) = (words[1].parse::<usize>(), kijun, senkou)
+            {
+                return Some(Indicator::Ichimoku((tenkan, kijun, senkou)));
+            }
+        }
+        "EXPR" => {
+            if let Some(e) = Expression::parse(&words[1..].join(" ")) {
+                return Some(Indicator::Expression(e));
+            }
+        }
+        "VOL" => {
+            let estimator = if words.len() > 2 {
+                VolatilityEstimator::from_name(words[2])
+            } else {
+                Some(VolatilityEstimator::default())
+            };
+            if let (Ok(n), Some(e)) = (words[1].parse::<usize>(), estimator) {
+                return Some(Indicator::Volatility((n, e)));
+            }
+        }
         _ => (),
     };
     None
@@ -281,10 +985,24 @@ impl Indicator {
             Self::SimpleMovingAverage(_) => IndicatorSource::Candle,
             Self::StandardDeviation(_) => IndicatorSource::Candle,
             Self::RelativeStrengthIndex(_) => IndicatorSource::Volume,
+            Self::AverageTrueRange(_) => IndicatorSource::Volume,
+            Self::AverageDirectionalIndex(_) => IndicatorSource::Volume,
+            Self::CCI(_) => IndicatorSource::Candle,
+            Self::WilliamsPercentR(_) => IndicatorSource::Volume,
+            Self::PivotPoints(_) => IndicatorSource::Candle,
+            Self::FibonacciRetracement(_) => IndicatorSource::Candle,
             Self::BollingerBands(_) => IndicatorSource::Candle,
+            Self::BollingerPercentB(_) => IndicatorSource::Volume,
+            Self::BollingerBandwidth(_) => IndicatorSource::Volume,
             Self::MovingAverageConvergenceDivergence(_) => IndicatorSource::Candle,
             Self::ResistanceLines(_) => IndicatorSource::Candle,
             Self::SupportLines(_) => IndicatorSource::Candle,
+            Self::Volatility(_) => IndicatorSource::Volume,
+            Self::KaufmanAdaptiveMovingAverage(_) => IndicatorSource::Candle,
+            Self::AnchoredVolumeWeightedAveragePrice(_) => IndicatorSource::Candle,
+            Self::CumulativeReturn(_) => IndicatorSource::Volume,
+            Self::Ichimoku(_) => IndicatorSource::Candle,
+            Self::Expression(_) => IndicatorSource::Candle,
         }
     }
 
@@ -294,10 +1012,24 @@ impl Indicator {
             Self::SimpleMovingAverage(_) => IndicatorDomain::Price,
             Self::StandardDeviation(_) => IndicatorDomain::Cartesian,
             Self::RelativeStrengthIndex(_) => IndicatorDomain::Percent,
+            Self::AverageTrueRange(_) => IndicatorDomain::Cartesian,
+            Self::AverageDirectionalIndex(_) => IndicatorDomain::Percent,
+            Self::CCI(_) => IndicatorDomain::Cartesian,
+            Self::WilliamsPercentR(_) => IndicatorDomain::Percent,
+            Self::PivotPoints(_) => IndicatorDomain::Price,
+            Self::FibonacciRetracement(_) => IndicatorDomain::Price,
             Self::BollingerBands(_) => IndicatorDomain::Price,
+            Self::BollingerPercentB(_) => IndicatorDomain::Percent,
+            Self::BollingerBandwidth(_) => IndicatorDomain::Cartesian,
             Self::MovingAverageConvergenceDivergence(_) => IndicatorDomain::Cartesian,
             Self::SupportLines(_) => IndicatorDomain::Price,
             Self::ResistanceLines(_) => IndicatorDomain::Price,
+            Self::Volatility(_) => IndicatorDomain::Percent,
+            Self::KaufmanAdaptiveMovingAverage(_) => IndicatorDomain::Price,
+            Self::AnchoredVolumeWeightedAveragePrice(_) => IndicatorDomain::Price,
+            Self::CumulativeReturn(_) => IndicatorDomain::Percent,
+            Self::Ichimoku(_) => IndicatorDomain::Price,
+            Self::Expression(e) => e.domain(),
         }
     }
 
@@ -307,12 +1039,28 @@ impl Indicator {
             Self::SimpleMovingAverage(n) => simple_moving_average_s(*n as usize, samples),
             Self::StandardDeviation(n) => standard_deviation_s(*n as usize, samples),
             Self::RelativeStrengthIndex(n) => relative_strength_index_s(*n as usize, samples),
+            Self::AverageTrueRange(n) => average_true_range_s(*n, samples),
+            Self::AverageDirectionalIndex(n) => adx_s(*n, samples),
+            Self::CCI(n) => cci_s(*n, samples),
+            Self::WilliamsPercentR(n) => williams_r_s(*n, samples),
+            Self::PivotPoints(session) => pivot_points_s(session, samples),
+            Self::FibonacciRetracement(range) => {
+                fibonacci_s(range.as_ref().map(|(a, b)| (a.value, b.value)), samples)
+            }
             Self::BollingerBands((n, w)) => bollinger_bands_s(*n, w.value, samples),
-            Self::MovingAverageConvergenceDivergence((fp, sp, ss)) => {
-                macd_s(*fp, *sp, *ss, samples)
+            Self::BollingerPercentB((n, w)) => bollinger_percent_b_s(*n, w.value, samples),
+            Self::BollingerBandwidth((n, w)) => bollinger_bandwidth_s(*n, w.value, samples),
+            Self::MovingAverageConvergenceDivergence((fp, sp, ss, ma)) => {
+                macd_s(*fp, *sp, *ss, *ma, samples)
             }
             Self::ResistanceLines(w) => resistance_lines(w.value, false, samples),
             Self::SupportLines(w) => resistance_lines(w.value, true, samples),
+            Self::Volatility((n, e)) => volatility_s(*n, e, samples),
+            Self::KaufmanAdaptiveMovingAverage((n, f, s)) => kama_s(*n, *f, *s, samples),
+            Self::AnchoredVolumeWeightedAveragePrice(t) => anchored_vwap_s(*t, samples),
+            Self::CumulativeReturn(t) => cumulative_return_s(*t, samples),
+            Self::Ichimoku((t, k, s)) => ichimoku_s(*t, *k, *s, samples),
+            Self::Expression(e) => e.compute_series(samples),
         }
     }
     pub fn compute(&self, samples: &[Sample]) -> Result<IndicatorData, DiError> {
@@ -321,10 +1069,108 @@ impl Indicator {
             Self::SimpleMovingAverage(n) => simple_moving_average(*n as usize, samples),
             Self::StandardDeviation(n) => standard_deviation(*n as usize, samples),
             Self::RelativeStrengthIndex(n) => relative_strength_index(*n as usize, samples),
+            Self::AverageTrueRange(n) => average_true_range(*n, samples),
+            Self::AverageDirectionalIndex(n) => last_of_matrix(adx_s(*n, samples)),
+            Self::CCI(n) => last_of(cci_s(*n, samples)),
+            Self::WilliamsPercentR(n) => last_of(williams_r_s(*n, samples)),
+            Self::PivotPoints(session) => last_of_matrix(pivot_points_s(session, samples)),
+            Self::FibonacciRetracement(range) => last_of_matrix(fibonacci_s(
+                range.as_ref().map(|(a, b)| (a.value, b.value)),
+                samples,
+            )),
             Self::BollingerBands((n, w)) => bollinger_bands(*n, w.value, samples),
-            Self::MovingAverageConvergenceDivergence((fp, sp, ss)) => macd(*fp, *sp, *ss, samples),
+            Self::BollingerPercentB((n, w)) => last_of(bollinger_percent_b_s(*n, w.value, samples)),
+            Self::BollingerBandwidth((n, w)) => {
+                last_of(bollinger_bandwidth_s(*n, w.value, samples))
+            }
+            Self::MovingAverageConvergenceDivergence((fp, sp, ss, ma)) => {
+                macd(*fp, *sp, *ss, *ma, samples)
+            }
             Self::ResistanceLines(w) => resistance_lines(w.value, false, samples),
             Self::SupportLines(w) => resistance_lines(w.value, true, samples),
+            Self::Volatility((n, e)) => volatility(*n, e, samples),
+            Self::KaufmanAdaptiveMovingAverage((n, f, s)) => last_of(kama_s(*n, *f, *s, samples)),
+            Self::AnchoredVolumeWeightedAveragePrice(t) => last_of(anchored_vwap_s(*t, samples)),
+            Self::CumulativeReturn(t) => last_of(cumulative_return_s(*t, samples)),
+            Self::Ichimoku((t, k, s)) => ichimoku(*t, *k, *s, samples),
+            Self::Expression(e) => e.compute(samples),
+        }
+    }
+
+    /// Bars each series of `compute_series` is drawn ahead of its sample,
+    /// behind when negative. Empty when no series is shifted.
+    pub fn shifts(&self) -> Vec<i64> {
+        match &self {
+            Self::Ichimoku((_, k, _)) => vec![0, 0, *k as i64, *k as i64, -(*k as i64)],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Values marked with a horizontal line under the series.
+    pub fn reference_lines(&self) -> Vec<f64> {
+        match &self {
+            Self::CCI(_) => vec![0.0],
+            // overbought and oversold
+            Self::WilliamsPercentR(_) => vec![-20.0, -80.0],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Lowest and highest values of a Percent domain indicator.
+    pub fn percent_range(&self) -> (f64, f64) {
+        match &self {
+            Self::WilliamsPercentR(_) => (-100.0, 0.0),
+            _ => (0.0, 100.0),
+        }
+    }
+
+    /// Series of `compute_series` whose gap is filled, as a cloud.
+    pub fn cloud(&self) -> Option<(usize, usize)> {
+        match &self {
+            Self::Ichimoku(_) => Some((2, 3)),
+            _ => None,
+        }
+    }
+    /// Timestamp the indicator starts from, for anchored indicators.
+    pub fn anchor(&self) -> Option<u64> {
+        match &self {
+            Self::AnchoredVolumeWeightedAveragePrice(t) => Some(*t),
+            Self::CumulativeReturn(t) => Some(*t),
+            _ => None,
+        }
+    }
+
+    /// Text form accepted by match_indicator_from_text.
+    pub fn to_text(&self) -> String {
+        match &self {
+            Self::ExponentialMovingAverage(n) => format!("ema {}", n),
+            Self::SimpleMovingAverage(n) => format!("sma {}", n),
+            Self::StandardDeviation(n) => format!("sdev {}", n),
+            Self::RelativeStrengthIndex(n) => format!("rsi {}", n),
+            Self::AverageTrueRange(n) => format!("atr {}", n),
+            Self::AverageDirectionalIndex(n) => format!("adx {}", n),
+            Self::CCI(n) => format!("cci {}", n),
+            Self::WilliamsPercentR(n) => format!("%r {}", n),
+            Self::PivotPoints(session) => format!("pivots {}", session.name()),
+            Self::FibonacciRetracement(range) => match range {
+                Some((from, to)) => format!("fib {} {}", from.value, to.value),
+                None => String::from("fib auto"),
+            },
+            Self::BollingerBands((n, w)) => format!("bbands {} {}", n, w.value),
+            Self::BollingerPercentB((n, w)) => format!("%b {} {}", n, w.value),
+            Self::BollingerBandwidth((n, w)) => format!("bbw {} {}", n, w.value),
+            Self::MovingAverageConvergenceDivergence((fp, sp, ss, ma)) => match ma {
+                MovingAverageType::Exponential => format!("macd {} {} {}", fp, sp, ss),
+                _ => format!("macd {} {} {} {}", fp, sp, ss, ma.name()),
+            },
+            Self::ResistanceLines(w) => format!("rl {}", w.value),
+            Self::SupportLines(w) => format!("sl {}", w.value),
+            Self::Volatility((n, e)) => format!("vol {} {}", n, e.name()),
+            Self::KaufmanAdaptiveMovingAverage((n, f, s)) => format!("kama {} {} {}", n, f, s),
+            Self::AnchoredVolumeWeightedAveragePrice(t) => format!("avwap {}", t),
+            Self::CumulativeReturn(t) => format!("cret {}", t),
+            Self::Ichimoku((t, k, s)) => format!("ichimoku {} {} {}", t, k, s),
+            Self::Expression(e) => format!("expr {}", e.to_text()),
         }
     }
     pub fn to_string(&self) -> String {
@@ -333,16 +1179,36 @@ impl Indicator {
             Self::SimpleMovingAverage(n) => format!("SMA {:?}", n),
             Self::StandardDeviation(n) => format!("sdev {:?}", n),
             Self::RelativeStrengthIndex(n) => format!("rsi {:?}", n),
+            Self::AverageTrueRange(n) => format!("ATR {:?}", n),
+            Self::AverageDirectionalIndex(n) => format!("ADX {:?}", n),
+            Self::CCI(n) => format!("CCI {:?}", n),
+            Self::WilliamsPercentR(n) => format!("%R {:?}", n),
+            Self::PivotPoints(session) => format!("Pivots {}", session.name()),
+            Self::FibonacciRetracement(range) => match range {
+                Some((from, to)) => format!("Fib {:?} -> {:?}", from.value, to.value),
+                None => String::from("Fib last swing"),
+            },
             Self::BollingerBands((n, w)) => format!("B-Bands {:?} {:?}", n, w.value),
-            Self::MovingAverageConvergenceDivergence((fp, sp, ss)) => {
-                format!("MACD {:?} {:?} {:?}", fp, sp, ss)
-            }
+            Self::BollingerPercentB((n, w)) => format!("%B {:?} {:?}", n, w.value),
+            Self::BollingerBandwidth((n, w)) => format!("B-Width {:?} {:?}", n, w.value),
+            Self::MovingAverageConvergenceDivergence((fp, sp, ss, ma)) => match ma {
+                MovingAverageType::Exponential => format!("MACD {:?} {:?} {:?}", fp, sp, ss),
+                _ => format!("MACD-{} {:?} {:?} {:?}", ma.name(), fp, sp, ss),
+            },
             Self::ResistanceLines(w) => {
                 format!("RL {:?}", w.value)
             }
             Self::SupportLines(w) => {
                 format!("SL {:?}", w.value)
             }
+            Self::Volatility((n, e)) => format!("vol-{} {:?}", e.name(), n),
+            Self::KaufmanAdaptiveMovingAverage((n, f, s)) => {
+                format!("KAMA {:?} {:?} {:?}", n, f, s)
+            }
+            Self::AnchoredVolumeWeightedAveragePrice(t) => format!("AVWAP @{}", t),
+            Self::CumulativeReturn(t) => format!("return @{}", t),
+            Self::Ichimoku((t, k, s)) => format!("Ichimoku {:?} {:?} {:?}", t, k, s),
+            Self::Expression(e) => e.to_text(),
         }
     }
 }
@@ -357,3 +1223,360 @@ impl Indicators {
         self.indicators.push(indicator.clone());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        match_indicator_from_text, zigzag, Indicator, IndicatorData, MovingAverageType,
+        SwingThreshold,
+    };
+    use crate::finance::Sample;
+    use crate::time::TimeUnit;
+
+    #[test]
+    fn test_zigzag() {
+        let samples: Vec<Sample> = [100.0, 104.0, 110.0, 108.0, 98.0, 96.0, 101.0, 107.0, 105.0]
+            .iter()
+            .enumerate()
+            .map(|(i, p)| Sample {
+                timestamp: i as u64,
+                open: *p,
+                high: *p,
+                low: *p,
+                close: *p,
+                ..Default::default()
+            })
+            .collect();
+        let pivots = zigzag(SwingThreshold::Percent(5.0), &samples);
+        let points: Vec<(usize, bool, bool)> = pivots
+            .iter()
+            .map(|p| (p.index, p.high, p.confirmed))
+            .collect();
+        assert_eq!(
+            points,
+            vec![
+                (0, false, true),
+                (2, true, true),
+                (5, false, true),
+                (7, true, false)
+            ]
+        );
+        assert_eq!(pivots[1].price, 110.0);
+        // a wide threshold never confirms a swing
+        assert!(zigzag(SwingThreshold::Percent(50.0), &samples).is_empty());
+        let pivots = zigzag(SwingThreshold::Atr(3, 1.5), &samples);
+        assert!(pivots.windows(2).all(|w| w[0].high != w[1].high));
+    }
+
+    #[test]
+    fn test_average_true_range() {
+        let samples: Vec<Sample> = [10.0, 12.0, 11.0, 13.0]
+            .iter()
+            .map(|p| Sample {
+                open: *p,
+                high: p + 1.0,
+                low: p - 1.0,
+                close: *p,
+                ..Default::default()
+            })
+            .collect();
+        let atr = match_indicator_from_text(&["ATR", "3"]).unwrap();
+        assert_eq!(atr, Indicator::AverageTrueRange(3));
+        assert_eq!(atr.to_text(), "atr 3");
+        match atr.compute_series(&samples) {
+            // the gaps between closes widen the true range past the bar range
+            Ok(IndicatorData::Vector(v)) => {
+                assert_eq!(v.len(), 4);
+                assert_eq!(v[0], 2.0);
+                assert!(v[1] > 2.0);
+            }
+            _ => panic!(),
+        }
+        assert!(matches!(
+            atr.compute(&samples),
+            Ok(IndicatorData::Scalar(v)) if v > 2.0
+        ));
+    }
+
+    #[test]
+    fn test_adx() {
+        // a steady uptrend has no downward movement
+        let samples: Vec<Sample> = (0..30)
+            .map(|i| Sample {
+                high: 11.0 + i as f64,
+                low: 9.0 + i as f64,
+                close: 10.0 + i as f64,
+                ..Default::default()
+            })
+            .collect();
+        let adx = match_indicator_from_text(&["adx", "5"]).unwrap();
+        assert_eq!(adx, Indicator::AverageDirectionalIndex(5));
+        match adx.compute_series(&samples) {
+            Ok(IndicatorData::Matrix(m)) => {
+                assert_eq!(m.len(), 3);
+                assert!(m[2].iter().all(|v| *v == 0.0));
+                assert!(m[1][29] > 0.0);
+                assert!(m[0].windows(2).all(|w| w[1] >= w[0]));
+                assert!(m[0][29] > 90.0);
+            }
+            _ => panic!(),
+        }
+        assert!(matches!(
+            adx.compute(&samples),
+            Ok(IndicatorData::Matrix(m)) if m.len() == 3 && m[0].len() == 1
+        ));
+    }
+
+    #[test]
+    fn test_cci() {
+        let samples: Vec<Sample> = [1.0, 2.0, 3.0]
+            .iter()
+            .map(|p| Sample {
+                high: *p,
+                low: *p,
+                close: *p,
+                ..Default::default()
+            })
+            .collect();
+        let cci = match_indicator_from_text(&["cci", "3"]).unwrap();
+        assert_eq!(cci, Indicator::CCI(3));
+        assert_eq!(cci.reference_lines(), vec![0.0]);
+        match cci.compute_series(&samples) {
+            Ok(IndicatorData::Vector(v)) => assert_eq!(v[0], 0.0),
+            _ => panic!(),
+        }
+        // mean 2, mean deviation 2/3
+        match cci.compute(&samples) {
+            Ok(IndicatorData::Scalar(v)) => assert!((v - 100.0).abs() < 1e-9),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_williams_r() {
+        let samples: Vec<Sample> = [(12.0, 8.0, 10.0), (14.0, 9.0, 14.0), (13.0, 10.0, 11.0)]
+            .iter()
+            .map(|(high, low, close)| Sample {
+                high: *high,
+                low: *low,
+                close: *close,
+                ..Default::default()
+            })
+            .collect();
+        let r = match_indicator_from_text(&["willr", "2"]).unwrap();
+        assert_eq!(r, Indicator::WilliamsPercentR(2));
+        assert_eq!(match_indicator_from_text(&["%R", "2"]), Some(r.clone()));
+        match r.compute_series(&samples) {
+            Ok(IndicatorData::Vector(v)) => assert_eq!(v, vec![-50.0, 0.0, -60.0]),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_pivot_points() {
+        let day = 24 * 3600;
+        let samples: Vec<Sample> = [
+            (0, 12.0, 8.0, 10.0),
+            (day / 2, 14.0, 9.0, 11.0),
+            (day, 20.0, 1.0, 5.0),
+            (day * 3 / 2, 12.0, 10.0, 11.0),
+        ]
+        .iter()
+        .map(|(t, high, low, close)| Sample {
+            timestamp: *t,
+            high: *high,
+            low: *low,
+            close: *close,
+            ..Default::default()
+        })
+        .collect();
+        let pivots = match_indicator_from_text(&["pivots", "1d"]).unwrap();
+        assert_eq!(pivots, Indicator::PivotPoints(TimeUnit::Day(1)));
+        assert!(match_indicator_from_text(&["pivots", "daily"]).is_none());
+        match pivots.compute_series(&samples) {
+            // high 14, low 8, close 11 of the first day
+            Ok(IndicatorData::Matrix(m)) => assert_eq!(
+                m,
+                vec![
+                    vec![17.0, 17.0],
+                    vec![14.0, 14.0],
+                    vec![11.0, 11.0],
+                    vec![8.0, 8.0],
+                    vec![5.0, 5.0]
+                ]
+            ),
+            _ => panic!(),
+        }
+        assert!(pivots.compute_series(&samples[..2]).is_err());
+    }
+
+    #[test]
+    fn test_fibonacci() {
+        let fib = match_indicator_from_text(&["fib", "100", "200"]).unwrap();
+        assert_eq!(fib.to_text(), "fib 100 200");
+        let samples = vec![Sample::default(); 3];
+        match fib.compute_series(&samples) {
+            Ok(IndicatorData::Matrix(m)) => {
+                assert_eq!(m.len(), 7);
+                assert_eq!(m[0], vec![200.0; 3]);
+                assert_eq!(m[3][0], 150.0);
+                assert_eq!(m[6][2], 100.0);
+            }
+            _ => panic!(),
+        }
+        let auto = match_indicator_from_text(&["fib", "auto"]).unwrap();
+        assert_eq!(auto, Indicator::FibonacciRetracement(None));
+        // without a swing the range of the samples is retraced
+        let samples: Vec<Sample> = [(12.0, 10.0), (11.0, 9.0)]
+            .iter()
+            .map(|(high, low)| Sample {
+                high: *high,
+                low: *low,
+                ..Default::default()
+            })
+            .collect();
+        match auto.compute(&samples) {
+            Ok(IndicatorData::Matrix(m)) => assert_eq!((m[0][0], m[6][0]), (12.0, 9.0)),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_ichimoku() {
+        let samples: Vec<Sample> = (0..6)
+            .map(|i| Sample {
+                high: 10.0 + i as f64,
+                low: i as f64,
+                close: 5.0 + i as f64,
+                ..Default::default()
+            })
+            .collect();
+        let ichimoku = match_indicator_from_text(&["ichimoku", "2", "3"]).unwrap();
+        assert_eq!(ichimoku, Indicator::Ichimoku((2, 3, 52)));
+        assert_eq!(ichimoku.shifts(), vec![0, 0, 3, 3, -3]);
+        match ichimoku.compute_series(&samples) {
+            Ok(IndicatorData::Matrix(m)) => {
+                assert_eq!(m.len(), 5);
+                // highs 13, 14 and lows 3, 4
+                assert_eq!(m[0][4], 8.5);
+                // highs 12..=14 and lows 2..=4
+                assert_eq!(m[1][4], 8.0);
+                assert_eq!(m[2][4], 8.25);
+                assert_eq!(m[3][4], 7.0);
+                assert_eq!(m[4][4], 9.0);
+            }
+            _ => panic!(),
+        }
+        match ichimoku.compute(&samples) {
+            Ok(IndicatorData::Matrix(m)) => {
+                assert_eq!(m[0][0], 9.5);
+                // the cloud over the last bar was projected from bar 2
+                assert_eq!(m[3][0], 6.0);
+                assert_eq!(m[4][0], 10.0);
+            }
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_anchored() {
+        let samples: Vec<Sample> = [(10.0, 1), (20.0, 1), (30.0, 3)]
+            .iter()
+            .enumerate()
+            .map(|(i, (p, v))| Sample {
+                timestamp: i as u64 * 1000,
+                high: *p,
+                low: *p,
+                close: *p,
+                volume: *v,
+                ..Default::default()
+            })
+            .collect();
+        let avwap = match_indicator_from_text(&["avwap", "1000"]).unwrap();
+        assert_eq!(avwap, Indicator::AnchoredVolumeWeightedAveragePrice(1000));
+        assert_eq!(avwap.anchor(), Some(1000));
+        match avwap.compute_series(&samples) {
+            Ok(IndicatorData::Vector(v)) => assert_eq!(v, vec![20.0, 27.5]),
+            _ => panic!(),
+        }
+        match Indicator::CumulativeReturn(500).compute(&samples) {
+            Ok(IndicatorData::Scalar(r)) => assert!((r - 50.0).abs() < 1e-9),
+            _ => panic!(),
+        }
+        assert!(Indicator::CumulativeReturn(5000)
+            .compute_series(&samples)
+            .is_err());
+    }
+
+    #[test]
+    fn test_bollinger_outputs() {
+        let samples: Vec<Sample> = [10.0, 12.0, 11.0, 13.0, 12.0, 16.0]
+            .iter()
+            .map(|p| Sample {
+                close: *p,
+                ..Default::default()
+            })
+            .collect();
+        let (b, w) = match (
+            match_indicator_from_text(&["%b", "3"])
+                .unwrap()
+                .compute(&samples),
+            match_indicator_from_text(&["bbw", "3", "2"])
+                .unwrap()
+                .compute(&samples),
+        ) {
+            (Ok(IndicatorData::Scalar(b)), Ok(IndicatorData::Scalar(w))) => (b, w),
+            _ => panic!(),
+        };
+        // the last close is above the middle band
+        assert!(b > 50.0);
+        assert!(w > 0.0);
+    }
+
+    #[test]
+    fn test_moving_average_types() {
+        let values = [1.0, 2.0, 3.0, 4.0];
+        let volumes = [1.0, 1.0, 0.0, 3.0];
+        assert_eq!(
+            MovingAverageType::Weighted.series(3, &values, &volumes),
+            vec![1.0, 5.0 / 3.0, 14.0 / 6.0, 20.0 / 6.0]
+        );
+        assert_eq!(
+            MovingAverageType::VolumeWeighted.series(2, &values, &volumes),
+            vec![1.0, 1.5, 2.0, 4.0]
+        );
+        let macd = match_indicator_from_text(&["macd", "12", "26", "9", "vwma"]).unwrap();
+        assert_eq!(
+            macd,
+            Indicator::MovingAverageConvergenceDivergence((
+                12,
+                26,
+                9,
+                MovingAverageType::VolumeWeighted
+            ))
+        );
+        assert_eq!(macd.to_text(), "macd 12 26 9 vwma");
+        assert!(match_indicator_from_text(&["macd", "12", "26", "9", "xma"]).is_none());
+    }
+
+    #[test]
+    fn test_kama() {
+        let samples: Vec<Sample> = [10.0, 11.0, 12.0, 13.0, 12.0, 13.0]
+            .iter()
+            .map(|p| Sample {
+                close: *p,
+                ..Default::default()
+            })
+            .collect();
+        let kama = match_indicator_from_text(&["kama", "3"]).unwrap();
+        assert_eq!(kama, Indicator::KaufmanAdaptiveMovingAverage((3, 2, 30)));
+        let v = match kama.compute_series(&samples) {
+            Ok(IndicatorData::Vector(v)) => v,
+            _ => panic!(),
+        };
+        // a steady trend moves with the fast constant: 10 + (2/3)^2
+        assert!((v[1] - (10.0 + 4.0 / 9.0)).abs() < 1e-9);
+        assert!(v.windows(2).take(3).all(|w| w[1] > w[0]));
+        assert!(v.iter().all(|k| *k >= 10.0 && *k <= 13.0));
+    }
+}
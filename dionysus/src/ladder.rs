@@ -0,0 +1,101 @@
+use crate::finance::{Book, Order, Side};
+
+/// Price level of a depth-of-market ladder.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Rung {
+    pub price: f64,
+    pub bid: f64,
+    pub ask: f64,
+    /// Quantity of the resting buy and sell orders at this price.
+    pub buys: f64,
+    pub sells: f64,
+}
+
+/// Rung of `price`, the epsilon keeps exact multiples in their own rung.
+fn rung_index(price: f64, step: f64, round: fn(f64) -> f64, epsilon: f64) -> i64 {
+    round(price / step + epsilon) as i64
+}
+
+/// Rungs `step` apart, `depth` on each side of the one holding `center`,
+/// highest price first. Bid levels are summed into the rung at or below them,
+/// asks into the rung at or above, so rungs never cross the spread. Orders of
+/// the book token are placed at their nearest rung.
+pub fn ladder(book: &Book, orders: &[Order], center: f64, step: f64, depth: usize) -> Vec<Rung> {
+    if step <= 0.0 {
+        return Vec::new();
+    }
+    let top = rung_index(center, step, f64::round, 0.0) + depth as i64;
+    let mut rungs: Vec<Rung> = (0..=2 * depth as i64)
+        .map(|i| Rung {
+            price: (top - i) as f64 * step,
+            ..Default::default()
+        })
+        .collect();
+    let count = rungs.len();
+    let slot = |index: i64| usize::try_from(top - index).ok().filter(|i| *i < count);
+    for line in &book.bids {
+        if let Some(i) = slot(rung_index(line.price, step, f64::floor, 1e-9)) {
+            rungs[i].bid += line.quantity;
+        }
+    }
+    for line in &book.asks {
+        if let Some(i) = slot(rung_index(line.price, step, f64::ceil, -1e-9)) {
+            rungs[i].ask += line.quantity;
+        }
+    }
+    for order in orders.iter().filter(|o| o.token == book.token) {
+        if let Some(i) = slot(rung_index(order.price, step, f64::round, 0.0)) {
+            match order.side {
+                Side::Buy => rungs[i].buys += order.quantity,
+                Side::Sell => rungs[i].sells += order.quantity,
+            }
+        }
+    }
+    rungs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ladder;
+    use crate::finance::{Book, BookLine, Order, OrderType, Side, TimeInForce, Token};
+    use crate::time::Date;
+
+    #[test]
+    fn test_ladder() {
+        let token = Token::pair("BTC", "USDT");
+        let line = |price: f64, quantity: f64| BookLine { price, quantity };
+        let book = Book {
+            token: token.clone(),
+            bids: vec![line(99.9, 1.0), line(99.5, 2.0), line(99.0, 3.0)],
+            asks: vec![line(100.1, 1.5), line(100.4, 0.5), line(105.0, 9.0)],
+        };
+        let order = |token: &Token, side: Side, price: f64| Order {
+            index: 0,
+            position_index: None,
+            id: Some(1),
+            token: token.clone(),
+            date: Date::from_timestamp(0),
+            side,
+            quantity: 0.25,
+            price,
+            stop_price: None,
+            order_type: OrderType::Limit,
+            tif: TimeInForce::default(),
+        };
+        let orders = vec![
+            order(&token, Side::Buy, 99.0),
+            order(&token, Side::Sell, 101.0),
+            order(&Token::pair("ETH", "USDT"), Side::Buy, 100.0),
+        ];
+        let rungs = ladder(&book, &orders, 100.0, 0.5, 2);
+        let prices: Vec<f64> = rungs.iter().map(|r| r.price).collect();
+        assert_eq!(prices, vec![101.0, 100.5, 100.0, 99.5, 99.0]);
+        // 100.1 and 100.4 round up to 100.5, 99.9 down to 99.5
+        assert_eq!((rungs[1].ask, rungs[1].bid), (2.0, 0.0));
+        assert_eq!(rungs[3].bid, 3.0);
+        assert_eq!((rungs[4].bid, rungs[4].buys), (3.0, 0.25));
+        assert_eq!(rungs[0].sells, 0.25);
+        assert_eq!((rungs[2].bid, rungs[2].ask, rungs[2].buys), (0.0, 0.0, 0.0));
+        assert!(ladder(&book, &orders, 100.0, 0.0, 2).is_empty());
+    }
+}
@@ -1,10 +1,35 @@
+pub mod alerts;
+pub mod allocation;
+pub mod analytics;
 pub mod backtest;
+pub mod bar_clock;
+pub mod book_recorder;
+pub mod bracket;
 pub mod counselor;
+pub mod dca;
+pub mod expression;
 pub mod finance;
+pub mod fixture;
+pub mod forward;
+pub mod futures;
 pub mod historical_data;
 pub mod indicators;
+pub mod ladder;
 pub mod market;
+pub mod mock;
+pub mod news;
+pub mod progress;
+pub mod refresher;
+pub mod renko;
+pub mod report;
+pub mod retry;
+pub mod risk;
+pub mod signal_log;
+pub mod snapshots;
+pub mod stats;
 pub mod strategy;
+pub mod stream_stats;
+pub mod symbols;
 pub mod time;
 pub mod trader;
 pub mod utils;
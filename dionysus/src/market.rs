@@ -1,5 +1,5 @@
 use crate::binance::BinanceMarket;
-use crate::finance::DiError;
+use crate::finance::{DiError, Token};
 
 #[derive(Default)]
 pub struct PairPrice {
@@ -55,13 +55,12 @@ impl Market for BinanceMarket {
                 let binance::model::Prices::AllPrices(prices) = answer;
                 return Ok(prices
                     .iter()
-                    .filter(|price| price.symbol.contains(currency))
-                    .map(|price| PairPrice {
-                        symbol: String::from(
-                            &price.symbol[..(price.symbol.len() - currency.len())],
-                        ),
-                        currency: String::from(&price.symbol[currency.len()..]),
-                        price: price.price,
+                    .filter_map(|price| {
+                        Token::parse_pair(&price.symbol, &[currency]).map(|token| PairPrice {
+                            symbol: token.get_symbol(),
+                            currency: token.get_currency(),
+                            price: price.price,
+                        })
                     })
                     .collect());
             }
@@ -74,16 +73,17 @@ impl Market for BinanceMarket {
             Ok(stats) => {
                 return Ok(stats
                     .iter()
-                    .filter(|stat| stat.symbol.contains(currency))
-                    .map(|stat| PairPriceStats {
-                        symbol: String::from(&stat.symbol[..(stat.symbol.len() - currency.len())]),
-                        currency: String::from(&stat.symbol[currency.len()..]),
-                        last_price: stat.last_price,
-                        volume: stat.volume,
-                        price_change_percent: stat
-                            .price_change_percent
-                            .parse::<f64>()
-                            .unwrap_or(0.0),
+                    .filter_map(|stat| {
+                        Token::parse_pair(&stat.symbol, &[currency]).map(|token| PairPriceStats {
+                            symbol: token.get_symbol(),
+                            currency: token.get_currency(),
+                            last_price: stat.last_price,
+                            volume: stat.volume,
+                            price_change_percent: stat
+                                .price_change_percent
+                                .parse::<f64>()
+                                .unwrap_or(0.0),
+                        })
                     })
                     .collect());
             }
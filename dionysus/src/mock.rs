@@ -0,0 +1,339 @@
+use crate::cache::Cache;
+use crate::finance::{DiError, Order, OrderStatus, Sample, Side, Token};
+use crate::historical_data::HistoricalData;
+use crate::market::{Market, PairPrice, PairPriceStats};
+use crate::time::{Date, TimeWindow};
+use crate::trader::Trader;
+use binance::model::Transaction;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+
+/// Scripted answer of the mock trader to the next submitted order.
+pub enum MockFill {
+    /// Fills the given fraction [0, 1] of the order quantity.
+    Fill(f64),
+    /// Rejects the order with the given error.
+    Reject(DiError),
+}
+
+/// Offline market serving canned prices and klines. Errors pushed with
+/// `inject_error` are returned (in order) by the next calls.
+#[derive(Default)]
+pub struct MockMarket {
+    pub prices: HashMap<Token, f64>,
+    pub klines: HashMap<Token, Vec<Sample>>,
+    pub cache: Cache,
+    errors: RefCell<VecDeque<DiError>>,
+}
+
+impl MockMarket {
+    pub fn with_samples(token: &Token, samples: &[Sample]) -> Self {
+        let mut market = MockMarket::default();
+        market.set_samples(token, samples);
+        market
+    }
+
+    pub fn set_samples(&mut self, token: &Token, samples: &[Sample]) {
+        self.klines.insert(token.clone(), samples.to_vec());
+        if let Some(last) = samples.last() {
+            self.prices.insert(token.clone(), last.close);
+        }
+    }
+
+    pub fn set_price(&mut self, token: &Token, price: f64) {
+        self.prices.insert(token.clone(), price);
+    }
+
+    pub fn inject_error(&self, error: DiError) {
+        self.errors.borrow_mut().push_back(error);
+    }
+
+    fn next_error(&self) -> Option<DiError> {
+        self.errors.borrow_mut().pop_front()
+    }
+
+    fn pair_price(&self, symbol: &str, currency: &str) -> Result<f64, DiError> {
+        match self.prices.get(&Token::pair(symbol, currency)) {
+            Some(price) => Ok(*price),
+            None => Err(DiError::NotFound),
+        }
+    }
+}
+
+impl Market for MockMarket {
+    fn get_price(&self, symbol: &str, currency: &str) -> Result<f64, DiError> {
+        if let Some(e) = self.next_error() {
+            return Err(e);
+        }
+        self.pair_price(symbol, currency)
+    }
+
+    fn get_24h_price(&self, symbol: &str, currency: &str) -> Result<PairPriceStats, DiError> {
+        if let Some(e) = self.next_error() {
+            return Err(e);
+        }
+        let price = self.pair_price(symbol, currency)?;
+        Ok(PairPriceStats {
+            symbol: String::from(symbol),
+            currency: String::from(currency),
+            price_change_percent: 0.0,
+            last_price: price,
+            volume: 0.0,
+        })
+    }
+
+    fn get_all_prices(&self, currency: &str) -> Result<Vec<PairPrice>, DiError> {
+        if let Some(e) = self.next_error() {
+            return Err(e);
+        }
+        Ok(self
+            .prices
+            .iter()
+            .filter(|(token, _)| token.get_currency() == currency)
+            .map(|(token, price)| PairPrice {
+                symbol: token.get_symbol(),
+                currency: token.get_currency(),
+                price: *price,
+            })
+            .collect())
+    }
+
+    fn get_all_24h_price_stats(&self, currency: &str) -> Result<Vec<PairPriceStats>, DiError> {
+        if let Some(e) = self.next_error() {
+            return Err(e);
+        }
+        Ok(self
+            .prices
+            .iter()
+            .filter(|(token, _)| token.get_currency() == currency)
+            .map(|(token, price)| PairPriceStats {
+                symbol: token.get_symbol(),
+                currency: token.get_currency(),
+                price_change_percent: 0.0,
+                last_price: *price,
+                volume: 0.0,
+            })
+            .collect())
+    }
+}
+
+impl HistoricalData for MockMarket {
+    fn append(&mut self, token: &Token, sample: &Sample) -> Result<(), DiError> {
+        if let Some(e) = self.next_error() {
+            return Err(e);
+        }
+        let v = vec![sample.clone()];
+        self.cache.write(token, &v[..])
+    }
+
+    fn fetch_last(&mut self, token: &Token, duration: &TimeWindow) -> Result<&[Sample], DiError> {
+        if let Some(e) = self.next_error() {
+            return Err(e);
+        }
+        let samples: Vec<Sample> = match self.klines.get(token) {
+            Some(klines) => klines
+                .iter()
+                .filter(|sample| sample.resolution == duration.resolution)
+                .cloned()
+                .collect(),
+            None => return Err(DiError::NotFound),
+        };
+        self.cache.write(token, &samples[..])?;
+        self.cache.read(token, duration)
+    }
+
+    fn get_last(&self, token: &Token, duration: &TimeWindow) -> Result<&[Sample], DiError> {
+        if let Some(e) = self.next_error() {
+            return Err(e);
+        }
+        self.cache.read(token, duration)
+    }
+}
+
+/// Offline trader recording submitted orders. Each order consumes the next
+/// scripted `MockFill`, orders are fully filled when the script is empty.
+#[derive(Default)]
+pub struct MockTrader {
+    pub submitted: RefCell<Vec<Order>>,
+    pub open_orders: RefCell<Vec<OrderStatus>>,
+    script: RefCell<VecDeque<MockFill>>,
+    next_id: RefCell<u64>,
+}
+
+impl MockTrader {
+    pub fn script(&self, fill: MockFill) {
+        self.script.borrow_mut().push_back(fill);
+    }
+
+    fn submit(&self, order: &Order) -> Result<Transaction, DiError> {
+        let ratio = match self.script.borrow_mut().pop_front() {
+            Some(MockFill::Reject(e)) => return Err(e),
+            Some(MockFill::Fill(ratio)) => ratio.clamp(0.0, 1.0),
+            None => 1.0,
+        };
+        let order_id = {
+            let mut next_id = self.next_id.borrow_mut();
+            *next_id += 1;
+            *next_id
+        };
+        let executed_qty = order.quantity * ratio;
+        let status = if ratio >= 1.0 {
+            "FILLED"
+        } else if ratio > 0.0 {
+            "PARTIALLY_FILLED"
+        } else {
+            "NEW"
+        };
+
+        let mut submitted = order.clone();
+        submitted.id = Some(order_id as i64);
+        if ratio < 1.0 {
+            self.open_orders.borrow_mut().push(OrderStatus {
+                order: submitted.clone(),
+                executed_qty,
+                status: String::from(status),
+                update_time: Date::now(),
+                is_working: true,
+            });
+        }
+        self.submitted.borrow_mut().push(submitted);
+
+        let transaction = serde_json::json!({
+            "symbol": order.token.to_string(),
+            "orderId": order_id,
+            "orderListId": -1,
+            "clientOrderId": format!("mock-{}", order_id),
+            "transactTime": Date::now().timestamp() as u64 * 1000,
+            "price": order.price,
+            "origQty": order.quantity,
+            "executedQty": executed_qty,
+            "cummulativeQuoteQty": executed_qty * order.price,
+            "status": status,
+            "timeInForce": format!("{:?}", order.tif),
+            "type": format!("{:?}", order.order_type).to_uppercase(),
+            "side": match order.side {
+                Side::Buy => "BUY",
+                Side::Sell => "SELL",
+            },
+            "fills": [],
+        });
+        serde_json::from_value(transaction).map_err(|e| DiError::Message(format!("{:?}", e)))
+    }
+}
+
+impl Trader for MockTrader {
+    fn buy_order(&self, order: &Order) -> Result<Transaction, DiError> {
+        self.submit(order)
+    }
+
+    fn sell_order(&self, order: &Order) -> Result<Transaction, DiError> {
+        self.submit(order)
+    }
+
+    fn get_all_open_orders(&self) -> Result<Vec<OrderStatus>, DiError> {
+        Ok(self.open_orders.borrow().clone())
+    }
+
+    fn cancel_order(&self, _token: &Token, id: u64) -> Result<(), DiError> {
+        let mut open_orders = self.open_orders.borrow_mut();
+        match open_orders
+            .iter()
+            .position(|status| status.order.id == Some(id as i64))
+        {
+            Some(i) => {
+                open_orders.remove(i);
+                Ok(())
+            }
+            None => Err(DiError::NotFound),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MockFill, MockMarket, MockTrader};
+    use crate::finance::{DiError, Order, OrderType, Sample, Side, TimeInForce, Token};
+    use crate::historical_data::HistoricalData;
+    use crate::time::{Date, TimeUnit, TimeWindow};
+    use crate::trader::Trader;
+
+    fn order(token: &Token) -> Order {
+        Order {
+            index: 0,
+            position_index: None,
+            id: None,
+            token: token.clone(),
+            date: Date::now(),
+            side: Side::Buy,
+            quantity: 2.0,
+            price: 10.0,
+            stop_price: None,
+            order_type: OrderType::Limit,
+            tif: TimeInForce::GTC,
+        }
+    }
+
+    #[test]
+    fn test_mock_market() {
+        let token = Token::pair("BTC", "USDT");
+        let samples: Vec<Sample> = (0..10)
+            .map(|i| Sample {
+                resolution: TimeUnit::Hour(1),
+                timestamp: i * 3600,
+                close: i as f64,
+                ..Default::default()
+            })
+            .collect();
+        let mut market = MockMarket::with_samples(&token, &samples);
+        let duration = TimeWindow {
+            resolution: TimeUnit::Hour(1),
+            count: 5,
+        };
+        assert_eq!(market.fetch_last(&token, &duration).unwrap().len(), 5);
+        market.inject_error(DiError::NotImplemented);
+        assert_eq!(
+            market.get_last(&token, &duration).err(),
+            Some(DiError::NotImplemented)
+        );
+        assert_eq!(market.get_last(&token, &duration).unwrap()[4].close, 9.0);
+    }
+
+    #[test]
+    fn test_mock_trader() {
+        let token = Token::pair("BTC", "USDT");
+        let trader = MockTrader::default();
+        trader.script(MockFill::Fill(0.5));
+        trader.script(MockFill::Reject(DiError::Error));
+        assert!(trader.create_order(&order(&token)).is_ok());
+        assert_eq!(trader.create_order(&order(&token)), Err(DiError::Error));
+        assert!(trader.create_order(&order(&token)).is_ok());
+        assert_eq!(trader.submitted.borrow().len(), 2);
+        let open_orders = trader.get_all_open_orders().unwrap();
+        assert_eq!(open_orders.len(), 1);
+        assert_eq!(open_orders[0].executed_qty, 1.0);
+    }
+
+    #[test]
+    fn test_mock_replace_order() {
+        let token = Token::pair("BTC", "USDT");
+        let trader = MockTrader::default();
+        trader.script(MockFill::Fill(0.0));
+        let id = trader.create_order(&order(&token)).unwrap();
+        assert_eq!(
+            trader.replace_order(id + 1, &order(&token)),
+            Err(DiError::NotFound)
+        );
+        assert_eq!(trader.submitted.borrow().len(), 1);
+
+        let mut amended = order(&token);
+        amended.price = 11.0;
+        trader.script(MockFill::Fill(0.0));
+        let new_id = trader.replace_order(id, &amended).unwrap();
+        assert_ne!(new_id, id);
+        let open_orders = trader.get_all_open_orders().unwrap();
+        assert_eq!(open_orders.len(), 1);
+        assert_eq!(open_orders[0].order.id, Some(new_id as i64));
+        assert_eq!(open_orders[0].order.price, 11.0);
+        assert_eq!(trader.cancel_order(&token, id), Err(DiError::NotFound));
+    }
+}
@@ -0,0 +1,148 @@
+use crate::finance::DiError;
+use regex::Regex;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Headline {
+    /// Title of the feed.
+    pub source: String,
+    pub title: String,
+    pub link: String,
+    pub published: String,
+    /// Loaded symbols mentioned in the title.
+    pub symbols: Vec<String>,
+}
+
+fn unescape(text: &str) -> String {
+    let text = text.trim();
+    let text = text
+        .strip_prefix("<![CDATA[")
+        .and_then(|t| t.strip_suffix("]]>"))
+        .unwrap_or(text);
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
+fn tag_text(xml: &str, tag: &str) -> String {
+    let re = Regex::new(&format!(r"(?s)<{}(?:\s[^>]*)?>(.*?)</{}>", tag, tag)).unwrap();
+    re.captures(xml)
+        .map(|c| unescape(&c[1]))
+        .unwrap_or_default()
+}
+
+/// Items of an RSS 2.0 feed. Missing fields are left empty.
+pub fn parse_rss(xml: &str) -> Vec<Headline> {
+    let item_re = Regex::new(r"(?s)<item(?:\s[^>]*)?>(.*?)</item>").unwrap();
+    let channel = xml.split("<item").next().unwrap_or_default();
+    let source = tag_text(channel, "title");
+    item_re
+        .captures_iter(xml)
+        .map(|c| Headline {
+            source: source.clone(),
+            title: tag_text(&c[1], "title"),
+            link: tag_text(&c[1], "link"),
+            published: tag_text(&c[1], "pubDate"),
+            symbols: Vec::new(),
+        })
+        .filter(|h| !h.title.is_empty())
+        .collect()
+}
+
+/// Tags headlines whose title mentions a symbol as a whole word.
+pub fn tag_symbols(headlines: &mut [Headline], symbols: &[String]) {
+    for headline in headlines.iter_mut() {
+        let title = headline.title.to_uppercase();
+        let words: Vec<&str> = title
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .collect();
+        headline.symbols = symbols
+            .iter()
+            .filter(|s| words.contains(&s.to_uppercase().as_str()))
+            .cloned()
+            .collect();
+    }
+}
+
+pub fn fetch_feed(url: &str) -> Result<Vec<Headline>, DiError> {
+    let xml = reqwest::blocking::get(url)
+        .and_then(|response| response.text())
+        .map_err(|e| DiError::Message(format!("{}: {}", url, e)))?;
+    Ok(parse_rss(&xml))
+}
+
+/// Downloads feeds in the background.
+pub struct NewsFeed {
+    channel: (
+        Sender<Result<Vec<Headline>, DiError>>,
+        Receiver<Result<Vec<Headline>, DiError>>,
+    ),
+}
+
+impl Default for NewsFeed {
+    fn default() -> Self {
+        Self {
+            channel: mpsc::channel(),
+        }
+    }
+}
+
+impl NewsFeed {
+    /// Fetches all `urls`, results are collected with `poll`.
+    pub fn refresh(&self, urls: &[String], symbols: &[String]) {
+        let sender = self.channel.0.clone();
+        let urls = urls.to_vec();
+        let symbols = symbols.to_vec();
+        thread::spawn(move || {
+            let mut headlines = Vec::new();
+            for url in &urls {
+                match fetch_feed(url) {
+                    Ok(feed) => headlines.extend(feed),
+                    Err(e) => {
+                        let _ = sender.send(Err(e));
+                    }
+                }
+            }
+            tag_symbols(&mut headlines, &symbols);
+            let _ = sender.send(Ok(headlines));
+        });
+    }
+
+    /// Finished downloads and errors, in arrival order.
+    pub fn poll(&self) -> Vec<Result<Vec<Headline>, DiError>> {
+        self.channel.1.try_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_rss, tag_symbols};
+
+    #[test]
+    fn test_parse_rss() {
+        let xml = r#"<rss><channel><title>Crypto News</title>
+            <item><title><![CDATA[BTC breaks $100k]]></title><link>https://a.b/1</link>
+            <pubDate>Mon, 06 Jan 2025 10:00:00 +0000</pubDate></item>
+            <item><title>ETH &amp; SOL rally, bitcoin flat</title><link>https://a.b/2</link></item>
+            </channel></rss>"#;
+        let mut headlines = parse_rss(xml);
+        assert_eq!(headlines.len(), 2);
+        assert_eq!(headlines[0].source, "Crypto News");
+        assert_eq!(headlines[0].title, "BTC breaks $100k");
+        assert_eq!(headlines[1].title, "ETH & SOL rally, bitcoin flat");
+        assert_eq!(headlines[1].published, "");
+        tag_symbols(
+            &mut headlines,
+            &[String::from("BTC"), String::from("SOL"), String::from("ET")],
+        );
+        assert_eq!(headlines[0].symbols, vec![String::from("BTC")]);
+        assert_eq!(headlines[1].symbols, vec![String::from("SOL")]);
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
+
+/// State of a long running task.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Progress {
+    pub task: usize,
+    pub name: String,
+    /// From 0 to 100.
+    pub percent: f64,
+    pub message: String,
+}
+
+impl Progress {
+    pub fn is_done(&self) -> bool {
+        self.percent >= 100.0
+    }
+}
+
+/// Handle given to a task to report its progress, can be moved to other threads.
+#[derive(Clone)]
+pub struct ProgressReporter {
+    task: usize,
+    name: String,
+    sender: Sender<Progress>,
+}
+
+impl ProgressReporter {
+    pub fn update(&self, percent: f64, message: &str) {
+        let _ = self.sender.send(Progress {
+            task: self.task,
+            name: self.name.clone(),
+            percent: percent.clamp(0.0, 100.0),
+            message: message.to_string(),
+        });
+    }
+
+    /// Reports `step` out of `total` steps done.
+    pub fn step(&self, step: usize, total: usize, message: &str) {
+        let percent = match total {
+            0 => 100.0,
+            _ => 100.0 * step as f64 / total as f64,
+        };
+        self.update(percent, message);
+    }
+
+    pub fn finish(&self, message: &str) {
+        self.update(100.0, message);
+    }
+}
+
+/// Collects the progress of all running tasks.
+pub struct ProgressHub {
+    channel: (Sender<Progress>, Receiver<Progress>),
+    next_task: usize,
+    tasks: BTreeMap<usize, (Progress, Instant)>,
+}
+
+impl Default for ProgressHub {
+    fn default() -> Self {
+        Self {
+            channel: mpsc::channel(),
+            next_task: 0,
+            tasks: BTreeMap::new(),
+        }
+    }
+}
+
+impl ProgressHub {
+    /// Registers a new task.
+    pub fn task(&mut self, name: &str) -> ProgressReporter {
+        let task = self.next_task;
+        self.next_task += 1;
+        let progress = Progress {
+            task,
+            name: name.to_string(),
+            ..Default::default()
+        };
+        self.tasks.insert(task, (progress, Instant::now()));
+        ProgressReporter {
+            task,
+            name: name.to_string(),
+            sender: self.channel.0.clone(),
+        }
+    }
+
+    /// Consumes pending updates.
+    pub fn poll(&mut self) {
+        for progress in self.channel.1.try_iter() {
+            self.tasks
+                .insert(progress.task, (progress.clone(), Instant::now()));
+        }
+    }
+
+    /// Last state of each task, ordered by creation.
+    pub fn tasks(&self) -> Vec<&Progress> {
+        self.tasks.values().map(|(p, _)| p).collect()
+    }
+
+    /// Drops tasks finished more than `older_than` ago.
+    pub fn clear_finished(&mut self, older_than: Duration) {
+        self.tasks
+            .retain(|_, (p, t)| !p.is_done() || t.elapsed() < older_than);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProgressHub;
+    use std::time::Duration;
+
+    #[test]
+    fn test_progress() {
+        let mut hub = ProgressHub::default();
+        let a = hub.task("a");
+        let b = hub.task("b");
+        a.step(1, 4, "first");
+        b.finish("done");
+        hub.poll();
+        let tasks = hub.tasks();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].percent, 25.0);
+        assert_eq!(tasks[0].message, "first");
+        assert!(tasks[1].is_done());
+        hub.clear_finished(Duration::ZERO);
+        assert_eq!(hub.tasks().len(), 1);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::binance::fetch_klines;
+use crate::finance::{DiError, Sample, Token};
+use crate::time::TimeUnit;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+/// Complete bars between `last` and the bar forming at `now` (seconds).
+pub fn bars_behind(last: &Sample, now: u64) -> i64 {
+    let elapsed = now.saturating_sub(last.seconds()) as i64;
+    (elapsed / last.resolution.num_seconds().max(1) - 1).max(0)
+}
+
+/// Latest klines of a token at a resolution.
+pub type Download = (Token, TimeUnit, Result<Vec<Sample>, DiError>);
+
+/// Downloads recent klines in the background, so caches of tokens whose
+/// stream lagged or dropped catch up without being selected.
+pub struct DataRefresher {
+    /// Time (seconds) of the last round of requests.
+    pub requested: i64,
+    pending: Vec<(Token, TimeUnit)>,
+    channel: (Sender<Download>, Receiver<Download>),
+}
+
+impl Default for DataRefresher {
+    fn default() -> Self {
+        Self {
+            requested: 0,
+            pending: Vec::new(),
+            channel: mpsc::channel(),
+        }
+    }
+}
+
+impl DataRefresher {
+    /// Downloads the last `count` bars, unless they are already downloading.
+    pub fn request(&mut self, token: &Token, resolution: &TimeUnit, count: u16) {
+        let key = (token.clone(), *resolution);
+        if self.pending.contains(&key) {
+            return;
+        }
+        self.pending.push(key.clone());
+        let sender = self.channel.0.clone();
+        thread::spawn(move || {
+            let result = fetch_klines(&key.0, &key.1, count);
+            let _ = sender.send((key.0, key.1, result));
+        });
+    }
+
+    /// Finished downloads, to be appended to the history caches.
+    pub fn poll(&mut self) -> Vec<Download> {
+        let downloads: Vec<Download> = self.channel.1.try_iter().collect();
+        for (token, resolution, _) in &downloads {
+            self.pending
+                .retain(|(t, r)| !(t == token && r == resolution));
+        }
+        downloads
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::bars_behind;
+    use crate::finance::Sample;
+    use crate::time::TimeUnit;
+
+    #[test]
+    fn test_bars_behind() {
+        let last = Sample {
+            resolution: TimeUnit::Min(1),
+            timestamp: 1_700_000_000_000,
+            ..Default::default()
+        };
+        // the last bar is still forming
+        assert_eq!(bars_behind(&last, 1_700_000_030), 0);
+        // the bar after it is forming
+        assert_eq!(bars_behind(&last, 1_700_000_090), 0);
+        assert_eq!(bars_behind(&last, 1_700_000_300), 4);
+    }
+}
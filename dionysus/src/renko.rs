@@ -0,0 +1,137 @@
+use crate::finance::Sample;
+use ta::indicators::AverageTrueRange;
+use ta::Next;
+
+/// Price move of a renko brick.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BrickSize {
+    Fixed(f64),
+    /// Average true range of the given period at the last sample.
+    Atr(usize),
+}
+
+impl BrickSize {
+    /// "atr <n>" or a fixed size.
+    pub fn from_words(words: &[&str]) -> Option<BrickSize> {
+        match words {
+            [atr, n] if atr.eq_ignore_ascii_case("atr") => n
+                .parse::<usize>()
+                .ok()
+                .filter(|n| *n > 0)
+                .map(BrickSize::Atr),
+            [size] => size
+                .parse::<f64>()
+                .ok()
+                .filter(|size| *size > 0.0)
+                .map(BrickSize::Fixed),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            BrickSize::Fixed(size) => format!("{}", size),
+            BrickSize::Atr(n) => format!("atr {}", n),
+        }
+    }
+
+    /// Size over `samples`, 0 when it can not be measured.
+    pub fn value(&self, samples: &[Sample]) -> f64 {
+        match self {
+            BrickSize::Fixed(size) => *size,
+            BrickSize::Atr(n) => match AverageTrueRange::new(*n) {
+                Ok(mut atr) => samples.iter().fold(0.0, |_, s| atr.next(s)),
+                Err(_) => 0.0,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Brick {
+    /// Timestamp of the sample that completed the brick.
+    pub timestamp: u64,
+    pub open: f64,
+    pub close: f64,
+}
+
+impl Brick {
+    pub fn rising(&self) -> bool {
+        self.close > self.open
+    }
+}
+
+/// Renko bricks of the closes. A brick is added each time the close moves a
+/// whole brick past the last one, reversals need two bricks.
+pub fn renko(size: BrickSize, samples: &[Sample]) -> Vec<Brick> {
+    let mut bricks: Vec<Brick> = Vec::new();
+    let size = size.value(samples);
+    let first = match samples.first() {
+        Some(first) if size > 0.0 => first,
+        _ => return bricks,
+    };
+    // range of the last brick
+    let (mut top, mut bottom) = (first.close, first.close);
+    for sample in &samples[1..] {
+        while sample.close >= top + size {
+            bricks.push(Brick {
+                timestamp: sample.timestamp,
+                open: top,
+                close: top + size,
+            });
+            bottom = top;
+            top += size;
+        }
+        while sample.close <= bottom - size {
+            bricks.push(Brick {
+                timestamp: sample.timestamp,
+                open: bottom,
+                close: bottom - size,
+            });
+            top = bottom;
+            bottom -= size;
+        }
+    }
+    bricks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{renko, BrickSize};
+    use crate::finance::Sample;
+
+    #[test]
+    fn test_renko() {
+        let samples: Vec<Sample> = [10.0, 12.5, 13.0, 12.0, 10.9, 9.0]
+            .iter()
+            .enumerate()
+            .map(|(i, close)| Sample {
+                timestamp: i as u64,
+                close: *close,
+                ..Default::default()
+            })
+            .collect();
+        let bricks: Vec<(u64, f64, f64)> = renko(BrickSize::Fixed(1.0), &samples)
+            .iter()
+            .map(|b| (b.timestamp, b.open, b.close))
+            .collect();
+        // 12 does not reverse the bricks up to 13, 10.9 does
+        assert_eq!(
+            bricks,
+            vec![
+                (1, 10.0, 11.0),
+                (1, 11.0, 12.0),
+                (2, 12.0, 13.0),
+                (4, 12.0, 11.0),
+                (5, 11.0, 10.0),
+                (5, 10.0, 9.0),
+            ]
+        );
+        assert!(renko(BrickSize::Fixed(0.0), &samples).is_empty());
+        assert_eq!(
+            BrickSize::from_words(&["ATR", "14"]),
+            Some(BrickSize::Atr(14))
+        );
+        assert_eq!(BrickSize::from_words(&["-1"]), None);
+    }
+}
@@ -0,0 +1,447 @@
+use crate::{
+    analytics::risk_metrics,
+    backtest::Backtest,
+    finance::{DiError, Order, Sample, Side, Token},
+    strategy::Chrysus,
+    time::Date,
+    utils::{compute_change_pct, sparkline},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Html,
+    Markdown,
+}
+
+impl ReportFormat {
+    /// Markdown for `.md`/`.markdown` files, html otherwise.
+    pub fn from_path(path: &str) -> ReportFormat {
+        let lower = path.to_lowercase();
+        if lower.ends_with(".md") || lower.ends_with(".markdown") {
+            ReportFormat::Markdown
+        } else {
+            ReportFormat::Html
+        }
+    }
+}
+
+/// Standalone summary of a backtest run.
+#[derive(Default, Clone, Debug)]
+pub struct Report {
+    pub title: String,
+    pub parameters: Vec<(String, String)>,
+    pub metrics: Vec<(String, String)>,
+    pub summary: BacktestMetrics,
+    /// (timestamp, portfolio value)
+    pub equity: Vec<(u64, f64)>,
+    pub trades: Vec<Order>,
+}
+
+/// Numeric results of a backtest, used to compare runs.
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct BacktestMetrics {
+    pub final_value: f64,
+    /// Percent
+    pub return_pct: f64,
+    pub buy_and_hold: f64,
+    pub max_drawdown: f64,
+    pub orders: usize,
+    pub buys: usize,
+    pub sells: usize,
+    /// Annualized, over the configured risk-free rate.
+    pub sharpe: Option<f64>,
+    /// Annualized percent and beta against the benchmark, if one is set.
+    pub alpha: Option<f64>,
+    pub beta: Option<f64>,
+}
+
+impl BacktestMetrics {
+    /// (name, value) of each metric, in display order.
+    pub fn values(&self) -> Vec<(&'static str, f64)> {
+        vec![
+            ("final value", self.final_value),
+            ("return %", self.return_pct),
+            ("buy and hold %", self.buy_and_hold),
+            ("max drawdown %", self.max_drawdown),
+            ("orders", self.orders as f64),
+            ("buys", self.buys as f64),
+            ("sells", self.sells as f64),
+            ("sharpe", self.sharpe.unwrap_or(f64::NAN)),
+            ("alpha %", self.alpha.unwrap_or(f64::NAN)),
+            ("beta", self.beta.unwrap_or(f64::NAN)),
+        ]
+    }
+}
+
+/// (metric, a, b, b - a) of two runs, for A/B comparisons.
+pub fn compare_metrics(
+    a: &BacktestMetrics,
+    b: &BacktestMetrics,
+) -> Vec<(&'static str, f64, f64, f64)> {
+    a.values()
+        .into_iter()
+        .zip(b.values())
+        .map(|((name, va), (_, vb))| (name, va, vb, vb - va))
+        .collect()
+}
+
+/// Portfolio value (cash + holdings of the backtested token) at each sample.
+/// Orders on other tokens (hedge legs) only affect the cash. Borrowed capital
+/// and funding payments are discounted.
+pub fn equity_curve(chrysus: &Chrysus, backtest: &Backtest, samples: &[Sample]) -> Vec<(u64, f64)> {
+    let mut cash = backtest.initial_capital + backtest.borrowed;
+    let mut quantity = 0.0;
+    let mut orders: Vec<&Order> = backtest.orders.iter().collect();
+    orders.sort_by_key(|o| o.date.timestamp());
+    let mut next = 0;
+    let mut next_funding = 0;
+    samples
+        .iter()
+        .map(|sample| {
+            while next < orders.len() && orders[next].date.timestamp() <= sample.timestamp as i64 {
+                let order = orders[next];
+                let notional = order.quantity * order.price;
+                cash -= notional * backtest.fee_rate;
+                let q = if order.token == chrysus.token {
+                    order.quantity
+                } else {
+                    0.0
+                };
+                match order.side {
+                    Side::Buy => {
+                        cash -= notional;
+                        quantity += q;
+                    }
+                    Side::Sell => {
+                        cash += notional;
+                        quantity -= q;
+                    }
+                }
+                next += 1;
+            }
+            while next_funding < backtest.funding.len()
+                && backtest.funding[next_funding].0 <= sample.timestamp
+            {
+                cash -= backtest.funding[next_funding].1;
+                next_funding += 1;
+            }
+            (
+                sample.timestamp,
+                cash + quantity * sample.close - backtest.borrowed,
+            )
+        })
+        .collect()
+}
+
+/// Largest peak to trough decline, in percent.
+pub fn max_drawdown(equity: &[f64]) -> f64 {
+    let mut peak = f64::MIN;
+    let mut drawdown: f64 = 0.0;
+    for value in equity {
+        peak = peak.max(*value);
+        if peak > 0.0 {
+            drawdown = drawdown.max((peak - value) / peak * 100.0);
+        }
+    }
+    drawdown
+}
+
+fn date_string(timestamp: u64) -> String {
+    let date = Date::from_timestamp(if timestamp > 100_000_000_000 {
+        timestamp / 1000
+    } else {
+        timestamp
+    });
+    date.pretty_string()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+impl Report {
+    pub fn from_backtest(chrysus: &Chrysus, backtest: &Backtest, samples: &[Sample]) -> Report {
+        let equity = equity_curve(chrysus, backtest, samples);
+        let values: Vec<f64> = equity.iter().map(|(_, v)| *v).collect();
+        let final_value = values.last().cloned().unwrap_or(backtest.initial_capital);
+        let buy_and_hold = match (samples.first(), samples.last()) {
+            (Some(first), Some(last)) => compute_change_pct(first.close, last.close),
+            _ => 0.0,
+        };
+        let buys = backtest
+            .orders
+            .iter()
+            .filter(|o| o.side == Side::Buy)
+            .count();
+        let summary = BacktestMetrics {
+            final_value,
+            return_pct: compute_change_pct(backtest.initial_capital, final_value),
+            buy_and_hold,
+            max_drawdown: max_drawdown(&values),
+            orders: backtest.orders.len(),
+            buys,
+            sells: backtest.orders.len() - buys,
+            ..Default::default()
+        };
+
+        let mut parameters = vec![
+            (String::from("token"), chrysus.token.name()),
+            (String::from("oracle"), chrysus.strategy.oracle.name()),
+            (
+                String::from("resolution"),
+                backtest.period.resolution.name(),
+            ),
+            (
+                String::from("samples"),
+                format!("{}", backtest.period.count),
+            ),
+            (
+                String::from("initial capital"),
+                format!("{:.2}", backtest.initial_capital),
+            ),
+        ];
+        if backtest.fee_rate > 0.0 {
+            parameters.push((
+                String::from("fee"),
+                format!("{:.3}%", backtest.fee_rate * 100.0),
+            ));
+        }
+        if backtest.borrowed > 0.0 {
+            parameters.push((
+                String::from("leverage"),
+                format!(
+                    "{:.2}x",
+                    (backtest.initial_capital + backtest.borrowed) / backtest.initial_capital
+                ),
+            ));
+        }
+        for counselor in chrysus.strategy.enabled_counselors() {
+            parameters.push((String::from("counselor"), counselor.name()));
+        }
+        if let (Some(first), Some(last)) = (samples.first(), samples.last()) {
+            parameters.push((String::from("from"), date_string(first.timestamp)));
+            parameters.push((String::from("to"), date_string(last.timestamp)));
+        }
+
+        let mut metrics = vec![
+            (
+                String::from("final value"),
+                format!("{:.2}", summary.final_value),
+            ),
+            (
+                String::from("return"),
+                format!("{:.2}%", summary.return_pct),
+            ),
+            (
+                String::from("buy and hold"),
+                format!("{:.2}%", summary.buy_and_hold),
+            ),
+            (
+                String::from("max drawdown"),
+                format!("{:.2}%", summary.max_drawdown),
+            ),
+            (String::from("orders"), format!("{}", summary.orders)),
+            (String::from("buys"), format!("{}", summary.buys)),
+            (String::from("sells"), format!("{}", summary.sells)),
+        ];
+        if backtest.fees > 0.0 {
+            metrics.push((String::from("fees paid"), format!("{:.2}", backtest.fees)));
+        }
+        if !backtest.funding.is_empty() {
+            metrics.push((
+                String::from("funding paid"),
+                format!("{:.2}", backtest.total_funding()),
+            ));
+        }
+
+        Report {
+            title: format!("Backtest {}", chrysus.name()),
+            parameters,
+            metrics,
+            summary,
+            equity,
+            trades: backtest.orders.clone(),
+        }
+    }
+
+    /// Adds the Sharpe ratio over `risk_free_pct` (yearly percent) and, with a
+    /// benchmark, alpha and beta against its samples over the same period.
+    pub fn add_risk_metrics(&mut self, benchmark: Option<(&Token, &[Sample])>, risk_free_pct: f64) {
+        let risk = risk_metrics(&self.equity, benchmark.map(|(_, s)| s), risk_free_pct);
+        self.summary.sharpe = risk.sharpe;
+        self.summary.alpha = risk.alpha;
+        self.summary.beta = risk.beta;
+        self.parameters.push((
+            String::from("risk-free rate"),
+            format!("{:.2}%", risk_free_pct),
+        ));
+        if let Some((token, _)) = benchmark {
+            self.parameters
+                .push((String::from("benchmark"), token.name()));
+        }
+        let or_dash = |value: Option<f64>, suffix: &str| match value {
+            Some(value) => format!("{:.2}{}", value, suffix),
+            None => String::from("-"),
+        };
+        self.metrics
+            .push((String::from("sharpe"), or_dash(risk.sharpe, "")));
+        if benchmark.is_some() {
+            self.metrics
+                .push((String::from("alpha"), or_dash(risk.alpha, "%")));
+            self.metrics
+                .push((String::from("beta"), or_dash(risk.beta, "")));
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("# {}\n\n", self.title);
+        md.push_str("## Parameters\n\n| parameter | value |\n|---|---|\n");
+        for (k, v) in &self.parameters {
+            md.push_str(&format!("| {} | {} |\n", k, v));
+        }
+        md.push_str("\n## Metrics\n\n| metric | value |\n|---|---|\n");
+        for (k, v) in &self.metrics {
+            md.push_str(&format!("| {} | {} |\n", k, v));
+        }
+        let values: Vec<f64> = self.equity.iter().map(|(_, v)| *v).collect();
+        md.push_str("\n## Equity\n\n```\n");
+        md.push_str(&sparkline(&values, 80));
+        md.push_str("\n```\n\n## Trades\n\n| date | side | token | quantity | price |\n|---|---|---|---|---|\n");
+        for order in &self.trades {
+            md.push_str(&format!(
+                "| {} | {:?} | {} | {:.6} | {:.6} |\n",
+                date_string(order.date.timestamp() as u64),
+                order.side,
+                order.token.name(),
+                order.quantity,
+                order.price
+            ));
+        }
+        md
+    }
+
+    fn svg_chart(&self, width: f64, height: f64) -> String {
+        if self.equity.len() < 2 {
+            return String::new();
+        }
+        let min = self.equity.iter().map(|(_, v)| *v).fold(f64::MAX, f64::min);
+        let max = self.equity.iter().map(|(_, v)| *v).fold(f64::MIN, f64::max);
+        let range = if max > min { max - min } else { 1.0 };
+        let n = (self.equity.len() - 1) as f64;
+        let points: Vec<String> = self
+            .equity
+            .iter()
+            .enumerate()
+            .map(|(i, (_, v))| {
+                format!(
+                    "{:.1},{:.1}",
+                    i as f64 / n * width,
+                    height - (v - min) / range * height
+                )
+            })
+            .collect();
+        format!(
+            "<svg width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\
+             <polyline fill=\"none\" stroke=\"#2a7ae2\" stroke-width=\"1.5\" points=\"{p}\"/>\
+             <text x=\"2\" y=\"12\">{max:.2}</text><text x=\"2\" y=\"{b}\">{min:.2}</text></svg>",
+            w = width,
+            h = height,
+            p = points.join(" "),
+            max = max,
+            min = min,
+            b = height - 2.0
+        )
+    }
+
+    pub fn to_html(&self) -> String {
+        let table = |rows: &[(String, String)]| -> String {
+            rows.iter()
+                .map(|(k, v)| {
+                    format!(
+                        "<tr><td>{}</td><td>{}</td></tr>",
+                        escape_html(k),
+                        escape_html(v)
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("\n")
+        };
+        let trades: Vec<String> = self
+            .trades
+            .iter()
+            .map(|order| {
+                format!(
+                    "<tr><td>{}</td><td>{:?}</td><td>{}</td><td>{:.6}</td><td>{:.6}</td></tr>",
+                    date_string(order.date.timestamp() as u64),
+                    order.side,
+                    escape_html(&order.token.name()),
+                    order.quantity,
+                    order.price
+                )
+            })
+            .collect();
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+             <style>body{{font-family:sans-serif;margin:2em}}table{{border-collapse:collapse}}\
+             td,th{{border:1px solid #ccc;padding:2px 8px;text-align:right}}</style>\n</head>\n<body>\n\
+             <h1>{title}</h1>\n<h2>Parameters</h2>\n<table>\n{parameters}\n</table>\n\
+             <h2>Metrics</h2>\n<table>\n{metrics}\n</table>\n<h2>Equity</h2>\n{chart}\n\
+             <h2>Trades</h2>\n<table>\n<tr><th>date</th><th>side</th><th>token</th><th>quantity</th><th>price</th></tr>\n\
+             {trades}\n</table>\n</body>\n</html>\n",
+            title = escape_html(&self.title),
+            parameters = table(&self.parameters),
+            metrics = table(&self.metrics),
+            chart = self.svg_chart(800.0, 240.0),
+            trades = trades.join("\n"),
+        )
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Html => self.to_html(),
+            ReportFormat::Markdown => self.to_markdown(),
+        }
+    }
+
+    /// Writes the report, choosing the format from the file extension.
+    pub fn write(&self, path: &str) -> Result<(), DiError> {
+        std::fs::write(path, self.render(ReportFormat::from_path(path)))
+            .map_err(|e| DiError::Message(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare_metrics, max_drawdown, BacktestMetrics, ReportFormat};
+
+    #[test]
+    fn test_max_drawdown() {
+        assert_eq!(max_drawdown(&[100.0, 120.0, 90.0, 130.0, 117.0]), 25.0);
+        assert_eq!(max_drawdown(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_compare_metrics() {
+        let a = BacktestMetrics {
+            return_pct: 5.0,
+            orders: 10,
+            ..Default::default()
+        };
+        let b = BacktestMetrics {
+            return_pct: 7.5,
+            orders: 4,
+            ..Default::default()
+        };
+        let diff = compare_metrics(&a, &b);
+        assert_eq!(diff[1], ("return %", 5.0, 7.5, 2.5));
+        assert_eq!(diff[4].3, -6.0);
+    }
+
+    #[test]
+    fn test_report_format() {
+        assert_eq!(ReportFormat::from_path("out.MD"), ReportFormat::Markdown);
+        assert_eq!(ReportFormat::from_path("out.html"), ReportFormat::Html);
+    }
+}
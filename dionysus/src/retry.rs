@@ -0,0 +1,125 @@
+use rand::{thread_rng, Rng};
+use std::time::Duration;
+
+/// How a failed call should be handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// Timeouts, server errors and rate limits, worth retrying.
+    Transient,
+    /// Local clock out of the server receive window, retry after a resync.
+    ClockDrift,
+    /// The request itself is wrong, retrying won't help.
+    Permanent,
+}
+
+/// Exponential backoff with full jitter.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total number of calls, including the first one.
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(8),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Upper bound of the wait before retry `attempt` (0 is the first retry).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt);
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    /// Random wait in [0, backoff(attempt)], so clients don't retry in lockstep.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.backoff(attempt)
+            .mul_f64(thread_rng().gen_range(0.0..=1.0))
+    }
+
+    /// Calls `call` until it succeeds, fails permanently or runs out of
+    /// attempts. `resync` is called before retrying a clock drift failure.
+    /// The last error is returned along with its classification.
+    pub fn run<T, E>(
+        &self,
+        mut call: impl FnMut() -> Result<T, E>,
+        classify: impl Fn(&E) -> Failure,
+        mut resync: impl FnMut(),
+    ) -> Result<T, (E, Failure)> {
+        let mut attempt = 0;
+        loop {
+            let e = match call() {
+                Ok(value) => return Ok(value),
+                Err(e) => e,
+            };
+            let failure = classify(&e);
+            attempt += 1;
+            if failure == Failure::Permanent || attempt >= self.max_attempts {
+                return Err((e, failure));
+            }
+            match failure {
+                Failure::ClockDrift => resync(),
+                _ => std::thread::sleep(self.delay(attempt - 1)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Failure, RetryPolicy};
+    use std::time::Duration;
+
+    #[test]
+    fn test_retry() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+        };
+        let mut calls = 0;
+        let result: Result<i32, (i32, Failure)> = policy.run(
+            || {
+                calls += 1;
+                if calls < 3 {
+                    Err(500)
+                } else {
+                    Ok(1)
+                }
+            },
+            |_| Failure::Transient,
+            || (),
+        );
+        assert_eq!(result, Ok(1));
+
+        let mut calls = 0;
+        let result: Result<i32, (i32, Failure)> = policy.run(
+            || {
+                calls += 1;
+                Err(400)
+            },
+            |_| Failure::Permanent,
+            || (),
+        );
+        assert_eq!(result, Err((400, Failure::Permanent)));
+        assert_eq!(calls, 1);
+
+        let mut resyncs = 0;
+        let result: Result<i32, (i32, Failure)> =
+            policy.run(|| Err(-1021), |_| Failure::ClockDrift, || resyncs += 1);
+        assert_eq!(result, Err((-1021, Failure::ClockDrift)));
+        assert_eq!(resyncs, 2);
+
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(0), Duration::from_millis(250));
+        assert_eq!(policy.backoff(2), Duration::from_secs(1));
+        assert_eq!(policy.backoff(10), Duration::from_secs(8));
+    }
+}
@@ -0,0 +1,60 @@
+use crate::finance::{DiError, Token};
+
+/// Symbols orders may be placed on. Entries are pairs (BTCUSDT) or base assets
+/// (BTC), case insensitive. An empty allow list allows every symbol not denied.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TradePermissions {
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+impl TradePermissions {
+    pub fn new(allow: &[String], deny: &[String]) -> Self {
+        let normalize = |list: &[String]| list.iter().map(|s| s.to_uppercase()).collect();
+        Self {
+            allow: normalize(allow),
+            deny: normalize(deny),
+        }
+    }
+
+    fn matches(list: &[String], token: &Token) -> bool {
+        let pair = token.to_string().to_uppercase();
+        let base = token.get_symbol().to_uppercase();
+        list.iter().any(|entry| *entry == pair || *entry == base)
+    }
+
+    pub fn check(&self, token: &Token) -> Result<(), DiError> {
+        if TradePermissions::matches(&self.deny, token) {
+            return Err(DiError::Message(format!("{} is denied", token.name())));
+        }
+        if !self.allow.is_empty() && !TradePermissions::matches(&self.allow, token) {
+            return Err(DiError::Message(format!("{} is not allowed", token.name())));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TradePermissions;
+    use crate::finance::Token;
+
+    #[test]
+    fn test_trade_permissions() {
+        let btc = Token::pair("BTC", "USDT");
+        let eth = Token::pair("ETH", "USDT");
+        let pepe = Token::pair("PEPE", "USDT");
+        assert!(TradePermissions::default().check(&pepe).is_ok());
+        let deny = TradePermissions::new(&[], &[String::from("pepe")]);
+        assert!(deny.check(&btc).is_ok());
+        assert!(deny.check(&pepe).is_err());
+        let allow = TradePermissions::new(
+            &[String::from("BTC"), String::from("ethusdt")],
+            &[String::from("ETHUSDT")],
+        );
+        assert!(allow.check(&btc).is_ok());
+        assert!(allow.check(&eth).is_err());
+        assert!(allow.check(&pepe).is_err());
+        assert!(allow.check(&Token::pair("BTC", "EUR")).is_ok());
+    }
+}
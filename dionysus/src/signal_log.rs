@@ -0,0 +1,181 @@
+use std::collections::VecDeque;
+
+use crate::{
+    counselor::{Advice, Counselor, Signal},
+    finance::Sample,
+    indicators::IndicatorData,
+    time::Date,
+};
+
+/// Number of signals kept by each strategy.
+pub const SIGNAL_LOG_CAPACITY: usize = 256;
+
+/// A non-neutral advice produced by a strategy.
+#[derive(Debug, Clone)]
+pub struct SignalRecord {
+    pub date: Date,
+    pub signal: Signal,
+    /// Price quoted when the advice was produced.
+    pub price: f64,
+    pub stop_price: f64,
+    pub stop_loss: f64,
+    pub take_profit: f64,
+    /// Last value(s) of the counselors' indicators.
+    pub indicators: Vec<(String, f64)>,
+    /// Number of orders placed because of this advice.
+    pub orders: usize,
+}
+
+impl SignalRecord {
+    pub fn new(advice: &Advice, price: f64, samples: &[Sample], counselors: &[Counselor]) -> Self {
+        let date = match samples.last() {
+            Some(sample) => Date::from_timestamp(sample.seconds()),
+            None => Date::now(),
+        };
+        SignalRecord {
+            date,
+            signal: advice.signal.clone(),
+            price,
+            stop_price: advice.stop_price,
+            stop_loss: advice.stop_loss,
+            take_profit: advice.take_profit,
+            indicators: snapshot(samples, counselors),
+            orders: 0,
+        }
+    }
+}
+
+/// Last values of all indicators used by `counselors`.
+pub fn snapshot(samples: &[Sample], counselors: &[Counselor]) -> Vec<(String, f64)> {
+    let mut values: Vec<(String, f64)> = Vec::new();
+    for counselor in counselors {
+        for indicator in counselor.indicators() {
+            let name = indicator.to_string();
+            match indicator.compute(samples) {
+                Ok(IndicatorData::Scalar(v)) => values.push((name, v)),
+                Ok(IndicatorData::Vector(v)) => {
+                    if let Some(last) = v.last() {
+                        values.push((name, *last));
+                    }
+                }
+                Ok(IndicatorData::Matrix(m)) => {
+                    for (i, row) in m.iter().enumerate() {
+                        if let Some(last) = row.last() {
+                            values.push((format!("{}[{}]", name, i), *last));
+                        }
+                    }
+                }
+                Err(_) => (),
+            }
+        }
+    }
+    values
+}
+
+/// Ring buffer with the most recent signals of a strategy.
+#[derive(Debug, Clone)]
+pub struct SignalLog {
+    pub capacity: usize,
+    records: VecDeque<SignalRecord>,
+}
+
+impl Default for SignalLog {
+    fn default() -> Self {
+        SignalLog::new(SIGNAL_LOG_CAPACITY)
+    }
+}
+
+impl SignalLog {
+    pub fn new(capacity: usize) -> Self {
+        SignalLog {
+            capacity,
+            records: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn push(&mut self, record: SignalRecord) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    /// Marks the latest record as having produced `orders` orders.
+    pub fn set_orders(&mut self, orders: usize) {
+        if let Some(record) = self.records.back_mut() {
+            record.orders = orders;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+
+    /// Last `n` records, most recent first.
+    pub fn last(&self, n: usize) -> Vec<&SignalRecord> {
+        self.records.iter().rev().take(n).collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SignalRecord> {
+        self.records.iter()
+    }
+
+    /// Records with the given signal, oldest first.
+    pub fn with_signal(&self, signal: &Signal) -> Vec<&SignalRecord> {
+        self.records
+            .iter()
+            .filter(|r| r.signal == *signal)
+            .collect()
+    }
+
+    /// Records produced at or after `date`, oldest first.
+    pub fn since(&self, date: &Date) -> Vec<&SignalRecord> {
+        self.records
+            .iter()
+            .filter(|r| r.date.timestamp() >= date.timestamp())
+            .collect()
+    }
+
+    /// Records that resulted in at least one order, oldest first.
+    pub fn executed(&self) -> Vec<&SignalRecord> {
+        self.records.iter().filter(|r| r.orders > 0).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SignalLog, SignalRecord};
+    use crate::counselor::{Advice, Signal};
+
+    #[test]
+    fn test_signal_log() {
+        let mut log = SignalLog::new(3);
+        for i in 0..5 {
+            let mut advice = Advice::default();
+            advice.signal = if i % 2 == 0 {
+                Signal::Buy
+            } else {
+                Signal::Sell
+            };
+            log.push(SignalRecord::new(&advice, i as f64, &[], &[]));
+            log.set_orders(i % 2);
+        }
+        assert_eq!(log.len(), 3);
+        let last = log.last(2);
+        assert_eq!(last[0].price, 4.0);
+        assert_eq!(last[1].price, 3.0);
+        assert_eq!(log.with_signal(&Signal::Buy).len(), 2);
+        assert_eq!(log.executed().len(), 1);
+    }
+}
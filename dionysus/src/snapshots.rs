@@ -0,0 +1,154 @@
+use crate::finance::DiError;
+use crate::time::Date;
+use crate::utils::compute_change_pct;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AssetSnapshot {
+    pub asset: String,
+    pub quantity: f64,
+    /// In the quote currency, None without a price.
+    pub value: Option<f64>,
+}
+
+/// Balances and valuation of the account at a point in time.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AccountSnapshot {
+    /// Seconds
+    pub timestamp: u64,
+    pub assets: Vec<AssetSnapshot>,
+    pub total: f64,
+}
+
+impl AccountSnapshot {
+    pub fn new(timestamp: u64, assets: Vec<AssetSnapshot>) -> Self {
+        let total = assets.iter().filter_map(|a| a.value).sum();
+        Self {
+            timestamp,
+            assets,
+            total,
+        }
+    }
+}
+
+/// P&L of a calendar month, from its first to its last snapshot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonthlyPnl {
+    /// `YYYY-MM`
+    pub month: String,
+    pub start: f64,
+    pub end: f64,
+    pub change_pct: f64,
+}
+
+/// Account snapshots, stored one json per line.
+#[derive(Debug, Default)]
+pub struct SnapshotLog {
+    pub snapshots: Vec<AccountSnapshot>,
+}
+
+impl SnapshotLog {
+    /// A missing file is an empty log, invalid lines are skipped.
+    pub fn load(path: &str) -> SnapshotLog {
+        let snapshots = match std::fs::read_to_string(path) {
+            Ok(text) => text
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        SnapshotLog { snapshots }
+    }
+
+    /// Whether `interval` seconds passed since the last snapshot.
+    pub fn is_due(&self, now: u64, interval: u64) -> bool {
+        match self.snapshots.last() {
+            Some(last) => now >= last.timestamp + interval,
+            None => true,
+        }
+    }
+
+    /// Appends the snapshot to the log and to the file.
+    pub fn record(&mut self, path: &str, snapshot: AccountSnapshot) -> Result<(), DiError> {
+        let line = serde_json::to_string(&snapshot).map_err(|e| DiError::Message(e.to_string()))?;
+        self.snapshots.push(snapshot);
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| writeln!(file, "{}", line))
+            .map_err(|e| DiError::Message(e.to_string()))
+    }
+
+    pub fn totals(&self) -> Vec<f64> {
+        self.snapshots.iter().map(|s| s.total).collect()
+    }
+
+    /// Month over month P&L. Each month starts from the last value of the
+    /// previous one when there is one.
+    pub fn monthly_pnl(&self) -> Vec<MonthlyPnl> {
+        let mut months: Vec<MonthlyPnl> = Vec::new();
+        for snapshot in &self.snapshots {
+            let month = Date::from_timestamp(snapshot.timestamp).month_string();
+            if let Some(last) = months.last_mut() {
+                if last.month == month {
+                    last.end = snapshot.total;
+                    continue;
+                }
+            }
+            let start = months.last().map(|m| m.end).unwrap_or(snapshot.total);
+            months.push(MonthlyPnl {
+                month,
+                start,
+                end: snapshot.total,
+                change_pct: 0.0,
+            });
+        }
+        for month in months.iter_mut() {
+            month.change_pct = compute_change_pct(month.start, month.end);
+        }
+        months
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AccountSnapshot, AssetSnapshot, SnapshotLog};
+
+    #[test]
+    fn test_snapshots() {
+        let snapshot = |timestamp: u64, value: f64| {
+            AccountSnapshot::new(
+                timestamp,
+                vec![
+                    AssetSnapshot {
+                        asset: String::from("USDT"),
+                        quantity: value,
+                        value: Some(value),
+                    },
+                    AssetSnapshot {
+                        asset: String::from("XYZ"),
+                        quantity: 1.0,
+                        value: None,
+                    },
+                ],
+            )
+        };
+        let mut log = SnapshotLog::default();
+        assert!(log.is_due(0, 86400));
+        // 2024-01-01, 2024-01-31, 2024-02-15
+        log.snapshots.push(snapshot(1704067200, 100.0));
+        log.snapshots.push(snapshot(1706659200, 110.0));
+        log.snapshots.push(snapshot(1707955200, 99.0));
+        assert!(!log.is_due(1707955200 + 3600, 86400));
+        assert!(log.is_due(1707955200 + 86400, 86400));
+        assert_eq!(log.totals(), vec![100.0, 110.0, 99.0]);
+        let months = log.monthly_pnl();
+        assert_eq!(months.len(), 2);
+        assert_eq!(months[0].month, "2024-01");
+        assert_eq!((months[0].start, months[0].end), (100.0, 110.0));
+        assert_eq!((months[1].start, months[1].end), (110.0, 99.0));
+        assert!((months[1].change_pct + 10.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,284 @@
+use crate::backtest::{match_round_trips, RoundTrip};
+use crate::finance::{Order, Token};
+use crate::time::Date;
+use std::collections::HashMap;
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Summary of a set of orders, closed by FIFO round trips per token.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TradeStats {
+    pub orders: usize,
+    pub round_trips: usize,
+    pub wins: usize,
+    /// Profit of the closed round trips, before fees.
+    pub realized: f64,
+    /// Open buys valued at the given prices.
+    pub unrealized: f64,
+    pub fees: f64,
+    pub largest_win: f64,
+    pub largest_loss: f64,
+}
+
+impl TradeStats {
+    /// `prices` value the open buys, tokens without a price are left out of the
+    /// unrealized P&L. Fees are `fee_rate` of each order value.
+    pub fn compute(orders: &[Order], prices: &HashMap<Token, f64>, fee_rate: f64) -> TradeStats {
+        let mut stats = TradeStats {
+            orders: orders.len(),
+            fees: orders.iter().map(|o| o.quantity * o.price * fee_rate).sum(),
+            ..Default::default()
+        };
+        let mut tokens: Vec<&Token> = Vec::new();
+        for order in orders {
+            if !tokens.contains(&&order.token) {
+                tokens.push(&order.token);
+            }
+        }
+        for token in tokens {
+            let (trips, open) = match_round_trips(orders.iter().filter(|o| o.token == *token));
+            for trip in &trips {
+                let profit = trip.profit();
+                stats.realized += profit;
+                if profit > 0.0 {
+                    stats.wins += 1;
+                }
+                stats.largest_win = stats.largest_win.max(profit);
+                stats.largest_loss = stats.largest_loss.min(profit);
+            }
+            stats.round_trips += trips.len();
+            if let Some(price) = prices.get(token) {
+                stats.unrealized += open
+                    .iter()
+                    .map(|(_, entry, quantity)| (price - entry) * quantity)
+                    .sum::<f64>();
+            }
+        }
+        stats
+    }
+
+    /// Percent of winning round trips, None without round trips.
+    pub fn win_rate(&self) -> Option<f64> {
+        match self.round_trips {
+            0 => None,
+            n => Some(self.wins as f64 * 100.0 / n as f64),
+        }
+    }
+
+    /// Realized plus unrealized, minus fees.
+    pub fn net(&self) -> f64 {
+        self.realized + self.unrealized - self.fees
+    }
+}
+
+/// Round trip of a strategy, with the counselors it consulted.
+#[derive(Debug, Clone)]
+pub struct TaggedTrip {
+    pub strategy: String,
+    pub counselors: Vec<String>,
+    pub trip: RoundTrip,
+}
+
+/// How round trips are grouped in a breakdown. Sessions use the UTC hour and
+/// weekday of the entry.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum BreakdownKey {
+    #[default]
+    Strategy,
+    Counselor,
+    Hour,
+    Weekday,
+}
+
+impl BreakdownKey {
+    pub const ALL: [BreakdownKey; 4] = [
+        BreakdownKey::Strategy,
+        BreakdownKey::Counselor,
+        BreakdownKey::Hour,
+        BreakdownKey::Weekday,
+    ];
+
+    pub fn from_name(name: &str) -> Option<BreakdownKey> {
+        BreakdownKey::ALL
+            .into_iter()
+            .find(|key| key.name() == name.to_lowercase())
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BreakdownKey::Strategy => "strategy",
+            BreakdownKey::Counselor => "counselor",
+            BreakdownKey::Hour => "hour",
+            BreakdownKey::Weekday => "weekday",
+        }
+    }
+
+    pub fn next(&self) -> BreakdownKey {
+        let index = BreakdownKey::ALL
+            .iter()
+            .position(|key| key == self)
+            .unwrap();
+        BreakdownKey::ALL[(index + 1) % BreakdownKey::ALL.len()]
+    }
+
+    /// Groups of `trip`, trips count toward each counselor of their strategy.
+    fn groups(&self, trip: &TaggedTrip) -> Vec<String> {
+        let entry = Date::from_timestamp(trip.trip.entry_timestamp);
+        match self {
+            BreakdownKey::Strategy => vec![trip.strategy.clone()],
+            BreakdownKey::Counselor => trip.counselors.clone(),
+            BreakdownKey::Hour => vec![format!("{:02}h", entry.hour())],
+            BreakdownKey::Weekday => vec![WEEKDAYS[entry.weekday() as usize].to_string()],
+        }
+    }
+}
+
+/// Round trips and profit of a group.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BreakdownRow {
+    pub group: String,
+    pub trips: usize,
+    pub wins: usize,
+    pub profit: f64,
+}
+
+impl BreakdownRow {
+    pub fn win_rate(&self) -> f64 {
+        self.wins as f64 * 100.0 / self.trips.max(1) as f64
+    }
+}
+
+/// Profit of `trips` by `key`. Strategies and counselors come most profitable
+/// first, sessions in time order.
+pub fn breakdown(trips: &[TaggedTrip], key: BreakdownKey) -> Vec<BreakdownRow> {
+    let mut rows: Vec<BreakdownRow> = Vec::new();
+    for trip in trips {
+        let profit = trip.trip.profit();
+        for group in key.groups(trip) {
+            let index = match rows.iter().position(|row| row.group == group) {
+                Some(index) => index,
+                None => {
+                    rows.push(BreakdownRow {
+                        group,
+                        ..Default::default()
+                    });
+                    rows.len() - 1
+                }
+            };
+            rows[index].trips += 1;
+            rows[index].profit += profit;
+            if profit > 0.0 {
+                rows[index].wins += 1;
+            }
+        }
+    }
+    match key {
+        BreakdownKey::Strategy | BreakdownKey::Counselor => {
+            rows.sort_by(|a, b| b.profit.total_cmp(&a.profit))
+        }
+        BreakdownKey::Hour => rows.sort_by(|a, b| a.group.cmp(&b.group)),
+        BreakdownKey::Weekday => {
+            rows.sort_by_key(|row| WEEKDAYS.iter().position(|day| *day == row.group))
+        }
+    }
+    rows
+}
+
+/// Every breakdown of `trips` as csv.
+pub fn breakdown_csv(trips: &[TaggedTrip]) -> String {
+    let mut csv = String::from("key,group,trips,wins,profit\n");
+    for key in BreakdownKey::ALL {
+        for row in breakdown(trips, key) {
+            csv.push_str(&format!(
+                "{},\"{}\",{},{},{}\n",
+                key.name(),
+                row.group.replace('"', "\"\""),
+                row.trips,
+                row.wins,
+                row.profit
+            ));
+        }
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{breakdown, breakdown_csv, BreakdownKey, TaggedTrip, TradeStats};
+    use crate::backtest::RoundTrip;
+    use crate::finance::{Order, OrderType, Side, TimeInForce, Token};
+    use crate::time::Date;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_trade_stats() {
+        let order = |token: &Token, side: Side, quantity: f64, price: f64| Order {
+            index: 0,
+            position_index: None,
+            id: None,
+            token: token.clone(),
+            date: Date::from_timestamp(0),
+            side,
+            quantity,
+            price,
+            stop_price: None,
+            order_type: OrderType::Market,
+            tif: TimeInForce::default(),
+        };
+        let btc = Token::pair("BTC", "USDT");
+        let eth = Token::pair("ETH", "USDT");
+        let orders = vec![
+            order(&btc, Side::Buy, 1.0, 100.0),
+            order(&eth, Side::Buy, 2.0, 10.0),
+            order(&btc, Side::Sell, 1.0, 110.0),
+            order(&eth, Side::Sell, 1.0, 8.0),
+        ];
+        let prices = HashMap::from([(eth.clone(), 12.0)]);
+        let stats = TradeStats::compute(&orders, &prices, 0.001);
+        assert_eq!(stats.orders, 4);
+        assert_eq!(stats.round_trips, 2);
+        assert_eq!(stats.win_rate(), Some(50.0));
+        assert_eq!((stats.largest_win, stats.largest_loss), (10.0, -2.0));
+        assert!((stats.realized - 8.0).abs() < 1e-9);
+        assert!((stats.unrealized - 2.0).abs() < 1e-9);
+        assert!((stats.fees - 0.238).abs() < 1e-9);
+        assert_eq!(TradeStats::default().win_rate(), None);
+    }
+
+    #[test]
+    fn test_breakdown() {
+        let trip = |strategy: &str, counselors: &[&str], entry: u64, profit: f64| TaggedTrip {
+            strategy: strategy.to_string(),
+            counselors: counselors.iter().map(|c| c.to_string()).collect(),
+            trip: RoundTrip {
+                entry_timestamp: entry,
+                entry_price: 100.0,
+                exit_timestamp: entry + 60,
+                exit_price: 100.0 + profit,
+                quantity: 1.0,
+            },
+        };
+        // 2024-01-01 was a Monday
+        let monday = 1_704_067_200;
+        let trips = vec![
+            trip("BTCUSDT delphi", &["rsi", "ema"], monday + 9 * 3600, 5.0),
+            trip("ETHUSDT delphi", &["ema"], monday + 86400, -2.0),
+            trip("BTCUSDT delphi", &["rsi", "ema"], monday + 10 * 3600, -1.0),
+        ];
+        let by_strategy = breakdown(&trips, BreakdownKey::Strategy);
+        assert_eq!(by_strategy[0].group, "BTCUSDT delphi");
+        assert_eq!((by_strategy[0].trips, by_strategy[0].wins), (2, 1));
+        assert_eq!(by_strategy[0].profit, 4.0);
+        let by_counselor = breakdown(&trips, BreakdownKey::Counselor);
+        assert_eq!(by_counselor[0].group, "rsi");
+        assert_eq!((by_counselor[1].trips, by_counselor[1].profit), (3, 2.0));
+        let by_hour = breakdown(&trips, BreakdownKey::Hour);
+        let hours: Vec<&str> = by_hour.iter().map(|row| row.group.as_str()).collect();
+        assert_eq!(hours, vec!["00h", "09h", "10h"]);
+        let by_day = breakdown(&trips, BreakdownKey::Weekday);
+        assert_eq!((by_day[0].group.as_str(), by_day[0].trips), ("Mon", 2));
+        assert_eq!(by_day[1].group, "Tue");
+        assert_eq!(BreakdownKey::from_name("Hour"), Some(BreakdownKey::Hour));
+        assert_eq!(breakdown_csv(&trips).lines().count(), 1 + 2 + 2 + 3 + 2);
+    }
+}
@@ -1,8 +1,10 @@
 use crate::{
-    counselor::{Advice, Counselor, Signal},
+    bracket::{last_atr, BracketOffset},
+    counselor::{Advice, AtrExits, Counselor, Signal},
     finance::*,
     historical_data::HistoricalData,
-    time::{Date, TimeWindow},
+    signal_log::{SignalLog, SignalRecord},
+    time::{Date, TimeUnit, TimeWindow},
     ERROR,
 };
 
@@ -30,12 +32,14 @@ impl Oracle {
         &self,
         quote: &Quote,
         history: &[Sample],
+        legs: &HashMap<Token, &[Sample]>,
+        hedges: &HashMap<Token, f64>,
         counselors: &[Counselor],
     ) -> Result<Decision, DiError> {
         match self {
             Oracle::Delphi => {
                 for counselor in counselors.iter() {
-                    if let Ok(advice) = counselor.run(quote, history) {
+                    if let Ok(advice) = counselor.run_with(quote, history, legs, hedges) {
                         match advice.signal {
                             Signal::Buy => return Ok(Decision { advice, pct: 0.7 }),
                             Signal::Sell => return Ok(Decision { advice, pct: 0.8 }),
@@ -57,14 +61,205 @@ impl Oracle {
     }
 }
 
+/// What to do with the unfilled part of a partially filled order.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PartialFillPolicy {
+    /// Keep it resting at its price.
+    #[default]
+    Leave,
+    /// Move it to the current best price of its side.
+    Amend,
+    /// Cancel it, unlocking its capital.
+    Cancel,
+}
+
+impl PartialFillPolicy {
+    pub fn from_name(name: &str) -> Option<PartialFillPolicy> {
+        match name.to_lowercase().as_str() {
+            "leave" => Some(PartialFillPolicy::Leave),
+            "amend" => Some(PartialFillPolicy::Amend),
+            "cancel" => Some(PartialFillPolicy::Cancel),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PartialFillPolicy::Leave => "leave",
+            PartialFillPolicy::Amend => "amend",
+            PartialFillPolicy::Cancel => "cancel",
+        }
+    }
+}
+
+/// How long a limit order of a strategy may rest before it is canceled.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum OrderTtl {
+    /// Candles of the strategy resolution.
+    Bars(u32),
+    Seconds(i64),
+}
+
+impl OrderTtl {
+    /// "3b" (bars) or a duration as "90s", "15m", "2h".
+    pub fn from_text(text: &str) -> Option<OrderTtl> {
+        let text = text.to_lowercase();
+        if let Some(bars) = text.strip_suffix('b') {
+            return bars
+                .parse::<u32>()
+                .ok()
+                .filter(|n| *n > 0)
+                .map(OrderTtl::Bars);
+        }
+        match TimeUnit::from_name(&text) {
+            TimeUnit::Unit(_) => None,
+            unit if unit.num_seconds() > 0 => Some(OrderTtl::Seconds(unit.num_seconds())),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            OrderTtl::Bars(n) => format!("{}b", n),
+            OrderTtl::Seconds(n) => format!("{}s", n),
+        }
+    }
+
+    pub fn num_seconds(&self, resolution: &TimeUnit) -> i64 {
+        match self {
+            OrderTtl::Bars(n) => *n as i64 * resolution.num_seconds(),
+            OrderTtl::Seconds(n) => *n,
+        }
+    }
+}
+
+/// Progressive sizing: every loss in a row shrinks the next buys by `step` of
+/// their size, down to `floor`, and every win gives one step back.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Heat {
+    pub step: f64,
+    pub floor: f64,
+}
+
+impl Heat {
+    /// Heat levels past this one are already sized at the floor.
+    fn max_level(&self) -> u32 {
+        if self.step <= 0.0 {
+            return 0;
+        }
+        ((1.0 - self.floor) / self.step).ceil().max(0.0) as u32
+    }
+
+    /// Fraction of the decided size kept at heat `level`.
+    pub fn factor(&self, level: u32) -> f64 {
+        (1.0 - self.step * level as f64)
+            .max(self.floor)
+            .clamp(0.0, 1.0)
+    }
+}
+
+/// ATR period of trailing stops given in ATRs.
+const TRAILING_ATR_PERIOD: usize = 14;
+
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct Strategy {
     pub oracle: Oracle,
     pub counselors: Vec<Counselor>,
     pub duration: TimeWindow,
+    /// Whether each counselor is consulted, missing entries are enabled.
+    #[serde(default)]
+    pub enabled: Vec<bool>,
+    #[serde(default)]
+    pub partial_fills: PartialFillPolicy,
+    /// Limit orders left unfilled for longer are canceled, None keeps them.
+    #[serde(default)]
+    pub order_ttl: Option<OrderTtl>,
+    /// Decides once per closed bar instead of on every kline update.
+    #[serde(default)]
+    pub closed_bars: bool,
+    /// Sizes buys down after losses, None always uses the decided size.
+    #[serde(default)]
+    pub heat: Option<Heat>,
+    /// Trailing stop of buys whose advice has none, None leaves them.
+    #[serde(default)]
+    pub trailing_stop: Option<BracketOffset>,
+    /// Exits of every advice in ATRs, None keeps the counselor ones (usually
+    /// the high and low of the last candle).
+    #[serde(default)]
+    pub atr_exits: Option<AtrExits>,
+}
+
+/// Version of the standalone strategy files.
+const STRATEGY_FILE_VERSION: u32 = 1;
+
+/// Strategy shared independently of the session state.
+#[derive(Serialize, Deserialize)]
+struct StrategyFile {
+    version: u32,
+    strategy: Strategy,
 }
 
 impl Strategy {
+    /// Writes the strategy to a standalone json file.
+    pub fn save(&self, path: &str) -> Result<(), DiError> {
+        let file = StrategyFile {
+            version: STRATEGY_FILE_VERSION,
+            strategy: self.clone(),
+        };
+        let data =
+            serde_json::to_string_pretty(&file).map_err(|e| DiError::Message(e.to_string()))?;
+        std::fs::write(path, data).map_err(|e| DiError::Message(e.to_string()))
+    }
+
+    /// Reads a strategy written by `save`.
+    pub fn load(path: &str) -> Result<Strategy, DiError> {
+        let data = std::fs::read_to_string(path).map_err(|e| DiError::Message(e.to_string()))?;
+        let file: StrategyFile =
+            serde_json::from_str(&data).map_err(|e| DiError::Message(e.to_string()))?;
+        if file.version > STRATEGY_FILE_VERSION {
+            return Err(DiError::Message(format!(
+                "{}: unsupported strategy file version {}",
+                path, file.version
+            )));
+        }
+        Ok(file.strategy)
+    }
+
+    pub fn is_enabled(&self, index: usize) -> bool {
+        self.enabled.get(index).copied().unwrap_or(true)
+    }
+
+    /// Mutes or unmutes a counselor, keeping its configuration.
+    pub fn set_enabled(&mut self, index: usize, enabled: bool) -> Result<(), DiError> {
+        if index >= self.counselors.len() {
+            return Err(DiError::NotFound);
+        }
+        if self.enabled.len() < self.counselors.len() {
+            self.enabled.resize(self.counselors.len(), true);
+        }
+        self.enabled[index] = enabled;
+        Ok(())
+    }
+
+    pub fn remove_counselor(&mut self, index: usize) {
+        if index < self.counselors.len() {
+            self.counselors.remove(index);
+        }
+        if index < self.enabled.len() {
+            self.enabled.remove(index);
+        }
+    }
+
+    /// Counselors that are not muted.
+    pub fn enabled_counselors(&self) -> Vec<Counselor> {
+        self.counselors
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.is_enabled(*i))
+            .map(|(_, c)| c.clone())
+            .collect()
+    }
+
     pub fn required_history_size(&self) -> usize {
         let mut ans: usize = 0;
         for c in &self.counselors {
@@ -76,8 +271,45 @@ impl Strategy {
         ans
     }
 
+    /// Other tokens whose histories the counselors need.
+    pub fn tokens(&self) -> Vec<Token> {
+        let mut tokens: Vec<Token> = Vec::new();
+        for token in self.counselors.iter().filter_map(|c| c.hedge_token()) {
+            if !tokens.contains(&token) {
+                tokens.push(token);
+            }
+        }
+        tokens
+    }
+
     pub fn run(&self, quote: &Quote, history: &[Sample]) -> Result<Decision, DiError> {
-        self.oracle.see(quote, history, &self.counselors)
+        self.run_with(quote, history, &HashMap::new(), &HashMap::new())
+    }
+
+    pub fn run_with(
+        &self,
+        quote: &Quote,
+        history: &[Sample],
+        legs: &HashMap<Token, &[Sample]>,
+        hedges: &HashMap<Token, f64>,
+    ) -> Result<Decision, DiError> {
+        let mut decision =
+            self.oracle
+                .see(quote, history, legs, hedges, &self.enabled_counselors())?;
+        if let Some(exits) = &self.atr_exits {
+            decision.advice.set_atr_exits(exits, history);
+        }
+        if let (Signal::Buy, None, Some(offset)) = (
+            &decision.advice.signal,
+            decision.advice.trailing,
+            &self.trailing_stop,
+        ) {
+            decision.advice.trailing = offset.distance(
+                decision.advice.stop_price,
+                last_atr(TRAILING_ATR_PERIOD, history),
+            );
+        }
+        Ok(decision)
     }
 
     pub fn name(&self) -> String {
@@ -85,6 +317,13 @@ impl Strategy {
     }
 }
 
+/// Protective stop of a position, following its high by `distance`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrailingStop {
+    pub distance: f64,
+    pub stop: f64,
+}
+
 #[derive(Clone)]
 pub struct Chrysus {
     pub active: bool,
@@ -96,6 +335,19 @@ pub struct Chrysus {
     pub balance: f64,
     pub book: Book,
     pub orders: HashMap<usize, Order>,
+    /// Holdings of hedge legs (negative when short).
+    pub hedge_balance: HashMap<Token, f64>,
+    pub signals: SignalLog,
+    /// Chart indicators in their text form (see match_indicator_from_text).
+    pub indicators: Vec<String>,
+    /// Bar (timestamp) of the last acted advice and the signals acted on in it.
+    acted: (u64, Vec<Signal>),
+    /// Losses in a row not yet paid back by wins.
+    heat: u32,
+    /// Trailing distance of buy orders not filled yet, by order index.
+    pending_trailing: HashMap<usize, f64>,
+    /// By position index.
+    pub trailing_stops: HashMap<usize, TrailingStop>,
     next_position_index: usize,
     next_order_index: usize,
 }
@@ -108,6 +360,7 @@ impl Serialize for Chrysus {
         let mut state = serializer.serialize_struct("Chrysus", 4)?;
         state.serialize_field("token", &self.token)?;
         state.serialize_field("strategy", &self.strategy)?;
+        state.serialize_field("indicators", &self.indicators)?;
         state.end()
     }
 }
@@ -131,22 +384,25 @@ impl<'de> Deserialize<'de> for Chrysus {
             {
                 let mut token = None;
                 let mut strategy = None;
+                let mut indicators: Option<Vec<String>> = None;
 
                 while let Some(key) = map.next_key()? {
                     match key {
                         "token" => token = Some(map.next_value()?),
                         "strategy" => strategy = Some(map.next_value()?),
+                        "indicators" => indicators = Some(map.next_value()?),
                         _ => (), //Err(A::Error::unknown_field()),
                     }
                 }
 
                 let mut chrysus = Chrysus::new(&token.unwrap());
                 chrysus.strategy = strategy.unwrap();
+                chrysus.indicators = indicators.unwrap_or_default();
                 Ok(chrysus)
             }
         }
 
-        const FIELDS: &[&str] = &["token", "strategy"];
+        const FIELDS: &[&str] = &["token", "strategy", "indicators"];
         deserializer.deserialize_struct("Chrysus", FIELDS, ChrysusVisitor)
     }
 }
@@ -163,6 +419,13 @@ impl Chrysus {
             balance: 0.0,
             book: Book::default(),
             orders: HashMap::new(),
+            hedge_balance: HashMap::new(),
+            signals: SignalLog::default(),
+            indicators: Vec::new(),
+            acted: (0, Vec::new()),
+            heat: 0,
+            pending_trailing: HashMap::new(),
+            trailing_stops: HashMap::new(),
             next_position_index: 0,
             next_order_index: 0,
         }
@@ -172,6 +435,83 @@ impl Chrysus {
         format!("{} {}", self.token.name(), self.strategy.name())
     }
 
+    /// Capital on the resting buy orders of the strategy.
+    pub fn locked_capital(&self) -> f64 {
+        self.locked_capital
+    }
+
+    /// Fraction of the decided size the next buy uses.
+    pub fn heat_factor(&self) -> f64 {
+        match &self.strategy.heat {
+            Some(heat) => heat.factor(self.heat),
+            None => 1.0,
+        }
+    }
+
+    /// Heats up after a position closed at a loss and cools down after a win.
+    fn record_close(&mut self, entry: f64, exit: f64) {
+        let max_level = self.strategy.heat.map(|h| h.max_level()).unwrap_or(0);
+        if exit < entry {
+            self.heat = (self.heat + 1).min(max_level);
+        } else {
+            self.heat = self.heat.saturating_sub(1);
+        }
+    }
+
+    /// Starts the trailing stop of a buy order once it opened a position.
+    fn open_trailing_stop(&mut self, order_index: usize, position_index: usize, entry: f64) {
+        if let Some(distance) = self.pending_trailing.remove(&order_index) {
+            self.trailing_stops.insert(
+                position_index,
+                TrailingStop {
+                    distance,
+                    stop: entry - distance,
+                },
+            );
+        }
+    }
+
+    /// Raises the trailing stops to the high of `sample` less their distance
+    /// and sells, at the bid, the positions whose stop the bid reached.
+    fn trail(&mut self, quote: &Quote, sample: &Sample) -> Vec<Order> {
+        let positions = &self.positions;
+        self.trailing_stops
+            .retain(|index, _| positions.contains_key(index));
+        let bid = match quote.bid {
+            Some(bid) => bid,
+            None => return Vec::new(),
+        };
+        let mut triggered: Vec<(usize, f64)> = Vec::new();
+        for (index, trailing) in self.trailing_stops.iter_mut() {
+            let position = &self.positions[index];
+            if position.attached_order.is_some() {
+                continue;
+            }
+            trailing.stop = trailing.stop.max(sample.high - trailing.distance);
+            if bid <= trailing.stop {
+                triggered.push((*index, position.quantity));
+            }
+        }
+        triggered.sort_by_key(|(index, _)| *index);
+        let token = self.token.clone();
+        let mut orders = Vec::new();
+        for (index, quantity) in triggered {
+            let order = self.push_order(
+                &token,
+                Side::Sell,
+                quantity,
+                bid,
+                Some(index),
+                &Advice::default(),
+            );
+            if let Some(position) = self.positions.get_mut(&index) {
+                position.attached_order = Some(order.index);
+            }
+            orders.push(order);
+        }
+        orders
+    }
+
     fn _print(&self) {
         let s = format!(
             "{:?} {:?} {:?} {:?}",
@@ -183,12 +523,76 @@ impl Chrysus {
         ERROR!("{:?}", s);
     }
 
+    fn push_order(
+        &mut self,
+        token: &Token,
+        side: Side,
+        quantity: f64,
+        price: f64,
+        position_index: Option<usize>,
+        advice: &Advice,
+    ) -> Order {
+        let order = Order {
+            index: self.next_order_index,
+            position_index,
+            id: None,
+            token: token.clone(),
+            date: Date::now(),
+            quantity,
+            side,
+            price,
+            stop_price: Some(price),
+            order_type: advice.order_type.clone(),
+            tif: advice.tif.clone(),
+        };
+        self.orders.insert(self.next_order_index, order.clone());
+        self.next_order_index += 1;
+        order
+    }
+
+    /// Orders on the hedge leg: open a short proportional to `shares` on buy
+    /// signals and buy back the whole short on sell signals.
+    fn compute_hedge_orders(&mut self, decision: &Decision, shares: f64) -> Vec<Order> {
+        let mut orders: Vec<Order> = Vec::new();
+        if let Some(hedge) = &decision.advice.hedge {
+            let held = *self.hedge_balance.get(&hedge.token).unwrap_or(&0.0);
+            match decision.advice.signal {
+                Signal::Buy if shares > 0.0 && hedge.ratio > 0.0 => orders.push(self.push_order(
+                    &hedge.token,
+                    Side::Sell,
+                    shares * hedge.ratio,
+                    hedge.price,
+                    None,
+                    &decision.advice,
+                )),
+                Signal::Sell if held < 0.0 => orders.push(self.push_order(
+                    &hedge.token,
+                    Side::Buy,
+                    -held,
+                    hedge.price,
+                    None,
+                    &decision.advice,
+                )),
+                _ => (),
+            }
+        }
+        orders
+    }
+
     fn compute_orders(&mut self, quote: &Quote, decision: &Decision) -> Vec<Order> {
         let mut orders: Vec<Order> = Vec::new();
+        let hedged = decision.advice.hedge.is_some();
+        let mut shares = 0.0;
+        if let (Signal::Buy, Some(hedge)) = (&decision.advice.signal, &decision.advice.hedge) {
+            // a hedged trade is entered once and only unwound by a sell signal
+            if *self.hedge_balance.get(&hedge.token).unwrap_or(&0.0) < 0.0 {
+                return orders;
+            }
+        }
         match decision.advice.signal {
             Signal::Buy => {
-                let available_capital = decision.pct * self.capital;
-                let shares = available_capital as f64 / decision.advice.stop_price;
+                let available_capital = decision.pct * self.heat_factor() * self.capital;
+                shares = available_capital as f64 / decision.advice.stop_price;
                 if shares > 0.0 {
                     self.locked_capital += available_capital;
                     self.capital -= available_capital;
@@ -207,13 +611,17 @@ impl Chrysus {
                     };
                     orders.push(order.clone());
                     self.orders.insert(self.next_order_index, order);
+                    if let Some(distance) = decision.advice.trailing {
+                        self.pending_trailing
+                            .insert(self.next_order_index, distance);
+                    }
                     self.next_order_index += 1;
                 }
             }
             Signal::Sell => {
                 for (position_index, position) in &mut self.positions {
                     if position.attached_order == None
-                        && decision.advice.stop_price > position.price
+                        && (hedged || decision.advice.stop_price > position.price)
                     {
                         let order = Order {
                             index: self.next_order_index,
@@ -237,19 +645,38 @@ impl Chrysus {
             }
             _ => (),
         }
+        if hedged {
+            orders.extend(self.compute_hedge_orders(decision, shares));
+        }
         orders
     }
 
     pub fn realize(&mut self, order: &Order) {
+        if order.token != self.token {
+            let held = self.hedge_balance.entry(order.token.clone()).or_insert(0.0);
+            match order.side {
+                Side::Sell => {
+                    *held -= order.quantity;
+                    self.capital += order.quantity * order.price;
+                }
+                Side::Buy => {
+                    *held += order.quantity;
+                    self.capital -= order.quantity * order.price;
+                }
+            }
+            return;
+        }
         match order.side {
             Side::Sell => {
-                if let Some(position_index) = order.position_index {
-                    self.positions.remove(&position_index);
+                if let Some(position) = order.position_index.and_then(|p| self.positions.remove(&p))
+                {
+                    self.record_close(position.price, order.price);
                 }
                 self.balance -= order.quantity;
                 self.capital += order.quantity * order.price;
             }
             Side::Buy => {
+                self.open_trailing_stop(order.index, self.next_position_index, order.price);
                 self.positions.insert(
                     self.next_position_index,
                     Position {
@@ -267,18 +694,490 @@ impl Chrysus {
         // self.print();
     }
 
+    /// Order placed on the exchange as `id`.
+    pub fn order_by_id(&self, id: i64) -> Option<&Order> {
+        self.orders.values().find(|o| o.id == Some(id))
+    }
+
+    /// Realizes the executed part of an order. Buys grow a single position per
+    /// order and sells shrink the position they close. Returns the policy for
+    /// the unfilled remainder, None once the order is completely filled.
+    pub fn realize_fill(&mut self, fill: &Fill) -> Option<PartialFillPolicy> {
+        let index = *self
+            .orders
+            .iter()
+            .find(|(_, o)| o.id == Some(fill.id))
+            .map(|(index, _)| index)?;
+        let order = self.orders.get_mut(&index)?;
+        order.quantity = fill.remaining;
+        match order.side {
+            Side::Buy => {
+                let position_index = match order.position_index {
+                    Some(position_index) => position_index,
+                    None => {
+                        order.position_index = Some(self.next_position_index);
+                        self.next_position_index += 1;
+                        self.next_position_index - 1
+                    }
+                };
+                // capital was locked at the order price
+                self.locked_capital -= fill.quantity * order.price;
+                self.balance += fill.quantity;
+                let position = self.positions.entry(position_index).or_insert(Position {
+                    token: order.token.clone(),
+                    quantity: 0.0,
+                    price: fill.price,
+                    date: Date::now(),
+                    attached_order: None,
+                });
+                let quantity = position.quantity + fill.quantity;
+                position.price =
+                    (position.price * position.quantity + fill.price * fill.quantity) / quantity;
+                position.quantity = quantity;
+                self.open_trailing_stop(index, position_index, fill.price);
+            }
+            Side::Sell => {
+                if let Some(position_index) = order.position_index {
+                    if let Some(position) = self.positions.get_mut(&position_index) {
+                        position.quantity -= fill.quantity;
+                        if position.quantity <= 1e-12 {
+                            let entry = position.price;
+                            self.positions.remove(&position_index);
+                            self.record_close(entry, fill.price);
+                        }
+                    }
+                }
+                self.balance -= fill.quantity;
+                self.capital += fill.quantity * fill.price;
+            }
+        }
+        if fill.remaining <= 1e-12 {
+            self.orders.remove(&index);
+            return None;
+        }
+        Some(self.strategy.partial_fills)
+    }
+
+    /// Drops the unfilled part of order `id` after its cancel, unlocking its
+    /// capital (buys) or freeing its position (sells).
+    pub fn cancel_remainder(&mut self, id: i64) {
+        let index = match self.orders.iter().find(|(_, o)| o.id == Some(id)) {
+            Some((index, _)) => *index,
+            None => return,
+        };
+        if let Some(order) = self.orders.remove(&index) {
+            match order.side {
+                Side::Buy => {
+                    self.locked_capital -= order.quantity * order.price;
+                    self.capital += order.quantity * order.price;
+                }
+                Side::Sell => {
+                    if let Some(position) = order
+                        .position_index
+                        .and_then(|p| self.positions.get_mut(&p))
+                    {
+                        position.attached_order = None;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Limit orders on the exchange placed before `now` (seconds) minus the
+    /// strategy order TTL. Cancel them with `cancel_remainder`.
+    pub fn stale_orders(&self, now: i64) -> Vec<Order> {
+        let ttl = match &self.strategy.order_ttl {
+            Some(ttl) => ttl.num_seconds(&self.strategy.duration.resolution),
+            None => return Vec::new(),
+        };
+        self.orders
+            .values()
+            .filter(|o| o.id.is_some() && matches!(o.order_type, OrderType::Limit))
+            .filter(|o| now - o.date.timestamp() >= ttl)
+            .cloned()
+            .collect()
+    }
+
+    /// Records the new exchange id and price of an amended remainder.
+    pub fn amend_remainder(&mut self, id: i64, new_id: i64, price: f64) {
+        if let Some(order) = self.orders.values_mut().find(|o| o.id == Some(id)) {
+            if order.side == Side::Buy {
+                // keep the locked capital matching the new price
+                let delta = order.quantity * (price - order.price);
+                self.locked_capital += delta;
+                self.capital -= delta;
+            }
+            order.id = Some(new_id);
+            order.price = price;
+        }
+    }
+
+    /// Cached history of the hedge legs of the strategy.
+    fn legs<'a>(
+        &self,
+        history: &'a (impl HistoricalData + ?Sized),
+    ) -> HashMap<Token, &'a [Sample]> {
+        let mut legs: HashMap<Token, &[Sample]> = HashMap::new();
+        for token in self.strategy.tokens() {
+            if let Ok(leg) = history.get_last(&token, &self.strategy.duration) {
+                legs.insert(token, leg);
+            }
+        }
+        legs
+    }
+
+    /// Decision and orders the strategy would produce at `quote` over the
+    /// latest history, leaving its state untouched.
+    pub fn preview(
+        &self,
+        quote: &Quote,
+        history: &(impl HistoricalData + ?Sized),
+    ) -> Result<(Decision, Vec<Order>), DiError> {
+        let samples = history.get_last(&self.token, &self.strategy.duration)?;
+        let decision =
+            self.strategy
+                .run_with(quote, samples, &self.legs(history), &self.hedge_balance)?;
+        let orders = self.clone().compute_orders(quote, &decision);
+        Ok((decision, orders))
+    }
+
     pub fn decide(&mut self, book: Book, history: &impl HistoricalData) -> Vec<Order> {
         self.book = book;
         if let Some(quote) = self.book.quote() {
             if let Ok(samples) = history.get_last(&self.token, &self.strategy.duration) {
-                match self.strategy.run(&quote, samples) {
-                    Ok(decision) => return self.compute_orders(&quote, &decision),
+                let mut orders = match samples.last() {
+                    Some(sample) => self.trail(&quote, sample),
+                    None => Vec::new(),
+                };
+                let legs = self.legs(history);
+                match self
+                    .strategy
+                    .run_with(&quote, samples, &legs, &self.hedge_balance)
+                {
+                    Ok(decision) => {
+                        if decision.advice.signal == Signal::None {
+                            orders.extend(self.compute_orders(&quote, &decision));
+                            return orders;
+                        }
+                        // book ticks re-evaluate the same bar, act once per bar and signal
+                        let bar = samples.last().map(|s| s.timestamp).unwrap_or_default();
+                        if self.acted.0 != bar {
+                            self.acted = (bar, Vec::new());
+                        }
+                        if self.acted.1.contains(&decision.advice.signal) {
+                            return orders;
+                        }
+                        self.acted.1.push(decision.advice.signal.clone());
+                        let price = quote.ask.or(quote.bid).unwrap_or(0.0);
+                        self.signals.push(SignalRecord::new(
+                            &decision.advice,
+                            price,
+                            samples,
+                            &self.strategy.counselors,
+                        ));
+                        let decided = self.compute_orders(&quote, &decision);
+                        self.signals.set_orders(decided.len());
+                        orders.extend(decided);
+                        return orders;
+                    }
                     Err(e) => {
                         ERROR!("{:?}", e);
                     }
                 };
+                return orders;
             }
         }
         Vec::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Chrysus, Heat, OrderTtl, PartialFillPolicy};
+    use crate::counselor::{Counselor, Signal};
+    use crate::finance::{
+        Book, BookLine, Fill, Order, OrderType, Quote, Sample, Side, TimeInForce, Token,
+    };
+    use crate::historical_data::HistoricalData;
+    use crate::mock::MockMarket;
+    use crate::time::{Date, TimeUnit, TimeWindow};
+
+    #[test]
+    fn test_partial_fills() {
+        let token = Token::pair("BTC", "USDT");
+        let order = |index: usize, id: i64, side: Side, position_index: Option<usize>| Order {
+            index,
+            position_index,
+            id: Some(id),
+            token: token.clone(),
+            date: Date::from_timestamp(0),
+            side,
+            quantity: 2.0,
+            price: 100.0,
+            stop_price: None,
+            order_type: OrderType::Limit,
+            tif: TimeInForce::default(),
+        };
+        let fill = |id: i64, side: Side, quantity: f64, price: f64, remaining: f64| Fill {
+            id,
+            token: token.clone(),
+            side,
+            quantity,
+            price,
+            remaining,
+        };
+        let mut chrysus = Chrysus::new(&token);
+        chrysus.strategy.partial_fills = PartialFillPolicy::Cancel;
+        chrysus.locked_capital = 200.0;
+        chrysus.orders.insert(0, order(0, 7, Side::Buy, None));
+
+        let policy = chrysus.realize_fill(&fill(7, Side::Buy, 0.5, 99.0, 1.5));
+        assert_eq!(policy, Some(PartialFillPolicy::Cancel));
+        assert_eq!(chrysus.locked_capital, 150.0);
+        assert_eq!(chrysus.balance, 0.5);
+        assert_eq!(chrysus.order_by_id(7).unwrap().quantity, 1.5);
+        assert_eq!(chrysus.positions[&0].price, 99.0);
+
+        chrysus.realize_fill(&fill(7, Side::Buy, 0.5, 101.0, 1.0));
+        assert_eq!(chrysus.positions.len(), 1);
+        assert_eq!(chrysus.positions[&0].quantity, 1.0);
+        assert_eq!(chrysus.positions[&0].price, 100.0);
+
+        chrysus.cancel_remainder(7);
+        assert!(chrysus.order_by_id(7).is_none());
+        assert_eq!(chrysus.locked_capital, 0.0);
+        assert_eq!(chrysus.capital, 100.0);
+
+        chrysus.orders.insert(1, order(1, 8, Side::Sell, Some(0)));
+        assert_eq!(
+            chrysus.realize_fill(&fill(8, Side::Sell, 1.0, 110.0, 0.0)),
+            None
+        );
+        assert!(chrysus.positions.is_empty());
+        assert!(chrysus.orders.is_empty());
+        assert_eq!(chrysus.capital, 210.0);
+    }
+
+    #[test]
+    fn test_stale_orders() {
+        assert_eq!(OrderTtl::from_text("3b"), Some(OrderTtl::Bars(3)));
+        assert_eq!(OrderTtl::from_text("15m"), Some(OrderTtl::Seconds(900)));
+        assert_eq!(OrderTtl::from_text("soon"), None);
+        let token = Token::pair("BTC", "USDT");
+        let mut chrysus = Chrysus::new(&token);
+        chrysus.strategy.duration.resolution = TimeUnit::Min(1);
+        let order = |id: Option<i64>, order_type: OrderType| Order {
+            index: 0,
+            position_index: None,
+            id,
+            token: token.clone(),
+            date: Date::from_timestamp(1_000_000),
+            side: Side::Buy,
+            quantity: 1.0,
+            price: 100.0,
+            stop_price: None,
+            order_type,
+            tif: TimeInForce::default(),
+        };
+        chrysus.orders.insert(0, order(Some(1), OrderType::Limit));
+        chrysus.orders.insert(1, order(Some(2), OrderType::Market));
+        chrysus.orders.insert(2, order(None, OrderType::Limit));
+        let placed = chrysus.orders[&0].date.timestamp();
+        assert!(chrysus.stale_orders(placed + 1000).is_empty());
+        chrysus.strategy.order_ttl = Some(OrderTtl::Bars(2));
+        assert!(chrysus.stale_orders(placed + 119).is_empty());
+        let stale = chrysus.stale_orders(placed + 120);
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].id, Some(1));
+    }
+
+    #[test]
+    fn test_indicators_state() {
+        let mut chrysus = Chrysus::new(&Token::pair("BTC", "USDT"));
+        chrysus.indicators = vec![String::from("ema 20"), String::from("expr sma(1)-sma(20)")];
+        let json = serde_json::to_string(&chrysus).unwrap();
+        let loaded: Chrysus = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.indicators, chrysus.indicators);
+        // states saved before the indicators were kept
+        let json = json.replace(",\"indicators\":[\"ema 20\",\"expr sma(1)-sma(20)\"]", "");
+        let loaded: Chrysus = serde_json::from_str(&json).unwrap();
+        assert!(loaded.indicators.is_empty());
+    }
+
+    #[test]
+    fn test_heat() {
+        let token = Token::pair("BTC", "USDT");
+        let mut chrysus = Chrysus::new(&token);
+        chrysus.record_close(100.0, 90.0);
+        assert_eq!(chrysus.heat_factor(), 1.0);
+        chrysus.strategy.heat = Some(Heat {
+            step: 0.25,
+            floor: 0.4,
+        });
+        let sell = |price: f64| Order {
+            index: 0,
+            position_index: Some(0),
+            id: None,
+            token: token.clone(),
+            date: Date::from_timestamp(0),
+            side: Side::Sell,
+            quantity: 1.0,
+            price,
+            stop_price: None,
+            order_type: OrderType::Market,
+            tif: TimeInForce::default(),
+        };
+        let mut close_at = |price: f64| {
+            let mut buy = sell(100.0);
+            buy.side = Side::Buy;
+            buy.position_index = None;
+            chrysus.next_position_index = 0;
+            chrysus.realize(&buy);
+            chrysus.realize(&sell(price));
+            chrysus.heat_factor()
+        };
+        assert_eq!(close_at(90.0), 0.75);
+        assert_eq!(close_at(95.0), 0.5);
+        assert_eq!(close_at(80.0), 0.4);
+        // losses past the floor do not need extra wins to pay back
+        assert_eq!(close_at(80.0), 0.4);
+        assert_eq!(close_at(110.0), 0.5);
+        assert_eq!(close_at(110.0), 0.75);
+        assert_eq!(close_at(110.0), 1.0);
+        assert_eq!(close_at(110.0), 1.0);
+    }
+
+    #[test]
+    fn test_trailing_stop() {
+        let token = Token::pair("BTC", "USDT");
+        let mut chrysus = Chrysus::new(&token);
+        let buy = Order {
+            index: 3,
+            position_index: None,
+            id: None,
+            token: token.clone(),
+            date: Date::from_timestamp(0),
+            side: Side::Buy,
+            quantity: 1.0,
+            price: 100.0,
+            stop_price: None,
+            order_type: OrderType::Market,
+            tif: TimeInForce::default(),
+        };
+        chrysus.pending_trailing.insert(3, 5.0);
+        chrysus.realize(&buy);
+        assert_eq!(chrysus.trailing_stops[&0].stop, 95.0);
+        let step = |chrysus: &mut Chrysus, bid: f64, high: f64| {
+            let quote = Quote {
+                token: token.clone(),
+                bid: Some(bid),
+                ask: Some(bid),
+                biddate: Date::now(),
+                askdate: Date::now(),
+            };
+            let sample = Sample {
+                high,
+                close: bid,
+                ..Default::default()
+            };
+            let orders = chrysus.trail(&quote, &sample);
+            (chrysus.trailing_stops.get(&0).map(|t| t.stop), orders)
+        };
+        let (stop, orders) = step(&mut chrysus, 108.0, 110.0);
+        assert_eq!((stop, orders.len()), (Some(105.0), 0));
+        // a lower high never lowers the stop
+        let (stop, orders) = step(&mut chrysus, 104.0, 106.0);
+        assert_eq!(stop, Some(105.0));
+        assert_eq!(orders.len(), 1);
+        assert_eq!((&orders[0].side, orders[0].price), (&Side::Sell, 104.0));
+        assert_eq!(orders[0].position_index, Some(0));
+        // the position is already being sold
+        assert!(step(&mut chrysus, 103.0, 104.0).1.is_empty());
+        chrysus.realize(&orders[0]);
+        assert_eq!(step(&mut chrysus, 103.0, 104.0).0, None);
+    }
+
+    #[test]
+    fn test_preview() {
+        let token = Token::pair("BTC", "USDT");
+        let samples: Vec<Sample> = (0..30)
+            .map(|i| Sample {
+                resolution: TimeUnit::Hour(1),
+                timestamp: i * 3600,
+                close: if i % 2 == 0 { 99.0 } else { 101.0 },
+                ..Default::default()
+            })
+            .collect();
+        let mut market = MockMarket::with_samples(&token, &samples);
+        let duration = TimeWindow {
+            resolution: TimeUnit::Hour(1),
+            count: 30,
+        };
+        market.fetch_last(&token, &duration).unwrap();
+        let mut chrysus = Chrysus::new(&token);
+        chrysus.strategy.duration = duration;
+        chrysus
+            .strategy
+            .counselors
+            .push(Counselor::MeanReversion((20, 2.0.into())));
+        chrysus.capital = 1000.0;
+        let quote = Quote {
+            token: token.clone(),
+            bid: Some(50.0),
+            ask: Some(50.0),
+            biddate: Date::now(),
+            askdate: Date::now(),
+        };
+        let (decision, orders) = chrysus.preview(&quote, &market).unwrap();
+        assert_eq!(decision.advice.signal, Signal::Buy);
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].side, Side::Buy);
+        assert!((orders[0].quantity * orders[0].price - 700.0).abs() < 1e-6);
+        // nothing was placed
+        assert_eq!(chrysus.capital, 1000.0);
+        assert!(chrysus.orders.is_empty());
+    }
+
+    #[test]
+    fn test_decide_once_per_bar() {
+        let token = Token::pair("BTC", "USDT");
+        let samples: Vec<Sample> = (0..30)
+            .map(|i| Sample {
+                resolution: TimeUnit::Hour(1),
+                timestamp: i * 3600,
+                close: if i % 2 == 0 { 99.0 } else { 101.0 },
+                ..Default::default()
+            })
+            .collect();
+        let mut market = MockMarket::with_samples(&token, &samples);
+        let duration = TimeWindow {
+            resolution: TimeUnit::Hour(1),
+            count: 30,
+        };
+        market.fetch_last(&token, &duration).unwrap();
+        let mut chrysus = Chrysus::new(&token);
+        chrysus.strategy.duration = duration;
+        chrysus
+            .strategy
+            .counselors
+            .push(Counselor::MeanReversion((20, 2.0.into())));
+        chrysus.capital = 1000.0;
+        let book = |price: f64| Book {
+            token: token.clone(),
+            bids: vec![BookLine {
+                price,
+                quantity: 1.0,
+            }],
+            asks: vec![BookLine {
+                price,
+                quantity: 1.0,
+            }],
+        };
+        // buy, sell and buy again on ticks of the same bar
+        let orders: usize = [50.0, 50.0, 150.0, 50.0, 150.0, 50.0]
+            .iter()
+            .map(|price| chrysus.decide(book(*price), &market).len())
+            .sum();
+        assert_eq!(orders, 1);
+    }
+}
@@ -0,0 +1,185 @@
+use crate::time::TimeUnit;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// Messages counted in the rate, in ms.
+const RATE_WINDOW: i64 = 10_000;
+/// Connection changes kept per stream.
+const MAX_CONNECTIONS: usize = 10;
+/// Silence (ms) after which a depth stream, updated every 100ms, is dead.
+pub const BOOK_IDLE_LIMIT: i64 = 60_000;
+/// Longest silence (ms) tolerated from a kline stream of any resolution.
+const MAX_KLINE_IDLE: i64 = 15 * 60_000;
+/// Added to the kline silence limit, in ms.
+const KLINE_IDLE_GRACE: i64 = 30_000;
+
+/// Silence (ms) after which a kline stream of `resolution` is dead. Klines
+/// update with every trade and at least once per bar when it closes.
+pub fn kline_idle_limit(resolution: &TimeUnit) -> i64 {
+    (resolution.num_seconds() * 1000).clamp(60_000, MAX_KLINE_IDLE) + KLINE_IDLE_GRACE
+}
+
+/// Stream connected or disconnected at `time` (ms).
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamConnection {
+    pub time: i64,
+    pub connected: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StreamStats {
+    pub messages: u64,
+    /// Time (ms) of the last message.
+    pub last_message: Option<i64>,
+    /// Messages that could not be delivered.
+    pub dropped: u64,
+    /// Messages superseded by a later one of the same stream before they were
+    /// consumed.
+    pub coalesced: u64,
+    pub connections: VecDeque<StreamConnection>,
+    /// Restarts after the stream went silent.
+    pub restarts: u64,
+    /// Times (ms) of the messages within the rate window.
+    recent: VecDeque<i64>,
+}
+
+impl StreamStats {
+    /// Messages per second over the last seconds.
+    pub fn rate(&self, now: i64) -> f64 {
+        let count = self
+            .recent
+            .iter()
+            .filter(|t| now - **t < RATE_WINDOW)
+            .count();
+        count as f64 * 1000.0 / RATE_WINDOW as f64
+    }
+
+    /// Milliseconds since the last message.
+    pub fn age(&self, now: i64) -> Option<i64> {
+        self.last_message.map(|t| (now - t).max(0))
+    }
+
+    /// Milliseconds without a message or a connection change, None before
+    /// the stream first connects.
+    pub fn idle(&self, now: i64) -> Option<i64> {
+        let changed = self.connections.back().map(|c| c.time);
+        changed.max(self.last_message).map(|t| (now - t).max(0))
+    }
+
+    /// Connections after the first one.
+    pub fn reconnects(&self) -> usize {
+        self.connections
+            .iter()
+            .filter(|c| c.connected)
+            .count()
+            .saturating_sub(1)
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connections.back().map_or(false, |c| c.connected)
+    }
+}
+
+/// Statistics of the market streams by stream name, shared with the stream
+/// threads.
+#[derive(Debug, Clone, Default)]
+pub struct StreamMonitor {
+    streams: Arc<Mutex<HashMap<String, StreamStats>>>,
+}
+
+impl StreamMonitor {
+    fn with(&self, stream: &str, f: impl FnOnce(&mut StreamStats)) {
+        let mut streams = self.streams.lock().unwrap();
+        f(streams.entry(stream.to_string()).or_default());
+    }
+
+    pub fn message(&self, stream: &str, now: i64) {
+        self.with(stream, |stats| {
+            stats.messages += 1;
+            stats.last_message = Some(now);
+            stats.recent.push_back(now);
+            while stats
+                .recent
+                .front()
+                .map_or(false, |t| now - t >= RATE_WINDOW)
+            {
+                stats.recent.pop_front();
+            }
+        });
+    }
+
+    pub fn dropped(&self, stream: &str) {
+        self.with(stream, |stats| stats.dropped += 1);
+    }
+
+    pub fn coalesced(&self, stream: &str, count: u64) {
+        self.with(stream, |stats| stats.coalesced += count);
+    }
+
+    pub fn restarted(&self, stream: &str) {
+        self.with(stream, |stats| stats.restarts += 1);
+    }
+
+    pub fn connection(&self, stream: &str, now: i64, connected: bool, error: Option<String>) {
+        self.with(stream, |stats| {
+            stats.connections.push_back(StreamConnection {
+                time: now,
+                connected,
+                error,
+            });
+            if stats.connections.len() > MAX_CONNECTIONS {
+                stats.connections.pop_front();
+            }
+        });
+    }
+
+    /// Statistics of every stream, by name.
+    pub fn snapshot(&self) -> Vec<(String, StreamStats)> {
+        let mut streams: Vec<(String, StreamStats)> = self
+            .streams
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, stats)| (name.clone(), stats.clone()))
+            .collect();
+        streams.sort_by(|a, b| a.0.cmp(&b.0));
+        streams
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{kline_idle_limit, StreamMonitor};
+    use crate::time::TimeUnit;
+
+    #[test]
+    fn test_stream_monitor() {
+        let monitor = StreamMonitor::default();
+        let stream = "btcusdt@kline_1m";
+        monitor.connection(stream, 0, true, None);
+        for i in 0..20 {
+            monitor.message(stream, i * 1000);
+        }
+        monitor.coalesced(stream, 3);
+        monitor.connection(stream, 20_000, false, Some(String::from("reset")));
+        monitor.connection(stream, 21_000, true, None);
+        let snapshot = monitor.snapshot();
+        assert_eq!(snapshot.len(), 1);
+        let stats = &snapshot[0].1;
+        assert_eq!(stats.messages, 20);
+        assert_eq!(stats.rate(19_500), 1.0);
+        assert_eq!(stats.age(25_000), Some(6_000));
+        assert_eq!((stats.coalesced, stats.dropped), (3, 0));
+        assert_eq!(stats.reconnects(), 1);
+        assert!(stats.is_connected());
+        assert_eq!(stats.idle(25_000), Some(4_000));
+    }
+
+    #[test]
+    fn test_kline_idle_limit() {
+        assert_eq!(kline_idle_limit(&TimeUnit::Sec(1)), 90_000);
+        assert_eq!(kline_idle_limit(&TimeUnit::Min(5)), 330_000);
+        assert_eq!(kline_idle_limit(&TimeUnit::Day(1)), 930_000);
+    }
+}
@@ -0,0 +1,159 @@
+use crate::binance::{fetch_exchange_info, ExchangeSymbolInfo};
+use crate::finance::{DiError, Token};
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+/// Exchange, filters, precision and trading status of every listed symbol,
+/// downloaded in the background so lookups never block.
+pub struct SymbolRegistry {
+    symbols: HashMap<Token, ExchangeSymbolInfo>,
+    /// Time (seconds) of the last refresh request.
+    pub requested: i64,
+    loading: bool,
+    /// Symbols looked up without exchange info, waiting for a refresh.
+    unknown: HashSet<String>,
+    channel: (
+        Sender<Result<Vec<ExchangeSymbolInfo>, DiError>>,
+        Receiver<Result<Vec<ExchangeSymbolInfo>, DiError>>,
+    ),
+}
+
+impl Default for SymbolRegistry {
+    fn default() -> Self {
+        Self {
+            symbols: HashMap::new(),
+            requested: 0,
+            loading: false,
+            unknown: HashSet::new(),
+            channel: mpsc::channel(),
+        }
+    }
+}
+
+impl SymbolRegistry {
+    /// Downloads the exchange info, results are collected with `poll`.
+    pub fn refresh(&mut self, now: i64) {
+        self.requested = now;
+        if self.loading {
+            return;
+        }
+        self.loading = true;
+        let sender = self.channel.0.clone();
+        thread::spawn(move || {
+            let _ = sender.send(fetch_exchange_info());
+        });
+    }
+
+    /// Stores finished downloads, symbols are kept as they were on errors.
+    pub fn poll(&mut self) -> Result<(), DiError> {
+        let mut result = Ok(());
+        for download in self.channel.1.try_iter().collect::<Vec<_>>() {
+            self.loading = false;
+            match download {
+                Ok(symbols) => {
+                    self.insert(symbols);
+                    self.unknown.clear();
+                }
+                Err(e) => result = Err(e),
+            }
+        }
+        result
+    }
+
+    pub fn insert(&mut self, symbols: Vec<ExchangeSymbolInfo>) {
+        for info in symbols {
+            self.symbols.insert(info.token(), info);
+        }
+    }
+
+    /// Maps an exchange symbol (ex: DOGEUSDT) to its token using the base/quote
+    /// assets of the cached exchange info. Unknown symbols are queued for a
+    /// refresh and parsed with the static quote currency list meanwhile.
+    pub fn token_from_symbol(&mut self, symbol: &str) -> Token {
+        match self.symbols.values().find(|info| info.symbol == symbol) {
+            Some(info) => info.token(),
+            None => {
+                self.unknown.insert(symbol.to_string());
+                Token::from_string(&symbol.to_string())
+            }
+        }
+    }
+
+    /// Whether symbols were looked up without exchange info since the last
+    /// refresh.
+    pub fn has_unknown(&self) -> bool {
+        !self.unknown.is_empty()
+    }
+
+    /// None while the exchange info is loading or for unlisted symbols.
+    pub fn get(&self, token: &Token) -> Option<&ExchangeSymbolInfo> {
+        self.symbols.get(token)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Token, &ExchangeSymbolInfo)> {
+        self.symbols.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SymbolRegistry;
+    use crate::binance::ExchangeSymbolInfo;
+    use crate::finance::Token;
+
+    #[test]
+    fn test_symbol_registry() {
+        let mut registry = SymbolRegistry::default();
+        registry.insert(vec![ExchangeSymbolInfo {
+            symbol: String::from("BTCUSDT"),
+            status: String::from("TRADING"),
+            base_asset: String::from("BTC"),
+            quote_asset: String::from("USDT"),
+            lot_min_qty: 0.00001,
+            tick_size: 0.01,
+            step_size: 0.00001,
+            min_notional: 5.0,
+            ..Default::default()
+        }]);
+        assert!(registry.get(&Token::pair("ETH", "USDT")).is_none());
+        let info = registry.get(&Token::pair("BTC", "USDT")).unwrap();
+        assert_eq!(info.round_quantity(0.123456789), 0.12345);
+        assert_eq!(info.round_quantity(0.3), 0.3);
+        assert_eq!(info.round_price(64123.456), 64123.46);
+        assert_eq!((info.price_decimals(), info.quantity_decimals()), (2, 5));
+        assert!(info.check_order(64000.0, 0.001).is_ok());
+        assert!(info.check_order(64000.0, 0.000001).is_err());
+        assert!(info.check_order(100.0, 0.001).is_err());
+    }
+
+    #[test]
+    fn test_token_from_symbol() {
+        let mut registry = SymbolRegistry::default();
+        registry.insert(vec![ExchangeSymbolInfo {
+            symbol: String::from("ETHRUB"),
+            base_asset: String::from("ETH"),
+            quote_asset: String::from("RUB"),
+            ..Default::default()
+        }]);
+        assert_eq!(
+            registry.token_from_symbol("ETHRUB"),
+            Token::pair("ETH", "RUB")
+        );
+        assert!(!registry.has_unknown());
+        // unlisted until the next refresh, parsed by its quote currency
+        assert_eq!(
+            registry.token_from_symbol("BTCTRY"),
+            Token::pair("BTC", "TRY")
+        );
+        assert!(registry.has_unknown());
+    }
+}
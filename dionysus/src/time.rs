@@ -1,4 +1,4 @@
-use chrono::{DateTime, TimeDelta, Utc};
+use chrono::{DateTime, Datelike, TimeDelta, Timelike, Utc};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std;
@@ -77,6 +77,34 @@ impl TimeUnit {
             TimeUnit::Year(n) => (365 * 24 * 60 * 60 * n) as i64,
         }
     }
+
+    /// Seconds until the candle opened at `open` (or the one forming after
+    /// it) closes, at time `now`. Both in seconds.
+    pub fn time_to_close(&self, open: u64, now: u64) -> u64 {
+        let length = self.num_seconds().max(1) as u64;
+        let elapsed = now.saturating_sub(open) % length;
+        length - elapsed
+    }
+
+    /// Start of the calendar session of this unit holding `timestamp`, both
+    /// in seconds: UTC midnight for days, Monday for weeks, the first day for
+    /// months and years. Other units split time from the epoch.
+    pub fn session_start(&self, timestamp: u64) -> u64 {
+        const DAY: u64 = 24 * 60 * 60;
+        let midnight = timestamp - timestamp % DAY;
+        let date = Date::from_timestamp(midnight).utc;
+        match self {
+            TimeUnit::Day(1) => midnight,
+            // the epoch was a thursday
+            TimeUnit::Week(1) => midnight - (midnight / DAY + 3) % 7 * DAY,
+            TimeUnit::Month(1) => midnight - (date.day0() as u64) * DAY,
+            TimeUnit::Year(1) => midnight - (date.ordinal0() as u64) * DAY,
+            unit => {
+                let length = unit.num_seconds().max(1) as u64;
+                timestamp - timestamp % length
+            }
+        }
+    }
 }
 
 impl Hash for TimeUnit {
@@ -156,6 +184,41 @@ impl Date {
     pub fn timestamp(&self) -> i64 {
         self.utc.timestamp()
     }
+    pub fn timestamp_millis(&self) -> i64 {
+        self.utc.timestamp_millis()
+    }
+    /// UTC date as `YYYY-MM-DD HH:MM`.
+    pub fn pretty_string(&self) -> String {
+        self.utc.format("%Y-%m-%d %H:%M").to_string()
+    }
+    /// UTC month as `YYYY-MM`.
+    pub fn month_string(&self) -> String {
+        self.utc.format("%Y-%m").to_string()
+    }
+    /// Hour of the day (UTC), from 0 to 23.
+    pub fn hour(&self) -> u32 {
+        self.utc.hour()
+    }
+    /// Day of the week (UTC), from 0 (Monday) to 6 (Sunday).
+    pub fn weekday(&self) -> u32 {
+        self.utc.weekday().num_days_from_monday()
+    }
+}
+
+/// Duration as `[Nd ]HH:MM:SS`.
+pub fn countdown_string(seconds: u64) -> String {
+    let days = seconds / 86400;
+    let rest = seconds % 86400;
+    let clock = format!(
+        "{:02}:{:02}:{:02}",
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60
+    );
+    match days {
+        0 => clock,
+        _ => format!("{}d {}", days, clock),
+    }
 }
 
 impl std::ops::Sub<TimeWindow> for Date {
@@ -231,6 +294,26 @@ mod tests {
         assert_eq!(TimeUnit::Min(1).name(), "1m".to_string());
     }
 
+    #[test]
+    fn test_time_to_close() {
+        let hour = TimeUnit::Hour(1);
+        assert_eq!(hour.time_to_close(3600, 3600 + 600), 3000);
+        assert_eq!(hour.time_to_close(3600, 2 * 3600 + 60), 3540);
+        assert_eq!(hour.time_to_close(3600, 0), 3600);
+    }
+
+    #[test]
+    fn test_session_start() {
+        // 2024-03-14 15:30 UTC, a thursday
+        let t = 1710430200;
+        assert_eq!(TimeUnit::Day(1).session_start(t), 1710374400);
+        // monday 2024-03-11
+        assert_eq!(TimeUnit::Week(1).session_start(t), 1710115200);
+        // 2024-03-01
+        assert_eq!(TimeUnit::Month(1).session_start(t), 1709251200);
+        assert_eq!(TimeUnit::Hour(4).session_start(t), 1710417600);
+    }
+
     #[test]
     fn test_period() {
         let period = Period::last(super::TimeWindow::days(100));
@@ -1,7 +1,7 @@
 use crate::binance::binance_error;
 use crate::finance::{DiError, Order, OrderStatus, OrderType, Side, TimeInForce, Token};
 use crate::time::Date;
-use crate::wallet::BinanceWallet;
+use crate::wallet::{with_signed_retry, BinanceWallet};
 use binance::model::Transaction;
 
 pub trait Trader {
@@ -17,6 +17,13 @@ pub trait Trader {
         }
     }
     fn get_all_open_orders(&self) -> Result<Vec<OrderStatus>, DiError>;
+    fn cancel_order(&self, token: &Token, id: u64) -> Result<(), DiError>;
+    /// Cancels order `id` and places `order` in its place, returning the new id.
+    /// If the cancel fails nothing is placed.
+    fn replace_order(&self, id: u64, order: &Order) -> Result<u64, DiError> {
+        self.cancel_order(&order.token, id)?;
+        self.create_order(order)
+    }
 }
 
 fn convert_tif(tif: &TimeInForce) -> binance::account::TimeInForce {
@@ -80,8 +87,15 @@ impl Trader for BinanceWallet {
         }
     }
 
+    fn cancel_order(&self, token: &Token, id: u64) -> Result<(), DiError> {
+        match self.account.cancel_order(token.to_string(), id) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(DiError::Message(binance_error(e.0))),
+        }
+    }
+
     fn get_all_open_orders(&self) -> Result<Vec<OrderStatus>, DiError> {
-        match self.account.get_all_open_orders() {
+        match with_signed_retry(&self.account, |account| account.get_all_open_orders()) {
             Ok(orders) => {
                 let mut r: Vec<OrderStatus> = Vec::new();
                 for o in orders {
@@ -89,7 +103,7 @@ impl Trader for BinanceWallet {
                         index: 0,
                         position_index: Some(0),
                         id: Some(o.order_id as i64),
-                        token: Token::from_string(&o.symbol),
+                        token: self.exchange.borrow_mut().token_from_symbol(&o.symbol),
                         date: Date::from_timestamp(o.time),
                         side: Side::from_string(&o.side),
                         quantity: o.orig_qty.parse::<f64>().unwrap(),
@@ -109,7 +123,7 @@ impl Trader for BinanceWallet {
                 }
                 Ok(r)
             }
-            Err(e) => Err(DiError::Message(format!("{:?}", e))),
+            Err(e) => Err(e),
         }
     }
 }
@@ -23,3 +23,78 @@ pub fn compute_change_pct(start: f64, end: f64) -> f64 {
     let frac = end / start;
     (frac - 1.0) * 100.0
 }
+
+/// Bar characters scaled between the lowest and highest value, at most about
+/// `width` of them.
+pub fn sparkline(values: &[f64], width: usize) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    if values.is_empty() || width == 0 {
+        return String::new();
+    }
+    let min = values.iter().cloned().fold(f64::MAX, f64::min);
+    let max = values.iter().cloned().fold(f64::MIN, f64::max);
+    let step = (values.len() as f64 / width as f64).max(1.0);
+    let mut line = String::new();
+    let mut i = 0.0;
+    while (i as usize) < values.len() {
+        let v = values[i as usize];
+        let level = if max > min {
+            ((v - min) / (max - min) * 7.0).round() as usize
+        } else {
+            0
+        };
+        line.push(BARS[level.min(7)]);
+        i += step;
+    }
+    line
+}
+
+/// `value` with `decimals` places, `decimal` as the decimal mark and digits
+/// grouped by thousands with `thousands`, if given.
+pub fn format_number(
+    value: f64,
+    decimals: usize,
+    decimal: char,
+    thousands: Option<char>,
+) -> String {
+    let text = format!("{:.*}", decimals, value.abs());
+    let (integer, fraction) = match text.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (text.as_str(), None),
+    };
+    let mut s = String::new();
+    if value < 0.0 && text.chars().any(|c| c.is_ascii_digit() && c != '0') {
+        s.push('-');
+    }
+    for (i, c) in integer.chars().enumerate() {
+        if let Some(separator) = thousands {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                s.push(separator);
+            }
+        }
+        s.push(c);
+    }
+    if let Some(fraction) = fraction {
+        s.push(decimal);
+        s.push_str(fraction);
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_number;
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(
+            format_number(1234567.891, 2, '.', Some(',')),
+            "1,234,567.89"
+        );
+        assert_eq!(format_number(-1234.5, 3, ',', Some('.')), "-1.234,500");
+        assert_eq!(format_number(999.999, 2, '.', Some(',')), "1,000.00");
+        assert_eq!(format_number(123.0, 0, '.', Some(',')), "123");
+        assert_eq!(format_number(-0.0001, 2, '.', None), "0.00");
+        assert_eq!(format_number(65000.5, 1, '.', Some('\'')), "65'000.5");
+    }
+}
@@ -1,21 +1,60 @@
+use crate::binance::{classify, measure_clock, rest_error, BinanceExchange};
 use crate::finance::{DiError, Token};
+use crate::retry::RetryPolicy;
+use crate::ERROR;
 use binance::account::Account;
 use binance::api::*;
 use binance::config::Config;
+use slog::{self, slog_error};
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fs::read_to_string;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
 
 #[derive(Debug, Default)]
 pub struct Asset {
     pub free: f64,
 }
 
+/// Trading fee rates, as fractions of the order value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeSchedule {
+    pub maker: f64,
+    pub taker: f64,
+    /// Whether fees are paid in BNB at a discount, None when unknown.
+    pub bnb_discount: Option<bool>,
+}
+
+impl Default for FeeSchedule {
+    /// Binance regular user rates.
+    fn default() -> Self {
+        Self {
+            maker: 0.001,
+            taker: 0.001,
+            bnb_discount: None,
+        }
+    }
+}
+
 pub trait DigitalWallet {
     fn get_balance(&self) -> Result<HashMap<Token, Asset>, DiError>;
+    fn get_fees(&self) -> Result<FeeSchedule, DiError> {
+        Err(DiError::NotImplemented)
+    }
 }
 
+/// Receive window of signed requests (ms), the Binance default.
+pub const DEFAULT_RECV_WINDOW: u64 = 5000;
+/// Largest receive window Binance accepts (ms).
+const MAX_RECV_WINDOW: u64 = 60000;
+/// Requests ahead of the server by more than this (ms) are rejected.
+const MAX_AHEAD: i64 = 1000;
+
 pub struct BinanceWallet {
     pub account: Account,
+    /// Maps the symbols of the account orders to tokens.
+    pub exchange: RefCell<BinanceExchange>,
 }
 
 impl Default for BinanceWallet {
@@ -37,35 +76,155 @@ impl BinanceWallet {
             let config = Config::default().set_rest_api_endpoint("https://testnet.binance.vision");
             Self {
                 account: Binance::new_with_config(None, None, &config),
+                exchange: RefCell::new(BinanceExchange::default()),
             }
         } else {
             Self {
                 account: Binance::new(api_key, secret_key),
+                exchange: RefCell::new(BinanceExchange::default()),
             }
         }
     }
+
+    /// Widens the receive window when the local clock is behind the server by
+    /// more than it (`drift` is server minus local, in ms), and restores the
+    /// default once the clocks agree again. A local clock ahead of the server
+    /// can't be compensated this way.
+    pub fn fit_recv_window(&mut self, drift: i64, latency: i64) -> Result<u64, DiError> {
+        fit_recv_window(&mut self.account, drift, latency)
+    }
+}
+
+fn fit_recv_window(account: &mut Account, drift: i64, latency: i64) -> Result<u64, DiError> {
+    if drift < -MAX_AHEAD {
+        return Err(DiError::Message(format!(
+            "local clock is {} ms ahead of the server",
+            -drift
+        )));
+    }
+    let needed = (drift + latency).max(0) as u64 + DEFAULT_RECV_WINDOW / 2;
+    let window = needed.clamp(DEFAULT_RECV_WINDOW, MAX_RECV_WINDOW);
+    account.recv_window = window;
+    if needed > MAX_RECV_WINDOW {
+        return Err(DiError::Message(format!(
+            "local clock is {} ms behind the server",
+            drift
+        )));
+    }
+    Ok(window)
+}
+
+/// Runs a signed Binance REST call under the default retry policy. Clock
+/// drift failures measure the drift against the server time and retry with
+/// the receive window fitted to it.
+/// Only for idempotent requests, orders are never retried.
+pub fn with_signed_retry<T>(
+    account: &Account,
+    mut call: impl FnMut(&Account) -> binance::errors::Result<T>,
+) -> Result<T, DiError> {
+    let account = RefCell::new(account.clone());
+    RetryPolicy::default()
+        .run(
+            || call(&account.borrow()),
+            classify,
+            || match measure_clock() {
+                Ok(sample) => {
+                    if let Err(e) =
+                        fit_recv_window(&mut account.borrow_mut(), sample.drift, sample.latency)
+                    {
+                        ERROR!("{:?}", e);
+                    }
+                }
+                Err(e) => ERROR!("clock resync failed {:?}", e),
+            },
+        )
+        .map_err(|(e, failure)| rest_error(e, failure))
+}
+
+/// Free balances of the account.
+fn fetch_balance(account: &Account) -> Result<HashMap<Token, Asset>, DiError> {
+    match with_signed_retry(account, |account| account.get_account()) {
+        Ok(answer) => {
+            let items: HashMap<Token, Asset> = answer
+                .balances
+                .into_iter()
+                .map(|x| {
+                    (
+                        Token::Symbol(x.asset.clone()),
+                        Asset {
+                            free: x.free.parse::<f64>().unwrap_or(0.0),
+                        },
+                    )
+                })
+                .filter(|(_, a)| a.free > 0.0)
+                .collect();
+            Ok(items)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Free balances of the account, downloaded in the background so the caller
+/// never waits on the retries of the signed request.
+pub struct BalanceMonitor {
+    /// Time (seconds) of the last refresh request.
+    pub requested: i64,
+    loading: bool,
+    channel: (
+        Sender<Result<HashMap<Token, Asset>, DiError>>,
+        Receiver<Result<HashMap<Token, Asset>, DiError>>,
+    ),
+}
+
+impl Default for BalanceMonitor {
+    fn default() -> Self {
+        Self {
+            requested: 0,
+            loading: false,
+            channel: mpsc::channel(),
+        }
+    }
+}
+
+impl BalanceMonitor {
+    /// Downloads the balances with the keys and receive window of `account`,
+    /// results are collected with `poll`.
+    pub fn refresh(&mut self, account: &Account, now: i64) {
+        self.requested = now;
+        if self.loading {
+            return;
+        }
+        self.loading = true;
+        let account = account.clone();
+        let sender = self.channel.0.clone();
+        thread::spawn(move || {
+            let _ = sender.send(fetch_balance(&account));
+        });
+    }
+
+    /// Last finished download, None while loading.
+    pub fn poll(&mut self) -> Option<Result<HashMap<Token, Asset>, DiError>> {
+        let download = self.channel.1.try_iter().last();
+        if download.is_some() {
+            self.loading = false;
+        }
+        download
+    }
 }
 
 impl DigitalWallet for BinanceWallet {
     fn get_balance(&self) -> Result<HashMap<Token, Asset>, DiError> {
-        match self.account.get_account() {
-            Ok(answer) => {
-                let items: HashMap<Token, Asset> = answer
-                    .balances
-                    .into_iter()
-                    .map(|x| {
-                        (
-                            Token::Symbol(x.asset.clone()),
-                            Asset {
-                                free: x.free.parse::<f64>().unwrap_or(0.0),
-                            },
-                        )
-                    })
-                    .filter(|(_, a)| a.free > 0.0)
-                    .collect();
-                Ok(items)
-            }
-            Err(e) => Err(DiError::Message(format!("{:?}", e))),
-        }
+        fetch_balance(&self.account)
+    }
+
+    /// Account commissions, given by the exchange in basis points. The client
+    /// doesn't expose the BNB burn setting, so the discount is left unknown.
+    fn get_fees(&self) -> Result<FeeSchedule, DiError> {
+        let answer = with_signed_retry(&self.account, |account| account.get_account())?;
+        Ok(FeeSchedule {
+            maker: answer.maker_commission as f64 / 10000.0,
+            taker: answer.taker_commission as f64 / 10000.0,
+            bnb_discount: None,
+        })
     }
 }
@@ -1,33 +1,133 @@
-use crate::cache::SampleCache;
-use crate::finance::{DiError, Sample};
-use crate::time::Period;
+use crate::cache::Cache;
+use crate::finance::{DiError, MarketEvent, MarketTick, Sample, Token};
+use crate::time::{Date, Period, TimeUnit, TimeWindow};
+use crate::utils::compute_change_pct;
+use crate::{ERROR, INFO};
+use slog::{self, slog_error, slog_info};
+use std::collections::HashMap;
+use std::sync::{
+    mpsc::{self, Receiver, Sender},
+    Arc, Mutex,
+};
+use std::time::Duration;
+use threadpool::ThreadPool;
 use yahoo_finance_api::{self as yahoo, time::OffsetDateTime};
 
-#[derive(Default)]
+const MAX_CONCURRENT_THREADS: usize = 20;
+/// Yahoo has no streams, subscribed symbols are polled at this interval.
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+/// Number of samples requested on each poll, enough to skip market closures.
+const POLL_SAMPLES: i64 = 10;
+
+/// Prices of stocks change with dividends and splits, adjusted series scale
+/// every sample so the history is continuous with today's prices.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PriceAdjustment {
+    Raw,
+    #[default]
+    Adjusted,
+}
+
+impl PriceAdjustment {
+    pub fn from_name(name: &str) -> Option<PriceAdjustment> {
+        match name.to_lowercase().as_str() {
+            "raw" => Some(PriceAdjustment::Raw),
+            "adjusted" | "adj" => Some(PriceAdjustment::Adjusted),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PriceAdjustment::Raw => "raw",
+            PriceAdjustment::Adjusted => "adjusted",
+        }
+    }
+}
+
+/// Scales the OHLC prices of `sample` by the ratio between the adjusted and
+/// the raw close. Invalid adjusted closes leave the sample untouched.
+pub fn adjust_sample(sample: &Sample, adjclose: f64) -> Sample {
+    if !adjclose.is_finite() || adjclose <= 0.0 || sample.close <= 0.0 {
+        return sample.clone();
+    }
+    let factor = adjclose / sample.close;
+    Sample {
+        open: sample.open * factor,
+        high: sample.high * factor,
+        low: sample.low * factor,
+        close: adjclose,
+        ..sample.clone()
+    }
+}
+
+/// Basic fundamentals of a stock/ETF, values not published are None.
+#[derive(Clone, Debug, Default)]
+pub struct Fundamentals {
+    pub market_cap: Option<f64>,
+    pub trailing_pe: Option<f64>,
+    pub forward_pe: Option<f64>,
+    pub week_52_low: Option<f64>,
+    pub week_52_high: Option<f64>,
+    /// End of the last reported quarter.
+    pub last_quarter: Option<Date>,
+}
+
+/// Listing details of a stock/ETF symbol.
+#[derive(Clone, Debug, Default)]
+pub struct YahooSymbolInfo {
+    pub symbol: String,
+    pub name: String,
+    pub exchange: String,
+    pub quote_type: String,
+    pub fundamentals: Fundamentals,
+}
+
 pub struct YahooMarket {
-    pub cache: SampleCache,
-}
-
-fn fetch_history(symbol: &str, period: &Period) -> Result<Vec<Sample>, DiError> {
-    let provider = yahoo::YahooConnector::new().unwrap();
-    let start = OffsetDateTime::from_unix_timestamp(period.start().timestamp()).unwrap();
-    let end = OffsetDateTime::from_unix_timestamp(period.end().timestamp()).unwrap();
-    let response;
-    match provider.get_quote_history_interval(
-        symbol,
-        start,
-        end,
-        &period.duration.resolution.name(),
-    ) {
-        Ok(y_response) => response = y_response,
-        Err(e) => panic!("{:?}", e),
-    };
-    let quotes = response.quotes().unwrap();
+    pub cache: Cache,
+    adjustment: PriceAdjustment,
+    pool: ThreadPool,
+    event_channel: (Sender<MarketEvent>, Receiver<MarketEvent>),
+    thread_control: Arc<Mutex<HashMap<String, bool>>>,
+}
+
+impl Default for YahooMarket {
+    fn default() -> Self {
+        Self {
+            cache: Cache::default(),
+            adjustment: PriceAdjustment::default(),
+            pool: ThreadPool::new(MAX_CONCURRENT_THREADS),
+            event_channel: mpsc::channel(),
+            thread_control: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+fn connector() -> Result<yahoo::YahooConnector, DiError> {
+    yahoo::YahooConnector::new().map_err(|e| DiError::Message(format!("YAHOO {:?}", e)))
+}
+
+pub fn fetch_history(
+    symbol: &str,
+    period: &Period,
+    adjustment: PriceAdjustment,
+) -> Result<Vec<Sample>, DiError> {
+    let provider = connector()?;
+    let start = OffsetDateTime::from_unix_timestamp(period.start().timestamp())
+        .map_err(|e| DiError::Message(format!("{:?}", e)))?;
+    let end = OffsetDateTime::from_unix_timestamp(period.end().timestamp())
+        .map_err(|e| DiError::Message(format!("{:?}", e)))?;
+    let response = provider
+        .get_quote_history_interval(symbol, start, end, &period.duration.resolution.name())
+        .map_err(|e| DiError::Message(format!("FETCH HISTORY {} {:?}", symbol, e)))?;
+    let quotes = response
+        .quotes()
+        .map_err(|e| DiError::Message(format!("FETCH HISTORY {} {:?}", symbol, e)))?;
 
     let mut data = Vec::new();
 
     for quote in quotes.iter() {
-        data.push(Sample {
+        let sample = Sample {
             resolution: period.duration.resolution,
             timestamp: quote.timestamp,
             open: quote.open,
@@ -35,7 +135,195 @@ fn fetch_history(symbol: &str, period: &Period) -> Result<Vec<Sample>, DiError>
             low: quote.low,
             close: quote.close,
             volume: quote.volume,
+        };
+        data.push(match adjustment {
+            PriceAdjustment::Raw => sample,
+            PriceAdjustment::Adjusted => adjust_sample(&sample, quote.adjclose),
         })
     }
     Ok(data)
 }
+
+/// Last price and daily change of a symbol.
+pub fn fetch_tick(token: &Token) -> Result<MarketTick, DiError> {
+    let provider = connector()?;
+    let symbol = token.get_symbol();
+    let response = provider
+        .get_latest_quotes(&symbol, "1d")
+        .map_err(|e| DiError::Message(format!("FETCH TICK {} {:?}", symbol, e)))?;
+    let quotes = response
+        .quotes()
+        .map_err(|e| DiError::Message(format!("FETCH TICK {} {:?}", symbol, e)))?;
+    match quotes.last() {
+        Some(last) => {
+            let change_pct = match quotes.len() {
+                0 | 1 => 0.0,
+                n => compute_change_pct(quotes[n - 2].close, last.close),
+            };
+            Ok(MarketTick {
+                token: token.clone(),
+                price: last.close,
+                change_pct,
+            })
+        }
+        None => Err(DiError::NotFound),
+    }
+}
+
+pub fn fetch_fundamentals(symbol: &str) -> Result<Fundamentals, DiError> {
+    let provider = connector()?;
+    let summary = provider
+        .get_ticker_info(symbol)
+        .map_err(|e| DiError::Message(format!("FETCH FUNDAMENTALS {} {:?}", symbol, e)))?;
+    let data = summary
+        .quote_summary
+        .and_then(|q| q.result)
+        .and_then(|r| r.into_iter().next())
+        .ok_or(DiError::NotFound)?;
+    let mut fundamentals = Fundamentals::default();
+    if let Some(detail) = data.summary_detail {
+        fundamentals.market_cap = detail.market_cap.map(|v| v as f64);
+        fundamentals.trailing_pe = detail.trailing_pe.map(|v| v as f64);
+        fundamentals.forward_pe = detail.forward_pe.map(|v| v as f64);
+        fundamentals.week_52_low = detail.fifty_two_week_low.map(|v| v as f64);
+        fundamentals.week_52_high = detail.fifty_two_week_high.map(|v| v as f64);
+    }
+    if let Some(statistics) = data.default_key_statistics {
+        fundamentals.last_quarter = statistics
+            .most_recent_quarter
+            .map(|t| Date::from_timestamp(t as u64));
+    }
+    Ok(fundamentals)
+}
+
+impl YahooMarket {
+    pub fn adjustment(&self) -> PriceAdjustment {
+        self.adjustment
+    }
+
+    /// Changes how prices are adjusted. Cached samples are dropped so raw and
+    /// adjusted prices never mix, history must be fetched again.
+    pub fn set_adjustment(&mut self, adjustment: PriceAdjustment) {
+        if self.adjustment != adjustment {
+            self.adjustment = adjustment;
+            self.cache = Cache::default();
+        }
+    }
+
+    pub fn get_events(&self) -> Vec<MarketEvent> {
+        let mut events: Vec<MarketEvent> = Vec::new();
+        for event in self.event_channel.1.try_iter() {
+            events.push(event);
+        }
+        events
+    }
+
+    fn register_service(&mut self, key: &str) -> bool {
+        let mut control = self.thread_control.lock().unwrap();
+
+        if control.contains_key(key) {
+            return false;
+        }
+        control.insert(String::from(key), true);
+        true
+    }
+
+    /// Polls the latest sample and price of `token`.
+    pub fn kline_service(&mut self, token: &Token, resolution: &TimeUnit) {
+        let key = format!("{}@{}", token.to_string().to_lowercase(), resolution.name());
+        if self.register_service(key.as_str()) {
+            let tx = self.event_channel.0.clone();
+            let res = resolution.clone();
+            let tk = token.clone();
+            let adjustment = self.adjustment;
+
+            self.pool.execute(move || {
+                INFO!("yahoo poll service: {:?}", key);
+                let window = TimeWindow {
+                    resolution: res,
+                    count: POLL_SAMPLES,
+                };
+                loop {
+                    match fetch_history(&tk.get_symbol(), &Period::last(window), adjustment) {
+                        Ok(samples) => {
+                            if let Some(sample) = samples.last() {
+                                if tx
+                                    .send(MarketEvent::KLine((tk.clone(), sample.clone())))
+                                    .is_err()
+                                {
+                                    break;
+                                }
+                            }
+                        }
+                        Err(e) => ERROR!("yahoo poll service error {:?}: {:?}", key, e),
+                    }
+                    match fetch_tick(&tk) {
+                        Ok(tick) => {
+                            if tx.send(MarketEvent::Ticks(vec![tick])).is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => ERROR!("yahoo poll service error {:?}: {:?}", key, e),
+                    }
+                    std::thread::sleep(POLL_INTERVAL);
+                }
+            });
+        }
+    }
+
+    pub fn fetch_info(&self, token: &Token) -> Result<YahooSymbolInfo, DiError> {
+        let provider = connector()?;
+        let symbol = token.get_symbol();
+        let result = provider
+            .search_ticker(&symbol)
+            .map_err(|e| DiError::Message(format!("SEARCH {} {:?}", symbol, e)))?;
+        match result
+            .quotes
+            .iter()
+            .find(|item| item.symbol.eq_ignore_ascii_case(&symbol))
+        {
+            Some(item) => Ok(YahooSymbolInfo {
+                symbol: item.symbol.clone(),
+                name: if item.long_name.is_empty() {
+                    item.short_name.clone()
+                } else {
+                    item.long_name.clone()
+                },
+                exchange: item.exchange.clone(),
+                quote_type: item.quote_type.clone(),
+                fundamentals: match fetch_fundamentals(&item.symbol) {
+                    Ok(fundamentals) => fundamentals,
+                    Err(e) => {
+                        ERROR!("{:?}", e);
+                        Fundamentals::default()
+                    }
+                },
+            }),
+            None => Err(DiError::NotFound),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::adjust_sample;
+    use crate::finance::Sample;
+
+    #[test]
+    fn test_adjust_sample() {
+        let sample = Sample {
+            open: 100.0,
+            high: 120.0,
+            low: 80.0,
+            close: 110.0,
+            ..Default::default()
+        };
+        // 2:1 split
+        let adjusted = adjust_sample(&sample, 55.0);
+        assert_eq!(adjusted.open, 50.0);
+        assert_eq!(adjusted.high, 60.0);
+        assert_eq!(adjusted.low, 40.0);
+        assert_eq!(adjusted.close, 55.0);
+        assert_eq!(adjust_sample(&sample, 0.0).close, 110.0);
+    }
+}
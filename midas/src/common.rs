@@ -11,6 +11,10 @@ use ratatui::{
 };
 
 use dionysus::counselor::Signal;
+use dionysus::finance::Token;
+use dionysus::utils::format_number;
+use std::collections::HashMap;
+use std::sync::RwLock;
 
 pub const NORMAL_FG: Color = BLUE.c50;
 pub const NORMAL_BG: Color = SLATE.c950;
@@ -20,6 +24,167 @@ pub const PROFIT_COLOR: Color = GREEN.c500;
 pub const LOSS_COLOR: Color = RED.c500;
 pub const WINDOW_BORDER: Color = RED.c50;
 pub const SELECTED_WINDOW_BORDER: Color = YELLOW.c50;
+/// Cached 1m closes summarized by the ticker sparklines.
+pub const SPARKLINE_CLOSES: usize = 60;
+pub const SPARKLINE_WIDTH: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CandleBody {
+    Filled,
+    Hollow,
+    /// Rising candles hollow, falling ones filled.
+    HollowUp,
+}
+
+impl CandleBody {
+    pub fn from_name(name: &str) -> Option<CandleBody> {
+        match name.to_lowercase().as_str() {
+            "filled" => Some(CandleBody::Filled),
+            "hollow" => Some(CandleBody::Hollow),
+            "hollow-up" => Some(CandleBody::HollowUp),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CandleWick {
+    /// Same color as the body.
+    Body,
+    Color(Color),
+    Hidden,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CandleStyle {
+    pub up: Color,
+    pub down: Color,
+    pub wick: CandleWick,
+    pub body: CandleBody,
+}
+
+impl CandleStyle {
+    pub fn color(&self, rising: bool) -> Color {
+        if rising {
+            self.up
+        } else {
+            self.down
+        }
+    }
+
+    /// None when wicks are hidden.
+    pub fn wick_color(&self, rising: bool) -> Option<Color> {
+        match self.wick {
+            CandleWick::Body => Some(self.color(rising)),
+            CandleWick::Color(color) => Some(color),
+            CandleWick::Hidden => None,
+        }
+    }
+
+    pub fn is_hollow(&self, rising: bool) -> bool {
+        match self.body {
+            CandleBody::Filled => false,
+            CandleBody::Hollow => true,
+            CandleBody::HollowUp => rising,
+        }
+    }
+}
+
+/// Colors that can be changed at runtime from the config file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    pub profit: Color,
+    pub loss: Color,
+    pub border: Color,
+    pub focus: Color,
+    pub candles: CandleStyle,
+}
+
+pub const DEFAULT_THEME: Theme = Theme {
+    profit: PROFIT_COLOR,
+    loss: LOSS_COLOR,
+    border: Color::White,
+    focus: Color::Yellow,
+    candles: CandleStyle {
+        up: PROFIT_COLOR,
+        down: LOSS_COLOR,
+        wick: CandleWick::Body,
+        body: CandleBody::Hollow,
+    },
+};
+
+static THEME: RwLock<Theme> = RwLock::new(DEFAULT_THEME);
+
+pub fn theme() -> Theme {
+    *THEME.read().unwrap()
+}
+
+pub fn set_theme(theme: Theme) {
+    *THEME.write().unwrap() = theme;
+}
+
+/// Separators and default decimal places of displayed numbers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NumberFormat {
+    pub decimal: char,
+    pub thousands: Option<char>,
+    /// Places of values, percents and tokens without a known precision.
+    pub decimals: usize,
+}
+
+pub const DEFAULT_NUMBER_FORMAT: NumberFormat = NumberFormat {
+    decimal: '.',
+    thousands: Some(','),
+    decimals: 2,
+};
+
+static NUMBER_FORMAT: RwLock<NumberFormat> = RwLock::new(DEFAULT_NUMBER_FORMAT);
+/// Token -> (price, quantity) decimal places from the exchange filters.
+static PRECISIONS: RwLock<Option<HashMap<Token, (usize, usize)>>> = RwLock::new(None);
+
+pub fn set_number_format(format: NumberFormat) {
+    *NUMBER_FORMAT.write().unwrap() = format;
+}
+
+pub fn set_precisions(precisions: HashMap<Token, (usize, usize)>) {
+    *PRECISIONS.write().unwrap() = Some(precisions);
+}
+
+fn precision(token: &Token) -> Option<(usize, usize)> {
+    PRECISIONS.read().unwrap().as_ref()?.get(token).copied()
+}
+
+pub fn format_decimals(value: f64, decimals: usize) -> String {
+    let format = *NUMBER_FORMAT.read().unwrap();
+    format_number(value, decimals, format.decimal, format.thousands)
+}
+
+/// Price of `token` with the decimal places of its price tick.
+pub fn format_price(token: &Token, value: f64) -> String {
+    let decimals = match precision(token) {
+        Some((price, _)) => price,
+        None => NUMBER_FORMAT.read().unwrap().decimals.max(4),
+    };
+    format_decimals(value, decimals)
+}
+
+/// Quantity of `token` with the decimal places of its quantity step.
+pub fn format_quantity(token: &Token, value: f64) -> String {
+    let decimals = match precision(token) {
+        Some((_, quantity)) => quantity,
+        None => NUMBER_FORMAT.read().unwrap().decimals.max(4),
+    };
+    format_decimals(value, decimals)
+}
+
+/// Values in the quote currency.
+pub fn format_value(value: f64) -> String {
+    format_decimals(value, NUMBER_FORMAT.read().unwrap().decimals)
+}
+
+pub fn format_percent(value: f64) -> String {
+    format!("{}%", format_decimals(value, 2))
+}
 
 pub fn color_from_signal(signal: &Signal) -> Color {
     match signal {
@@ -29,6 +194,20 @@ pub fn color_from_signal(signal: &Signal) -> Color {
     }
 }
 
+/// Background color for heatmap cells: red for positive and blue for negative
+/// values, more intense as |value| approaches `max`.
+pub fn heat_color(value: f64, max: f64) -> Color {
+    if value.is_nan() || max <= 0.0 {
+        return Color::DarkGray;
+    }
+    let intensity = ((value.abs() / max).min(1.0) * 200.0) as u8;
+    if value > 0.0 {
+        Color::Rgb(55 + intensity, 40, 40)
+    } else {
+        Color::Rgb(40, 40, 55 + intensity)
+    }
+}
+
 pub fn popup_area(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
     let vertical = Layout::vertical([Constraint::Percentage(percent_y)]).flex(Flex::Center);
     let horizontal = Layout::horizontal([Constraint::Percentage(percent_x)]).flex(Flex::Center);
@@ -49,7 +228,8 @@ pub fn block(title: &str) -> Block {
 }
 
 pub fn focus_style(focus: bool) -> Style {
-    Style::default().fg(if focus { Color::Yellow } else { Color::White })
+    let theme = theme();
+    Style::default().fg(if focus { theme.focus } else { theme.border })
 }
 
 pub struct ListWindow<T> {
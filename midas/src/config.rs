@@ -0,0 +1,507 @@
+use crate::common::{
+    CandleBody, CandleStyle, CandleWick, NumberFormat, Theme, DEFAULT_NUMBER_FORMAT, DEFAULT_THEME,
+};
+use crate::w_window::WindowType;
+use dionysus::counselor::match_oracle_from_text;
+use dionysus::finance::Token;
+use dionysus::strategy::{Oracle, OrderTtl, Strategy};
+use dionysus::time::TimeUnit;
+use dionysus::{ERROR, INFO};
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+use slog::{slog_error, slog_info};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::SystemTime;
+
+/// Colors by name ("green", "light-red") or hex ("#1e90ff").
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub profit: String,
+    pub loss: String,
+    pub border: String,
+    pub focus: String,
+    /// Candle colors, profit and loss when empty.
+    pub candle_up: String,
+    pub candle_down: String,
+    /// Wick color, the body color when empty, "none" hides wicks.
+    pub candle_wick: String,
+    /// "filled", "hollow" or "hollow-up" (rising candles hollow).
+    pub candle_body: String,
+    /// Candles in the border color, rising ones hollow, for terminals with
+    /// few colors. Overrides the other candle settings.
+    pub monochrome: bool,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            profit: DEFAULT_THEME.profit.to_string(),
+            loss: DEFAULT_THEME.loss.to_string(),
+            border: DEFAULT_THEME.border.to_string(),
+            focus: DEFAULT_THEME.focus.to_string(),
+            candle_up: String::new(),
+            candle_down: String::new(),
+            candle_wick: String::new(),
+            candle_body: String::from("hollow"),
+            monochrome: false,
+        }
+    }
+}
+
+fn parse_color(name: &str, default: Color) -> Color {
+    match Color::from_str(name) {
+        Ok(color) => color,
+        Err(_) => {
+            ERROR!("config: invalid color {:?}", name);
+            default
+        }
+    }
+}
+
+impl ThemeConfig {
+    pub fn theme(&self) -> Theme {
+        let profit = parse_color(&self.profit, DEFAULT_THEME.profit);
+        let loss = parse_color(&self.loss, DEFAULT_THEME.loss);
+        let border = parse_color(&self.border, DEFAULT_THEME.border);
+        Theme {
+            profit,
+            loss,
+            border,
+            focus: parse_color(&self.focus, DEFAULT_THEME.focus),
+            candles: self.candles(profit, loss, border),
+        }
+    }
+
+    fn candles(&self, profit: Color, loss: Color, border: Color) -> CandleStyle {
+        if self.monochrome {
+            return CandleStyle {
+                up: border,
+                down: border,
+                wick: CandleWick::Body,
+                body: CandleBody::HollowUp,
+            };
+        }
+        let color_or = |name: &str, default: Color| match name {
+            "" => default,
+            name => parse_color(name, default),
+        };
+        let wick = match self.candle_wick.as_str() {
+            "" => CandleWick::Body,
+            "none" => CandleWick::Hidden,
+            name => CandleWick::Color(parse_color(name, border)),
+        };
+        let body = CandleBody::from_name(&self.candle_body).unwrap_or_else(|| {
+            ERROR!("config: invalid candle body {:?}", self.candle_body);
+            DEFAULT_THEME.candles.body
+        });
+        CandleStyle {
+            up: color_or(&self.candle_up, profit),
+            down: color_or(&self.candle_down, loss),
+            wick,
+            body,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskLimits {
+    /// Value, in quote currency, of orders placed from the UI.
+    pub max_order_value: f64,
+    /// Percent of the free quote currency balance given to each active strategy.
+    pub strategy_allocation: f64,
+    /// Stop loss and take profit of brackets, "2%" or "1.5atr".
+    pub bracket_stop: String,
+    pub bracket_take: String,
+    /// Symbols (BTCUSDT) or base assets (BTC) orders may be placed on, all
+    /// when empty. Denied ones are never traded.
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+    /// Phrase the UNLOCK command must be given to resume trading.
+    pub unlock_phrase: String,
+}
+
+impl Default for RiskLimits {
+    fn default() -> Self {
+        Self {
+            max_order_value: 10.0,
+            strategy_allocation: 10.0,
+            bracket_stop: String::from("2%"),
+            bracket_take: String::from("4%"),
+            allow: Vec::new(),
+            deny: Vec::new(),
+            unlock_phrase: String::from("resume trading"),
+        }
+    }
+}
+
+/// Sizes of the main panels, in percent of the screen.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutConfig {
+    /// Width of each side column (strategy/book and wallet/market/log).
+    pub side_panel: u16,
+    /// Height of the log in the right column.
+    pub log: u16,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            side_panel: 18,
+            log: 20,
+        }
+    }
+}
+
+/// Strategy given to tokens loaded without one. Counselors use the ORACLE
+/// command syntax, e.g. "ema-cross 50 200".
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrategyConfig {
+    /// "delphi" or "dodona"
+    pub oracle: String,
+    pub counselors: Vec<String>,
+    pub resolution: String,
+    pub history: i64,
+    /// Strategies wait for the history their counselors need before activating.
+    pub warmup: bool,
+    /// Unfilled limit orders are canceled after "3b" (bars) or "90s", "" never.
+    pub order_ttl: String,
+    /// Strategies decide when bars close instead of on every kline update.
+    pub closed_bars: bool,
+}
+
+impl Default for StrategyConfig {
+    fn default() -> Self {
+        Self {
+            oracle: String::from("delphi"),
+            counselors: vec![String::from("mean-reversion 20 2")],
+            resolution: TimeUnit::default().name(),
+            history: 200,
+            warmup: true,
+            order_ttl: String::new(),
+            closed_bars: false,
+        }
+    }
+}
+
+impl StrategyConfig {
+    pub fn strategy(&self) -> Strategy {
+        let mut strategy = Strategy::default();
+        strategy.oracle = match self.oracle.to_lowercase().as_str() {
+            "delphi" => Oracle::Delphi,
+            "dodona" => Oracle::Dodona,
+            name => {
+                ERROR!("config: unknown oracle {:?}", name);
+                Oracle::default()
+            }
+        };
+        for text in &self.counselors {
+            let words: Vec<&str> = text.split_whitespace().collect();
+            match match_oracle_from_text(&words) {
+                Some(counselor) => strategy.counselors.push(counselor),
+                None => ERROR!("config: invalid counselor {:?}", text),
+            }
+        }
+        strategy.duration.resolution = match TimeUnit::from_name(&self.resolution) {
+            TimeUnit::Unit(_) => {
+                ERROR!("config: invalid resolution {:?}", self.resolution);
+                TimeUnit::default()
+            }
+            resolution => resolution,
+        };
+        strategy.duration.count = self.history;
+        strategy.closed_bars = self.closed_bars;
+        if !self.order_ttl.is_empty() {
+            strategy.order_ttl = OrderTtl::from_text(&self.order_ttl);
+            if strategy.order_ttl.is_none() {
+                ERROR!("config: invalid order ttl {:?}", self.order_ttl);
+            }
+        }
+        strategy
+    }
+}
+
+/// Periodic account snapshots (balances and valuation).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapshotConfig {
+    pub interval_hours: f64,
+    /// Json lines file the snapshots are appended to.
+    pub path: String,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            interval_hours: 24.0,
+            path: String::from("snapshots.jsonl"),
+        }
+    }
+}
+
+/// Order book snapshots persisted for replay.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BookRecorderConfig {
+    pub enabled: bool,
+    /// Directory of the book files, one per token.
+    pub path: String,
+    /// Minimum time between snapshots of a token (ms).
+    pub interval_ms: u64,
+    /// Levels kept on each side.
+    pub depth: usize,
+}
+
+impl Default for BookRecorderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: String::from("books"),
+            interval_ms: 1000,
+            depth: 20,
+        }
+    }
+}
+
+/// How often the UI redraws and data is refreshed. Slower cadences trade
+/// latency for CPU, e.g. on low-powered machines or over SSH.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CadenceConfig {
+    /// Main loop period: redraws, input and market events (ms).
+    pub tick_ms: u64,
+    /// Refresh of the wallet, market and strategy panels (ms).
+    pub panels_ms: u64,
+    /// Wallet balances refetch (s).
+    pub balance_s: i64,
+    /// Open orders refetch (s).
+    pub open_orders_s: i64,
+    /// Rerun of backtests after new candles (s), 0 only reruns on changes.
+    pub backtest_s: u64,
+}
+
+impl Default for CadenceConfig {
+    fn default() -> Self {
+        Self {
+            tick_ms: 16,
+            panels_ms: 16,
+            balance_s: 60,
+            open_orders_s: 15,
+            backtest_s: 0,
+        }
+    }
+}
+
+/// How numbers are displayed. Prices and quantities use the decimal places of
+/// the exchange filters of their symbol.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormatConfig {
+    /// Separators: "en" 1,234.56, "de" 1.234,56, "fr" 1 234,56, "ch" 1'234.56.
+    pub locale: String,
+    /// Groups thousands.
+    pub grouping: bool,
+    /// Decimal places of values and of symbols without exchange info.
+    pub decimals: usize,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self {
+            locale: String::from("en"),
+            grouping: true,
+            decimals: DEFAULT_NUMBER_FORMAT.decimals,
+        }
+    }
+}
+
+impl FormatConfig {
+    pub fn number_format(&self) -> NumberFormat {
+        let (decimal, thousands) = match self.locale.to_lowercase().as_str() {
+            "en" => ('.', ','),
+            "de" => (',', '.'),
+            "fr" => (',', ' '),
+            "ch" => ('.', '\''),
+            locale => {
+                ERROR!("config: invalid locale {:?}", locale);
+                ('.', ',')
+            }
+        };
+        NumberFormat {
+            decimal,
+            thousands: if self.grouping { Some(thousands) } else { None },
+            decimals: self.decimals.min(12),
+        }
+    }
+}
+
+/// Inputs of the risk adjusted metrics of backtests and forward tests.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsConfig {
+    /// Yearly risk-free rate (%) discounted in Sharpe and alpha.
+    pub risk_free_rate: f64,
+    /// Symbol alpha and beta are measured against, e.g. "BTCUSDT" or "SPY".
+    /// Empty skips them.
+    pub benchmark: String,
+}
+
+impl MetricsConfig {
+    pub fn benchmark(&self) -> Option<Token> {
+        match self.benchmark.trim() {
+            "" => None,
+            symbol => Some(Token::from_string(&symbol.to_string())),
+        }
+    }
+}
+
+/// RSS feeds of the news window.
+const DEFAULT_NEWS_FEEDS: [&str; 2] = [
+    "https://www.coindesk.com/arc/outboundfeeds/rss/",
+    "https://cointelegraph.com/rss",
+];
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub theme: ThemeConfig,
+    pub format: FormatConfig,
+    /// Window name -> key that opens it, e.g. "oracle": "o".
+    pub keys: HashMap<String, char>,
+    pub risk: RiskLimits,
+    pub layout: LayoutConfig,
+    /// Command name -> commands separated by ';'.
+    pub aliases: HashMap<String, String>,
+    /// RSS feed urls of the news window.
+    pub news: Vec<String>,
+    pub snapshots: SnapshotConfig,
+    pub strategy: StrategyConfig,
+    pub books: BookRecorderConfig,
+    pub cadence: CadenceConfig,
+    /// Order book levels are grouped in buckets this percent of the mid price
+    /// wide, 0 shows every level.
+    pub book_bucket_pct: f64,
+    pub metrics: MetricsConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: ThemeConfig::default(),
+            format: FormatConfig::default(),
+            keys: HashMap::new(),
+            risk: RiskLimits::default(),
+            layout: LayoutConfig::default(),
+            aliases: HashMap::new(),
+            news: DEFAULT_NEWS_FEEDS.iter().map(|s| s.to_string()).collect(),
+            snapshots: SnapshotConfig::default(),
+            strategy: StrategyConfig::default(),
+            books: BookRecorderConfig::default(),
+            cadence: CadenceConfig::default(),
+            book_bucket_pct: 0.0,
+            metrics: MetricsConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    pub fn from_file(path: &str) -> Option<Config> {
+        let data = std::fs::read_to_string(path).ok()?;
+        match serde_json::from_str(&data) {
+            Ok(config) => Some(config),
+            Err(e) => {
+                ERROR!("config {}: {:?}", path, e);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self, path: &str) {
+        match std::fs::File::create(path) {
+            Ok(file) => {
+                if let Err(e) = serde_json::to_writer_pretty(file, self) {
+                    ERROR!("config {}: {:?}", path, e);
+                }
+            }
+            Err(e) => ERROR!("config {}: {:?}", path, e),
+        }
+    }
+
+    /// Key bindings with a known window name, in name order.
+    pub fn key_bindings(&self) -> Vec<(WindowType, char)> {
+        let mut names: Vec<&String> = self.keys.keys().collect();
+        names.sort();
+        let mut bindings = Vec::new();
+        for name in names {
+            let key = &self.keys[name];
+            match window_from_name(name) {
+                Some(window_type) => bindings.push((window_type, *key)),
+                None => ERROR!("config: unknown window {:?}", name),
+            }
+        }
+        bindings
+    }
+}
+
+fn window_from_name(name: &str) -> Option<WindowType> {
+    match name.to_lowercase().as_str() {
+        "command" => Some(WindowType::INPUT),
+        "log" => Some(WindowType::LOG),
+        "oracle" => Some(WindowType::ORACLE),
+        "help" => Some(WindowType::HELP),
+        "info" => Some(WindowType::INFO),
+        "order" => Some(WindowType::ORDER),
+        "correlation" => Some(WindowType::CORRELATION),
+        "seasonality" => Some(WindowType::SEASONALITY),
+        "volcone" => Some(WindowType::VOL_CONE),
+        "signals" => Some(WindowType::SIGNALS),
+        "indicator" => Some(WindowType::INDICATOR),
+        "backtest_diff" => Some(WindowType::BACKTEST_DIFF),
+        "backtest_rank" => Some(WindowType::BACKTEST_RANK),
+        "news" => Some(WindowType::NEWS),
+        "stats" => Some(WindowType::STATS),
+        "breakdown" => Some(WindowType::BREAKDOWN),
+        "streams" => Some(WindowType::STREAMS),
+        "dca" => Some(WindowType::DCA),
+        "ladder" => Some(WindowType::LADDER),
+        _ => None,
+    }
+}
+
+/// Reloads the config file whenever its modification time changes.
+pub struct ConfigWatcher {
+    path: String,
+    modified: Option<SystemTime>,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            modified: None,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// New config if the file changed since the last call. Invalid files are
+    /// reported and ignored so the current config stays in place.
+    pub fn poll(&mut self) -> Option<Config> {
+        let modified = std::fs::metadata(&self.path)
+            .and_then(|m| m.modified())
+            .ok()?;
+        if self.modified == Some(modified) {
+            return None;
+        }
+        self.modified = Some(modified);
+        let config = Config::from_file(&self.path)?;
+        INFO!("config loaded from {}", self.path);
+        Some(config)
+    }
+}
@@ -0,0 +1,8 @@
+/// Price alert lines and the crosshair used to place them.
+#[derive(Default)]
+pub struct AlertsGraph {
+    pub prices: Vec<f64>,
+    pub crosshair: Option<f64>,
+    /// Bar under the crosshair, anchors indicators.
+    pub crosshair_bar: usize,
+}
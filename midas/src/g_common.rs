@@ -28,6 +28,17 @@ impl ChartDomain {
     }
 
     pub fn draw(&self, ctx: &mut Context) {
+        self.draw_labels(ctx, |price| format!("{:.2}", price));
+    }
+
+    /// Price labels as the percent change from `base`.
+    pub fn draw_percent(&self, ctx: &mut Context, base: f64) {
+        self.draw_labels(ctx, |price| {
+            format!("{:+.2}%", (price / base - 1.0) * 100.0)
+        });
+    }
+
+    fn draw_labels(&self, ctx: &mut Context, label: impl Fn(f64) -> String) {
         let x_offset = self.bounds[0][0] + self.size(0) * 0.01;
         let bottom_price = (self.bounds[1][0] * 100.0).floor() as i64;
         let top_price = (self.bounds[1][1] * 100.0).ceil() as i64;
@@ -39,7 +50,7 @@ impl ChartDomain {
             ctx.print(
                 x_offset,
                 i as f64 / 100.0,
-                format!("{:.2}", i as f64 / 100.0).set_style(Color::White),
+                label(i as f64 / 100.0).set_style(Color::White),
             );
         }
     }
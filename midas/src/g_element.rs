@@ -1,18 +1,28 @@
 use std::f64;
 
 use crate::{
-    common::{color_from_signal, LOSS_COLOR, PROFIT_COLOR},
+    common::{color_from_signal, theme},
+    g_alerts::AlertsGraph,
     g_book::BookGraph,
     g_common::ChartDomain,
     g_curve::Curve,
-    g_indicators::{IndicatorGraph, IndicatorsGraph},
+    g_heatmap::BookHeatmap,
+    g_indicators::{Cloud, IndicatorGraph, IndicatorsGraph},
+    g_liquidation::LiquidationGraph,
+    g_orders::OrdersGraph,
     g_samples::SamplesGraph,
     g_strategy::StrategyGraph,
+    g_trades::TradesGraph,
+    g_volume_profile::VolumeProfileGraph,
+};
+use dionysus::{
+    counselor::Signal,
+    finance::{OrderType, Side},
+    indicators::IndicatorSource,
 };
-use dionysus::{counselor::Signal, indicators::IndicatorSource};
 use ratatui::{
-    style::Styled,
-    widgets::canvas::{Context, Line, Rectangle},
+    style::{Color, Styled},
+    widgets::canvas::{Context, Line},
 };
 
 pub trait GraphElement {
@@ -47,46 +57,7 @@ impl GraphElement for SamplesGraph {
 
     fn draw(&self, domain: &ChartDomain, dest: &IndicatorSource, ctx: &mut Context) {
         if *dest == IndicatorSource::Candle {
-            // candlestick
-            let mut i = 0;
-            for sample in &self.data {
-                let candle_color = if sample.close > sample.open {
-                    PROFIT_COLOR
-                } else {
-                    LOSS_COLOR
-                };
-
-                let x = domain.dx * i as f64;
-
-                ctx.draw(&Line::new(
-                    x,
-                    sample.low,
-                    x,
-                    sample.close.min(sample.open),
-                    candle_color,
-                ));
-
-                ctx.draw(&Line::new(
-                    x,
-                    sample.high,
-                    x,
-                    sample.close.max(sample.open),
-                    candle_color,
-                ));
-
-                ctx.draw(&Rectangle {
-                    x: x - 0.3,
-                    y: if sample.close > sample.open {
-                        sample.open
-                    } else {
-                        sample.close
-                    },
-                    width: 0.6,
-                    height: (sample.close - sample.open).abs(),
-                    color: candle_color,
-                });
-                i += 1;
-            }
+            self.draw_candles(domain, ctx);
         }
     }
 }
@@ -95,7 +66,7 @@ impl GraphElement for IndicatorGraph {
     fn draw(&self, domain: &ChartDomain, dest: &IndicatorSource, ctx: &mut Context) {
         match self {
             IndicatorGraph::SingleCurve(c) => c.draw(domain, dest, ctx),
-            IndicatorGraph::Curves(m) => {
+            IndicatorGraph::Curves(m) | IndicatorGraph::Cloud(m, _) => {
                 for c in m {
                     c.draw(domain, dest, ctx);
                 }
@@ -105,6 +76,33 @@ impl GraphElement for IndicatorGraph {
     }
 }
 
+/// Vertical lines drawn per bar to fill a cloud.
+const CLOUD_LINES: usize = 4;
+const CLOUD_UP: Color = Color::Rgb(25, 70, 40);
+const CLOUD_DOWN: Color = Color::Rgb(80, 30, 35);
+
+impl GraphElement for Cloud {
+    fn draw(&self, domain: &ChartDomain, _: &IndicatorSource, ctx: &mut Context) {
+        let (x0, y0) = self.origin;
+        for pair in self.spans.windows(2) {
+            let ((xa, a0, b0), (xb, a1, b1)) = (pair[0], pair[1]);
+            for k in 0..CLOUD_LINES {
+                let t = k as f64 / CLOUD_LINES as f64;
+                let a = a0 + (a1 - a0) * t;
+                let b = b0 + (b1 - b0) * t;
+                let x = (xa + (xb - xa) * t) * domain.dx + x0;
+                ctx.draw(&Line {
+                    x1: x,
+                    x2: x,
+                    y1: a + y0,
+                    y2: b + y0,
+                    color: if a >= b { CLOUD_UP } else { CLOUD_DOWN },
+                });
+            }
+        }
+    }
+}
+
 impl GraphElement for IndicatorsGraph {
     fn draw(&self, domain: &ChartDomain, dest: &IndicatorSource, ctx: &mut Context) {
         for (i, ig) in &self.indicators {
@@ -128,13 +126,39 @@ impl GraphElement for StrategyGraph {
                     );
                 }
             }
-            for order in &self.backtest.orders {
-                ctx.print(
-                    domain.x(order.date.timestamp() as u64),
-                    order.price,
-                    format!("{:?}", order.side), //.set_style(color_from_signal(&advice.signal)),
-                );
+        }
+    }
+}
+
+impl GraphElement for TradesGraph {
+    fn draw(&self, domain: &ChartDomain, dest: &IndicatorSource, ctx: &mut Context) {
+        if *dest != IndicatorSource::Candle {
+            return;
+        }
+        for trip in &self.trips {
+            if !self.is_visible(trip.exit_timestamp) {
+                continue;
+            }
+            ctx.draw(&Line {
+                x1: domain.x(trip.entry_timestamp),
+                y1: trip.entry_price,
+                x2: domain.x(trip.exit_timestamp),
+                y2: trip.exit_price,
+                color: match trip.profit() >= 0.0 {
+                    true => theme().profit,
+                    false => theme().loss,
+                },
+            });
+        }
+        for (timestamp, price, side) in &self.markers {
+            if !self.is_visible(*timestamp) {
+                continue;
             }
+            let (marker, color) = match side {
+                Side::Buy => ("▲", theme().profit),
+                Side::Sell => ("▼", theme().loss),
+            };
+            ctx.print(domain.x(*timestamp), *price, marker.set_style(color));
         }
     }
 }
@@ -161,3 +185,225 @@ impl GraphElement for BookGraph {
         }
     }
 }
+
+/// Number of dashes drawn across the visible width.
+const DASHES: usize = 60;
+
+impl GraphElement for AlertsGraph {
+    fn draw(&self, domain: &ChartDomain, _: &IndicatorSource, ctx: &mut Context) {
+        let [x0, x1] = domain.bounds[0];
+        let step = (x1 - x0) / DASHES as f64;
+        for price in &self.prices {
+            for i in 0..DASHES {
+                let x = x0 + i as f64 * step;
+                ctx.draw(&Line {
+                    x1: x,
+                    x2: x + step * 0.5,
+                    y1: *price,
+                    y2: *price,
+                    color: Color::Magenta,
+                });
+            }
+            ctx.print(
+                x0,
+                *price,
+                format!("alert {:.6}", price).set_style(Color::Magenta),
+            );
+        }
+        if let Some(price) = self.crosshair {
+            let x = self.crosshair_bar as f64 * domain.dx;
+            ctx.draw(&Line {
+                x1: x,
+                x2: x,
+                y1: domain.bounds[1][0],
+                y2: domain.bounds[1][1],
+                color: Color::Gray,
+            });
+            ctx.draw(&Line {
+                x1: x0,
+                x2: x1,
+                y1: price,
+                y2: price,
+                color: Color::Gray,
+            });
+            ctx.print(
+                x1 - step * 8.0,
+                price,
+                format!("{:.6}", price).set_style(Color::Gray),
+            );
+        }
+    }
+}
+
+/// Color of a book level holding `t` (0..1) of the largest resting size.
+fn heat_color(t: f64) -> Color {
+    let t = t.clamp(0.0, 1.0).sqrt();
+    Color::Rgb(
+        (20.0 + 235.0 * t) as u8,
+        (20.0 + 200.0 * t) as u8,
+        (60.0 * (1.0 - t)) as u8,
+    )
+}
+
+impl GraphElement for BookHeatmap {
+    fn draw(&self, domain: &ChartDomain, _: &IndicatorSource, ctx: &mut Context) {
+        let latest = match self.snapshots.back() {
+            Some((timestamp, _)) => *timestamp,
+            None => return,
+        };
+        let max_quantity = self.max_quantity();
+        if !self.enabled || max_quantity <= 0.0 || domain.time_step == 0 {
+            return;
+        }
+        let x = |timestamp: u64| {
+            (self.x_pos - (latest - timestamp) as f64 / domain.time_step as f64) * domain.dx
+        };
+        for (i, (timestamp, book)) in self.snapshots.iter().enumerate() {
+            let x1 = x(*timestamp);
+            let x2 = match self.snapshots.get(i + 1) {
+                Some((next, _)) => x(*next),
+                None => x1 + domain.dx * 0.3,
+            };
+            for l in book.bids.iter().chain(book.asks.iter()) {
+                ctx.draw(&Line {
+                    x1,
+                    x2,
+                    y1: l.price,
+                    y2: l.price,
+                    color: heat_color(l.quantity / max_quantity),
+                });
+            }
+        }
+    }
+}
+
+/// Widest profile bar, as a fraction of the visible width.
+const PROFILE_WIDTH: f64 = 0.2;
+/// Horizontal lines drawn per profile bin.
+const PROFILE_LINES: usize = 3;
+const PROFILE_COLOR: Color = Color::Rgb(60, 70, 90);
+const PROFILE_POC: Color = Color::Rgb(150, 120, 50);
+
+impl GraphElement for VolumeProfileGraph {
+    fn draw(&self, domain: &ChartDomain, _: &IndicatorSource, ctx: &mut Context) {
+        let max_volume = self.profile.max_volume();
+        if !self.enabled || max_volume <= 0.0 {
+            return;
+        }
+        let right = domain.bounds[0][1];
+        let width = domain.size(0) * PROFILE_WIDTH;
+        let poc = self.profile.poc();
+        let step = self.profile.bin_size / PROFILE_LINES as f64;
+        for (i, volume) in self.profile.volumes.iter().enumerate() {
+            if *volume <= 0.0 {
+                continue;
+            }
+            let color = match poc == Some(i) {
+                true => PROFILE_POC,
+                false => PROFILE_COLOR,
+            };
+            let bottom = self.profile.low + i as f64 * self.profile.bin_size;
+            for k in 0..PROFILE_LINES {
+                let y = bottom + (k as f64 + 0.5) * step;
+                ctx.draw(&Line {
+                    x1: right - width * volume / max_volume,
+                    x2: right,
+                    y1: y,
+                    y2: y,
+                    color,
+                });
+            }
+        }
+    }
+}
+
+/// Horizontal lines used to fill the visible price range.
+const FILL_LINES: f64 = 150.0;
+
+impl GraphElement for LiquidationGraph {
+    fn draw(&self, domain: &ChartDomain, _: &IndicatorSource, ctx: &mut Context) {
+        let [x0, x1] = domain.bounds[0];
+        let step = domain.size(1) / FILL_LINES;
+        if step <= 0.0 {
+            return;
+        }
+        for zone in &self.zones {
+            let t = zone.weight.clamp(0.0, 1.0);
+            let color = match zone.long {
+                true => Color::Rgb((60.0 + 195.0 * t) as u8, 30, 30),
+                false => Color::Rgb(30, (60.0 + 195.0 * t) as u8, 30),
+            };
+            let mut y = zone.low;
+            while y <= zone.high {
+                ctx.draw(&Line {
+                    x1: x0,
+                    x2: x1,
+                    y1: y,
+                    y2: y,
+                    color,
+                });
+                y += step;
+            }
+        }
+    }
+}
+
+impl GraphElement for OrdersGraph {
+    fn draw(&self, domain: &ChartDomain, _: &IndicatorSource, ctx: &mut Context) {
+        let [x0, x1] = domain.bounds[0];
+        let step = (x1 - x0) / DASHES as f64;
+        let label_x = x1 - step * 12.0;
+        for order in &self.orders {
+            let color = match order.side {
+                Side::Buy => theme().profit,
+                Side::Sell => theme().loss,
+            };
+            ctx.draw(&Line {
+                x1: x0,
+                x2: x1,
+                y1: order.price,
+                y2: order.price,
+                color,
+            });
+            ctx.print(
+                label_x,
+                order.price,
+                format!(
+                    "{:?} {:.6} @ {:.6}",
+                    order.side, order.quantity, order.price
+                )
+                .set_style(color),
+            );
+            // stop orders trigger at the stop price, dashed
+            if let (OrderType::StopMarket | OrderType::StopLimit, Some(stop)) =
+                (&order.order_type, order.stop_price)
+            {
+                for i in 0..DASHES {
+                    let x = x0 + i as f64 * step;
+                    ctx.draw(&Line {
+                        x1: x,
+                        x2: x + step * 0.5,
+                        y1: stop,
+                        y2: stop,
+                        color,
+                    });
+                }
+                ctx.print(label_x, stop, format!("stop {:.6}", stop).set_style(color));
+            }
+        }
+        for (price, quantity) in &self.entries {
+            ctx.draw(&Line {
+                x1: x0,
+                x2: x1,
+                y1: *price,
+                y2: *price,
+                color: Color::Yellow,
+            });
+            ctx.print(
+                x0,
+                *price,
+                format!("entry {:.6} x {:.6}", price, quantity).set_style(Color::Yellow),
+            );
+        }
+    }
+}
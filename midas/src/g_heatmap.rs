@@ -0,0 +1,55 @@
+use dionysus::{book_recorder::BookRecord, finance::Book};
+use std::collections::VecDeque;
+
+/// Snapshots kept for the heatmap.
+const MAX_SNAPSHOTS: usize = 600;
+/// Minimum time between recorded snapshots (ms).
+const SNAPSHOT_INTERVAL: u64 = 1000;
+
+/// Resting liquidity over time, from successive book snapshots.
+#[derive(Default)]
+pub struct BookHeatmap {
+    pub enabled: bool,
+    /// (local time in ms, book)
+    pub snapshots: VecDeque<(u64, Book)>,
+    /// x of the latest snapshot, in bars.
+    pub x_pos: f64,
+}
+
+impl BookHeatmap {
+    pub fn record(&mut self, timestamp: u64, book: &Book) {
+        if let Some((last, _)) = self.snapshots.back() {
+            if timestamp < last + SNAPSHOT_INTERVAL {
+                return;
+            }
+        }
+        self.snapshots.push_back((timestamp, book.clone()));
+        while self.snapshots.len() > MAX_SNAPSHOTS {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Replaces the snapshots with recorded ones, evenly thinned to fit.
+    pub fn replay(&mut self, records: &[BookRecord]) {
+        let step = records.len().div_ceil(MAX_SNAPSHOTS).max(1);
+        self.snapshots = records
+            .iter()
+            .step_by(step)
+            .map(|r| (r.timestamp, r.book.clone()))
+            .collect();
+        self.enabled = true;
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    /// Largest resting size of all snapshots, used to shade the levels.
+    pub fn max_quantity(&self) -> f64 {
+        self.snapshots
+            .iter()
+            .flat_map(|(_, book)| book.bids.iter().chain(book.asks.iter()))
+            .map(|l| l.quantity)
+            .fold(0.0, f64::max)
+    }
+}
@@ -6,9 +6,20 @@ use dionysus::{
 use random_color::RandomColor;
 use ratatui::style::Color;
 
+const REFERENCE_LINE_COLOR: Color = Color::DarkGray;
+
+/// Area between two curves, shaded by which one is on top.
+#[derive(Default)]
+pub struct Cloud {
+    /// (x, first curve, second curve) of each bar.
+    pub spans: Vec<(f64, f64, f64)>,
+    pub origin: (f64, f64),
+}
+
 pub enum IndicatorGraph {
     SingleCurve(Curve),
     Curves(Vec<Curve>),
+    Cloud(Vec<Curve>, Cloud),
     Empty(Color),
 }
 
@@ -17,6 +28,7 @@ impl IndicatorGraph {
         match self {
             IndicatorGraph::SingleCurve(c) => c.color,
             IndicatorGraph::Curves(m) => m[0].color,
+            IndicatorGraph::Cloud(m, _) => m[0].color,
             IndicatorGraph::Empty(c) => c.clone(),
         }
     }
@@ -54,16 +66,18 @@ impl IndicatorsGraph {
         c
     }
 
+    /// Curves of each row, shifted by `shifts` bars when given.
     fn curves_from_matrix(
         &self,
         x: usize,
         v: &Vec<Vec<f64>>,
+        shifts: &[i64],
         color: &Color,
         y0: f64,
     ) -> Vec<Curve> {
         let mut curves: Vec<Curve> = Vec::new();
         for i in 0..v.len() {
-            let mut xi = x;
+            let mut xi = x as i64 + shifts.get(i).copied().unwrap_or(0);
             let mut points = Vec::new();
             for j in 0..v[0].len() {
                 points.push((xi as f64, v[i][j]));
@@ -79,6 +93,40 @@ impl IndicatorsGraph {
         curves
     }
 
+    /// Fill between the curves `a` and `b`, point by point.
+    fn cloud_from_curves(&self, curves: &[Curve], (a, b): (usize, usize)) -> Cloud {
+        match (curves.get(a), curves.get(b)) {
+            (Some(a), Some(b)) => Cloud {
+                origin: a.origin,
+                spans: a
+                    .points
+                    .iter()
+                    .zip(&b.points)
+                    .map(|(a, b)| (a.0, a.1, b.1))
+                    .collect(),
+            },
+            _ => Cloud::default(),
+        }
+    }
+
+    /// Bars the shifted series reach past the last sample.
+    pub fn lead(&self) -> usize {
+        self.indicators
+            .iter()
+            .flat_map(|(indicator, _)| indicator.shifts())
+            .max()
+            .unwrap_or(0)
+            .max(0) as usize
+    }
+
+    /// Clouds of the indicators, drawn under the candles.
+    pub fn clouds(&self) -> impl Iterator<Item = &Cloud> {
+        self.indicators.iter().filter_map(|(_, graph)| match graph {
+            IndicatorGraph::Cloud(_, cloud) => Some(cloud),
+            _ => None,
+        })
+    }
+
     pub fn add_indicator(&mut self, indicator: &Indicator) {
         let mut rng_color = RandomColor::new();
         let rgb = rng_color.to_rgb_array();
@@ -87,6 +135,37 @@ impl IndicatorsGraph {
             .push((indicator.clone(), IndicatorGraph::Empty(color)));
     }
 
+    /// Replaces the indicator at `index`, keeping its color.
+    pub fn set_indicator(&mut self, index: usize, indicator: &Indicator) {
+        if let Some(entry) = self.indicators.get_mut(index) {
+            let color = entry.1.get_color();
+            *entry = (indicator.clone(), IndicatorGraph::Empty(color));
+        }
+    }
+
+    pub fn remove_indicator(&mut self, index: usize) -> Option<Indicator> {
+        match index < self.indicators.len() {
+            true => Some(self.indicators.remove(index).0),
+            false => None,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.indicators.clear();
+    }
+
+    /// Index of the first indicator with the text form `name` ("ema 20"), or
+    /// just its kind ("ema").
+    pub fn position(&self, name: &str) -> Option<usize> {
+        let name = name.to_lowercase();
+        let texts: Vec<String> = self.indicators.iter().map(|(i, _)| i.to_text()).collect();
+        texts.iter().position(|text| *text == name).or_else(|| {
+            texts
+                .iter()
+                .position(|text| text.split_whitespace().next() == Some(name.as_str()))
+        })
+    }
+
     pub fn compute(&mut self, samples: &[Sample]) {
         for i in 0..self.indicators.len() {
             let mut y0 = 0.0;
@@ -95,33 +174,58 @@ impl IndicatorsGraph {
                     IndicatorDomain::Price => y0 = 0.0,
                     _ => y0 = samples.last().unwrap().open,
                 }
+            } else if self.indicators[i].0.domain() == IndicatorDomain::Percent {
+                // the lower pane spans 0 to 100
+                y0 = -self.indicators[i].0.percent_range().0;
             }
             match self.indicators[i].0.compute_series(samples) {
-                Ok(r) => match r {
-                    IndicatorData::Scalar(s) => {
-                        self.indicators[i].1 = IndicatorGraph::SingleCurve(self.curve_from_scalar(
-                            samples.len() as f64,
-                            s,
-                            y0,
-                        ));
+                Ok(r) => {
+                    match r {
+                        IndicatorData::Scalar(s) => {
+                            self.indicators[i].1 = IndicatorGraph::SingleCurve(
+                                self.curve_from_scalar(samples.len() as f64, s, y0),
+                            );
+                        }
+                        IndicatorData::Vector(v) => {
+                            let curve = self.curve_from_vector(
+                                samples.len().saturating_sub(v.len()),
+                                &v,
+                                &self.indicators[i].1.get_color(),
+                                y0,
+                            );
+                            let levels = self.indicators[i].0.reference_lines();
+                            self.indicators[i].1 = match levels.is_empty() {
+                                true => IndicatorGraph::SingleCurve(curve),
+                                false => {
+                                    let mut curves = vec![curve];
+                                    for level in levels {
+                                        let mut line =
+                                            self.curve_from_scalar(samples.len() as f64, level, y0);
+                                        line.color = REFERENCE_LINE_COLOR;
+                                        curves.push(line);
+                                    }
+                                    IndicatorGraph::Curves(curves)
+                                }
+                            }
+                        }
+                        IndicatorData::Matrix(m) => {
+                            let curves = self.curves_from_matrix(
+                                samples.len().saturating_sub(m[0].len()),
+                                &m,
+                                &self.indicators[i].0.shifts(),
+                                &self.indicators[i].1.get_color(),
+                                y0,
+                            );
+                            self.indicators[i].1 = match self.indicators[i].0.cloud() {
+                                Some(rows) => {
+                                    let cloud = self.cloud_from_curves(&curves, rows);
+                                    IndicatorGraph::Cloud(curves, cloud)
+                                }
+                                None => IndicatorGraph::Curves(curves),
+                            }
+                        }
                     }
-                    IndicatorData::Vector(v) => {
-                        self.indicators[i].1 = IndicatorGraph::SingleCurve(self.curve_from_vector(
-                            samples.len().saturating_sub(v.len()),
-                            &v,
-                            &self.indicators[i].1.get_color(),
-                            y0,
-                        ))
-                    }
-                    IndicatorData::Matrix(m) => {
-                        self.indicators[i].1 = IndicatorGraph::Curves(self.curves_from_matrix(
-                            samples.len().saturating_sub(m[0].len()),
-                            &m,
-                            &self.indicators[i].1.get_color(),
-                            y0,
-                        ))
-                    }
-                },
+                }
                 _ => (),
             };
         }
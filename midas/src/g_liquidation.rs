@@ -0,0 +1,7 @@
+use dionysus::futures::LiquidationZone;
+
+/// Estimated liquidation clusters shaded behind the candles.
+#[derive(Default)]
+pub struct LiquidationGraph {
+    pub zones: Vec<LiquidationZone>,
+}
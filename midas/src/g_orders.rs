@@ -0,0 +1,9 @@
+use dionysus::finance::Order;
+
+/// Open orders and position entries of the pair, drawn as price lines.
+#[derive(Default)]
+pub struct OrdersGraph {
+    pub orders: Vec<Order>,
+    /// (entry price, quantity)
+    pub entries: Vec<(f64, f64)>,
+}
@@ -1,24 +1,59 @@
-use crate::{
-    common::{LOSS_COLOR, PROFIT_COLOR},
-    g_common::ChartDomain,
+use crate::{common::theme, g_common::ChartDomain};
+use dionysus::{
+    finance::Sample,
+    renko::{renko, Brick, BrickSize},
 };
-use dionysus::finance::Sample;
-use ratatui::widgets::canvas::{Context, Rectangle};
+use ratatui::widgets::canvas::{Context, Line, Rectangle};
+
+/// Width of candle bodies and volume bars, in samples.
+const BODY_WIDTH: f64 = 0.6;
+/// Vertical lines filling a body.
+const FILL_LINES: usize = 5;
 
 #[derive(Default)]
 pub struct SamplesGraph {
     pub data: Vec<Sample>,
     pub data_bounds: [[f64; 2]; 2],
+    /// Draws renko bricks of this size instead of candles.
+    renko: Option<BrickSize>,
+    bricks: Vec<Brick>,
 }
 
 impl SamplesGraph {
     pub fn update(&mut self, samples: &[Sample]) {
         self.data.clear();
         self.data = samples.iter().map(|x| x.clone()).collect();
+        self.update_bricks();
         self.compute_bounds();
     }
 
+    pub fn renko(&self) -> Option<BrickSize> {
+        self.renko
+    }
+
+    pub fn set_renko(&mut self, size: Option<BrickSize>) {
+        self.renko = size;
+        if !self.data.is_empty() {
+            self.update_bricks();
+            self.compute_bounds();
+        }
+    }
+
+    fn update_bricks(&mut self) {
+        self.bricks = match self.renko {
+            Some(size) => renko(size, &self.data),
+            None => Vec::new(),
+        };
+    }
+
     fn compute_bounds(&mut self) {
+        if self.renko.is_some() && !self.bricks.is_empty() {
+            let (low, high) = self.bricks.iter().fold((f64::MAX, f64::MIN), |(l, h), b| {
+                (l.min(b.open.min(b.close)), h.max(b.open.max(b.close)))
+            });
+            self.data_bounds = [[0.0, self.bricks.len() as f64], [low, high]];
+            return;
+        }
         let mut price_bounds = [self.data[0].low, self.data[0].high];
         let time_bounds = [0.0, self.data.len() as f64];
 
@@ -31,7 +66,61 @@ impl SamplesGraph {
         self.data_bounds[1] = price_bounds;
     }
 
+    /// Bricks as filled bodies in the candle colors, one per x step.
+    fn draw_bricks(&self, domain: &ChartDomain, ctx: &mut Context) {
+        let style = theme().candles;
+        for (i, brick) in self.bricks.iter().enumerate() {
+            let color = style.color(brick.rising());
+            let x = domain.dx * i as f64;
+            let bottom = brick.open.min(brick.close);
+            let top = brick.open.max(brick.close);
+            for j in 0..FILL_LINES {
+                let x = x - BODY_WIDTH / 2.0 + BODY_WIDTH * j as f64 / (FILL_LINES - 1) as f64;
+                ctx.draw(&Line::new(x, bottom, x, top, color));
+            }
+        }
+    }
+
+    /// Candlesticks in the theme candle style, or renko bricks.
+    pub fn draw_candles(&self, domain: &ChartDomain, ctx: &mut Context) {
+        if self.renko.is_some() {
+            return self.draw_bricks(domain, ctx);
+        }
+        let style = theme().candles;
+        for (i, sample) in self.data.iter().enumerate() {
+            let rising = sample.close > sample.open;
+            let color = style.color(rising);
+            let x = domain.dx * i as f64;
+            let bottom = sample.close.min(sample.open);
+            let top = sample.close.max(sample.open);
+
+            if let Some(wick_color) = style.wick_color(rising) {
+                ctx.draw(&Line::new(x, sample.low, x, bottom, wick_color));
+                ctx.draw(&Line::new(x, sample.high, x, top, wick_color));
+            }
+
+            if style.is_hollow(rising) {
+                ctx.draw(&Rectangle {
+                    x: x - BODY_WIDTH / 2.0,
+                    y: bottom,
+                    width: BODY_WIDTH,
+                    height: top - bottom,
+                    color,
+                });
+            } else {
+                for j in 0..FILL_LINES {
+                    let x = x - BODY_WIDTH / 2.0 + BODY_WIDTH * j as f64 / (FILL_LINES - 1) as f64;
+                    ctx.draw(&Line::new(x, bottom, x, top, color));
+                }
+            }
+        }
+    }
+
     pub fn draw_volume(&self, domain: &ChartDomain, ctx: &mut Context) {
+        // bricks have no volume
+        if self.renko.is_some() {
+            return;
+        }
         // candlestick
         let mut i = 0;
 
@@ -44,19 +133,16 @@ impl SamplesGraph {
 
         let scale = 100.0 / (max_volume as f64);
 
+        let style = theme().candles;
         for sample in &self.data {
-            let candle_color = if sample.close > sample.open {
-                PROFIT_COLOR
-            } else {
-                LOSS_COLOR
-            };
+            let candle_color = style.color(sample.close > sample.open);
 
             let x = domain.dx * i as f64;
 
             ctx.draw(&Rectangle {
-                x: x - 0.3,
+                x: x - BODY_WIDTH / 2.0,
                 y: 0.0,
-                width: 0.6,
+                width: BODY_WIDTH,
                 height: (sample.volume as f64) * scale,
                 color: candle_color,
             });
@@ -1,10 +1,9 @@
 use crate::g_indicators::IndicatorsGraph;
-use dionysus::{backtest::Backtest, counselor::Advice, finance::Sample, strategy::Strategy};
+use dionysus::{counselor::Advice, finance::Sample, strategy::Strategy};
 
 #[derive(Default)]
 pub struct StrategyGraph {
     pub indicators: IndicatorsGraph,
-    pub backtest: Backtest,
     pub advices: Vec<Advice>,
 }
 
@@ -17,10 +16,6 @@ impl StrategyGraph {
         }
     }
 
-    pub fn set_backtest(&mut self, backtest: &Backtest) {
-        self.backtest = backtest.clone();
-    }
-
     pub fn compute(&mut self, samples: &[Sample]) {
         self.indicators.compute(samples);
         //self.advices = self.oracle.run_series(samples).unwrap();
@@ -0,0 +1,34 @@
+use dionysus::{
+    backtest::{Backtest, RoundTrip},
+    finance::{Side, Token},
+};
+
+/// Orders of the backtest, drawn as buy/sell markers with each round trip
+/// joined by a segment colored by its result.
+#[derive(Default)]
+pub struct TradesGraph {
+    /// (timestamp, price, side)
+    pub markers: Vec<(u64, f64, Side)>,
+    pub trips: Vec<RoundTrip>,
+    /// Timestamp of the last visible bar while scrubbing, later orders are hidden.
+    pub playhead: Option<u64>,
+}
+
+impl TradesGraph {
+    pub fn set_backtest(&mut self, backtest: &Backtest, token: &Token) {
+        self.markers = backtest
+            .orders
+            .iter()
+            .filter(|o| o.token == *token)
+            .map(|o| (o.date.timestamp() as u64, o.price, o.side.clone()))
+            .collect();
+        self.trips = backtest.round_trips(token);
+    }
+
+    pub fn is_visible(&self, timestamp: u64) -> bool {
+        match self.playhead {
+            Some(playhead) => timestamp <= playhead,
+            None => true,
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use dionysus::analytics::{volume_profile, VolumeProfile};
+use dionysus::finance::Sample;
+
+/// Price levels of the profile.
+const PROFILE_BINS: usize = 40;
+
+/// Volume traded by price over the loaded bars, drawn as a horizontal
+/// histogram on the right edge of the candle chart.
+#[derive(Default)]
+pub struct VolumeProfileGraph {
+    pub enabled: bool,
+    pub profile: VolumeProfile,
+}
+
+impl VolumeProfileGraph {
+    pub fn compute(&mut self, samples: &[Sample]) {
+        self.profile = volume_profile(samples, PROFILE_BINS);
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+}
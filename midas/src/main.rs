@@ -1,149 +1,426 @@
 use clap::Parser;
 use color_eyre::Result;
 use crossterm::event::{self, Event};
+use dionysus::allocation::Rebalancer;
+use dionysus::analytics::{
+    annualization_factor, seasonality, volatility_cone, VolatilityEstimator,
+};
 use dionysus::backtest::Backtest;
+use dionysus::book_recorder::{BookRecorder, BookReplay};
+use dionysus::bracket::{last_atr, Bracket, BracketOffset, BracketTemplate};
+use dionysus::brownian::PriceModel;
+use dionysus::counselor::{match_oracle_from_text, AtrExits};
 use dionysus::finance::{Order, OrderType, Side, TimeInForce, Token};
+use dionysus::futures::{liquidation_zones, load_funding, Perpetual};
 use dionysus::historical_data::HistoricalData;
-use dionysus::indicators::match_indicator_from_text;
-use dionysus::strategy::Strategy;
+use dionysus::indicators::{match_indicator_from_text, Indicator};
+use dionysus::news::NewsFeed;
+use dionysus::renko::BrickSize;
+use dionysus::report::Report;
+use dionysus::risk::TradePermissions;
+use dionysus::snapshots::SnapshotLog;
+use dionysus::stats::BreakdownKey;
+use dionysus::strategy::{Heat, OrderTtl, PartialFillPolicy, Strategy};
 use dionysus::time::{Date, TimeUnit};
 use dionysus::trader::Trader;
-use dionysus::ERROR;
+use dionysus::utils::sparkline;
+use dionysus::yahoo::PriceAdjustment;
+use dionysus::{ERROR, INFO};
 use ratatui::{
     layout::{Constraint, Layout},
     DefaultTerminal, Frame,
 };
-use slog::slog_error;
+use slog::{slog_error, slog_info};
 use slog_scope;
 use std::collections::HashMap;
-use std::io;
+use std::io::{self, IsTerminal};
 use w_window::WindowType;
 use w_window_manager::WindowManager;
 
 mod common;
+mod config;
+mod g_alerts;
 mod g_book;
 mod g_common;
 mod g_curve;
 mod g_element;
+mod g_heatmap;
 mod g_indicators;
+mod g_liquidation;
+mod g_orders;
 mod g_samples;
 mod g_strategy;
+mod g_trades;
+mod g_volume_profile;
 mod midas;
+mod setup;
 mod w_backtest;
+mod w_backtest_diff;
+mod w_backtest_rank;
+mod w_breakdown;
 mod w_command;
+mod w_correlation;
+mod w_dca;
 mod w_graph;
 mod w_help;
+mod w_indicator;
 mod w_info;
 mod w_interactible;
+mod w_ladder;
 mod w_log;
 mod w_market;
+mod w_news;
 mod w_oracle;
 mod w_order;
 mod w_order_book;
+mod w_seasonality;
+mod w_shutdown;
+mod w_signals;
+mod w_stats;
 mod w_strategy;
+mod w_streams;
 mod w_symbol_tabs;
+mod w_vol_cone;
 mod w_wallet;
 mod w_window;
 mod w_window_manager;
 
+use config::{Config, ConfigWatcher};
 use midas::{Midas, MidasEvent};
+use w_command::CommandResult;
 use w_graph::GraphView;
 use w_interactible::InteractionEvent;
 
+/// Number of most recent returns used in correlation analysis.
+const CORRELATION_WINDOW: usize = 100;
+/// Default risk aversion of the mean-variance allocation (ALLOCATE command).
+const ALLOCATION_RISK_AVERSION: f64 = 1.0;
+/// Limit of aliases and scripts calling other aliases and scripts.
+const MAX_COMMAND_DEPTH: usize = 8;
+/// Time finished tasks stay in the status line.
+const PROGRESS_LINGER: std::time::Duration = std::time::Duration::from_secs(3);
+/// Leverages assumed by the LIQ command when none are given.
+const LIQUIDATION_LEVERAGES: [f64; 4] = [10.0, 25.0, 50.0, 100.0];
+/// Price bins of the estimated liquidation zones.
+const LIQUIDATION_BINS: usize = 60;
+/// Bars of the ATR used by ATR bracket offsets.
+const BRACKET_ATR_PERIOD: usize = 14;
+/// ATR period of the exits command when none is given.
+const ATR_EXITS_PERIOD: usize = 14;
+/// Lookbacks, in bars, of the volatility cone.
+const VOL_CONE_WINDOWS: [usize; 6] = [10, 20, 40, 60, 90, 120];
+/// Saved pairs and strategies, created on the first run.
+const STATE_FILE: &str = "state.json";
+/// Simulated minutes per second of sandboxes started without a speed.
+const SANDBOX_SPEED: f64 = 60.0;
+
 pub struct App {
     midas: Midas,
     exit: bool,
     state_file: String,
     backtests: HashMap<usize, Backtest>,
     window_manager: WindowManager,
+    config: Config,
+    config_watcher: ConfigWatcher,
+    command_depth: usize,
+    /// Commands file executed once the saved pairs are open.
+    script: Option<String>,
+    news: NewsFeed,
+    snapshots: SnapshotLog,
+    book_recorder: BookRecorder,
+    /// Candles arrived since the last backtest run.
+    backtests_stale: bool,
+    last_backtest: std::time::Instant,
+    /// Symbols in the registry when the display precisions were set.
+    precision_symbols: usize,
 }
 
 impl App {
-    pub fn new(keys_file: &str, use_test_api: bool) -> App {
+    pub fn new(keys_file: &str, use_test_api: bool, config_file: &str) -> App {
         App {
             midas: Midas::new(keys_file, use_test_api),
             exit: false,
-            state_file: String::from("state.json"),
+            state_file: String::from(STATE_FILE),
             backtests: HashMap::new(),
             window_manager: WindowManager::new(),
+            config: Config::default(),
+            config_watcher: ConfigWatcher::new(config_file),
+            command_depth: 0,
+            script: None,
+            news: NewsFeed::default(),
+            snapshots: SnapshotLog::default(),
+            book_recorder: BookRecorder::new("books", 1000, 20),
+            backtests_stale: false,
+            last_backtest: std::time::Instant::now(),
+            precision_symbols: 0,
+        }
+    }
+
+    /// Applies the config file if it changed. Market subscriptions are kept.
+    fn reload_config(&mut self) {
+        if let Some(config) = self.config_watcher.poll() {
+            common::set_theme(config.theme.theme());
+            common::set_number_format(config.format.number_format());
+            self.window_manager.set_keys(&config.key_bindings());
+            self.midas.default_strategy = config.strategy.strategy();
+            self.midas.allocation_pct = config.risk.strategy_allocation;
+            self.midas.enforce_warmup = config.strategy.warmup;
+            self.midas.balance_interval = config.cadence.balance_s.max(1);
+            self.midas.open_orders_interval = config.cadence.open_orders_s.max(1);
+            self.midas.permissions = TradePermissions::new(&config.risk.allow, &config.risk.deny);
+            self.midas.risk_free_rate = config.metrics.risk_free_rate;
+            self.midas.set_benchmark(config.metrics.benchmark());
+            self.book_recorder.dir = config.books.path.clone();
+            self.book_recorder.interval = config.books.interval_ms;
+            self.book_recorder.depth = config.books.depth;
+            self.config = config;
         }
     }
 
     fn open_tab(&mut self, midas_index: usize) {
         if let Some(c) = self.midas.get(midas_index) {
-            if c.token.is_pair() {
-                if let Some(samples) = self.midas.get_history(midas_index) {
-                    let mut graph = GraphView::default();
-                    graph.set_strategy(&c.strategy);
-                    graph.set_data(samples);
-                    graph.reset_camera();
-                    self.window_manager.tabs().add(&c.token, midas_index);
-                    self.window_manager.open_chart(midas_index, graph);
-                    self.run_backtest();
+            if let Some(samples) = self.midas.get_history(midas_index) {
+                let mut graph = GraphView::default();
+                graph.clock_offset = self.midas.exchange.clock_offset;
+                graph.set_strategy(&c.strategy);
+                for text in &c.indicators {
+                    let words: Vec<&str> = text.split_whitespace().collect();
+                    match match_indicator_from_text(&words) {
+                        Some(indicator) => graph.add_indicator(&indicator),
+                        None => ERROR!("invalid indicator {:?} of {}", text, c.token.name()),
+                    }
                 }
+                graph.set_data(samples);
+                graph.reset_camera();
+                self.window_manager.tabs().add(&c.token, midas_index);
+                self.window_manager.open_chart(midas_index, graph);
+                self.run_backtest();
             }
         }
     }
 
-    fn add_tab(&mut self, symbol: &str, currency: &str) {
+    fn add_tab(&mut self, symbol: &str, currency: &str, strategy: &Strategy) -> CommandResult {
         let pair = Token::pair(
             String::from(symbol).to_uppercase().as_str(),
             String::from(currency).to_uppercase().as_str(),
         );
 
-        if let Some(index) = self.midas.add_token(&pair) {
-            self.open_tab(index);
+        match self.midas.add_token_with(&pair, strategy) {
+            Some(index) => {
+                self.open_tab(index);
+                Ok(format!("loaded {}", pair.name()))
+            }
+            None => Err(format!("could not load {}", pair.name())),
         }
     }
 
-    fn set_history_size(&mut self, n: usize) {
-        if let Some((midas_index, pair)) = self.window_manager.tabs().current() {
-            if let Some(graph_view) = self.window_manager.chart(midas_index) {
-                let mut time_window = graph_view.time_window.clone();
-                time_window.count = n as i64;
-                match self.midas.market.fetch_last(&pair, &time_window) {
-                    Ok(samples) => {
-                        graph_view.set_data(samples);
-                        self.run_backtest();
-                    }
-                    Err(e) => ERROR!("{:?}", e),
-                }
+    /// load <symbol> [currency = usdt] [counselor...] [resolution [history]]
+    /// Omitted parts of the strategy come from the configured default.
+    fn load(&mut self, words: &[&str]) -> CommandResult {
+        let is_resolution = |word: &str| !matches!(TimeUnit::from_name(word), TimeUnit::Unit(_));
+        let symbol = match words.first() {
+            Some(symbol) => *symbol,
+            None => {
+                return Err(String::from(
+                    "usage: load <symbol> [currency] [counselor...] [resolution [history]]",
+                ))
+            }
+        };
+        let mut rest = &words[1..];
+        let mut currency = "usdt";
+        if let Some(word) = rest.first() {
+            if match_oracle_from_text(rest).is_none() && !is_resolution(word) {
+                currency = word;
+                rest = &rest[1..];
+            }
+        }
+        let mut strategy = self.midas.default_strategy.clone();
+        let resolution_index = rest.iter().position(|w| is_resolution(w));
+        let counselor_words = &rest[..resolution_index.unwrap_or(rest.len())];
+        if !counselor_words.is_empty() {
+            match match_oracle_from_text(counselor_words) {
+                Some(counselor) => strategy.counselors = vec![counselor],
+                None => return Err(format!("invalid counselor {:?}", counselor_words.join(" "))),
             }
         }
+        if let Some(i) = resolution_index {
+            strategy.duration.resolution = TimeUnit::from_name(rest[i]);
+            if let Some(history) = rest.get(i + 1).and_then(|w| w.parse::<i64>().ok()) {
+                strategy.duration.count = history;
+            }
+        }
+        self.add_tab(symbol, currency, &strategy)
     }
 
-    fn set_resolution(&mut self, resolution_name: &str) {
-        if let Some((midas_index, curr_token)) = self.window_manager.tabs().current() {
-            if let Some(c) = self.midas.get(midas_index) {
-                let mut s = c.strategy.clone();
-                s.duration.resolution = TimeUnit::from_name(resolution_name);
-                self.midas.set_strategy(midas_index, &s);
-                if let Some(graph_view) = self.window_manager.chart(midas_index) {
-                    match self.midas.market.get_last(&curr_token, &s.duration) {
-                        Ok(samples) => {
-                            graph_view.set_data(samples);
-                            self.run_backtest();
-                        }
-                        Err(e) => ERROR!("{:?}", e),
-                    }
-                }
+    fn add_stock(&mut self, symbol: &str) -> CommandResult {
+        let token = Token::Symbol(symbol.to_uppercase());
+        match self.midas.add_token(&token) {
+            Some(index) => {
+                self.open_tab(index);
+                Ok(format!("loaded {}", token.name()))
             }
+            None => Err(format!("could not load {}", token.name())),
         }
     }
 
-    fn update_strategy(&mut self, strategy: &Strategy) {
-        if let Some((midas_index, token)) = self.window_manager.tabs().current() {
-            self.midas.set_strategy(midas_index, strategy);
-            if let Some(graph_view) = self.window_manager.chart(midas_index) {
-                match self.midas.market.get_last(&token, &strategy.duration) {
-                    Ok(samples) => {
-                        graph_view.set_data(samples);
-                        self.run_backtest();
-                    }
-                    Err(e) => ERROR!("{:?}", e),
-                }
+    /// Opens another strategy/chart of an already loaded symbol at a different
+    /// resolution, pairs not loaded yet are quoted in usdt.
+    fn add_resolution(&mut self, words: &[&str]) -> CommandResult {
+        if words.len() < 2 {
+            return Err(String::from("usage: LOADRES <symbol> <resolution>"));
+        }
+        let symbol = words[0].to_uppercase();
+        let token = self
+            .midas
+            .hesperides
+            .iter()
+            .map(|c| c.token.clone())
+            .find(|token| match token {
+                Token::Pair((base, _)) => *base == symbol,
+                Token::Symbol(name) => *name == symbol,
+                _ => false,
+            })
+            .unwrap_or_else(|| Token::pair(symbol.as_str(), "USDT"));
+        let resolution = TimeUnit::from_name(words[1]);
+        if let TimeUnit::Unit(_) = resolution {
+            return Err(format!("invalid resolution {:?}", words[1]));
+        }
+        match self.midas.add_resolution(&token, &resolution) {
+            Some(index) => {
+                self.open_tab(index);
+                Ok(format!("loaded {} {}", token.name(), resolution.name()))
+            }
+            None => Err(format!(
+                "could not load {} {}",
+                token.name(),
+                resolution.name()
+            )),
+        }
+    }
+
+    fn add_simulation(&mut self, words: &[&str]) -> CommandResult {
+        let params: Vec<f64> = words
+            .iter()
+            .take(3)
+            .filter_map(|w| w.parse::<f64>().ok())
+            .collect();
+        if params.len() < 3 {
+            return Err(String::from(
+                "usage: SIM <mu> <sigma> <start_price> <resolution>",
+            ));
+        }
+        let resolution = match words.get(3) {
+            Some(name) => TimeUnit::from_name(name),
+            None => TimeUnit::default(),
+        };
+        match self
+            .midas
+            .add_simulation(params[0], params[1], params[2], &resolution)
+        {
+            Some(index) => {
+                self.open_tab(index);
+                Ok(format!(
+                    "simulating {}",
+                    self.midas.hesperides[index].token.name()
+                ))
             }
+            None => Err(String::from("simulation not started")),
+        }
+    }
+
+    /// sandbox <gbm|ou|jump> [speed = 60]: synthetic copy of the current pair
+    /// streamed `speed` simulated minutes per second, paper trading its
+    /// strategy. sandbox speed <speed> changes the speed of the current one.
+    fn sandbox(&mut self, words: &[&str]) -> CommandResult {
+        let usage = "usage: sandbox <gbm|ou|jump> [speed = 60] | sandbox speed <speed>";
+        let (midas_index, token) = self.current()?;
+        let speed = words.get(1).and_then(|w| w.parse::<f64>().ok());
+        if words.first().map(|w| w.to_lowercase()).as_deref() == Some("speed") {
+            return match speed {
+                Some(speed) if self.midas.set_simulation_speed(&token, speed) => Ok(format!(
+                    "{} streams {} minutes per second",
+                    token.name(),
+                    speed
+                )),
+                Some(_) => Err(format!("{} is not a sandbox", token.name())),
+                None => Err(String::from(usage)),
+            };
+        }
+        let model = words
+            .first()
+            .and_then(|w| PriceModel::from_name(w))
+            .ok_or(usage)?;
+        let speed = speed.unwrap_or(SANDBOX_SPEED);
+        match self.midas.add_sandbox(midas_index, model.clone(), speed) {
+            Some(index) => {
+                self.open_tab(index);
+                let sandbox = &self.midas.hesperides[index].token;
+                let state = if self.midas.warming_up().contains(&index) {
+                    "warming up"
+                } else {
+                    "paper trading"
+                };
+                Ok(format!(
+                    "sandbox {} ({}, {}x) {}",
+                    sandbox.name(),
+                    model.name(),
+                    speed,
+                    state
+                ))
+            }
+            None => Err(format!(
+                "no history of {} to start a sandbox from",
+                token.name()
+            )),
+        }
+    }
+
+    fn set_history_size(&mut self, n: usize) -> CommandResult {
+        let (midas_index, pair) = self.current()?;
+        let graph_view = self
+            .window_manager
+            .chart(midas_index)
+            .ok_or("no chart open")?;
+        let mut time_window = graph_view.time_window.clone();
+        time_window.count = n as i64;
+        let samples = self
+            .midas
+            .history_mut(&pair)
+            .fetch_last(&pair, &time_window)
+            .map_err(|e| format!("{:?}", e))?;
+        graph_view.set_data(samples);
+        self.run_backtest();
+        Ok(format!("{} history of {} samples", pair.name(), n))
+    }
+
+    fn set_resolution(&mut self, resolution_name: &str) -> CommandResult {
+        let resolution = TimeUnit::from_name(resolution_name);
+        if let TimeUnit::Unit(_) = resolution {
+            return Err(format!("invalid resolution {:?}", resolution_name));
+        }
+        let (midas_index, _) = self.current()?;
+        let mut strategy = match self.midas.get(midas_index) {
+            Some(c) => c.strategy.clone(),
+            None => return Err(String::from("no pair open")),
+        };
+        strategy.duration.resolution = resolution;
+        self.update_strategy(&strategy)?;
+        Ok(format!("resolution {}", resolution.name()))
+    }
+
+    fn update_strategy(&mut self, strategy: &Strategy) -> CommandResult {
+        let (midas_index, token) = self.current()?;
+        self.midas.set_strategy(midas_index, strategy);
+        if let Some(graph_view) = self.window_manager.chart(midas_index) {
+            let samples = self
+                .midas
+                .history(&token)
+                .get_last(&token, &strategy.duration)
+                .map_err(|e| format!("{:?}", e))?;
+            graph_view.set_data(samples);
+            self.run_backtest();
         }
+        Ok(String::new())
     }
 
     fn open_oracle(&mut self) {
@@ -162,9 +439,156 @@ impl App {
             }
         }
         if let Some(t) = token {
+            if t.is_pair() {
+                self.window_manager.info().update(
+                    self.midas.exchange.server_time,
+                    self.midas.symbols.get(&t),
+                    &self.midas.fees,
+                    &t,
+                );
+            } else {
+                match self.midas.stocks.fetch_info(&t) {
+                    Ok(info) => self.window_manager.info().update_stock(&info, &t),
+                    Err(e) => ERROR!("{:?}", e),
+                }
+            }
+        }
+    }
+
+    /// Report of the current pair over the chart period.
+    fn current_report(&mut self) -> Result<Report, String> {
+        let (midas_index, _) = self.current()?;
+        let time_window = self
+            .window_manager
+            .chart(midas_index)
+            .ok_or("no chart open")?
+            .time_window
+            .clone();
+        self.midas.fetch_benchmark(&time_window);
+        self.midas
+            .report(midas_index, &time_window)
+            .map_err(|e| format!("{:?}", e))
+    }
+
+    /// Keeps the backtest of the current pair as the baseline (A) of the diff view.
+    fn pin_backtest(&mut self) -> CommandResult {
+        let report = self.current_report()?;
+        let message = format!("pinned {}", report.title);
+        self.window_manager.backtest_diff().pin(report);
+        Ok(message)
+    }
+
+    fn open_backtest_diff(&mut self) {
+        match self.current_report() {
+            Ok(report) => self.window_manager.backtest_diff().update(report),
+            Err(e) => ERROR!("{}", e),
+        }
+    }
+
+    fn open_indicators(&mut self) {
+        if let Some(midas_index) = self.window_manager.tabs().current_midas_index() {
+            let indicators: Vec<Indicator> = match self.window_manager.chart(midas_index) {
+                Some(graph_view) => graph_view
+                    .custom_indicators
+                    .indicators
+                    .iter()
+                    .map(|(indicator, _)| indicator.clone())
+                    .collect(),
+                None => Vec::new(),
+            };
             self.window_manager
-                .info()
-                .update(&mut self.midas.exchange, &t);
+                .indicator()
+                .open(midas_index, indicators);
+        }
+    }
+
+    fn update_indicator(&mut self) {
+        let midas_index = self.window_manager.indicator().midas_index();
+        if let Some((index, indicator)) = self.window_manager.indicator().take_edited() {
+            if let Some(graph_view) = self.window_manager.chart(midas_index) {
+                graph_view.set_indicator(index, &indicator);
+            }
+            self.save_indicators(midas_index);
+        }
+    }
+
+    /// Copies the chart indicators to the pair, so they are saved with the state.
+    fn save_indicators(&mut self, midas_index: usize) {
+        let texts: Vec<String> = match self.window_manager.chart(midas_index) {
+            Some(graph_view) => graph_view
+                .custom_indicators
+                .indicators
+                .iter()
+                .map(|(indicator, _)| indicator.to_text())
+                .collect(),
+            None => return,
+        };
+        if let Some(chrysus) = self.midas.hesperides.get_mut(midas_index) {
+            chrysus.indicators = texts;
+        }
+    }
+
+    fn open_correlation(&mut self) {
+        let matrix = self.midas.correlations(CORRELATION_WINDOW);
+        self.window_manager
+            .correlation()
+            .update(matrix, CORRELATION_WINDOW);
+    }
+
+    fn open_seasonality(&mut self) {
+        if let Some(midas_index) = self.window_manager.tabs().current_midas_index() {
+            if let (Some(token), Some(samples)) = (
+                self.midas.get_token(midas_index),
+                self.midas.get_history(midas_index),
+            ) {
+                let s = seasonality(samples);
+                self.window_manager.seasonality().update(&token, s);
+            }
+        }
+    }
+
+    fn open_vol_cone(&mut self) {
+        if let Some(midas_index) = self.window_manager.tabs().current_midas_index() {
+            if let (Some(c), Some(samples)) = (
+                self.midas.get(midas_index),
+                self.midas.get_history(midas_index),
+            ) {
+                let estimator = self.window_manager.vol_cone().estimator();
+                let annualization = annualization_factor(&c.strategy.duration.resolution);
+                let cone = volatility_cone(&estimator, samples, &VOL_CONE_WINDOWS, annualization);
+                self.window_manager
+                    .vol_cone()
+                    .update(&c.token, estimator, cone);
+            }
+        }
+    }
+
+    /// volcone [cc|pk|gk]
+    fn vol_cone(&mut self, estimator: Option<&&str>) -> CommandResult {
+        if let Some(name) = estimator {
+            let estimator =
+                VolatilityEstimator::from_name(name).ok_or("usage: volcone [cc|pk|gk]")?;
+            self.window_manager.vol_cone().set_estimator(estimator);
+        }
+        self.open_vol_cone();
+        self.window_manager.open_float(WindowType::VOL_CONE);
+        Ok(String::new())
+    }
+
+    /// Signals of the forward test of the current pair, or of its last backtest.
+    fn open_signals(&mut self) {
+        if let Some((midas_index, token)) = self.window_manager.tabs().current() {
+            if let Some(forward_test) = self.midas.forward_tests.get(&midas_index) {
+                let records = forward_test.chrysus.signals.last(usize::MAX);
+                self.window_manager
+                    .signals()
+                    .update(&token, "forward", records);
+            } else if let Some(backtest) = self.backtests.get(&midas_index) {
+                let records = backtest.signals.last(usize::MAX);
+                self.window_manager
+                    .signals()
+                    .update(&token, "backtest", records);
+            }
         }
     }
 
@@ -176,7 +600,7 @@ impl App {
         if let Some(token) = self.midas.get_token(midas_index) {
             if let Some(graph_view) = self.window_manager.chart(midas_index) {
                 let time_window = graph_view.time_window.clone();
-                match self.midas.market.get_last(&token, &time_window) {
+                match self.midas.history(&token).get_last(&token, &time_window) {
                     Ok(samples) => {
                         graph_view.set_data(samples);
                     }
@@ -187,22 +611,32 @@ impl App {
     }
 
     pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        self.reload_config();
         self.midas.init(&self.state_file);
+        self.snapshots = SnapshotLog::load(&self.config.snapshots.path);
+        self.window_manager
+            .wallet()
+            .set_history(self.snapshots.totals());
         for midas_index in 0..self.midas.hesperides.len() {
             self.open_tab(midas_index);
         }
+        if let Some(script) = self.script.take() {
+            let result = self.source(&script);
+            self.show_result(result);
+        }
 
         //self.run_command("oracle mean-reversion 10");
         //self.run_command("oracle macd-crossover 12 26 9");
         //self.run_command("oracle macd-zero-cross 12 26 9");
         //self.run_command("oracle ema-cross 50 200");
 
-        let tick_rate = std::time::Duration::from_millis(16);
         let mut last_tick = std::time::Instant::now();
+        let mut last_panels = std::time::Instant::now();
 
         while !self.exit {
             terminal.draw(|frame| self.draw(frame))?;
 
+            let tick_rate = std::time::Duration::from_millis(self.config.cadence.tick_ms.max(1));
             let timeout = tick_rate.saturating_sub(last_tick.elapsed());
             if event::poll(timeout)? {
                 self.handle_events()?;
@@ -214,19 +648,45 @@ impl App {
                     match event {
                         MidasEvent::KLineUpdate(midas_index) => {
                             self.update_graph(midas_index);
+                            self.backtests_stale = true;
+                        }
+                        MidasEvent::Alert(alert) => {
+                            INFO!(
+                                "ALERT {} {:?} {}",
+                                alert.token.name(),
+                                alert.direction,
+                                alert.price
+                            );
+                            self.update_alert_lines();
+                        }
+                        MidasEvent::Rebalance(orders) => {
+                            for order in &orders {
+                                INFO!(
+                                    "rebalance {:?} {:.6} {} @ {}",
+                                    order.side,
+                                    order.quantity,
+                                    order.token.name(),
+                                    order.price
+                                );
+                            }
                         }
+                        // strategies deciding on closed bars are stepped by midas
+                        MidasEvent::BarClosed(_, _) => (),
                         MidasEvent::BookUpdate(token) => {
+                            self.record_book(&token);
                             if let Some((midas_index, current_token)) =
                                 self.window_manager.tabs().current()
                             {
                                 if current_token == token {
                                     if let Some(book) = self.midas.get_book(&token) {
+                                        let book = book.bucketed(self.config.book_bucket_pct);
                                         if let Some(graph_view) =
                                             self.window_manager.chart(midas_index)
                                         {
                                             graph_view.book_w.set_book(&book);
                                         }
                                         self.window_manager.book().update_with(book);
+                                        self.open_ladder();
                                     }
                                 }
                             }
@@ -234,23 +694,265 @@ impl App {
                     };
                 }
 
-                self.window_manager
-                    .wallet()
-                    .update(self.midas.get_balance(), &self.midas.ticks);
+                let panels_rate = std::time::Duration::from_millis(self.config.cadence.panels_ms);
+                if last_panels.elapsed() >= panels_rate {
+                    last_panels = std::time::Instant::now();
+                    self.update_precisions();
+                    self.window_manager
+                        .wallet()
+                        .update(self.midas.get_balance(), &self.midas.ticks);
 
-                self.window_manager
-                    .market()
-                    .update_with(self.midas.ticks.clone());
+                    self.window_manager
+                        .market()
+                        .update_with(self.midas.ticks.clone());
 
-                let midas_index = self.window_manager.tabs().current_midas_index();
-                self.window_manager
-                    .strategy()
-                    .update(&self.midas, &self.backtests, midas_index);
+                    self.open_streams();
+
+                    let sparklines = self.sparklines();
+                    self.window_manager.market().set_sparklines(&sparklines);
+                    self.window_manager.tabs().set_sparklines(&sparklines);
+                    let staleness = (0..self.midas.hesperides.len())
+                        .map(|index| (index, self.midas.bars_behind(index)))
+                        .collect();
+                    self.window_manager.tabs().set_staleness(staleness);
+
+                    let midas_index = self.window_manager.tabs().current_midas_index();
+                    self.window_manager.strategy().update(
+                        &self.midas,
+                        &self.backtests,
+                        midas_index,
+                    );
+                }
+
+                let backtest_rate = self.config.cadence.backtest_s;
+                if self.backtests_stale
+                    && backtest_rate > 0
+                    && self.last_backtest.elapsed().as_secs() >= backtest_rate
+                {
+                    self.run_backtest();
+                }
+
+                self.update_order_lines();
+                self.update_progress();
+                self.update_news();
+                self.update_snapshots();
+                self.reload_config();
             }
         }
+        self.midas.save_cache();
         Ok(())
     }
 
+    /// Downloads the configured feeds, tagging the loaded symbols.
+    fn open_stats(&mut self) {
+        let stats = self.midas.daily_stats();
+        let risk = self.midas.forward_risk();
+        self.window_manager.stats().update(&stats, &risk);
+    }
+
+    /// Display decimals of every symbol, set again when the exchange info grows.
+    fn update_precisions(&mut self) {
+        if self.midas.symbols.len() == self.precision_symbols {
+            return;
+        }
+        self.precision_symbols = self.midas.symbols.len();
+        common::set_precisions(
+            self.midas
+                .symbols
+                .iter()
+                .map(|(token, info)| {
+                    (
+                        token.clone(),
+                        (info.price_decimals(), info.quantity_decimals()),
+                    )
+                })
+                .collect(),
+        );
+    }
+
+    fn open_streams(&mut self) {
+        let streams = self.midas.market.streams.snapshot();
+        let now = Date::now().timestamp_millis();
+        self.window_manager.streams().update(&streams, now);
+    }
+
+    fn open_dca(&mut self) {
+        self.window_manager.dca().update(&self.midas.dca);
+    }
+
+    /// dca <symbol> [currency = usdt] <notional> every <interval> | dca rm <id> | dca
+    fn dca(&mut self, words: &[&str]) -> CommandResult {
+        let usage = "usage: dca <symbol> [currency] <notional> every <interval> | dca rm <id>";
+        let mut message = String::new();
+        if words.first().map(|w| w.to_uppercase()) == Some(String::from("RM")) {
+            match words.get(1).and_then(|w| w.parse::<usize>().ok()) {
+                Some(id) if self.midas.dca.remove(id) => message = format!("dca #{} removed", id),
+                Some(id) => return Err(format!("unknown dca #{}", id)),
+                None => return Err(String::from(usage)),
+            }
+        } else if !words.is_empty() {
+            let (token, rest) = match words.get(1).map(|w| w.parse::<f64>().is_ok()) {
+                Some(true) => (Token::pair(&words[0].to_uppercase(), "USDT"), &words[1..]),
+                Some(false) => (
+                    Token::pair(&words[0].to_uppercase(), &words[1].to_uppercase()),
+                    &words[2..],
+                ),
+                None => return Err(String::from(usage)),
+            };
+            let (notional, interval) = match rest {
+                [notional, every, interval] if every.to_uppercase() == "EVERY" => (
+                    notional.parse::<f64>().unwrap_or_default(),
+                    TimeUnit::from_name(interval),
+                ),
+                _ => (0.0, TimeUnit::Unit(0)),
+            };
+            if notional <= 0.0
+                || matches!(interval, TimeUnit::Unit(_))
+                || interval.num_seconds() <= 0
+            {
+                return Err(String::from(usage));
+            }
+            let id = self
+                .midas
+                .dca
+                .add(&token, notional, interval, Date::now().timestamp());
+            message = format!(
+                "dca #{} {} {} every {}",
+                id,
+                token.name(),
+                notional,
+                interval.name()
+            );
+        }
+        self.open_dca();
+        self.window_manager.open_float(WindowType::DCA);
+        Ok(message)
+    }
+
+    fn open_breakdown(&mut self) {
+        let trips = self.midas.tagged_trips();
+        self.window_manager.breakdown().update(trips);
+    }
+
+    /// breakdown [strategy|counselor|hour|weekday]
+    fn breakdown(&mut self, key: Option<&&str>) -> CommandResult {
+        if let Some(word) = key {
+            let key = BreakdownKey::from_name(word)
+                .ok_or("usage: breakdown [strategy|counselor|hour|weekday]")?;
+            self.window_manager.breakdown().set_key(key);
+        }
+        self.open_breakdown();
+        self.window_manager.open_float(WindowType::BREAKDOWN);
+        Ok(String::new())
+    }
+
+    fn open_news(&mut self) {
+        let mut symbols: Vec<String> = self
+            .midas
+            .hesperides
+            .iter()
+            .map(|c| c.token.symbol().to_string())
+            .collect();
+        symbols.sort();
+        symbols.dedup();
+        self.news.refresh(&self.config.news, &symbols);
+        self.window_manager.news().set_loading();
+    }
+
+    fn update_news(&mut self) {
+        for result in self.news.poll() {
+            match result {
+                Ok(headlines) => self.window_manager.news().update(headlines),
+                Err(e) => ERROR!("news: {:?}", e),
+            }
+        }
+    }
+
+    /// Records an account snapshot when the configured interval elapsed.
+    fn update_snapshots(&mut self) {
+        let interval = (self.config.snapshots.interval_hours * 3600.0) as u64;
+        let now = Date::now().timestamp() as u64;
+        // wait for prices so the valuation is complete
+        if self.midas.get_balance().is_empty()
+            || self.midas.ticks.is_empty()
+            || !self.snapshots.is_due(now, interval)
+        {
+            return;
+        }
+        let snapshot = self.midas.account_snapshot();
+        if let Err(e) = self.snapshots.record(&self.config.snapshots.path, snapshot) {
+            ERROR!("snapshot: {:?}", e);
+        }
+        self.window_manager
+            .wallet()
+            .set_history(self.snapshots.totals());
+    }
+
+    /// Logs the month over month P&L of the account snapshots.
+    fn log_pnl(&mut self) -> CommandResult {
+        let months = self.snapshots.monthly_pnl();
+        if months.is_empty() {
+            return Err(String::from("no account snapshots yet"));
+        }
+        for month in &months {
+            INFO!(
+                "{}  {:.2} -> {:.2}  ({:+.2}%)",
+                month.month,
+                month.start,
+                month.end,
+                month.change_pct
+            );
+        }
+        Ok(format!("{} months of P&L in the log", months.len()))
+    }
+
+    /// Recent price action of the market and loaded tokens with cached 1m closes.
+    fn sparklines(&self) -> HashMap<Token, String> {
+        let tokens = self
+            .midas
+            .ticks
+            .keys()
+            .chain(self.midas.hesperides.iter().map(|c| &c.token));
+        let mut sparklines = HashMap::new();
+        for token in tokens {
+            let closes = self.midas.recent_closes(token, common::SPARKLINE_CLOSES);
+            if closes.len() > 1 {
+                sparklines.insert(token.clone(), sparkline(&closes, common::SPARKLINE_WIDTH));
+            }
+        }
+        sparklines
+    }
+
+    /// Shows running tasks in the command box, finished ones linger for a while.
+    fn update_progress(&mut self) {
+        self.midas.progress.poll();
+        self.midas.progress.clear_finished(PROGRESS_LINGER);
+        let mut status: Vec<String> = Vec::new();
+        if self.midas.locked {
+            status.push(String::from("LOCKED"));
+        }
+        status.extend(
+            self.midas
+                .progress
+                .tasks()
+                .iter()
+                .map(|p| match p.is_done() {
+                    true => format!("{} done", p.name),
+                    false => format!("{} {:.0}% {}", p.name, p.percent, p.message),
+                }),
+        );
+        for index in self.midas.warming_up() {
+            if let Some(token) = self.midas.get_token(*index) {
+                let (cached, required) = self.midas.warmup(*index);
+                status.push(format!("{} warm-up {}/{}", token.name(), cached, required));
+            }
+        }
+        if let Some(clock) = self.midas.clock() {
+            status.push(format!("{} ms, drift {} ms", clock.latency, clock.drift));
+        }
+        self.window_manager.command().set_status(status.join(" | "));
+    }
+
     fn draw(&mut self, frame: &mut Frame) {
         //     0                     1                               2
         //  -------------------------------------------------------------------
@@ -270,10 +972,11 @@ impl App {
         let layout_ab = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]);
 
         // 0-book 1-chart 2-wallet
+        let side = self.config.layout.side_panel.min(45);
         let layout_b_012 = Layout::horizontal([
-            Constraint::Percentage(18),
-            Constraint::Percentage(64),
-            Constraint::Percentage(18),
+            Constraint::Percentage(side),
+            Constraint::Percentage(100 - 2 * side),
+            Constraint::Percentage(side),
         ]);
 
         // a-book b-oracle
@@ -287,7 +990,7 @@ impl App {
         let layout_b_2_abc = Layout::vertical([
             Constraint::Percentage(30),
             Constraint::Min(0),
-            Constraint::Percentage(20),
+            Constraint::Percentage(self.config.layout.log.min(70)),
         ]);
 
         let [symbol_tabs_area, b_area] = layout_ab.areas(frame.area());
@@ -325,17 +1028,45 @@ impl App {
         match event::read()? {
             Event::Key(key_event) => match self.window_manager.handle_key_event(&key_event) {
                 InteractionEvent::Escape => self.exit(),
+                InteractionEvent::Quit(cancel_orders) => self.quit(cancel_orders),
                 InteractionEvent::RunCommand(command) => self.run_command(command.as_str()),
                 InteractionEvent::SymbolSelect(midas_index) => {
                     self.window_manager.select_chart(midas_index)
                 }
                 InteractionEvent::UpdateStrategy => {
-                    self.update_strategy(&self.window_manager.get_oracle())
+                    let result = self.update_strategy(&self.window_manager.get_oracle());
+                    self.show_result(result);
+                }
+                InteractionEvent::UpdateIndicator => self.update_indicator(),
+                InteractionEvent::EditOrder => self.edit_order(),
+                InteractionEvent::AddAlert => {
+                    if let Some((midas_index, _)) = self.window_manager.tabs().current() {
+                        if let Some(price) = self
+                            .window_manager
+                            .chart(midas_index)
+                            .and_then(|g| g.crosshair())
+                        {
+                            let result = self.add_alert(price);
+                            self.show_result(result);
+                        }
+                    }
                 }
                 InteractionEvent::WindowOpen(window_type) => match window_type {
                     WindowType::ORACLE => self.open_oracle(),
                     WindowType::INFO => self.open_info(),
                     WindowType::ORDER => self.open_order(),
+                    WindowType::CORRELATION => self.open_correlation(),
+                    WindowType::SEASONALITY => self.open_seasonality(),
+                    WindowType::SIGNALS => self.open_signals(),
+                    WindowType::INDICATOR => self.open_indicators(),
+                    WindowType::BACKTEST_DIFF => self.open_backtest_diff(),
+                    WindowType::NEWS => self.open_news(),
+                    WindowType::STATS => self.open_stats(),
+                    WindowType::BREAKDOWN => self.open_breakdown(),
+                    WindowType::STREAMS => self.open_streams(),
+                    WindowType::DCA => self.open_dca(),
+                    WindowType::LADDER => self.open_ladder(),
+                    WindowType::VOL_CONE => self.open_vol_cone(),
                     _ => (),
                 },
                 _ => (),
@@ -345,42 +1076,488 @@ impl App {
         Ok(())
     }
 
+    /// Runs `command` and shows its outcome under the command input.
     fn run_command(&mut self, command: &str) {
-        if command.is_empty() {
-            return;
+        let result = self.execute(command);
+        self.show_result(result);
+    }
+
+    fn show_result(&mut self, result: CommandResult) {
+        log_result(&result);
+        self.window_manager.command().set_result(result);
+    }
+
+    /// Current tab, commands acting on the current pair fail without one.
+    fn current(&mut self) -> Result<(usize, Token), String> {
+        self.window_manager
+            .tabs()
+            .current()
+            .ok_or_else(|| String::from("no pair open"))
+    }
+
+    fn execute(&mut self, command: &str) -> CommandResult {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        let name = match words.first() {
+            Some(name) => name.to_lowercase(),
+            None => return Ok(String::new()),
+        };
+        if let Some(commands) = self.config.aliases.get(&name) {
+            return self.run_alias(&commands.clone());
         }
-        let words: Vec<&str> = command.split(' ').collect();
-        match words[0].to_uppercase().as_str() {
-            "LOAD" => self.add_tab(words[1], if words.len() > 2 { words[2] } else { "usdt" }),
-            "GRAPH" => self.add_indicator(&words[1..]),
-            "RES" => self.set_resolution(&words[1]),
+        match name.to_uppercase().as_str() {
+            "LOAD" => self.load(&words[1..]),
+            "LOADRES" => self.add_resolution(&words[1..]),
+            "STOCK" => match words.get(1) {
+                Some(symbol) => self.add_stock(symbol),
+                None => Err(String::from("usage: stock <symbol>")),
+            },
+            "SIM" => self.add_simulation(&words[1..]),
+            "SANDBOX" => self.sandbox(&words[1..]),
+            "GRAPH" => self.graph(&words[1..]),
+            "RENKO" => self.set_renko(&words[1..]),
+            "RES" => match words.get(1) {
+                Some(resolution) => self.set_resolution(resolution),
+                None => Err(String::from("usage: res <resolution>")),
+            },
             "ORACLE" => self.add_oracle(&words[1..]),
-            "SAVE" => self.midas.save_state(&self.state_file),
-            "HIST" => {
-                if let Ok(n) = words[1].parse::<usize>() {
-                    self.set_history_size(n);
+            "SAVE" => match self.midas.save_state(&self.state_file) {
+                Ok(()) => Ok(format!("state saved to {}", self.state_file)),
+                Err(e) => Err(format!("{}: {:?}", self.state_file, e)),
+            },
+            "HIST" => match words.get(1).and_then(|w| w.parse::<usize>().ok()) {
+                Some(n) => self.set_history_size(n),
+                None => Err(String::from("usage: hist <samples>")),
+            },
+            "BACKTEST" => match words.get(1).map(|w| w.to_uppercase()).as_deref() {
+                None => {
+                    self.run_backtest();
+                    Ok(format!("backtested {} charts", self.backtests.len()))
                 }
+                Some("ALL") => self.backtest_all(&words[2..]),
+                Some(_) => Err(String::from("usage: backtest [all [resolution [bars]]]")),
+            },
+            "PREVIEW" => self.preview(),
+            "PIN" => self.pin_backtest(),
+            "ACTIVATE" => self.toggle_active(),
+            "ALLOCATE" => self.allocate(&words[1..]),
+            "REPORT" => self.write_report(words.get(1)),
+            "EXPORT" => self.export(&words[1..]),
+            "IMPORT" => self.import(&words[1..]),
+            "COUNSELOR" => self.toggle_counselor(&words[1..]),
+            "PNL" => self.log_pnl(),
+            "STATS" => {
+                self.open_stats();
+                self.window_manager.open_float(WindowType::STATS);
+                Ok(String::new())
             }
-            "BACKTEST" => self.run_backtest(),
+            "BREAKDOWN" => self.breakdown(words.get(1)),
+            "VOLCONE" => self.vol_cone(words.get(1)),
+            "DCA" => self.dca(&words[1..]),
+            "LADDER" => self.ladder(&words[1..]),
+            "BOOKS" => self.replay_books(&words[1..]),
+            "REBALANCE" => self.set_rebalancer(&words[1..]),
+            "ADJUST" => self.set_price_adjustment(words.get(1)),
             "BUY" => self.create_order(Side::Buy),
             "SELL" => self.create_order(Side::Sell),
-            _ => (),
+            "AMEND" => self.amend_order(&words[1..]),
+            "BRACKET" => self.bracket_order(&words[1..]),
+            "LOCK" => self.lock(),
+            "UNLOCK" => self.unlock(&words[1..]),
+            "BUCKET" => self.set_book_bucket(words.get(1)),
+            "PARTIAL" => self.set_partial_fills(words.get(1)),
+            "TTL" => self.set_order_ttl(words.get(1)),
+            "CLOSED" => self.set_closed_bars(words.get(1)),
+            "HEAT" => self.set_heat(&words[1..]),
+            "TRAIL" => self.set_trailing_stop(&words[1..]),
+            "EXITS" => self.set_atr_exits(&words[1..]),
+            "ALIAS" => self.set_alias(command),
+            "ALERT" => self.alert(&words[1..]),
+            "PERP" => self.set_perpetual(&words[1..]),
+            "LIQ" => self.show_liquidations(&words[1..]),
+            "SOURCE" => match words.get(1) {
+                Some(path) => self.source(path),
+                None => Err(String::from("usage: source <file>")),
+            },
+            _ => Err(format!("unknown command {:?}", words[0])),
+        }
+    }
+
+    /// perp <leverage> [funding.csv] | perp off
+    fn set_perpetual(&mut self, words: &[&str]) -> CommandResult {
+        let (_, token) = self.current()?;
+        match words.first().map(|w| w.to_lowercase()).as_deref() {
+            Some("off") => {
+                self.midas.perpetuals.remove(&token);
+                Ok(format!("{} backtested as spot", token.name()))
+            }
+            Some(leverage) => {
+                let leverage = match leverage.parse::<f64>() {
+                    Ok(leverage) if leverage >= 1.0 => leverage,
+                    _ => return Err(format!("invalid leverage {:?}", leverage)),
+                };
+                let funding = match words.get(1) {
+                    Some(path) => load_funding(path).map_err(|e| format!("{}: {:?}", path, e))?,
+                    None => Vec::new(),
+                };
+                let message = format!(
+                    "{} backtested as perpetual, {}x, {} funding rates",
+                    token.name(),
+                    leverage,
+                    funding.len()
+                );
+                self.midas
+                    .perpetuals
+                    .insert(token, Perpetual::new(leverage, funding));
+                Ok(message)
+            }
+            None => Err(String::from(
+                "usage: perp <leverage> [funding.csv] | perp off",
+            )),
+        }
+    }
+
+    /// liq [leverage...] | liq off: shades estimated liquidation clusters of
+    /// the current perpetual, using volume as a proxy of the open interest.
+    fn show_liquidations(&mut self, words: &[&str]) -> CommandResult {
+        let (midas_index, token) = self.current()?;
+        if words.first().map(|w| w.to_lowercase()).as_deref() == Some("off") {
+            if let Some(graph_view) = self.window_manager.chart(midas_index) {
+                graph_view.liquidations.zones.clear();
+            }
+            return Ok(String::from("liquidation zones off"));
+        }
+        if !self.midas.perpetuals.contains_key(&token) {
+            return Err(format!(
+                "{} is not a perpetual, set it with: perp",
+                token.name()
+            ));
+        }
+        let mut leverages: Vec<f64> = words.iter().filter_map(|w| w.parse().ok()).collect();
+        if leverages.is_empty() {
+            leverages = LIQUIDATION_LEVERAGES.to_vec();
+        }
+        let zones = match self.midas.get_history(midas_index) {
+            Some(samples) => {
+                let volumes: Vec<f64> = samples.iter().map(|s| s.volume as f64).collect();
+                liquidation_zones(samples, &volumes, &leverages, LIQUIDATION_BINS)
+            }
+            None => return Err(format!("no history of {}", token.name())),
+        };
+        let message = format!("{} liquidation zones of {}", zones.len(), token.name());
+        if let Some(graph_view) = self.window_manager.chart(midas_index) {
+            graph_view.liquidations.zones = zones;
+        }
+        Ok(message)
+    }
+
+    /// alert <price> | alert rm <id> | alert (list)
+    fn alert(&mut self, words: &[&str]) -> CommandResult {
+        match words.first().map(|w| w.to_lowercase()).as_deref() {
+            None => {
+                let alerts = self.midas.alerts.all();
+                for alert in alerts {
+                    INFO!(
+                        "alert {} {} {:?} {}",
+                        alert.id,
+                        alert.token.name(),
+                        alert.direction,
+                        alert.price
+                    );
+                }
+                Ok(format!("{} alerts", alerts.len()))
+            }
+            Some("rm") => match words.get(1).and_then(|w| w.parse::<usize>().ok()) {
+                Some(id) => {
+                    if !self.midas.alerts.remove(id) {
+                        return Err(format!("unknown alert {}", id));
+                    }
+                    self.update_alert_lines();
+                    Ok(format!("alert {} removed", id))
+                }
+                None => Err(String::from("usage: alert rm <id>")),
+            },
+            Some(price) => match price.parse::<f64>() {
+                Ok(price) => self.add_alert(price),
+                Err(_) => Err(format!("invalid alert price {:?}", price)),
+            },
+        }
+    }
+
+    /// Registers a price alert on the current token.
+    fn add_alert(&mut self, price: f64) -> CommandResult {
+        let (midas_index, token) = self.current()?;
+        let current = match self.midas.ticks.get(&token) {
+            Some(tick) => tick.price,
+            None => match self.midas.get_history(midas_index).and_then(|s| s.last()) {
+                Some(sample) => sample.close,
+                None => return Err(format!("no price of {} yet", token.name())),
+            },
         };
+        let id = self.midas.alerts.add(&token, price, current);
+        self.update_alert_lines();
+        Ok(format!("alert {} {} at {}", id, token.name(), price))
+    }
+
+    /// Adds the latest book of `token` to the heatmaps of its charts.
+    fn record_book(&mut self, token: &Token) {
+        let book = match self.midas.get_book(token) {
+            Some(book) => book,
+            None => return,
+        };
+        let now = (Date::now().timestamp() * 1000 + self.midas.exchange.clock_offset) as u64;
+        if self.config.books.enabled {
+            if let Err(e) = self.book_recorder.record(now, &book) {
+                ERROR!("book recorder: {:?}", e);
+            }
+        }
+        for midas_index in 0..self.midas.hesperides.len() {
+            if self.midas.hesperides[midas_index].token == *token {
+                if let Some(graph_view) = self.window_manager.chart(midas_index) {
+                    graph_view.heatmap.record(now, &book);
+                }
+            }
+        }
     }
 
-    fn add_indicator(&mut self, words: &[&str]) {
-        if let Some((midas_index, _)) = self.window_manager.tabs().current() {
+    /// `books replay [hours = 1]` shows the recorded books of the current pair
+    /// in its heatmap.
+    fn replay_books(&mut self, words: &[&str]) -> CommandResult {
+        let hours = match (
+            words.first().map(|w| w.to_uppercase()).as_deref(),
+            words.get(1),
+        ) {
+            (Some("REPLAY"), None) => 1.0,
+            (Some("REPLAY"), Some(h)) => h.parse::<f64>().unwrap_or(1.0),
+            _ => return Err(String::from("usage: books replay [hours]")),
+        };
+        let (midas_index, token) = self.current()?;
+        let replay =
+            BookReplay::load(&self.config.books.path, &token).map_err(|e| format!("{:?}", e))?;
+        let to = (Date::now().timestamp() * 1000 + self.midas.exchange.clock_offset) as u64;
+        let from = to.saturating_sub((hours * 3600000.0) as u64);
+        let records = replay.between(from, to);
+        let graph_view = self
+            .window_manager
+            .chart(midas_index)
+            .ok_or("no chart open")?;
+        graph_view.heatmap.replay(records);
+        Ok(format!(
+            "{} recorded books of {}",
+            records.len(),
+            token.name()
+        ))
+    }
+
+    /// Open orders and position entries of each chart.
+    fn update_order_lines(&mut self) {
+        for midas_index in 0..self.midas.hesperides.len() {
+            let chrysus = &self.midas.hesperides[midas_index];
+            let orders: Vec<Order> = self
+                .midas
+                .open_orders
+                .iter()
+                .filter(|o| o.token == chrysus.token)
+                .cloned()
+                .collect();
+            let entries: Vec<(f64, f64)> = chrysus
+                .positions
+                .values()
+                .map(|p| (p.price, p.quantity))
+                .collect();
             if let Some(graph_view) = self.window_manager.chart(midas_index) {
-                ERROR!("add indicator {:?}", midas_index);
-                match match_indicator_from_text(&words) {
-                    Some(indicator) => graph_view.add_indicator(&indicator),
-                    None => (),
-                };
+                graph_view.orders.orders = orders;
+                graph_view.orders.entries = entries;
+            }
+        }
+    }
+
+    /// Shows the pending alerts of each token on its chart.
+    fn update_alert_lines(&mut self) {
+        for midas_index in 0..self.midas.hesperides.len() {
+            let token = self.midas.hesperides[midas_index].token.clone();
+            let prices: Vec<f64> = self
+                .midas
+                .alerts
+                .active(&token)
+                .iter()
+                .map(|a| a.price)
+                .collect();
+            if let Some(graph_view) = self.window_manager.chart(midas_index) {
+                graph_view.alerts.prices = prices;
+            }
+        }
+    }
+
+    fn run_alias(&mut self, commands: &str) -> CommandResult {
+        if self.command_depth >= MAX_COMMAND_DEPTH {
+            return Err(format!("command nesting too deep: {}", commands));
+        }
+        let commands: Vec<&str> = commands.split(';').map(|c| c.trim()).collect();
+        self.run_commands(&commands)
+    }
+
+    /// Runs the commands of a file, one per line. Empty lines and lines
+    /// starting with # are skipped.
+    fn source(&mut self, path: &str) -> CommandResult {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("source {}: {:?}", path, e))?;
+        if self.command_depth >= MAX_COMMAND_DEPTH {
+            return Err(format!("command nesting too deep: {}", path));
+        }
+        INFO!("source {}", path);
+        let commands: Vec<&str> = text
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect();
+        self.run_commands(&commands)
+            .map(|message| format!("source {}: {}", path, message))
+            .map_err(|e| format!("source {}: {}", path, e))
+    }
+
+    /// Runs each of `commands` one level deeper, logging their outcomes. Fails
+    /// when any of them failed, the others are still applied.
+    fn run_commands(&mut self, commands: &[&str]) -> CommandResult {
+        self.command_depth += 1;
+        let mut failed = 0;
+        for command in commands {
+            let result = self.execute(command);
+            log_result(&result);
+            if result.is_err() {
+                failed += 1;
+            }
+        }
+        self.command_depth -= 1;
+        match failed {
+            0 => Ok(format!("{} commands run", commands.len())),
+            _ => Err(format!("{} of {} commands failed", failed, commands.len())),
+        }
+    }
+
+    /// alias <name> = <command>; <command>...  (define)
+    /// alias <name>                            (remove)
+    /// alias                                   (list)
+    fn set_alias(&mut self, command: &str) -> CommandResult {
+        let args = command.trim()[5..].trim();
+        if args.is_empty() {
+            for (name, commands) in &self.config.aliases {
+                INFO!("alias {} = {}", name, commands);
+            }
+            return Ok(format!("{} aliases", self.config.aliases.len()));
+        }
+        let message = match args.split_once('=') {
+            Some((name, commands)) => {
+                let name = name.trim().to_lowercase();
+                if name.is_empty() || name.contains(' ') {
+                    return Err(format!("invalid alias name {:?}", name));
+                }
+                let message = format!("alias {} = {}", name, commands.trim());
+                self.config
+                    .aliases
+                    .insert(name, commands.trim().to_string());
+                message
+            }
+            None => {
+                if self.config.aliases.remove(&args.to_lowercase()).is_none() {
+                    return Err(format!("unknown alias {:?}", args));
+                }
+                format!("alias {} removed", args)
+            }
+        };
+        self.config.save(self.config_watcher.path());
+        Ok(message)
+    }
+
+    /// renko <size> | renko atr <n> | renko off
+    fn set_renko(&mut self, words: &[&str]) -> CommandResult {
+        let (midas_index, _) = self.current()?;
+        let size = match words {
+            [off] if off.eq_ignore_ascii_case("off") => None,
+            words => {
+                Some(BrickSize::from_words(words).ok_or("usage: renko <size> | atr <n> | off")?)
+            }
+        };
+        self.window_manager
+            .chart(midas_index)
+            .ok_or("no chart open")?
+            .set_renko(size);
+        Ok(match size {
+            Some(size) => format!("renko bricks of {}", size.name()),
+            None => String::from("candles"),
+        })
+    }
+
+    /// graph <indicator> <params> | graph list | graph rm <index|name> | graph clear
+    fn graph(&mut self, words: &[&str]) -> CommandResult {
+        match words.first().map(|w| w.to_lowercase()).as_deref() {
+            Some("list") => {
+                let (midas_index, _) = self.current()?;
+                let graph_view = self
+                    .window_manager
+                    .chart(midas_index)
+                    .ok_or("no chart open")?;
+                let indicators = &graph_view.custom_indicators.indicators;
+                for (i, (indicator, _)) in indicators.iter().enumerate() {
+                    INFO!("graph {} {}", i, indicator.to_text());
+                }
+                Ok(format!("{} indicators", indicators.len()))
+            }
+            Some("rm") if words.len() > 1 => {
+                let (midas_index, _) = self.current()?;
+                let graph_view = self
+                    .window_manager
+                    .chart(midas_index)
+                    .ok_or("no chart open")?;
+                let name = words[1..].join(" ");
+                let indicator = graph_view
+                    .remove_indicator(&name)
+                    .ok_or_else(|| format!("unknown indicator {:?}", name))?;
+                self.save_indicators(midas_index);
+                Ok(format!("graph {} removed", indicator.to_text()))
+            }
+            Some("rm") => Err(String::from("usage: graph rm <index|name>")),
+            Some("clear") => {
+                let (midas_index, _) = self.current()?;
+                let graph_view = self
+                    .window_manager
+                    .chart(midas_index)
+                    .ok_or("no chart open")?;
+                let count = graph_view.custom_indicators.indicators.len();
+                graph_view.custom_indicators.clear();
+                self.save_indicators(midas_index);
+                Ok(format!("{} indicators removed", count))
+            }
+            _ => self.add_indicator(words),
+        }
+    }
+
+    fn add_indicator(&mut self, words: &[&str]) -> CommandResult {
+        let (midas_index, _) = self.current()?;
+        let graph_view = self
+            .window_manager
+            .chart(midas_index)
+            .ok_or("no chart open")?;
+        // anchored indicators default to the bar under the crosshair
+        let anchor = graph_view.crosshair_timestamp().map(|t| t.to_string());
+        let mut words = words.to_vec();
+        // retraces the last swing without a range
+        if words.len() == 1 && words[0].eq_ignore_ascii_case("FIB") {
+            words.push("auto");
+        }
+        if words.len() == 1 && ["AVWAP", "CRET"].contains(&words[0].to_uppercase().as_str()) {
+            match &anchor {
+                Some(anchor) => words.push(anchor),
+                None => return Err(format!("{} needs a timestamp or the crosshair", words[0])),
             }
         }
+        let indicator = match_indicator_from_text(&words)
+            .ok_or_else(|| format!("invalid indicator {:?}", words.join(" ")))?;
+        graph_view.add_indicator(&indicator);
+        self.save_indicators(midas_index);
+        Ok(format!("graph {}", words.join(" ")))
     }
 
-    fn add_oracle(&mut self, words: &[&str]) {
+    fn add_oracle(&mut self, _words: &[&str]) -> CommandResult {
         //match match_oracle_from_text(&words) {
         //    Some(oracle) => {
         //        for s in &mut self.strategy_w {
@@ -392,65 +1569,779 @@ impl App {
         //    }
         //    None => (),
         //};
+        Err(String::from(
+            "oracle is not implemented, edit counselors in the oracle window",
+        ))
     }
 
     fn run_backtest(&mut self) {
-        for (midas_index, _) in self.midas.hesperides.iter().enumerate() {
+        self.backtests_stale = false;
+        self.last_backtest = std::time::Instant::now();
+        let n = self.midas.hesperides.len();
+        let reporter = self.midas.progress.task("backtest");
+        for midas_index in 0..n {
+            reporter.step(
+                midas_index,
+                n,
+                &self.midas.hesperides[midas_index].token.name(),
+            );
             if let Some(graph_view) = self.window_manager.chart(midas_index) {
                 let bt = self
                     .midas
                     .run_backtest(midas_index, &graph_view.time_window);
-                graph_view.set_backtest(&bt);
+                graph_view.set_backtest(&bt, &self.midas.hesperides[midas_index].token);
                 self.backtests.insert(midas_index, bt.clone());
             }
         }
+        reporter.finish("");
+    }
+
+    /// backtest all [resolution [bars]]: the strategy of the current pair on
+    /// every loaded symbol, over its own period unless given.
+    fn backtest_all(&mut self, words: &[&str]) -> CommandResult {
+        let usage = "usage: backtest all [resolution [bars]]";
+        let (midas_index, _) = self.current()?;
+        let mut strategy = match self.midas.get(midas_index) {
+            Some(c) => c.strategy.clone(),
+            None => return Err(String::from("no pair open")),
+        };
+        if let Some(name) = words.first() {
+            strategy.duration.resolution = TimeUnit::from_name(name);
+            if let TimeUnit::Unit(_) = strategy.duration.resolution {
+                return Err(String::from(usage));
+            }
+        }
+        if let Some(bars) = words.get(1) {
+            strategy.duration.count = bars.parse::<i64>().ok().filter(|n| *n > 1).ok_or(usage)?;
+        }
+        let period = strategy.duration.clone();
+        let (reports, failed) = self.midas.backtest_all(&strategy, &period);
+        for (token, e) in &failed {
+            ERROR!("backtest all {}: {:?}", token.name(), e);
+        }
+        let subject = format!(
+            "{} {} x {}",
+            strategy.oracle.name(),
+            period.resolution.name(),
+            period.count
+        );
+        self.window_manager
+            .backtest_rank()
+            .update(subject, &reports);
+        self.window_manager.open_float(WindowType::BACKTEST_RANK);
+        match failed.len() {
+            0 => Ok(format!("backtested {} symbols", reports.len())),
+            n => Err(format!(
+                "backtested {} symbols, {} without history",
+                reports.len(),
+                n
+            )),
+        }
+    }
+
+    /// Logs what the current strategy would do right now, without trading.
+    fn preview(&mut self) -> CommandResult {
+        let (midas_index, token) = self.current()?;
+        let (capital, decision, orders) = self
+            .midas
+            .preview(midas_index)
+            .map_err(|e| format!("preview {}: {:?}", token.name(), e))?;
+        let advice = &decision.advice;
+        INFO!(
+            "preview {}: {:?} {:.0}% of {:.2} capital",
+            token.name(),
+            advice.signal,
+            decision.pct * 100.0,
+            capital
+        );
+        if let Some(heat) = self.midas.get(midas_index).map(|c| c.heat_factor()) {
+            if heat < 1.0 {
+                INFO!("  heat keeps {:.0}% of the size", heat * 100.0);
+            }
+        }
+        if advice.stop_loss > 0.0 || advice.take_profit > 0.0 {
+            INFO!(
+                "  stop loss {} take profit {}",
+                advice.stop_loss,
+                advice.take_profit
+            );
+        }
+        if let Some(distance) = advice.trailing {
+            INFO!("  trailing stop {} under the high", distance);
+        }
+        if orders.is_empty() {
+            INFO!("  no orders");
+        }
+        for order in &orders {
+            let risk = match advice.stop_loss {
+                stop if stop > 0.0 => {
+                    format!("risk {:.2}", (order.price - stop).abs() * order.quantity)
+                }
+                _ => String::from("no stop"),
+            };
+            let blocked = match self.midas.check_order(order) {
+                Ok(()) => String::new(),
+                Err(e) => format!(" blocked: {:?}", e),
+            };
+            INFO!(
+                "  {:?} {:?} {:.6} {} @ {} = {:.2}, {}{}",
+                order.side,
+                order.order_type,
+                order.quantity,
+                order.token.name(),
+                order.price,
+                order.quantity * order.price,
+                risk,
+                blocked
+            );
+        }
+        Ok(format!("preview {}: {} orders", token.name(), orders.len()))
+    }
+
+    fn toggle_active(&mut self) -> CommandResult {
+        let (midas_index, token) = self.current()?;
+        if self.midas.toggle_active(midas_index) {
+            Ok(format!("forward test of {} started", token.name()))
+        } else if self.midas.warming_up().contains(&midas_index) {
+            let (cached, required) = self.midas.warmup(midas_index);
+            Ok(format!(
+                "{} warming up, {}/{} samples cached",
+                token.name(),
+                cached,
+                required
+            ))
+        } else {
+            Ok(format!("forward test of {} stopped", token.name()))
+        }
+    }
+
+    fn write_report(&mut self, path: Option<&&str>) -> CommandResult {
+        let (midas_index, token) = self.current()?;
+        let graph_view = self
+            .window_manager
+            .chart(midas_index)
+            .ok_or("no chart open")?;
+        let path = match path {
+            Some(p) => p.to_string(),
+            None => format!("report_{}.html", token.get_symbol().to_lowercase()),
+        };
+        self.midas
+            .write_report(midas_index, &graph_view.time_window, &path)
+            .map_err(|e| format!("{:?}", e))?;
+        Ok(format!("report written to {}", path))
+    }
+
+    /// export portfolio <path> | export strategy <path> | export trades <path>
+    fn export(&mut self, words: &[&str]) -> CommandResult {
+        match (
+            words.first().map(|w| w.to_uppercase()).as_deref(),
+            words.get(1),
+        ) {
+            (Some("PORTFOLIO"), Some(path)) => match self.midas.export_portfolio(path) {
+                Ok(()) => Ok(format!("portfolio exported to {}", path)),
+                Err(e) => Err(format!("{:?}", e)),
+            },
+            (Some("STRATEGY"), Some(path)) => {
+                let (midas_index, token) = self.current()?;
+                match self.midas.hesperides[midas_index].strategy.save(path) {
+                    Ok(()) => Ok(format!("strategy of {} exported to {}", token.name(), path)),
+                    Err(e) => Err(format!("{:?}", e)),
+                }
+            }
+            (Some("TRADES"), Some(path)) => match self.midas.export_breakdown(path) {
+                Ok(()) => Ok(format!("trade breakdown exported to {}", path)),
+                Err(e) => Err(format!("{:?}", e)),
+            },
+            _ => Err(String::from(
+                "usage: export portfolio|strategy|trades <path>",
+            )),
+        }
+    }
+
+    /// import strategy <path>: replaces the strategy of the current tab.
+    fn import(&mut self, words: &[&str]) -> CommandResult {
+        match (
+            words.first().map(|w| w.to_uppercase()).as_deref(),
+            words.get(1),
+        ) {
+            (Some("STRATEGY"), Some(path)) => {
+                let strategy = Strategy::load(path).map_err(|e| format!("{:?}", e))?;
+                let (midas_index, _) = self.current()?;
+                if let Some(graph_view) = self.window_manager.chart(midas_index) {
+                    graph_view.set_strategy(&strategy);
+                }
+                self.update_strategy(&strategy)?;
+                Ok(format!("strategy imported from {}", path))
+            }
+            _ => Err(String::from("usage: import strategy <path>")),
+        }
+    }
+
+    /// `counselor <n> on|off`, n counts from 1 in the oracle window order.
+    fn toggle_counselor(&mut self, words: &[&str]) -> CommandResult {
+        let enabled = match words.get(1).map(|w| w.to_uppercase()).as_deref() {
+            Some("ON") => Some(true),
+            Some("OFF") => Some(false),
+            _ => None,
+        };
+        let index = words.first().and_then(|w| w.parse::<usize>().ok());
+        let (index, enabled) = match (index, enabled) {
+            (Some(n), Some(enabled)) if n > 0 => (n - 1, enabled),
+            _ => return Err(String::from("usage: counselor <n> on|off")),
+        };
+        let (midas_index, _) = self.current()?;
+        let mut strategy = match self.midas.get(midas_index) {
+            Some(c) => c.strategy.clone(),
+            None => return Err(String::from("no pair open")),
+        };
+        strategy
+            .set_enabled(index, enabled)
+            .map_err(|_| format!("no counselor {}", index + 1))?;
+        self.update_strategy(&strategy)?;
+        Ok(format!(
+            "counselor {} {}",
+            index + 1,
+            if enabled { "on" } else { "off" }
+        ))
+    }
+
+    /// Stops every order placement until `unlock <phrase>`.
+    fn lock(&mut self) -> CommandResult {
+        self.midas.locked = true;
+        Ok(format!(
+            "session locked, unlock with: unlock {}",
+            self.config.risk.unlock_phrase
+        ))
+    }
+
+    fn unlock(&mut self, words: &[&str]) -> CommandResult {
+        if !self.midas.locked {
+            return Err(String::from("session is not locked"));
+        }
+        if words.join(" ").trim().to_lowercase() != self.config.risk.unlock_phrase.to_lowercase() {
+            return Err(String::from("wrong unlock phrase"));
+        }
+        self.midas.locked = false;
+        Ok(String::from("session unlocked"))
+    }
+
+    /// bucket <pct> | bucket off
+    fn set_book_bucket(&mut self, word: Option<&&str>) -> CommandResult {
+        let pct = match word.map(|w| w.to_lowercase()).as_deref() {
+            Some("off") => 0.0,
+            text => match text.and_then(|t| t.trim_end_matches('%').parse::<f64>().ok()) {
+                Some(pct) if pct >= 0.0 => pct,
+                _ => return Err(String::from("usage: bucket <pct> | bucket off")),
+            },
+        };
+        self.config.book_bucket_pct = pct;
+        Ok(format!("book buckets: {}%", pct))
+    }
+
+    /// partial <leave|amend|cancel>
+    fn set_partial_fills(&mut self, word: Option<&&str>) -> CommandResult {
+        let (midas_index, _) = self.current()?;
+        let policy = word
+            .and_then(|w| PartialFillPolicy::from_name(w))
+            .ok_or("usage: partial leave|amend|cancel")?;
+        self.midas.set_partial_fills(midas_index, policy);
+        Ok(format!("partial fills: {}", policy.name()))
+    }
+
+    /// ttl <3b | 90s | 15m> | ttl off
+    fn set_order_ttl(&mut self, word: Option<&&str>) -> CommandResult {
+        let (midas_index, _) = self.current()?;
+        let ttl = match word.map(|w| w.to_lowercase()).as_deref() {
+            Some("off") => None,
+            text => match text.and_then(OrderTtl::from_text) {
+                Some(ttl) => Some(ttl),
+                None => {
+                    return Err(String::from(
+                        "usage: ttl <bars>b | <duration> (90s, 15m) | off",
+                    ))
+                }
+            },
+        };
+        self.midas.set_order_ttl(midas_index, ttl);
+        Ok(format!(
+            "order ttl: {}",
+            ttl.map(|t| t.name()).unwrap_or(String::from("off"))
+        ))
+    }
+
+    /// closed on|off
+    fn set_closed_bars(&mut self, word: Option<&&str>) -> CommandResult {
+        let (midas_index, _) = self.current()?;
+        let closed_bars = match word.map(|w| w.to_lowercase()).as_deref() {
+            Some("on") => true,
+            Some("off") => false,
+            _ => return Err(String::from("usage: closed on|off")),
+        };
+        self.midas.set_closed_bars(midas_index, closed_bars);
+        Ok(format!(
+            "decisions on {}",
+            if closed_bars {
+                "closed bars"
+            } else {
+                "every kline update"
+            }
+        ))
+    }
+
+    /// heat <step%> [floor%] | heat off
+    fn set_heat(&mut self, words: &[&str]) -> CommandResult {
+        let (midas_index, _) = self.current()?;
+        let usage = "usage: heat <step%> [floor%] | off";
+        let heat = match words {
+            [word] if word.eq_ignore_ascii_case("off") => None,
+            [step] | [step, _] => {
+                let pct = |word: &str| {
+                    word.trim_end_matches('%')
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|v| (0.0..=100.0).contains(v))
+                        .map(|v| v / 100.0)
+                };
+                let step = pct(step).filter(|s| *s > 0.0).ok_or(usage)?;
+                let floor = match words.get(1) {
+                    Some(word) => pct(word).ok_or(usage)?,
+                    None => 0.0,
+                };
+                Some(Heat { step, floor })
+            }
+            _ => return Err(String::from(usage)),
+        };
+        self.midas.set_heat(midas_index, heat);
+        Ok(match heat {
+            Some(heat) => format!(
+                "heat: -{:.0}% per loss, floor {:.0}%",
+                heat.step * 100.0,
+                heat.floor * 100.0
+            ),
+            None => String::from("heat off"),
+        })
+    }
+
+    /// exits <stop atrs> <take atrs> [period = 14] | off
+    fn set_atr_exits(&mut self, words: &[&str]) -> CommandResult {
+        let (midas_index, _) = self.current()?;
+        let usage = "usage: exits <stop atrs> <take atrs> [period = 14] | off";
+        let exits = match words {
+            [word] if word.eq_ignore_ascii_case("off") => None,
+            [stop, take] | [stop, take, _] => {
+                let multiple = |word: &str| word.parse::<f64>().ok().filter(|m| *m > 0.0);
+                let period = match words.get(2) {
+                    Some(word) => word.parse::<usize>().ok().filter(|n| *n > 0).ok_or(usage)?,
+                    None => ATR_EXITS_PERIOD,
+                };
+                Some(AtrExits {
+                    period,
+                    stop_loss: multiple(stop).ok_or(usage)?,
+                    take_profit: multiple(take).ok_or(usage)?,
+                })
+            }
+            _ => return Err(String::from(usage)),
+        };
+        self.midas.set_atr_exits(midas_index, exits);
+        Ok(match exits {
+            Some(exits) => format!(
+                "exits: stop {} atr, take {} atr (atr {})",
+                exits.stop_loss, exits.take_profit, exits.period
+            ),
+            None => String::from("exits from the counselors"),
+        })
+    }
+
+    /// trail <2%|1.5atr> | off
+    fn set_trailing_stop(&mut self, words: &[&str]) -> CommandResult {
+        let (midas_index, _) = self.current()?;
+        let offset = match words {
+            [word] if word.eq_ignore_ascii_case("off") => None,
+            [word] => match BracketOffset::from_text(word) {
+                Some(offset) => Some(offset),
+                None => return Err(format!("invalid trailing distance {:?}", word)),
+            },
+            _ => return Err(String::from("usage: trail <2%|1.5atr> | off")),
+        };
+        self.midas.set_trailing_stop(midas_index, offset);
+        Ok(match offset {
+            Some(offset) => format!("buys trail a stop {} under their high", offset.name()),
+            None => String::from("trailing stop off"),
+        })
+    }
+
+    fn allocate(&mut self, words: &[&str]) -> CommandResult {
+        let risk_aversion = match words.first() {
+            Some(w) => w.parse::<f64>().unwrap_or(ALLOCATION_RISK_AVERSION),
+            None => ALLOCATION_RISK_AVERSION,
+        };
+        let (allocation, orders) = self.midas.allocation(CORRELATION_WINDOW, risk_aversion);
+        for (i, token) in allocation.tokens.iter().enumerate() {
+            INFO!(
+                "{} weight {:.2}% mean return {:.4}%",
+                token.name(),
+                allocation.weights[i] * 100.0,
+                allocation.expected_returns[i] * 100.0
+            );
+        }
+        for order in &orders {
+            INFO!(
+                "rebalance {:?} {:.6} {} @ {}",
+                order.side,
+                order.quantity,
+                order.token.name(),
+                order.price
+            );
+        }
+        Ok(format!(
+            "allocation of {} tokens, {} rebalance orders",
+            allocation.tokens.len(),
+            orders.len()
+        ))
+    }
+
+    fn set_rebalancer(&mut self, words: &[&str]) -> CommandResult {
+        if words.first().map(|w| w.to_uppercase()) == Some(String::from("OFF")) {
+            self.midas.rebalancer = None;
+            return Ok(String::from("rebalancing off"));
+        }
+        let rebalancer = Rebalancer::from_text(words)
+            .ok_or_else(|| format!("invalid rebalance targets {:?}", words))?;
+        let message = format!("rebalancing {}", rebalancer.name());
+        self.midas.rebalancer = Some(rebalancer);
+        Ok(message)
+    }
+
+    fn set_price_adjustment(&mut self, name: Option<&&str>) -> CommandResult {
+        let adjustment = name
+            .and_then(|n| PriceAdjustment::from_name(n))
+            .ok_or("usage: ADJUST <raw|adjusted>")?;
+        for midas_index in self.midas.set_price_adjustment(adjustment) {
+            self.update_graph(midas_index);
+        }
+        self.run_backtest();
+        Ok(format!("stock prices {}", adjustment.name()))
+    }
+
+    fn create_order(&mut self, signal: Side) -> CommandResult {
+        let (_, token) = self.current()?;
+        if signal == Side::Sell {
+            return Err(String::from(
+                "sell is not supported, use: ladder sell | bracket sell",
+            ));
+        }
+        let token_info = match self.midas.symbols.get(&token) {
+            Some(info) => info.clone(),
+            None => return Err(format!("no exchange info for {} yet", token.name())),
+        };
+        let quote = self
+            .midas
+            .get_book(&token)
+            .and_then(|book| book.quote())
+            .ok_or_else(|| format!("no quote of {} yet", token.name()))?;
+        let price = token_info.round_price(quote.ask.unwrap_or(0.0));
+        let shares = token_info.round_quantity(self.config.risk.max_order_value / price);
+        token_info
+            .check_order(price, shares)
+            .map_err(|e| format!("{:?}", e))?;
+        let order = Order {
+            index: 0,
+            position_index: None,
+            id: None,
+            token: quote.token.clone(),
+            date: Date::now(),
+            quantity: shares,
+            side: Side::Buy,
+            price,
+            stop_price: None,
+            order_type: OrderType::Limit,
+            tif: TimeInForce::default(),
+        };
+        self.midas
+            .check_order(&order)
+            .map_err(|e| format!("{:?}", e))?;
+        self.midas
+            .wallet
+            .buy_order(&order)
+            .map_err(|e| format!("buy: {:?}", e))?;
+        Ok(format!(
+            "buy {} {} @ {}",
+            common::format_quantity(&token, shares),
+            token.name(),
+            common::format_price(&token, price)
+        ))
+    }
+
+    /// Amends the order submitted through the form of the orders window.
+    fn edit_order(&mut self) {
+        if let Some((id, price, quantity)) = self.window_manager.order().take_amend() {
+            let result = self.amend(id, price, Some(quantity));
+            self.show_result(result);
+        }
+    }
+
+    /// amend <order id> <price> [quantity]: cancels a resting order and places
+    /// it again with the new price, keeping the unfilled quantity by default.
+    fn amend_order(&mut self, words: &[&str]) -> CommandResult {
+        let (id, price) = match (
+            words.first().and_then(|w| w.parse::<u64>().ok()),
+            words.get(1).and_then(|w| w.parse::<f64>().ok()),
+        ) {
+            (Some(id), Some(price)) => (id, price),
+            _ => return Err(String::from("usage: amend <order id> <price> [quantity]")),
+        };
+        self.amend(id, price, words.get(2).and_then(|w| w.parse::<f64>().ok()))
+    }
+
+    /// Cancels the resting order `id` and places it again at `price`, with
+    /// `quantity` or else its unfilled quantity.
+    fn amend(&mut self, id: u64, price: f64, quantity: Option<f64>) -> CommandResult {
+        let status = self
+            .midas
+            .wallet
+            .get_all_open_orders()
+            .map_err(|e| format!("{:?}", e))?
+            .into_iter()
+            .find(|o| o.order.id == Some(id as i64))
+            .ok_or_else(|| format!("no open order {}", id))?;
+        let mut order = status.order.clone();
+        order.id = None;
+        order.date = Date::now();
+        order.price = price;
+        order.quantity = quantity.unwrap_or(status.order.quantity - status.executed_qty);
+        if let Some(info) = self.midas.symbols.get(&order.token) {
+            order.price = info.round_price(order.price);
+            order.quantity = info.round_quantity(order.quantity);
+            info.check_order(order.price, order.quantity)
+                .map_err(|e| format!("amend {}: {:?}", id, e))?;
+        }
+        self.midas
+            .check_order(&order)
+            .map_err(|e| format!("amend {}: {:?}", id, e))?;
+        let result = match self.midas.wallet.replace_order(id, &order) {
+            Ok(new_id) => Ok(format!("order {} replaced by {}", id, new_id)),
+            Err(e) => Err(format!("amend {}: {:?}", id, e)),
+        };
+        self.midas.refresh_open_orders();
+        self.open_order();
+        result
     }
 
-    fn create_order(&mut self, signal: Side) {
+    /// bracket <buy|sell> <quantity> <price> [stop] [take]: limit entry on the
+    /// current pair with a stop loss and a take profit, as "2%" or "1.5atr".
+    fn bracket_order(&mut self, words: &[&str]) -> CommandResult {
+        let usage = "usage: bracket <buy|sell> <quantity> <price> [stop] [take]";
+        let side = match words.first().map(|w| w.to_lowercase()).as_deref() {
+            Some("buy") => Side::Buy,
+            Some("sell") => Side::Sell,
+            _ => return Err(String::from(usage)),
+        };
+        let (quantity, price) = match (
+            words.get(1).and_then(|w| w.parse::<f64>().ok()),
+            words.get(2).and_then(|w| w.parse::<f64>().ok()),
+        ) {
+            (Some(quantity), Some(price)) => (quantity, price),
+            _ => return Err(String::from(usage)),
+        };
+        let risk = &self.config.risk;
+        let stop = words.get(3).copied().unwrap_or(risk.bracket_stop.as_str());
+        let take = words.get(4).copied().unwrap_or(risk.bracket_take.as_str());
+        let template = match (
+            BracketOffset::from_text(stop),
+            BracketOffset::from_text(take),
+        ) {
+            (Some(stop_loss), Some(take_profit)) => BracketTemplate {
+                stop_loss,
+                take_profit,
+            },
+            _ => return Err(String::from(usage)),
+        };
+        let (midas_index, _) = self.current()?;
+        let token = self.midas.hesperides[midas_index].token.clone();
+        let atr = self
+            .midas
+            .get_history(midas_index)
+            .and_then(|samples| last_atr(BRACKET_ATR_PERIOD, samples));
+        let mut entry = Order {
+            index: 0,
+            position_index: None,
+            id: None,
+            token: token.clone(),
+            date: Date::now(),
+            quantity,
+            side,
+            price,
+            stop_price: None,
+            order_type: OrderType::Limit,
+            tif: TimeInForce::default(),
+        };
+        if let Some(info) = self.midas.symbols.get(&token) {
+            entry.price = info.round_price(entry.price);
+            entry.quantity = info.round_quantity(entry.quantity);
+        }
+        let mut bracket =
+            Bracket::from_template(entry, &template, atr).map_err(|e| format!("{:?}", e))?;
+        if let Some(info) = self.midas.symbols.get(&token) {
+            bracket.stop_loss = info.round_price(bracket.stop_loss);
+            bracket.take_profit = info.round_price(bracket.take_profit);
+        }
+        let (stop_loss, take_profit) = (bracket.stop_loss, bracket.take_profit);
+        match self.midas.submit_bracket(bracket) {
+            Ok(()) => Ok(format!(
+                "bracket {} {:.6} @ {} stop {} take {}",
+                token.name(),
+                quantity,
+                price,
+                stop_loss,
+                take_profit
+            )),
+            Err(e) => Err(format!("bracket: {:?}", e)),
+        }
+    }
+
+    fn open_ladder(&mut self) {
         if let Some((_, token)) = self.window_manager.tabs().current() {
-            // get token info
-            let token_info = self.midas.exchange.get(&token);
+            let tick = self
+                .midas
+                .symbols
+                .get(&token)
+                .map(|info| info.tick_size)
+                .unwrap_or(0.0);
             if let Some(book) = self.midas.get_book(&token) {
-                if let Some(quote) = book.quote() {
-                    // get symbol info
-                    let price = quote.ask.unwrap_or(0.0);
-                    // consider 1 dollar
-                    let shares = 10.0 / price;
-                    if shares < token_info.lot_min_qty {
-                        let cost = price * token_info.lot_min_qty;
-                        ERROR!("min cost is: {}", cost);
-                    }
-                    match signal {
-                        Side::Buy => {
-                            let order = Order {
-                                index: 0,
-                                position_index: None,
-                                id: None,
-                                token: quote.token.clone(),
-                                date: Date::now(),
-                                quantity: (shares * 100.0).round() / 100.0,
-                                side: Side::Buy,
-                                price,
-                                stop_price: None,
-                                order_type: OrderType::Limit,
-                                tif: TimeInForce::default(),
-                            };
-                            ERROR!("{:?}", order);
-                            ERROR!("{:?}", self.midas.wallet.buy_order(&order));
-                        }
-                        Side::Sell => (),
-                        _ => (),
-                    }
-                }
+                self.window_manager
+                    .ladder()
+                    .update(&book, &self.midas.open_orders, tick);
             }
         }
     }
 
+    /// ladder buy|sell <price> [quantity] | ladder cancel <price>|all: limit
+    /// orders of the current pair at a ladder rung, sized by the risk max order
+    /// value by default.
+    fn ladder(&mut self, words: &[&str]) -> CommandResult {
+        let usage = "usage: ladder buy|sell <price> [quantity] | ladder cancel <price>|all";
+        let (_, token) = self.current()?;
+        let action = words.first().map(|w| w.to_lowercase());
+        let result = if action.as_deref() == Some("cancel") {
+            let step = self.window_manager.ladder().step();
+            let orders: Vec<Order> = match words.get(1).copied() {
+                Some("all") => self
+                    .midas
+                    .open_orders
+                    .iter()
+                    .filter(|o| o.token == token)
+                    .cloned()
+                    .collect(),
+                Some(price) => match price.parse::<f64>() {
+                    Ok(price) => self
+                        .midas
+                        .open_orders
+                        .iter()
+                        .filter(|o| o.token == token && (o.price - price).abs() <= step / 2.0)
+                        .cloned()
+                        .collect(),
+                    Err(_) => return Err(String::from(usage)),
+                },
+                None => return Err(String::from(usage)),
+            };
+            let cancelled = self.midas.cancel_orders(&orders);
+            if cancelled == orders.len() {
+                Ok(format!("cancelled {} orders", cancelled))
+            } else {
+                Err(format!(
+                    "cancelled {} of {} orders",
+                    cancelled,
+                    orders.len()
+                ))
+            }
+        } else {
+            let side = match action.as_deref() {
+                Some("buy") => Side::Buy,
+                Some("sell") => Side::Sell,
+                _ => return Err(String::from(usage)),
+            };
+            let price = match words.get(1).and_then(|w| w.parse::<f64>().ok()) {
+                Some(price) if price > 0.0 => price,
+                _ => return Err(String::from(usage)),
+            };
+            let info = match self.midas.symbols.get(&token) {
+                Some(info) => info.clone(),
+                None => return Err(format!("no exchange info for {} yet", token.name())),
+            };
+            let price = info.round_price(price);
+            let quantity =
+                info.round_quantity(match words.get(2).and_then(|w| w.parse::<f64>().ok()) {
+                    Some(quantity) => quantity,
+                    None => self.config.risk.max_order_value / price,
+                });
+            info.check_order(price, quantity)
+                .map_err(|e| format!("{:?}", e))?;
+            let order = Order {
+                index: 0,
+                position_index: None,
+                id: None,
+                token: token.clone(),
+                date: Date::now(),
+                quantity,
+                side,
+                price,
+                stop_price: None,
+                order_type: OrderType::Limit,
+                tif: TimeInForce::default(),
+            };
+            self.midas
+                .check_order(&order)
+                .map_err(|e| format!("{:?}", e))?;
+            let result = match side {
+                Side::Buy => self.midas.wallet.buy_order(&order),
+                Side::Sell => self.midas.wallet.sell_order(&order),
+            };
+            match result {
+                Ok(_) => Ok(format!(
+                    "ladder {:?} {} {} @ {}",
+                    side,
+                    common::format_quantity(&token, quantity),
+                    token.name(),
+                    common::format_price(&token, price)
+                )),
+                Err(e) => Err(format!("ladder: {:?}", e)),
+            }
+        };
+        self.midas.refresh_open_orders();
+        self.open_ladder();
+        result
+    }
+
+    /// Asks before leaving open orders or positions behind.
     fn exit(&mut self) {
+        let summary = self.midas.exit_summary();
+        if summary.is_empty() {
+            self.exit = true;
+            return;
+        }
+        self.window_manager.shutdown().update(&summary);
+        self.window_manager.open_float(WindowType::SHUTDOWN);
+    }
+
+    fn quit(&mut self, cancel_orders: bool) {
+        if cancel_orders {
+            let orders = std::mem::take(&mut self.window_manager.shutdown().orders);
+            let cancelled = self.midas.cancel_orders(&orders);
+            INFO!("cancelled {} of {} open orders", cancelled, orders.len());
+        }
         self.exit = true;
     }
 }
 
+/// Logs the message of a command outcome, errors as errors.
+fn log_result(result: &CommandResult) {
+    match result {
+        Ok(message) if !message.is_empty() => INFO!("{}", message),
+        Ok(_) => (),
+        Err(message) => ERROR!("{}", message),
+    }
+}
+
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
@@ -461,14 +2352,76 @@ struct Args {
     /// Number of times to greet
     #[arg(short, long, default_value_t = false)]
     test: bool,
+
+    /// Config file (theme, key bindings, risk limits, layout), reloaded on change
+    #[arg(long, default_value = "config.json")]
+    config: String,
+
+    /// File with midas commands, one per line, executed at startup
+    #[arg(long)]
+    script: Option<String>,
+
+    /// Write backtest reports (.html or .md) of the saved pairs and exit without the TUI
+    #[arg(long)]
+    report: Option<String>,
+}
+
+/// Backtests every pair of the state file over its strategy duration. With
+/// more than one pair, the symbol is appended to the file name.
+fn headless_report(args: &Args, path: &str) {
+    let mut midas = Midas::new(args.keys.as_str(), args.test);
+    if let Some(config) = Config::from_file(&args.config) {
+        midas.risk_free_rate = config.metrics.risk_free_rate;
+        midas.set_benchmark(config.metrics.benchmark());
+    }
+    if let Err(e) = midas.load_state(&String::from(STATE_FILE)) {
+        eprintln!("{} {:?}", STATE_FILE, e);
+        return;
+    }
+    let count = midas.hesperides.len();
+    for index in 0..count {
+        let chrysus = &midas.hesperides[index];
+        let file = if count > 1 {
+            match path.rsplit_once('.') {
+                Some((stem, ext)) => {
+                    format!(
+                        "{}_{}.{}",
+                        stem,
+                        chrysus.token.get_symbol().to_lowercase(),
+                        ext
+                    )
+                }
+                None => format!("{}_{}", path, chrysus.token.get_symbol().to_lowercase()),
+            }
+        } else {
+            path.to_string()
+        };
+        let period = chrysus.strategy.duration.clone();
+        match midas.write_report(index, &period, &file) {
+            Ok(()) => println!("{}", file),
+            Err(e) => eprintln!("{} {:?}", chrysus.token.name(), e),
+        }
+    }
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
+    if let Some(path) = &args.report {
+        headless_report(&args, path);
+        return Ok(());
+    }
+    if !std::path::Path::new(STATE_FILE).exists() && io::stdin().is_terminal() {
+        setup::first_run(
+            STATE_FILE,
+            &Config::from_file(&args.config).unwrap_or_default(),
+        );
+    }
     let _guard = w_log::init();
     color_eyre::install()?;
     let mut terminal = ratatui::init();
-    let app_result = App::new(args.keys.as_str(), args.test).run(&mut terminal);
+    let mut app = App::new(args.keys.as_str(), args.test, args.config.as_str());
+    app.script = args.script.clone();
+    let app_result = app.run(&mut terminal);
     ratatui::restore();
     Ok(app_result?)
 }
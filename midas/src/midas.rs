@@ -1,78 +1,615 @@
-use slog::slog_error;
+use slog::{slog_error, slog_info};
 use std::collections::HashMap;
 use std::fs::File;
 
 use dionysus::{
-    backtest::{backtest, Backtest},
-    binance::{BinanceExchange, BinanceMarket},
-    counselor::Counselor,
-    finance::{Book, MarketEvent, MarketTick, Order, Sample, Token},
+    alerts::{Alerts, PriceAlert},
+    allocation::{allocate, rebalance_orders, Allocation, Rebalancer},
+    analytics::{
+        annualization_factor, correlation_matrix, risk_metrics, volatility, CorrelationMatrix,
+        RiskMetrics, VolatilityEstimator,
+    },
+    backtest::{backtest_with_settings, match_round_trips, Backtest, BacktestSettings},
+    bar_clock::BarClock,
+    binance::{
+        depth_stream, kline_stream, BinanceExchange, BinanceMarket, ClockMonitor, ClockSample,
+    },
+    bracket::{Bracket, BracketOffset, BracketTracker},
+    brownian::{BrownianMotionMarket, PriceModel},
+    cache::Cache,
+    counselor::{AtrExits, Counselor},
+    dca::{DcaPurchase, DcaScheduler},
+    finance::{
+        Book, BookLine, DiError, Fill, MarketEvent, MarketTick, Order, Position, Quote, Sample,
+        Side, Token,
+    },
+    forward::ForwardTest,
+    futures::Perpetual,
     historical_data::HistoricalData,
-    strategy::{Chrysus, Strategy},
-    time::TimeWindow,
-    wallet::{BinanceWallet, DigitalWallet},
-    ERROR,
+    progress::ProgressHub,
+    refresher::{bars_behind, DataRefresher},
+    report::Report,
+    risk::TradePermissions,
+    snapshots::{AccountSnapshot, AssetSnapshot},
+    stats::{breakdown_csv, TaggedTrip, TradeStats},
+    strategy::{Chrysus, Decision, Heat, OrderTtl, PartialFillPolicy, Strategy},
+    stream_stats::{kline_idle_limit, StreamStats, BOOK_IDLE_LIMIT},
+    symbols::SymbolRegistry,
+    time::{Date, TimeUnit, TimeWindow},
+    trader::Trader,
+    wallet::{BalanceMonitor, BinanceWallet, DigitalWallet, FeeSchedule},
+    yahoo::{PriceAdjustment, YahooMarket},
+    ERROR, INFO,
 };
 
+/// Snapshot of the Binance market cache, restored on launch.
+const BINANCE_CACHE_FILE: &str = "cache_binance.json";
+
+/// Pair (symbol, currency) of the state created on the first run.
+pub const STARTER_PAIR: (&str, &str) = ("BTC", "USDT");
+
+/// Paper capital of forward tests, same as backtests.
+const FORWARD_TEST_CAPITAL: f64 = 1000.0;
+
+/// Default seconds between wallet balance refreshes.
+const BALANCE_INTERVAL: i64 = 60;
+
+/// Time between latency and clock drift measures.
+const CLOCK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Seconds between checks for tokens whose cached history fell behind.
+const REFRESH_INTERVAL: i64 = 30;
+
+/// Seconds between refreshes of the exchange symbol info.
+const SYMBOLS_INTERVAL: i64 = 3600;
+
+/// Minimum seconds between refreshes asked by symbols missing from it.
+const UNKNOWN_SYMBOLS_INTERVAL: i64 = 60;
+
+/// Default seconds between refreshes of the open orders of the account.
+const OPEN_ORDERS_INTERVAL: i64 = 15;
+
+/// Seconds between checks for silent market streams.
+const WATCHDOG_INTERVAL: i64 = 15;
+
 pub enum MidasEvent {
     BookUpdate(Token),
     KLineUpdate(usize),
+    Rebalance(Vec<Order>),
+    Alert(PriceAlert),
+    /// The period of a bar ended, by the exchange clock.
+    BarClosed(Token, TimeUnit),
+}
+
+/// Orders and positions left behind when the session ends.
+#[derive(Default)]
+pub struct ExitSummary {
+    pub orders: Vec<Order>,
+    pub positions: Vec<Position>,
+    /// Value (quote currency) of the positions and open orders, at the last
+    /// prices.
+    pub exposure: f64,
+}
+
+impl ExitSummary {
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty() && self.positions.is_empty()
+    }
 }
 
 pub struct Midas {
     pub exchange: BinanceExchange,
+    /// Filters and status of exchange symbols, refreshed in the background.
+    pub symbols: SymbolRegistry,
+    /// Catches up the history of tokens that fell behind.
+    refresher: DataRefresher,
+    /// Closes the bars of the streamed klines.
+    bar_clock: BarClock,
     pub wallet: BinanceWallet,
     pub market: BinanceMarket,
+    /// History and prices of stocks and etfs (Symbol tokens).
+    pub stocks: YahooMarket,
     pub hesperides: Vec<Chrysus>,
     pub ticks: HashMap<Token, MarketTick>,
     pub books: HashMap<Token, Book>,
+    pub rebalancer: Option<Rebalancer>,
+    /// Recurring market buys.
+    pub dca: DcaScheduler,
+    /// Paper trading of active strategies, by hesperides index.
+    pub forward_tests: HashMap<usize, ForwardTest>,
+    /// Synthetic markets of simulated tokens.
+    pub simulations: HashMap<Token, BrownianMotionMarket>,
+    /// Progress of long running operations (backfills, backtests).
+    pub progress: ProgressHub,
+    pub alerts: Alerts,
+    /// Tokens backtested as perpetual futures.
+    pub perpetuals: HashMap<Token, Perpetual>,
+    /// Account fee rates, also charged in backtests.
+    pub fees: FeeSchedule,
+    /// Strategy of new tokens.
+    pub default_strategy: Strategy,
+    /// Percent of the free quote balance given to each active strategy.
+    pub allocation_pct: f64,
+    /// Active strategies changed, their capital is split again with the next
+    /// balance.
+    allocation_pending: bool,
+    /// Seconds between balance refreshes.
+    pub balance_interval: i64,
+    /// Activation waits until the history required by the counselors is cached.
+    pub enforce_warmup: bool,
+    /// Strategies waiting for their history to be activated.
+    warming_up: Vec<usize>,
+    /// Open orders of the account, manual and placed by strategies.
+    pub open_orders: Vec<Order>,
+    /// Entries with their stop loss and take profit.
+    pub brackets: BracketTracker,
+    /// Symbols orders may be placed on.
+    pub permissions: TradePermissions,
+    /// Yearly percent, discounted from returns in Sharpe and alpha.
+    pub risk_free_rate: f64,
+    /// Token alpha and beta are measured against.
+    benchmark: Option<Token>,
+    /// No orders are placed, manual or automated, while the session is locked.
+    /// Exits of open brackets still follow their entries.
+    pub locked: bool,
+    /// Time (seconds) of the last open orders refresh.
+    last_open_orders: i64,
+    pub open_orders_interval: i64,
+    open_orders_failed: bool,
+    /// Time (seconds) of the last check for silent streams.
+    last_watchdog: i64,
+    clock: ClockMonitor,
+    /// API keys, also used by the user stream.
+    keys_file: String,
     balance: HashMap<Token, f64>,
+    balances: BalanceMonitor,
 }
 
 impl Midas {
     pub fn new(keys_file: &str, use_test_api: bool) -> Midas {
         Self {
             exchange: BinanceExchange::default(),
+            symbols: SymbolRegistry::default(),
+            refresher: DataRefresher::default(),
+            bar_clock: BarClock::default(),
             wallet: BinanceWallet::new(&keys_file, use_test_api),
             market: BinanceMarket::new(use_test_api),
+            stocks: YahooMarket::default(),
             hesperides: Vec::new(),
             ticks: HashMap::new(),
             books: HashMap::new(),
+            rebalancer: None,
+            dca: DcaScheduler::default(),
+            forward_tests: HashMap::new(),
+            simulations: HashMap::new(),
+            progress: ProgressHub::default(),
+            alerts: Alerts::default(),
+            perpetuals: HashMap::new(),
+            fees: FeeSchedule::default(),
+            default_strategy: Self::builtin_strategy(),
+            allocation_pct: 10.0,
+            enforce_warmup: true,
+            warming_up: Vec::new(),
+            allocation_pending: false,
+            balance_interval: BALANCE_INTERVAL,
+            open_orders: Vec::new(),
+            brackets: BracketTracker::default(),
+            permissions: TradePermissions::default(),
+            risk_free_rate: 0.0,
+            benchmark: None,
+            locked: false,
+            last_open_orders: 0,
+            open_orders_interval: OPEN_ORDERS_INTERVAL,
+            open_orders_failed: false,
+            last_watchdog: 0,
+            clock: ClockMonitor::default(),
+            keys_file: keys_file.to_string(),
             balance: HashMap::new(),
+            balances: BalanceMonitor::default(),
         }
     }
 
     pub fn init(&mut self, state_file: &String) {
-        self.load_state(state_file);
+        self.load_cache();
+        match self.load_state(state_file) {
+            Ok(()) => (),
+            Err(DiError::NotFound) => self.bootstrap_state(state_file),
+            // a broken file is left for the user to fix
+            Err(e) => ERROR!("state {:?}", e),
+        }
         self.market.day_ticker_all_service("USDT");
+        self.market.user_stream_service(&self.keys_file);
+        self.clock.start(CLOCK_INTERVAL);
+        self.symbols.refresh(Date::now().timestamp());
         self.balance = HashMap::new();
-        match self.wallet.get_balance() {
-            Ok(balance) => {
-                for (token, asset) in balance {
-                    self.balance.insert(token.clone(), asset.free);
+        self.allocation_pending = true;
+        self.balances
+            .refresh(&self.wallet.account, Date::now().timestamp());
+        match self.wallet.get_fees() {
+            Ok(fees) => self.fees = fees,
+            Err(e) => ERROR!("fees: {:?}", e),
+        }
+    }
+
+    /// Stores the free balances of a finished download, they are kept as they
+    /// were on errors. A pending allocation waits for them.
+    fn poll_balance(&mut self) {
+        match self.balances.poll() {
+            Some(Ok(balance)) => {
+                self.balance = balance
+                    .into_iter()
+                    .map(|(token, asset)| (token, asset.free))
+                    .collect();
+                if self.allocation_pending {
+                    self.allocate_capital();
                 }
             }
-            Err(e) => ERROR!("{:?}", e),
+            Some(Err(e)) => ERROR!("{:?}", e),
+            None => (),
         };
     }
 
-    pub fn save_state(&self, filename: &String) {
-        let file = File::create(filename.as_str()).unwrap();
-        if let Err(e) = serde_json::to_writer_pretty(file, &self.hesperides) {
-            ERROR!("{:?}", e);
+    /// Splits the quote currency of the active strategies among them, giving
+    /// each `allocation_pct`, less when they would take more than all of it.
+    /// The split counts the free balance and the capital on resting orders,
+    /// which already belongs to the slice of its strategy. Runs only when the
+    /// active strategies change, fills adjust the capital in between.
+    fn allocate_capital(&mut self) {
+        self.allocation_pending = false;
+        let mut active: HashMap<String, (usize, f64)> = HashMap::new();
+        for chrysus in self.hesperides.iter().filter(|c| c.active) {
+            let entry = active
+                .entry(chrysus.token.get_currency())
+                .or_insert((0, 0.0));
+            entry.0 += 1;
+            entry.1 += chrysus.locked_capital();
+        }
+        let pct = self.allocation_pct.clamp(0.0, 100.0) / 100.0;
+        for chrysus in self.hesperides.iter_mut().filter(|c| c.active) {
+            let currency = chrysus.token.get_currency();
+            let (count, locked) = active[&currency];
+            let free = *self
+                .balance
+                .get(&Token::Symbol(currency.clone()))
+                .unwrap_or(&0.0);
+            let share = pct.min(1.0 / count as f64);
+            chrysus.capital = ((free + locked) * share - chrysus.locked_capital()).max(0.0);
+        }
+    }
+
+    /// Latest REST latency and clock drift.
+    pub fn clock(&self) -> Option<ClockSample> {
+        self.clock.last
+    }
+
+    /// Follows the measured drift in server time estimates and signed requests.
+    fn update_clock(&mut self) {
+        if let Some(sample) = self.clock.poll() {
+            self.exchange.clock_offset = sample.drift;
+            let window = self.wallet.account.recv_window;
+            match self.wallet.fit_recv_window(sample.drift, sample.latency) {
+                Ok(new_window) if new_window != window => {
+                    INFO!(
+                        "receive window {} ms, drift {} ms",
+                        new_window,
+                        sample.drift
+                    )
+                }
+                Ok(_) => (),
+                Err(e) => ERROR!("{:?}", e),
+            }
+        }
+    }
+
+    /// Orders of the forward tests since the start of the UTC day.
+    pub fn daily_stats(&self) -> TradeStats {
+        let now = Date::now().timestamp();
+        let day_start = now - now.rem_euclid(86400);
+        let mut orders: Vec<Order> = Vec::new();
+        let mut prices: HashMap<Token, f64> = HashMap::new();
+        for forward_test in self.forward_tests.values() {
+            orders.extend(
+                forward_test
+                    .orders
+                    .iter()
+                    .filter(|o| o.date.timestamp() >= day_start)
+                    .cloned(),
+            );
+            let token = &forward_test.chrysus.token;
+            let price = match self.ticks.get(token) {
+                Some(tick) => tick.price,
+                None => forward_test.last_price,
+            };
+            prices.insert(token.clone(), price);
+        }
+        orders.sort_by_key(|o| o.date.timestamp());
+        TradeStats::compute(&orders, &prices, self.fees.taker)
+    }
+
+    /// Closed round trips of the forward tests, tagged with their strategy.
+    pub fn tagged_trips(&self) -> Vec<TaggedTrip> {
+        let mut trips = Vec::new();
+        for forward_test in self.forward_tests.values() {
+            let chrysus = &forward_test.chrysus;
+            let strategy = format!("{} {}", chrysus.token.name(), chrysus.strategy.name());
+            let counselors: Vec<String> = chrysus
+                .strategy
+                .enabled_counselors()
+                .iter()
+                .map(|c| c.name())
+                .collect();
+            let (round_trips, _) = match_round_trips(forward_test.orders.iter());
+            trips.extend(round_trips.into_iter().map(|trip| TaggedTrip {
+                strategy: strategy.clone(),
+                counselors: counselors.clone(),
+                trip,
+            }));
+        }
+        trips.sort_by_key(|t| t.trip.entry_timestamp);
+        trips
+    }
+
+    /// Realizes an execution in the strategy that placed the order and applies
+    /// the strategy policy to the unfilled remainder.
+    fn handle_fill(&mut self, mut fill: Fill) {
+        fill.token = self.symbols.token_from_symbol(&fill.token.to_string());
+        let index = match self
+            .hesperides
+            .iter()
+            .position(|c| c.order_by_id(fill.id).is_some())
+        {
+            Some(index) => index,
+            None => return,
+        };
+        INFO!(
+            "fill {} {:?} {:.6} @ {} ({:.6} left)",
+            fill.token.name(),
+            fill.side,
+            fill.quantity,
+            fill.price,
+            fill.remaining
+        );
+        match self.hesperides[index].realize_fill(&fill) {
+            None | Some(PartialFillPolicy::Leave) => (),
+            Some(PartialFillPolicy::Cancel) => {
+                match self.wallet.cancel_order(&fill.token, fill.id as u64) {
+                    Ok(()) => self.hesperides[index].cancel_remainder(fill.id),
+                    Err(e) => ERROR!("cancel remainder {}: {:?}", fill.id, e),
+                }
+            }
+            Some(PartialFillPolicy::Amend) => {
+                let quote = self.books.get(&fill.token).and_then(|book| book.quote());
+                let price = match (&fill.side, quote) {
+                    (Side::Buy, Some(quote)) => quote.bid,
+                    (Side::Sell, Some(quote)) => quote.ask,
+                    (_, None) => None,
+                };
+                let mut order = match (self.hesperides[index].order_by_id(fill.id), price) {
+                    (Some(order), Some(_)) => order.clone(),
+                    _ => return,
+                };
+                order.id = None;
+                order.date = Date::now();
+                order.price = price.unwrap();
+                if let Err(e) = self.check_order(&order) {
+                    ERROR!("amend remainder {}: {:?}", fill.id, e);
+                    return;
+                }
+                match self.wallet.replace_order(fill.id as u64, &order) {
+                    Ok(new_id) => {
+                        self.hesperides[index].amend_remainder(fill.id, new_id as i64, order.price)
+                    }
+                    Err(e) => ERROR!("amend remainder {}: {:?}", fill.id, e),
+                }
+            }
+        }
+        self.last_open_orders = 0;
+    }
+
+    /// Risk checks every order goes through before reaching the exchange.
+    pub fn check_order(&self, order: &Order) -> Result<(), DiError> {
+        if self.locked {
+            return Err(DiError::Message(String::from("session is locked")));
+        }
+        self.permissions.check(&order.token)
+    }
+
+    /// Places the entry of the bracket, its exits follow the fill.
+    pub fn submit_bracket(&mut self, bracket: Bracket) -> Result<(), DiError> {
+        self.check_order(&bracket.entry)?;
+        self.brackets.submit(&self.wallet, bracket)?;
+        self.refresh_open_orders();
+        Ok(())
+    }
+
+    /// Complete bars missing from the cached history of strategy `index`.
+    /// Only exchange pairs are tracked, stock markets close.
+    pub fn bars_behind(&self, index: usize) -> i64 {
+        let chrysus = &self.hesperides[index];
+        if !chrysus.token.is_pair() || self.simulations.contains_key(&chrysus.token) {
+            return 0;
+        }
+        let duration = TimeWindow {
+            resolution: chrysus.strategy.duration.resolution,
+            count: 1,
+        };
+        match self
+            .history(&chrysus.token)
+            .get_last(&chrysus.token, &duration)
+        {
+            Ok([.., last]) => bars_behind(last, self.exchange.server_now()),
+            _ => 0,
+        }
+    }
+
+    /// Appends finished downloads and requests the missing bars of exchange
+    /// pairs that fell behind, whether their chart is shown or not.
+    fn refresh_history(&mut self, events: &mut Vec<MidasEvent>) {
+        for (token, resolution, download) in self.refresher.poll() {
+            let samples = match download {
+                Ok(samples) => samples,
+                Err(e) => {
+                    ERROR!("refresh {}: {:?}", token.name(), e);
+                    continue;
+                }
+            };
+            for sample in &samples {
+                if let Err(e) = self.market.append(&token, sample) {
+                    ERROR!("{:?}", e);
+                }
+            }
+            for (index, chrysus) in self.hesperides.iter().enumerate() {
+                if chrysus.token == token && chrysus.strategy.duration.resolution == resolution {
+                    events.push(MidasEvent::KLineUpdate(index));
+                }
+            }
+        }
+        let now = Date::now().timestamp();
+        if now - self.refresher.requested < REFRESH_INTERVAL {
+            return;
+        }
+        self.refresher.requested = now;
+        for index in 0..self.hesperides.len() {
+            let chrysus = &self.hesperides[index];
+            let behind = self.bars_behind(index);
+            if behind > 0 {
+                // the forming bar comes along
+                let count = (behind + 1).min(chrysus.strategy.duration.count.max(1)) as u16;
+                self.refresher.request(
+                    &chrysus.token,
+                    &chrysus.strategy.duration.resolution,
+                    count,
+                );
+            }
+        }
+    }
+
+    /// Cancels strategy limit orders that outlived their TTL, unlocking their
+    /// capital.
+    fn cancel_stale_orders(&mut self) {
+        let now = Date::now().timestamp();
+        for index in 0..self.hesperides.len() {
+            for order in self.hesperides[index].stale_orders(now) {
+                let id = order.id.unwrap();
+                match self.wallet.cancel_order(&order.token, id as u64) {
+                    Ok(()) => {
+                        INFO!("canceled stale order {} {}", id, order.token.name());
+                        self.hesperides[index].cancel_remainder(id);
+                    }
+                    Err(e) => ERROR!("cancel stale order {}: {:?}", id, e),
+                }
+            }
+        }
+    }
+
+    /// Fetches the open orders, with the unfilled quantity.
+    pub fn refresh_open_orders(&mut self) {
+        self.last_open_orders = Date::now().timestamp();
+        match self.wallet.get_all_open_orders() {
+            Ok(statuses) => {
+                let ticks = &self.ticks;
+                for e in self.brackets.update(&self.wallet, &statuses, |token| {
+                    ticks.get(token).map(|tick| tick.price)
+                }) {
+                    ERROR!("bracket {:?}", e);
+                }
+                self.open_orders = statuses
+                    .into_iter()
+                    .map(|status| {
+                        let mut order = status.order;
+                        order.quantity -= status.executed_qty;
+                        order
+                    })
+                    .collect();
+                self.open_orders_failed = false;
+            }
+            Err(e) => {
+                // keep retrying without repeating the error
+                if !self.open_orders_failed {
+                    ERROR!("open orders: {:?}", e);
+                }
+                self.open_orders_failed = true;
+            }
+        }
+    }
+
+    /// Yahoo snapshots are kept per price adjustment so they never mix.
+    fn yahoo_cache_file(&self) -> String {
+        format!("cache_yahoo_{}.json", self.stocks.adjustment().name())
+    }
+
+    /// Writes the market caches to disk, so a restart only fetches new bars.
+    pub fn save_cache(&self) {
+        if let Err(e) = self.market.cache.save(BINANCE_CACHE_FILE) {
+            ERROR!("cache snapshot {:?}", e);
+        }
+        if let Err(e) = self.stocks.cache.save(&self.yahoo_cache_file()) {
+            ERROR!("cache snapshot {:?}", e);
         }
     }
 
-    pub fn load_state(&mut self, filename: &String) {
-        let data = std::fs::read_to_string(filename).expect("Unable to read file");
-        self.hesperides = serde_json::from_str(&data).expect("Unable to parse");
-        for i in 0..self.hesperides.len() {
+    fn load_cache(&mut self) {
+        if let Ok(cache) = Cache::load(BINANCE_CACHE_FILE) {
+            self.market.cache = cache;
+        }
+        if let Ok(cache) = Cache::load(&self.yahoo_cache_file()) {
+            self.stocks.cache = cache;
+        }
+    }
+
+    /// Saves all pairs except simulated ones, which only live in the session.
+    pub fn save_state(&self, filename: &String) -> Result<(), DiError> {
+        let file = File::create(filename.as_str())
+            .map_err(|e| DiError::Message(format!("{}: {:?}", filename, e)))?;
+        let hesperides: Vec<&Chrysus> = self
+            .hesperides
+            .iter()
+            .filter(|c| !self.simulations.contains_key(&c.token))
+            .collect();
+        serde_json::to_writer_pretty(file, &hesperides)
+            .map_err(|e| DiError::Message(format!("{}: {:?}", filename, e)))
+    }
+
+    /// Loads the saved pairs, `NotFound` when the file does not exist.
+    pub fn load_state(&mut self, filename: &String) -> Result<(), DiError> {
+        let data = std::fs::read_to_string(filename).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => DiError::NotFound,
+            _ => DiError::Message(format!("{}: {}", filename, e)),
+        })?;
+        self.hesperides = serde_json::from_str(&data)
+            .map_err(|e| DiError::Message(format!("{}: {}", filename, e)))?;
+        let n = self.hesperides.len();
+        let reporter = self.progress.task("backfill");
+        for i in 0..n {
+            reporter.step(i, n, &self.hesperides[i].token.name());
             self.init_token(i);
         }
+        reporter.finish("");
+        Ok(())
+    }
+
+    /// First run: starts with `STARTER_PAIR` on the default strategy and saves
+    /// it as the state.
+    fn bootstrap_state(&mut self, filename: &String) {
+        let token = Token::pair(STARTER_PAIR.0, STARTER_PAIR.1);
+        INFO!("no {}, starting with {}", filename, token.name());
+        self.add_token(&token);
+        if let Err(e) = self.save_state(filename) {
+            ERROR!("{:?}", e);
+        }
     }
 
     fn init_token(&mut self, index: usize) {
         let chrysus = &self.hesperides[index];
+        if let Some(simulation) = self.simulations.get_mut(&chrysus.token) {
+            if let Err(e) = simulation.fetch_last(&chrysus.token, &chrysus.strategy.duration) {
+                ERROR!("ERROR {:?} {:?}.", e, chrysus.token);
+                return;
+            }
+            simulation.kline_service(&chrysus.token, &chrysus.strategy.duration.resolution);
+            simulation.order_book_service(&chrysus.token);
+            return;
+        }
         if chrysus.token.is_pair() {
             match self
                 .market
@@ -91,49 +628,626 @@ impl Midas {
             self.market
                 .kline_service(&chrysus.token, &chrysus.strategy.duration.resolution);
             self.market.order_book_service(&chrysus.token);
+            let duration = chrysus.strategy.duration.clone();
+            for token in chrysus.strategy.tokens() {
+                if let Err(e) = self.market.fetch_last(&token, &duration) {
+                    ERROR!("ERROR {:?} {:?}.", e, token);
+                    continue;
+                }
+                self.market.kline_service(&token, &duration.resolution);
+            }
+            self.fetch_benchmark(&duration);
+        } else if let Token::Symbol(_) = chrysus.token {
+            // stocks and etfs
+            let duration = chrysus.strategy.duration.clone();
+            let mut tokens = vec![chrysus.token.clone()];
+            tokens.extend(chrysus.strategy.tokens());
+            for token in tokens {
+                if let Err(e) = self.stocks.fetch_last(&token, &duration) {
+                    ERROR!("ERROR {:?} {:?}.", e, token);
+                    continue;
+                }
+                self.stocks.kline_service(&token, &duration.resolution);
+            }
+            self.fetch_benchmark(&duration);
         }
     }
 
-    pub fn add_token(&mut self, token: &Token) -> Option<usize> {
-        let index = self.hesperides.len();
-        self.hesperides.push(Chrysus::new(token));
+    /// Switches stock prices between raw and adjusted, fetching their history
+    /// again. Returns the indices of the affected pairs.
+    pub fn set_price_adjustment(&mut self, adjustment: PriceAdjustment) -> Vec<usize> {
+        self.stocks.set_adjustment(adjustment);
+        let indices: Vec<usize> = self
+            .hesperides
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| matches!(c.token, Token::Symbol(_)))
+            .map(|(index, _)| index)
+            .collect();
+        for index in &indices {
+            self.init_token(*index);
+        }
+        indices
+    }
+
+    fn is_leg(&self, token: &Token) -> bool {
+        self.hesperides
+            .iter()
+            .any(|t| t.token != *token && t.strategy.tokens().contains(token))
+    }
+
+    /// Historical data of `token`, from its simulation, the exchange (pairs)
+    /// or yahoo (stocks).
+    pub fn history(&self, token: &Token) -> &dyn HistoricalData {
+        match self.simulations.get(token) {
+            Some(simulation) => simulation,
+            None if token.is_pair() => &self.market,
+            None => &self.stocks,
+        }
+    }
+
+    /// Last `count` cached 1m closes of `token`, empty if none are cached.
+    pub fn recent_closes(&self, token: &Token, count: usize) -> Vec<f64> {
+        let duration = TimeWindow {
+            resolution: TimeUnit::Min(1),
+            count: count as i64,
+        };
+        match self.history(token).get_last(token, &duration) {
+            Ok(samples) => samples.iter().map(|s| s.close).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    pub fn history_mut(&mut self, token: &Token) -> &mut dyn HistoricalData {
+        match self.simulations.get_mut(token) {
+            Some(simulation) => simulation,
+            None if token.is_pair() => &mut self.market,
+            None => &mut self.stocks,
+        }
+    }
+
+    /// Strategy of pairs loaded without one: MeanReversion(20, 2) over 200 samples.
+    pub fn builtin_strategy() -> Strategy {
         let mut strategy = Strategy::default();
         strategy
             .counselors
             .push(Counselor::MeanReversion((20, 2.0.into())));
         strategy.duration.count = 200;
+        strategy
+    }
+
+    pub fn add_token(&mut self, token: &Token) -> Option<usize> {
+        self.add_token_with(token, &self.default_strategy.clone())
+    }
+
+    pub fn add_token_with(&mut self, token: &Token, strategy: &Strategy) -> Option<usize> {
+        let index = self.hesperides.len();
+        self.hesperides.push(Chrysus::new(token));
+        self.set_strategy(index, strategy);
+        Some(index)
+    }
+
+    /// Adds another view of `token` at `resolution`, starting from the strategy
+    /// of its first view.
+    pub fn add_resolution(&mut self, token: &Token, resolution: &TimeUnit) -> Option<usize> {
+        let mut strategy = match self.hesperides.iter().find(|c| c.token == *token) {
+            Some(c) => c.strategy.clone(),
+            None => self.default_strategy.clone(),
+        };
+        strategy.duration.resolution = *resolution;
+        let index = self.hesperides.len();
+        self.hesperides.push(Chrysus::new(token));
+        self.set_strategy(index, &strategy);
+        Some(index)
+    }
+
+    /// Adds a synthetic pair (SIM<n>USDT) following a geometric brownian motion.
+    pub fn add_simulation(
+        &mut self,
+        mu: f64,
+        sigma: f64,
+        start_price: f64,
+        resolution: &TimeUnit,
+    ) -> Option<usize> {
+        let name = format!("SIM{}", self.simulations.len() + 1);
+        let token = Token::pair(name.as_str(), "USDT");
+        self.simulations.insert(
+            token.clone(),
+            BrownianMotionMarket::new(mu, sigma, start_price),
+        );
+        let index = self.hesperides.len();
+        self.hesperides.push(Chrysus::new(&token));
+        let mut strategy = self.default_strategy.clone();
+        strategy.duration.resolution = *resolution;
         self.set_strategy(index, &strategy);
         Some(index)
     }
 
+    /// Synthetic copy of the pair of `index` for offline demos: `model` prices
+    /// from its last close with its realized volatility, streamed `speed`
+    /// simulated minutes per second, its strategy paper traded from the start.
+    pub fn add_sandbox(&mut self, index: usize, model: PriceModel, speed: f64) -> Option<usize> {
+        let token = self.hesperides[index].token.clone();
+        let mut strategy = self.hesperides[index].strategy.clone();
+        let samples = self.get_history(index).unwrap_or(&[]);
+        let start_price = samples.last().map(|s| s.close).filter(|p| *p > 0.0)?;
+        let sigma = volatility(&VolatilityEstimator::CloseToClose, samples)
+            .map(|v| v * annualization_factor(&strategy.duration.resolution))
+            .filter(|v| *v > 0.0)
+            .unwrap_or(0.4);
+        let mut simulation = BrownianMotionMarket::new(0.0, sigma, start_price);
+        simulation.model = match model {
+            PriceModel::OrnsteinUhlenbeck { theta, .. } => PriceModel::OrnsteinUhlenbeck {
+                theta,
+                mean: start_price,
+            },
+            model => model,
+        };
+        let sandbox = Token::pair(
+            &format!("{}.SBX{}", token.get_symbol(), self.simulations.len() + 1),
+            &token.get_currency(),
+        );
+        self.simulations.insert(sandbox.clone(), simulation);
+        self.set_simulation_speed(&sandbox, speed);
+        let sandbox_index = self.hesperides.len();
+        self.hesperides.push(Chrysus::new(&sandbox));
+        // simulations are not bar clocked, the paper trader steps on klines
+        strategy.closed_bars = false;
+        self.set_strategy(sandbox_index, &strategy);
+        self.toggle_active(sandbox_index);
+        Some(sandbox_index)
+    }
+
+    /// Simulated minutes streamed per second by the simulation of `token`.
+    pub fn set_simulation_speed(&mut self, token: &Token, speed: f64) -> bool {
+        match self.simulations.get_mut(token) {
+            Some(simulation) if speed > 0.0 => {
+                simulation.tick_interval = std::time::Duration::from_secs_f64(1.0 / speed);
+                true
+            }
+            _ => false,
+        }
+    }
+
     pub fn run_backtest(&self, index: usize, period: &TimeWindow) -> Backtest {
-        match self.market.get_last(&self.hesperides[index].token, &period) {
-            Ok(samples) => {
-                return backtest(&self.hesperides[index], samples);
+        match self.backtest(&self.hesperides[index], period) {
+            Ok(bt) => bt,
+            Err(e) => {
+                ERROR!("{:?}", e);
+                Backtest::default()
+            }
+        }
+    }
+
+    /// Backtest of `chrysus` over the cached history of `period`.
+    fn backtest(&self, chrysus: &Chrysus, period: &TimeWindow) -> Result<Backtest, DiError> {
+        let samples = self
+            .history(&chrysus.token)
+            .get_last(&chrysus.token, period)?;
+        let mut legs: HashMap<Token, &[Sample]> = HashMap::new();
+        for token in chrysus.strategy.tokens() {
+            match self.history(&token).get_last(&token, period) {
+                Ok(leg) => {
+                    legs.insert(token, leg);
+                }
+                Err(e) => ERROR!("{:?}", e),
             }
-            Err(e) => ERROR!("{:?}", e),
         }
-        Backtest::default()
+        let settings = BacktestSettings {
+            fee_rate: self.fees.taker,
+            perpetual: self
+                .perpetuals
+                .get(&chrysus.token)
+                .cloned()
+                .unwrap_or_default(),
+        };
+        Ok(backtest_with_settings(chrysus, samples, &legs, &settings))
+    }
+
+    /// Reports of `strategy` backtested over `period` on every loaded symbol,
+    /// simulations aside, fetching the history they lack. Symbols without
+    /// history are returned with their error.
+    pub fn backtest_all(
+        &mut self,
+        strategy: &Strategy,
+        period: &TimeWindow,
+    ) -> (Vec<(Token, Report)>, Vec<(Token, DiError)>) {
+        let mut tokens: Vec<Token> = Vec::new();
+        for chrysus in &self.hesperides {
+            if !tokens.contains(&chrysus.token) && !self.simulations.contains_key(&chrysus.token) {
+                tokens.push(chrysus.token.clone());
+            }
+        }
+        self.fetch_benchmark(period);
+        let (mut reports, mut failed) = (Vec::new(), Vec::new());
+        let reporter = self.progress.task("backtest all");
+        for (i, token) in tokens.iter().enumerate() {
+            reporter.step(i, tokens.len(), &token.name());
+            let mut chrysus = Chrysus::new(token);
+            chrysus.strategy = strategy.clone();
+            for leg in chrysus.strategy.tokens() {
+                if let Err(e) = self.history_mut(&leg).fetch_last(&leg, period) {
+                    ERROR!("{:?}", e);
+                }
+            }
+            let result = self
+                .history_mut(token)
+                .fetch_last(token, period)
+                .map(|_| ())
+                .and_then(|_| self.backtest(&chrysus, period))
+                .and_then(|bt| {
+                    let samples = self.history(token).get_last(token, period)?;
+                    let mut report = Report::from_backtest(&chrysus, &bt, samples);
+                    let benchmark = self.benchmark_samples(period);
+                    report.add_risk_metrics(
+                        self.benchmark.as_ref().zip(benchmark),
+                        self.risk_free_rate,
+                    );
+                    Ok(report)
+                });
+            match result {
+                Ok(report) => reports.push((token.clone(), report)),
+                Err(e) => failed.push((token.clone(), e)),
+            }
+        }
+        reporter.finish("");
+        (reports, failed)
+    }
+
+    /// Writes a report (html or markdown, from the extension) of the backtest
+    /// of the given pair over `period`.
+    /// Backtests the strategy of `index` and summarizes the run.
+    pub fn report(&self, index: usize, period: &TimeWindow) -> Result<Report, DiError> {
+        let chrysus = &self.hesperides[index];
+        let samples = self
+            .history(&chrysus.token)
+            .get_last(&chrysus.token, period)?;
+        let bt = self.run_backtest(index, period);
+        let mut report = Report::from_backtest(chrysus, &bt, samples);
+        let benchmark = self.benchmark_samples(period);
+        report.add_risk_metrics(self.benchmark.as_ref().zip(benchmark), self.risk_free_rate);
+        Ok(report)
+    }
+
+    /// Sets the benchmark of alpha and beta, fetching its history at the
+    /// periods of the loaded pairs.
+    pub fn set_benchmark(&mut self, benchmark: Option<Token>) {
+        if benchmark == self.benchmark {
+            return;
+        }
+        self.benchmark = benchmark;
+        let periods: Vec<TimeWindow> = self
+            .hesperides
+            .iter()
+            .map(|c| c.strategy.duration.clone())
+            .collect();
+        for period in periods {
+            self.fetch_benchmark(&period);
+        }
+    }
+
+    /// Brings the benchmark history over `period` up to date.
+    pub fn fetch_benchmark(&mut self, period: &TimeWindow) {
+        if let Some(benchmark) = self.benchmark.clone() {
+            if let Err(e) = self.history_mut(&benchmark).fetch_last(&benchmark, period) {
+                ERROR!("benchmark {} {:?}", benchmark.name(), e);
+            }
+        }
+    }
+
+    fn benchmark_samples(&self, period: &TimeWindow) -> Option<&[Sample]> {
+        let benchmark = self.benchmark.as_ref()?;
+        self.history(benchmark).get_last(benchmark, period).ok()
+    }
+
+    /// Sharpe, alpha and beta of each forward test since its activation.
+    pub fn forward_risk(&self) -> Vec<(String, RiskMetrics)> {
+        let mut risk: Vec<(String, RiskMetrics)> = self
+            .forward_tests
+            .values()
+            .map(|forward_test| {
+                let chrysus = &forward_test.chrysus;
+                let period = &chrysus.strategy.duration;
+                let equity = match self
+                    .history(&chrysus.token)
+                    .get_last(&chrysus.token, period)
+                {
+                    Ok(samples) => forward_test.equity(samples),
+                    Err(_) => Vec::new(),
+                };
+                (
+                    chrysus.token.name(),
+                    risk_metrics(&equity, self.benchmark_samples(period), self.risk_free_rate),
+                )
+            })
+            .collect();
+        risk.sort_by(|a, b| a.0.cmp(&b.0));
+        risk
+    }
+
+    pub fn write_report(
+        &self,
+        index: usize,
+        period: &TimeWindow,
+        path: &str,
+    ) -> Result<(), DiError> {
+        self.report(index, period)?.write(path)
+    }
+
+    /// Latest known price of a pair in its quote currency.
+    fn last_price(&self, token: &Token) -> Option<f64> {
+        if let Some(tick) = self.ticks.get(token) {
+            return Some(tick.price);
+        }
+        let index = self.hesperides.iter().position(|c| c.token == *token)?;
+        self.get_history(index)?.last().map(|s| s.close)
+    }
+
+    /// Price of a wallet asset in USDT.
+    fn usdt_price(&self, asset: &Token) -> Option<f64> {
+        match asset.to_string().as_str() {
+            "USDT" => Some(1.0),
+            symbol => self.last_price(&Token::pair(symbol, "USDT")),
+        }
+    }
+
+    /// Current balances valued in USDT.
+    pub fn account_snapshot(&self) -> AccountSnapshot {
+        let mut assets: Vec<AssetSnapshot> = self
+            .balance
+            .iter()
+            .map(|(token, quantity)| AssetSnapshot {
+                asset: token.to_string(),
+                quantity: *quantity,
+                value: self.usdt_price(token).map(|p| p * quantity),
+            })
+            .collect();
+        assets.sort_by(|a, b| a.asset.cmp(&b.asset));
+        AccountSnapshot::new(Date::now().timestamp() as u64, assets)
+    }
+
+    /// Writes balances, strategy positions and open orders to a csv file.
+    pub fn export_portfolio(&self, path: &str) -> Result<(), DiError> {
+        let mut csv = String::from(
+            "kind,token,side,quantity,price,cost_basis,market_value,unrealized_pnl,order_id,date\n",
+        );
+        let mut balances: Vec<(&Token, &f64)> = self.balance.iter().collect();
+        balances.sort_by_key(|(token, _)| token.to_string());
+        for (token, quantity) in balances {
+            let price = self.usdt_price(token);
+            csv.push_str(&format!(
+                "balance,{},,{},{},,{},,,\n",
+                token.to_string(),
+                quantity,
+                price.map(|p| p.to_string()).unwrap_or_default(),
+                price
+                    .map(|p| (p * quantity).to_string())
+                    .unwrap_or_default(),
+            ));
+        }
+        for chrysus in &self.hesperides {
+            let price = self.last_price(&chrysus.token);
+            for position in chrysus.positions.values() {
+                let cost_basis = position.price * position.quantity;
+                let value = price.map(|p| p * position.quantity);
+                csv.push_str(&format!(
+                    "position,{},Buy,{},{},{},{},{},,{}\n",
+                    position.token.to_string(),
+                    position.quantity,
+                    position.price,
+                    cost_basis,
+                    value.map(|v| v.to_string()).unwrap_or_default(),
+                    value
+                        .map(|v| (v - cost_basis).to_string())
+                        .unwrap_or_default(),
+                    position.date.pretty_string(),
+                ));
+            }
+        }
+        for status in self.wallet.get_all_open_orders()? {
+            let order = &status.order;
+            csv.push_str(&format!(
+                "order,{},{:?},{},{},,,,{},{}\n",
+                order.token.to_string(),
+                order.side,
+                order.quantity - status.executed_qty,
+                order.price,
+                order.id.map(|id| id.to_string()).unwrap_or_default(),
+                order.date.pretty_string(),
+            ));
+        }
+        std::fs::write(path, csv).map_err(|e| DiError::Message(e.to_string()))
+    }
+
+    /// Open orders on the exchange and positions of the strategies. Falls
+    /// back to the last refreshed orders if the exchange can't be reached.
+    pub fn exit_summary(&self) -> ExitSummary {
+        let orders = match self.wallet.get_all_open_orders() {
+            Ok(statuses) => statuses
+                .into_iter()
+                .map(|status| {
+                    let mut order = status.order;
+                    order.quantity -= status.executed_qty;
+                    order
+                })
+                .collect(),
+            Err(e) => {
+                ERROR!("open orders: {:?}", e);
+                self.open_orders.clone()
+            }
+        };
+        let positions: Vec<Position> = self
+            .hesperides
+            .iter()
+            .flat_map(|chrysus| chrysus.positions.values().cloned())
+            .collect();
+        let exposure = orders.iter().map(|o| o.quantity * o.price).sum::<f64>()
+            + positions
+                .iter()
+                .map(|p| p.quantity * self.last_price(&p.token).unwrap_or(p.price))
+                .sum::<f64>();
+        ExitSummary {
+            orders,
+            positions,
+            exposure,
+        }
+    }
+
+    /// Cancels `orders`, returning how many were cancelled.
+    pub fn cancel_orders(&self, orders: &[Order]) -> usize {
+        let mut cancelled = 0;
+        for order in orders {
+            if let Some(id) = order.id {
+                match self.wallet.cancel_order(&order.token, id as u64) {
+                    Ok(()) => cancelled += 1,
+                    Err(e) => ERROR!("cancel {} {}: {:?}", order.token.name(), id, e),
+                }
+            }
+        }
+        cancelled
+    }
+
+    /// Round trips of the forward tests grouped by strategy, counselor, hour
+    /// and weekday.
+    pub fn export_breakdown(&self, path: &str) -> Result<(), DiError> {
+        std::fs::write(path, breakdown_csv(&self.tagged_trips()))
+            .map_err(|e| DiError::Message(e.to_string()))
     }
 
     pub fn get_history(&self, index: usize) -> Option<&[Sample]> {
         let t = &self.hesperides[index];
-        match self.market.get_last(&t.token, &t.strategy.duration) {
+        match self
+            .history(&t.token)
+            .get_last(&t.token, &t.strategy.duration)
+        {
             Ok(samples) => return Some(samples),
             Err(e) => ERROR!("{:?}", e),
         }
         None
     }
 
+    /// Correlation of returns between all loaded pairs over the last `window` samples.
+    pub fn correlations(&self, window: usize) -> CorrelationMatrix {
+        let mut series: Vec<(Token, &[Sample])> = Vec::new();
+        for (index, chrysus) in self.hesperides.iter().enumerate() {
+            if !chrysus.token.is_pair() || series.iter().any(|(t, _)| *t == chrysus.token) {
+                continue;
+            }
+            if let Some(samples) = self.get_history(index) {
+                series.push((chrysus.token.clone(), samples));
+            }
+        }
+        correlation_matrix(&series[..], window)
+    }
+
+    /// Mean-variance target weights across loaded pairs and the orders that move
+    /// the wallet balance towards them.
+    pub fn allocation(&self, window: usize, risk_aversion: f64) -> (Allocation, Vec<Order>) {
+        let mut series: Vec<(Token, &[Sample])> = Vec::new();
+        for (index, chrysus) in self.hesperides.iter().enumerate() {
+            if !chrysus.token.is_pair() || series.iter().any(|(t, _)| *t == chrysus.token) {
+                continue;
+            }
+            if let Some(samples) = self.get_history(index) {
+                series.push((chrysus.token.clone(), samples));
+            }
+        }
+        let allocation = allocate(&series[..], window, risk_aversion);
+        let mut holdings: HashMap<Token, f64> = HashMap::new();
+        let mut prices: HashMap<Token, f64> = HashMap::new();
+        for (token, samples) in &series {
+            if let Some(sample) = samples.last() {
+                prices.insert(token.clone(), sample.close);
+            }
+            if let Some(q) = self.balance.get(&Token::Symbol(token.get_symbol())) {
+                holdings.insert(token.clone(), *q);
+            }
+        }
+        let cash = match series.first() {
+            Some((token, _)) => *self
+                .balance
+                .get(&Token::Symbol(token.get_currency()))
+                .unwrap_or(&0.0),
+            None => 0.0,
+        };
+        let orders = rebalance_orders(&allocation, &holdings, &prices, cash, 1.0);
+        (allocation, orders)
+    }
+
     pub fn set_strategy(&mut self, index: usize, strategy: &Strategy) {
         self.hesperides[index].strategy = strategy.clone();
+        if self.forward_tests.contains_key(&index) {
+            // the forward test restarts with the new strategy
+            self.forward_tests.insert(
+                index,
+                ForwardTest::new(&self.hesperides[index], FORWARD_TEST_CAPITAL),
+            );
+        }
         self.init_token(index);
     }
 
+    /// Unlike `set_strategy`, keeps the forward test running.
+    pub fn set_partial_fills(&mut self, index: usize, policy: PartialFillPolicy) {
+        self.hesperides[index].strategy.partial_fills = policy;
+    }
+
+    pub fn set_order_ttl(&mut self, index: usize, ttl: Option<OrderTtl>) {
+        self.hesperides[index].strategy.order_ttl = ttl;
+    }
+
+    pub fn set_closed_bars(&mut self, index: usize, closed_bars: bool) {
+        self.hesperides[index].strategy.closed_bars = closed_bars;
+    }
+
+    pub fn set_heat(&mut self, index: usize, heat: Option<Heat>) {
+        self.hesperides[index].strategy.heat = heat;
+    }
+
+    pub fn set_atr_exits(&mut self, index: usize, exits: Option<AtrExits>) {
+        self.hesperides[index].strategy.atr_exits = exits;
+    }
+
+    pub fn set_trailing_stop(&mut self, index: usize, offset: Option<BracketOffset>) {
+        self.hesperides[index].strategy.trailing_stop = offset;
+    }
+
     pub fn get(&self, index: usize) -> Option<&Chrysus> {
         Some(&self.hesperides[index])
     }
 
+    /// Dry run of strategy `index` at the current book, or at the last close
+    /// without one. Inactive strategies are sized with the capital activation
+    /// would give them. Returns the capital with the decision and its orders.
+    pub fn preview(&self, index: usize) -> Result<(f64, Decision, Vec<Order>), DiError> {
+        let mut chrysus = self.hesperides[index].clone();
+        if !chrysus.active {
+            let free = self
+                .balance
+                .get(&Token::Symbol(chrysus.token.get_currency()))
+                .unwrap_or(&0.0);
+            chrysus.capital = free * self.allocation_pct.clamp(0.0, 100.0) / 100.0;
+        }
+        let quote = match self.books.get(&chrysus.token).and_then(|book| book.quote()) {
+            Some(quote) => quote,
+            None => {
+                let close = self
+                    .get_history(index)
+                    .and_then(|samples| samples.last())
+                    .map(|sample| sample.close)
+                    .ok_or(DiError::NotFound)?;
+                Quote {
+                    token: chrysus.token.clone(),
+                    bid: Some(close),
+                    ask: Some(close),
+                    biddate: Date::now(),
+                    askdate: Date::now(),
+                }
+            }
+        };
+        let (decision, orders) = chrysus.preview(&quote, self.history(&chrysus.token))?;
+        Ok((chrysus.capital, decision, orders))
+    }
+
     pub fn get_token(&self, index: usize) -> Option<Token> {
         Some(self.hesperides[index].token.clone())
     }
@@ -142,6 +1256,223 @@ impl Midas {
         self.balance.clone()
     }
 
+    /// Activates (or deactivates) a strategy, starting its forward test. A
+    /// strategy without its required history waits in warm-up instead, toggling
+    /// it again cancels the wait.
+    pub fn toggle_active(&mut self, index: usize) -> bool {
+        if let Some(position) = self.warming_up.iter().position(|i| *i == index) {
+            self.warming_up.remove(position);
+            return false;
+        }
+        if !self.hesperides[index].active {
+            if self.locked {
+                ERROR!("session is locked");
+                return false;
+            }
+            if let Err(e) = self.permissions.check(&self.hesperides[index].token) {
+                ERROR!("{:?}", e);
+                return false;
+            }
+        }
+        if !self.hesperides[index].active && self.enforce_warmup {
+            let (cached, required) = self.warmup(index);
+            if cached < required {
+                self.warming_up.push(index);
+                return false;
+            }
+        }
+        self.set_active(index, !self.hesperides[index].active);
+        self.hesperides[index].active
+    }
+
+    fn set_active(&mut self, index: usize, active: bool) {
+        let chrysus = &mut self.hesperides[index];
+        chrysus.active = active;
+        if active {
+            self.forward_tests
+                .insert(index, ForwardTest::new(chrysus, FORWARD_TEST_CAPITAL));
+        } else {
+            self.forward_tests.remove(&index);
+            self.hesperides[index].capital = 0.0;
+        }
+        // split with a fresh balance, fetched without blocking the caller
+        self.allocation_pending = true;
+        self.balances
+            .refresh(&self.wallet.account, Date::now().timestamp());
+    }
+
+    /// Cached and required samples of the strategy history.
+    pub fn warmup(&self, index: usize) -> (usize, usize) {
+        let chrysus = &self.hesperides[index];
+        let required = chrysus.strategy.required_history_size();
+        let duration = TimeWindow {
+            resolution: chrysus.strategy.duration.resolution,
+            count: required as i64,
+        };
+        let cached = match self
+            .history(&chrysus.token)
+            .get_last(&chrysus.token, &duration)
+        {
+            Ok(samples) => samples.len(),
+            Err(_) => 0,
+        };
+        (cached, required)
+    }
+
+    /// Strategies waiting for their history.
+    pub fn warming_up(&self) -> &[usize] {
+        &self.warming_up
+    }
+
+    /// Activates the strategies whose history is complete.
+    fn update_warmup(&mut self) {
+        let ready: Vec<usize> = self
+            .warming_up
+            .iter()
+            .cloned()
+            .filter(|index| {
+                let (cached, required) = self.warmup(*index);
+                cached >= required
+            })
+            .collect();
+        for index in ready {
+            self.warming_up.retain(|i| *i != index);
+            self.set_active(index, true);
+            INFO!(
+                "{} warmed up, forward test started",
+                self.hesperides[index].token.name()
+            );
+        }
+    }
+
+    fn step_forward_test(&mut self, index: usize, sample: &Sample) {
+        let token = self.hesperides[index].token.clone();
+        let book = match self.books.get(&token) {
+            Some(book) => book.clone(),
+            None => Book {
+                token: token.clone(),
+                bids: vec![BookLine {
+                    price: sample.close,
+                    quantity: 1.0,
+                }],
+                asks: vec![BookLine {
+                    price: sample.close,
+                    quantity: 1.0,
+                }],
+            },
+        };
+        if let Some(forward_test) = self.forward_tests.get_mut(&index) {
+            let orders = match self.simulations.get(&token) {
+                Some(simulation) => forward_test.step(book, simulation),
+                None if token.is_pair() => forward_test.step(book, &self.market),
+                None => forward_test.step(book, &self.stocks),
+            };
+            for order in orders {
+                INFO!(
+                    "forward {} {:?} {:.6} @ {}",
+                    token.name(),
+                    order.side,
+                    order.quantity,
+                    order.price
+                );
+            }
+        }
+    }
+
+    /// Steps the forward tests deciding on closed bars with the bar opened at
+    /// `open` (seconds), if it was cached.
+    fn close_bar(&mut self, token: &Token, resolution: &TimeUnit, open: u64) {
+        for index in 0..self.hesperides.len() {
+            let chrysus = &self.hesperides[index];
+            if chrysus.token != *token
+                || chrysus.strategy.duration.resolution != *resolution
+                || !chrysus.strategy.closed_bars
+            {
+                continue;
+            }
+            let sample = self
+                .get_history(index)
+                .and_then(|samples| samples.iter().rev().find(|s| s.seconds() == open))
+                .cloned();
+            if let Some(sample) = sample {
+                self.step_forward_test(index, &sample);
+            }
+        }
+    }
+
+    /// Orders of the rebalancing mode, if a rebalance is due.
+    fn rebalance(&mut self) -> Vec<Order> {
+        let prices: HashMap<Token, f64> = self
+            .ticks
+            .iter()
+            .map(|(token, tick)| (token.clone(), tick.price))
+            .collect();
+        let orders = match &mut self.rebalancer {
+            Some(rebalancer) => {
+                rebalancer.orders(Date::now().timestamp(), &self.balance, &prices, 1.0)
+            }
+            None => Vec::new(),
+        };
+        orders
+            .into_iter()
+            .filter(|order| match self.check_order(order) {
+                Ok(()) => true,
+                Err(e) => {
+                    ERROR!("rebalance {:?}", e);
+                    false
+                }
+            })
+            .collect()
+    }
+
+    /// Places the market buys of the due DCA plans.
+    fn run_dca(&mut self) {
+        for plan in self.dca.due(Date::now().timestamp()) {
+            let (order, result) =
+                match (self.last_price(&plan.token), self.symbols.get(&plan.token)) {
+                    (Some(price), Some(info)) => {
+                        let order = plan.order(price, |q| info.round_quantity(q));
+                        let result = info
+                            .check_order(price, order.quantity)
+                            .and_then(|_| self.check_order(&order))
+                            .and_then(|_| self.wallet.create_order(&order));
+                        (order, result)
+                    }
+                    (price, _) => (
+                        plan.order(price.unwrap_or_default(), |q| q),
+                        Err(DiError::Message(String::from("no price or exchange info"))),
+                    ),
+                };
+            let purchase = match result {
+                Ok(id) => {
+                    INFO!(
+                        "dca #{} bought {:.6} {} @ ~{}",
+                        plan.id,
+                        order.quantity,
+                        order.token.name(),
+                        order.price
+                    );
+                    DcaPurchase {
+                        plan: plan.id,
+                        order,
+                        order_id: Some(id),
+                        error: None,
+                    }
+                }
+                Err(e) => {
+                    ERROR!("dca #{} {}: {:?}", plan.id, plan.token.name(), e);
+                    DcaPurchase {
+                        plan: plan.id,
+                        order,
+                        order_id: None,
+                        error: Some(format!("{:?}", e)),
+                    }
+                }
+            };
+            self.dca.record(purchase);
+        }
+    }
+
     fn update_ticks(&mut self, ticks: Vec<MarketTick>) {
         for tick in ticks {
             if let Some(t) = self.ticks.get_mut(&tick.token) {
@@ -162,22 +1493,60 @@ impl Midas {
 
     pub fn touch(&mut self) -> Vec<MidasEvent> {
         let mut events: Vec<MidasEvent> = Vec::new();
-        for event in self.market.get_events() {
+        let mut market_events = self.market.get_events();
+        market_events.extend(self.stocks.get_events());
+        for simulation in self.simulations.values_mut() {
+            market_events.extend(simulation.get_events());
+        }
+        let mut closed: Vec<(Token, TimeUnit, u64)> = Vec::new();
+        for event in market_events {
             match event {
                 MarketEvent::KLine((token, sample)) => {
-                    for (index, t) in self.hesperides.iter().enumerate() {
-                        if t.token == token {
-                            if let Err(e) = self.market.append(&t.token, &sample) {
-                                ERROR!("{:?}", e);
-                            } else {
-                                if sample.resolution == t.strategy.duration.resolution {
-                                    events.push(MidasEvent::KLineUpdate(index));
-                                }
-                            }
+                    if !self.hesperides.iter().any(|t| t.token == token) && !self.is_leg(&token) {
+                        continue;
+                    }
+                    for alert in self.alerts.check(&token, sample.low, sample.high) {
+                        events.push(MidasEvent::Alert(alert));
+                    }
+                    if let Err(e) = self.history_mut(&token).append(&token, &sample) {
+                        ERROR!("{:?}", e);
+                        continue;
+                    }
+                    let updated: Vec<usize> = self
+                        .hesperides
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, t)| {
+                            t.token == token && sample.resolution == t.strategy.duration.resolution
+                        })
+                        .map(|(index, _)| index)
+                        .collect();
+                    // simulated bars don't follow the clock
+                    let clocked = !self.simulations.contains_key(&token);
+                    if clocked {
+                        if let Some(open) =
+                            self.bar_clock
+                                .observe(&token, &sample.resolution, sample.seconds())
+                        {
+                            closed.push((token.clone(), sample.resolution, open));
+                        }
+                    }
+                    for index in updated {
+                        events.push(MidasEvent::KLineUpdate(index));
+                        if !(clocked && self.hesperides[index].strategy.closed_bars) {
+                            self.step_forward_test(index, &sample);
+                        }
+                    }
+                }
+                MarketEvent::Ticks(ticks) => {
+                    for tick in &ticks {
+                        for alert in self.alerts.check(&tick.token, tick.price, tick.price) {
+                            events.push(MidasEvent::Alert(alert));
                         }
                     }
+                    self.update_ticks(ticks);
                 }
-                MarketEvent::Ticks(ticks) => self.update_ticks(ticks),
+                MarketEvent::Fill(fill) => self.handle_fill(fill),
                 MarketEvent::OrderBook(book) => {
                     let token = book.token.clone();
                     self.books.insert(token.clone(), book);
@@ -192,8 +1561,99 @@ impl Midas {
                 }
             };
         }
+        closed.extend(self.bar_clock.poll(self.exchange.server_now()));
+        for (token, resolution, open) in closed {
+            self.close_bar(&token, &resolution, open);
+            events.push(MidasEvent::BarClosed(token, resolution));
+        }
+        let orders = self.rebalance();
+        if !orders.is_empty() {
+            events.push(MidasEvent::Rebalance(orders));
+        }
+        self.run_dca();
+        self.refresh_history(&mut events);
+        self.update_warmup();
+        if Date::now().timestamp() - self.balances.requested >= self.balance_interval {
+            self.balances
+                .refresh(&self.wallet.account, Date::now().timestamp());
+        }
+        self.poll_balance();
+        self.update_clock();
+        if let Err(e) = self.symbols.poll() {
+            ERROR!("exchange info {:?}", e);
+        }
+        let since_symbols = Date::now().timestamp() - self.symbols.requested;
+        if since_symbols >= SYMBOLS_INTERVAL
+            || (self.symbols.has_unknown() && since_symbols >= UNKNOWN_SYMBOLS_INTERVAL)
+        {
+            self.symbols.refresh(Date::now().timestamp());
+        }
+        if Date::now().timestamp() - self.last_open_orders >= self.open_orders_interval {
+            self.cancel_stale_orders();
+            self.refresh_open_orders();
+        }
+        if Date::now().timestamp() - self.last_watchdog >= WATCHDOG_INTERVAL {
+            self.last_watchdog = Date::now().timestamp();
+            self.watch_streams();
+        }
         events
     }
 
+    /// Restarts the kline and book streams silent for longer than their
+    /// resolution allows. A dead websocket stalls without an error.
+    fn watch_streams(&mut self) {
+        let now = Date::now().timestamp_millis();
+        let stats: HashMap<String, StreamStats> =
+            self.market.streams.snapshot().into_iter().collect();
+        let silent = |stream: &str, limit: i64| {
+            stats
+                .get(stream)
+                .and_then(|s| s.idle(now))
+                .filter(|idle| *idle > limit)
+        };
+        let mut klines: Vec<(Token, TimeUnit)> = Vec::new();
+        let mut books: Vec<Token> = Vec::new();
+        for chrysus in &self.hesperides {
+            if !chrysus.token.is_pair() || self.simulations.contains_key(&chrysus.token) {
+                continue;
+            }
+            let resolution = chrysus.strategy.duration.resolution;
+            let mut tokens = vec![chrysus.token.clone()];
+            tokens.extend(chrysus.strategy.tokens());
+            for token in tokens {
+                if !klines.contains(&(token.clone(), resolution)) {
+                    klines.push((token, resolution));
+                }
+            }
+            if !books.contains(&chrysus.token) {
+                books.push(chrysus.token.clone());
+            }
+        }
+        for (token, resolution) in klines {
+            let stream = kline_stream(&token, &resolution);
+            if let Some(idle) = silent(&stream, kline_idle_limit(&resolution)) {
+                ERROR!(
+                    "watchdog: {} silent for {}s, restarting",
+                    stream,
+                    idle / 1000
+                );
+                self.market.stop_service(&stream);
+                self.market.kline_service(&token, &resolution);
+            }
+        }
+        for token in books {
+            let stream = depth_stream(&token);
+            if let Some(idle) = silent(&stream, BOOK_IDLE_LIMIT) {
+                ERROR!(
+                    "watchdog: {} silent for {}s, restarting",
+                    stream,
+                    idle / 1000
+                );
+                self.market.stop_service(&stream);
+                self.market.order_book_service(&token);
+            }
+        }
+    }
+
     fn _submit(&mut self, _orders: Vec<Order>) {}
 }
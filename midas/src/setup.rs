@@ -0,0 +1,63 @@
+use crate::config::Config;
+use crate::midas::STARTER_PAIR;
+use dionysus::counselor::match_oracle_from_text;
+use dionysus::finance::Token;
+use dionysus::strategy::Chrysus;
+use dionysus::time::TimeUnit;
+use std::io::{self, Write};
+
+/// Answer to `question`, `default` when empty or stdin is closed.
+fn ask(question: &str, default: &str) -> String {
+    print!("{} [{}]: ", question, default);
+    let _ = io::stdout().flush();
+    let mut answer = String::new();
+    match io::stdin().read_line(&mut answer) {
+        Ok(_) if !answer.trim().is_empty() => answer.trim().to_string(),
+        _ => default.to_string(),
+    }
+}
+
+/// First run without a state file: asks on the terminal for a starter pair,
+/// its counselors and resolution, and writes the state with it. Empty answers
+/// keep the starter pair and the configured default strategy.
+pub fn first_run(path: &str, config: &Config) {
+    println!("{} not found, setting up a starter pair.", path);
+    let symbol = ask("symbol", STARTER_PAIR.0).to_uppercase();
+    let currency = ask("currency", STARTER_PAIR.1).to_uppercase();
+    let mut strategy = config.strategy.strategy();
+    let default_counselors = config.strategy.counselors.join("; ");
+    let counselors = ask("counselors, separated by ';'", &default_counselors);
+    if counselors != default_counselors {
+        let parsed: Vec<_> = counselors
+            .split(';')
+            .map(|text| {
+                let words: Vec<&str> = text.split_whitespace().collect();
+                match_oracle_from_text(&words)
+            })
+            .collect();
+        if parsed.iter().all(|c| c.is_some()) {
+            strategy.counselors = parsed.into_iter().flatten().collect();
+        } else {
+            println!("invalid counselors, keeping {}", default_counselors);
+        }
+    }
+    let resolution = ask("resolution", &strategy.duration.resolution.name());
+    match TimeUnit::from_name(&resolution) {
+        TimeUnit::Unit(_) => println!(
+            "invalid resolution, keeping {}",
+            strategy.duration.resolution.name()
+        ),
+        resolution => strategy.duration.resolution = resolution,
+    }
+    let mut chrysus = Chrysus::new(&Token::pair(&symbol, &currency));
+    chrysus.strategy = strategy;
+    let result = std::fs::File::create(path)
+        .map_err(|e| e.to_string())
+        .and_then(|file| {
+            serde_json::to_writer_pretty(file, &vec![chrysus]).map_err(|e| e.to_string())
+        });
+    match result {
+        Ok(()) => println!("{} written.", path),
+        Err(e) => eprintln!("{}: {}", path, e),
+    }
+}
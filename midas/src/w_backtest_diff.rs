@@ -0,0 +1,165 @@
+use crate::common;
+use dionysus::report::{compare_metrics, Report};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style},
+    symbols,
+    text::Line,
+    widgets::{Axis, Cell, Chart, Dataset, GraphType, Paragraph, Row, Table, Widget},
+};
+
+const A_COLOR: Color = Color::Yellow;
+const B_COLOR: Color = Color::Cyan;
+
+/// Side by side comparison of a pinned backtest (A) and the latest one (B).
+#[derive(Default)]
+pub struct BacktestDiffWindow {
+    a: Option<Report>,
+    b: Option<Report>,
+}
+
+impl BacktestDiffWindow {
+    pub fn pin(&mut self, report: Report) {
+        self.a = Some(report);
+    }
+
+    pub fn update(&mut self, report: Report) {
+        self.b = Some(report);
+    }
+
+    /// Parameters that differ between runs as (name, a, b).
+    fn parameter_changes(a: &Report, b: &Report) -> Vec<(String, String, String)> {
+        let mut changes = Vec::new();
+        let n = a.parameters.len().max(b.parameters.len());
+        for i in 0..n {
+            let pa = a.parameters.get(i);
+            let pb = b.parameters.get(i);
+            if pa != pb {
+                let name = pa.or(pb).map(|(k, _)| k.clone()).unwrap_or_default();
+                changes.push((
+                    name,
+                    pa.map(|(_, v)| v.clone()).unwrap_or_default(),
+                    pb.map(|(_, v)| v.clone()).unwrap_or_default(),
+                ));
+            }
+        }
+        changes
+    }
+
+    fn render_metrics(a: &Report, b: &Report, area: Rect, buf: &mut Buffer) {
+        let header =
+            Row::new(vec!["", "A", "B", "B - A"]).style(Style::default().fg(common::NORMAL_FG));
+        let mut rows: Vec<Row> = compare_metrics(&a.summary, &b.summary)
+            .into_iter()
+            // risk metrics neither run could compute
+            .filter(|(_, va, vb, _)| !(va.is_nan() && vb.is_nan()))
+            .map(|(name, va, vb, delta)| {
+                // trade counts and beta are neither better nor worse, less drawdown is better
+                let better = match name {
+                    "orders" | "buys" | "sells" | "buy and hold %" | "beta" => None,
+                    "max drawdown %" => Some(delta < 0.0),
+                    _ => Some(delta > 0.0),
+                };
+                let color = match better {
+                    Some(_) if delta == 0.0 => common::NORMAL_FG,
+                    Some(true) => common::theme().profit,
+                    Some(false) => common::theme().loss,
+                    None => common::NORMAL_FG,
+                };
+                Row::new(vec![
+                    Cell::from(name),
+                    Cell::from(format!("{:.2}", va)),
+                    Cell::from(format!("{:.2}", vb)),
+                    Cell::from(format!("{:+.2}", delta)).style(Style::default().fg(color)),
+                ])
+            })
+            .collect();
+        for (name, pa, pb) in BacktestDiffWindow::parameter_changes(a, b) {
+            rows.push(Row::new(vec![
+                Cell::from(name),
+                Cell::from(pa),
+                Cell::from(pb),
+                Cell::from("changed"),
+            ]));
+        }
+        let widths = [
+            Constraint::Length(16),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Length(12),
+        ];
+        Table::new(rows, widths).header(header).render(area, buf);
+    }
+
+    fn render_equity(a: &Report, b: &Report, area: Rect, buf: &mut Buffer) {
+        let to_points = |report: &Report| -> Vec<(f64, f64)> {
+            report.equity.iter().map(|(t, v)| (*t as f64, *v)).collect()
+        };
+        let points_a = to_points(a);
+        let points_b = to_points(b);
+        let all = points_a.iter().chain(points_b.iter());
+        let (mut x_min, mut x_max, mut y_min, mut y_max) = (f64::MAX, f64::MIN, f64::MAX, f64::MIN);
+        for (x, y) in all {
+            x_min = x_min.min(*x);
+            x_max = x_max.max(*x);
+            y_min = y_min.min(*y);
+            y_max = y_max.max(*y);
+        }
+        if x_min > x_max {
+            return;
+        }
+        let datasets = vec![
+            Dataset::default()
+                .name("A")
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(A_COLOR))
+                .data(&points_a),
+            Dataset::default()
+                .name("B")
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(B_COLOR))
+                .data(&points_b),
+        ];
+        Chart::new(datasets)
+            .x_axis(Axis::default().bounds([x_min, x_max]))
+            .y_axis(
+                Axis::default()
+                    .bounds([y_min, y_max])
+                    .labels([format!("{:.2}", y_min), format!("{:.2}", y_max)]),
+            )
+            .render(area, buf);
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let block = common::block("BACKTEST A/B");
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let (a, b) = match (&self.a, &self.b) {
+            (Some(a), Some(b)) => (a, b),
+            (None, _) => {
+                Paragraph::new("pin a backtest first with: pin").render(inner, buf);
+                return;
+            }
+            (Some(_), None) => {
+                Paragraph::new("no backtest to compare, run: backtest").render(inner, buf);
+                return;
+            }
+        };
+        let [title_area, metrics_area, equity_area] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+        ])
+        .areas(inner);
+        Paragraph::new(vec![
+            Line::styled(format!("A: {}", a.title), A_COLOR),
+            Line::styled(format!("B: {}", b.title), B_COLOR),
+        ])
+        .render(title_area, buf);
+        BacktestDiffWindow::render_metrics(a, b, metrics_area, buf);
+        BacktestDiffWindow::render_equity(a, b, equity_area, buf);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::common;
+use dionysus::finance::Token;
+use dionysus::report::{BacktestMetrics, Report};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::Style,
+    widgets::{Cell, Row, Table, Widget},
+};
+
+/// Order of the batch backtest ranking, cycled with 'g'.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum RankKey {
+    #[default]
+    Return,
+    /// Return over buy and hold.
+    Excess,
+    Sharpe,
+    Drawdown,
+}
+
+impl RankKey {
+    const ALL: [RankKey; 4] = [
+        RankKey::Return,
+        RankKey::Excess,
+        RankKey::Sharpe,
+        RankKey::Drawdown,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RankKey::Return => "return",
+            RankKey::Excess => "excess return",
+            RankKey::Sharpe => "sharpe",
+            RankKey::Drawdown => "drawdown",
+        }
+    }
+
+    fn next(&self) -> RankKey {
+        let index = RankKey::ALL.iter().position(|key| key == self).unwrap();
+        RankKey::ALL[(index + 1) % RankKey::ALL.len()]
+    }
+
+    /// Higher is better, runs without a sharpe come last.
+    fn score(&self, metrics: &BacktestMetrics) -> f64 {
+        match self {
+            RankKey::Return => metrics.return_pct,
+            RankKey::Excess => metrics.return_pct - metrics.buy_and_hold,
+            RankKey::Sharpe => metrics.sharpe.unwrap_or(f64::MIN),
+            RankKey::Drawdown => -metrics.max_drawdown,
+        }
+    }
+}
+
+/// One strategy backtested on every loaded symbol, best first.
+#[derive(Default)]
+pub struct BacktestRankWindow {
+    /// Strategy and period of the runs.
+    subject: String,
+    key: RankKey,
+    runs: Vec<(Token, BacktestMetrics)>,
+}
+
+impl BacktestRankWindow {
+    pub fn update(&mut self, subject: String, reports: &[(Token, Report)]) {
+        self.subject = subject;
+        self.runs = reports
+            .iter()
+            .map(|(token, report)| (token.clone(), report.summary.clone()))
+            .collect();
+        self.sort();
+    }
+
+    pub fn next_key(&mut self) {
+        self.key = self.key.next();
+        self.sort();
+    }
+
+    fn sort(&mut self) {
+        let key = self.key;
+        self.runs
+            .sort_by(|(_, a), (_, b)| key.score(b).total_cmp(&key.score(a)));
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let theme = common::theme();
+        let title = format!("BACKTEST ALL {} by {}", self.subject, self.key.name());
+        let header = Row::new(vec![
+            "#", "symbol", "return", "buy&hold", "excess", "drawdown", "sharpe", "orders",
+        ])
+        .style(Style::default().fg(common::NORMAL_FG));
+        let rows: Vec<Row> = self
+            .runs
+            .iter()
+            .enumerate()
+            .map(|(i, (token, m))| {
+                let excess = m.return_pct - m.buy_and_hold;
+                let color = |v: f64| match v {
+                    v if v > 0.0 => Style::default().fg(theme.profit),
+                    v if v < 0.0 => Style::default().fg(theme.loss),
+                    _ => Style::default(),
+                };
+                Row::new(vec![
+                    Cell::from((i + 1).to_string()),
+                    Cell::from(token.name()),
+                    Cell::from(common::format_percent(m.return_pct)).style(color(m.return_pct)),
+                    Cell::from(common::format_percent(m.buy_and_hold)),
+                    Cell::from(common::format_percent(excess)).style(color(excess)),
+                    Cell::from(common::format_percent(m.max_drawdown)),
+                    Cell::from(m.sharpe.map(|s| format!("{:.2}", s)).unwrap_or_default()),
+                    Cell::from(m.orders.to_string()),
+                ])
+            })
+            .collect();
+        let widths = [
+            Constraint::Length(4),
+            Constraint::Fill(2),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+        ];
+        Table::new(rows, widths)
+            .header(header)
+            .block(common::block(title.as_str()))
+            .render(area, buf);
+    }
+}
@@ -0,0 +1,55 @@
+use crate::common;
+use crate::common::ListWindow;
+use dionysus::stats::{breakdown, BreakdownKey, TaggedTrip};
+use ratatui::text::Line;
+
+#[derive(Default)]
+pub struct BreakdownWindow {
+    list_window: ListWindow<String>,
+    key: BreakdownKey,
+    trips: Vec<TaggedTrip>,
+}
+
+impl BreakdownWindow {
+    pub fn update(&mut self, trips: Vec<TaggedTrip>) {
+        self.trips = trips;
+        self.refresh();
+    }
+
+    pub fn set_key(&mut self, key: BreakdownKey) {
+        self.key = key;
+        self.refresh();
+    }
+
+    /// Groups the round trips by the next key, cycled with 'g'.
+    pub fn next_key(&mut self) {
+        self.set_key(self.key.next());
+    }
+
+    fn refresh(&mut self) {
+        self.list_window.items = breakdown(&self.trips, self.key)
+            .iter()
+            .map(|row| {
+                format!(
+                    "{:<24} {:>5} {:>6.1}% {:>+12.4}",
+                    row.group,
+                    row.trips,
+                    row.win_rate(),
+                    row.profit
+                )
+            })
+            .collect();
+        self.list_window.items.insert(
+            0,
+            format!("{:<24} {:>5} {:>7} {:>12}", "", "trips", "wins", "profit"),
+        );
+    }
+
+    pub fn render(&mut self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
+        let title = format!("BREAKDOWN by {} (forward tests, UTC)", self.key.name());
+        let block = common::block(title.as_str());
+        self.list_window.render(area, buf, block, |line| {
+            Line::styled(format!(" {}", line), common::NORMAL_FG)
+        });
+    }
+}
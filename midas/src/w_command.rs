@@ -2,15 +2,25 @@ use crate::common;
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
+    style::Style,
+    text::Line,
     widgets::{Paragraph, Widget},
 };
 
+/// Outcome of a command: what it did, or why it did nothing or only part of it.
+/// Empty messages are not shown.
+pub type CommandResult = Result<String, String>;
+
 #[derive(Default)]
 pub struct CommandInput {
     /// Current value of the input box
     input: String,
     /// Position of cursor in the editor area.
     character_index: usize,
+    /// Progress of running tasks, shown in the title.
+    status: String,
+    /// Outcome of the last command, shown under the input.
+    result: Option<CommandResult>,
 }
 
 impl CommandInput {
@@ -22,6 +32,14 @@ impl CommandInput {
         self.input.clone()
     }
 
+    pub fn set_status(&mut self, status: String) {
+        self.status = status;
+    }
+
+    pub fn set_result(&mut self, result: CommandResult) {
+        self.result = Some(result);
+    }
+
     pub fn cursor_position(&self) -> u16 {
         self.character_index as u16
     }
@@ -85,8 +103,18 @@ impl CommandInput {
     }
 
     pub fn draw(&self, area: Rect, buf: &mut Buffer, focus: bool) {
-        let input = Paragraph::new(self.input.as_str())
-            .block(common::block("COMMAND").border_style(common::focus_style(focus)));
+        let block = common::block("COMMAND").border_style(common::focus_style(focus));
+        let block = match self.status.is_empty() {
+            true => block,
+            false => block.title(Line::from(self.status.as_str()).right_aligned()),
+        };
+        let theme = common::theme();
+        let result = match &self.result {
+            Some(Ok(message)) => Line::styled(message.as_str(), Style::default().fg(theme.profit)),
+            Some(Err(message)) => Line::styled(message.as_str(), Style::default().fg(theme.loss)),
+            None => Line::default(),
+        };
+        let input = Paragraph::new(vec![Line::from(self.input.as_str()), result]).block(block);
         input.render(area, buf);
     }
 }
@@ -0,0 +1,61 @@
+use crate::common;
+use dionysus::analytics::CorrelationMatrix;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::Style,
+    widgets::{Cell, Row, Table, Widget},
+};
+
+#[derive(Default)]
+pub struct CorrelationWindow {
+    matrix: CorrelationMatrix,
+    window: usize,
+}
+
+impl CorrelationWindow {
+    pub fn update(&mut self, matrix: CorrelationMatrix, window: usize) {
+        self.matrix = matrix;
+        self.window = window;
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let title = format!("CORRELATION (last {} returns)", self.window);
+        let header = Row::new(
+            std::iter::once(Cell::from(""))
+                .chain(
+                    self.matrix
+                        .tokens
+                        .iter()
+                        .map(|token| Cell::from(token.get_symbol())),
+                )
+                .collect::<Vec<Cell>>(),
+        );
+        let rows: Vec<Row> = self
+            .matrix
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, values)| {
+                let mut cells = vec![Cell::from(self.matrix.tokens[i].get_symbol())];
+                for value in values {
+                    let text = if value.is_nan() {
+                        String::from("  -")
+                    } else {
+                        format!("{: .2}", value)
+                    };
+                    cells.push(
+                        Cell::from(text)
+                            .style(Style::default().bg(common::heat_color(*value, 1.0))),
+                    );
+                }
+                Row::new(cells)
+            })
+            .collect();
+        let widths = vec![Constraint::Length(10); self.matrix.tokens.len() + 1];
+        Table::new(rows, widths)
+            .header(header.style(Style::default().fg(common::NORMAL_FG)))
+            .block(common::block(title.as_str()))
+            .render(area, buf);
+    }
+}
@@ -0,0 +1,61 @@
+use crate::common;
+use crate::common::ListWindow;
+use dionysus::dca::DcaScheduler;
+use dionysus::time::Date;
+use ratatui::text::Line;
+
+/// Upcoming purchases listed.
+const UPCOMING: usize = 10;
+/// Past purchases listed, latest first.
+const PAST: usize = 20;
+
+#[derive(Default)]
+pub struct DcaWindow {
+    list_window: ListWindow<String>,
+}
+
+impl DcaWindow {
+    pub fn update(&mut self, dca: &DcaScheduler) {
+        let mut lines = vec![String::from("PLANS")];
+        lines.extend(dca.plans.iter().map(|plan| format!("  {}", plan.name())));
+        lines.push(String::from("UPCOMING"));
+        for (time, plan) in dca.upcoming(UPCOMING) {
+            lines.push(format!(
+                "  {}  #{} {} {}",
+                Date::from_timestamp(time as u64).pretty_string(),
+                plan.id,
+                plan.token.name(),
+                common::format_value(plan.notional)
+            ));
+        }
+        lines.push(String::from("PAST"));
+        for purchase in dca.purchases.iter().rev().take(PAST) {
+            let order = &purchase.order;
+            let result = match (&purchase.order_id, &purchase.error) {
+                (Some(id), _) => format!(
+                    "{} @ ~{} (order {})",
+                    common::format_quantity(&order.token, order.quantity),
+                    common::format_price(&order.token, order.price),
+                    id
+                ),
+                (None, Some(e)) => format!("failed: {}", e),
+                (None, None) => String::new(),
+            };
+            lines.push(format!(
+                "  {}  #{} {} {}",
+                order.date.pretty_string(),
+                purchase.plan,
+                order.token.name(),
+                result
+            ));
+        }
+        self.list_window.items = lines;
+    }
+
+    pub fn render(&mut self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
+        let block = common::block("DCA (UTC)");
+        self.list_window.render(area, buf, block, |line| {
+            Line::styled(format!(" {}", line), common::NORMAL_FG)
+        });
+    }
+}
@@ -8,19 +8,25 @@ use ratatui::{
 };
 
 use crate::{
-    common, g_book::BookGraph, g_common::ChartDomain, g_element::GraphElement,
-    g_indicators::IndicatorsGraph, g_samples::SamplesGraph, g_strategy::StrategyGraph,
+    common, g_alerts::AlertsGraph, g_book::BookGraph, g_common::ChartDomain,
+    g_element::GraphElement, g_heatmap::BookHeatmap, g_indicators::IndicatorsGraph,
+    g_liquidation::LiquidationGraph, g_orders::OrdersGraph, g_samples::SamplesGraph,
+    g_strategy::StrategyGraph, g_trades::TradesGraph, g_volume_profile::VolumeProfileGraph,
 };
 use dionysus::{
     backtest::Backtest,
-    finance::Sample,
+    finance::{Sample, Token},
     indicators::{Indicator, IndicatorSource},
+    renko::BrickSize,
     strategy::Strategy,
-    time::TimeWindow,
+    time::{countdown_string, Date, TimeWindow},
     INFO,
 };
 use slog::slog_info;
 
+/// Brick size of the renko view toggled from the chart.
+const RENKO_BRICK: BrickSize = BrickSize::Atr(14);
+
 pub struct GraphView {
     pub book_w: BookGraph,
     pub candle_w: ChartDomain,
@@ -30,7 +36,22 @@ pub struct GraphView {
     pub samples: SamplesGraph,
     pub strategy: StrategyGraph,
     pub custom_indicators: IndicatorsGraph,
+    pub alerts: AlertsGraph,
+    pub heatmap: BookHeatmap,
+    pub volume_profile: VolumeProfileGraph,
+    pub liquidations: LiquidationGraph,
+    pub orders: OrdersGraph,
+    pub trades: TradesGraph,
     pub time_window: TimeWindow,
+    /// Full history, the graphs may only see part of it while scrubbing.
+    history: Vec<Sample>,
+    /// Number of bars visible in scrub mode.
+    playhead: Option<usize>,
+    /// Server minus local clock (ms), for the candle countdown.
+    pub clock_offset: i64,
+    /// Prices are labeled as percent change from the first visible bar, or
+    /// from the crosshair bar when it is shown.
+    pub percent: bool,
 }
 
 impl Default for GraphView {
@@ -44,33 +65,173 @@ impl Default for GraphView {
             samples: SamplesGraph::default(),
             strategy: StrategyGraph::default(),
             custom_indicators: IndicatorsGraph::default(),
+            alerts: AlertsGraph::default(),
+            heatmap: BookHeatmap::default(),
+            volume_profile: VolumeProfileGraph::default(),
+            liquidations: LiquidationGraph::default(),
+            orders: OrdersGraph::default(),
+            trades: TradesGraph::default(),
             time_window: TimeWindow::default(),
+            history: Vec::new(),
+            playhead: None,
+            clock_offset: 0,
+            percent: false,
         }
     }
 }
 
 impl GraphView {
     pub fn set_data(&mut self, samples: &[Sample]) {
-        self.samples.update(samples);
+        self.history = samples.to_vec();
         self.time_window.resolution = samples[0].resolution.clone();
         self.time_window.count = samples.len() as i64;
-        self.strategy.compute(samples);
-        self.custom_indicators.compute(samples);
-        self.book_w.x_pos = samples.len() as f64;
         self.candle_w.timestamp = samples[0].timestamp;
         self.candle_w.time_step = samples[0].resolution.num_seconds() as u64 * 1000;
+        self.update_visible();
+    }
+
+    /// Feeds the graphs with the history up to the playhead.
+    fn update_visible(&mut self) {
+        let n = match self.playhead {
+            Some(n) => n.clamp(1, self.history.len()),
+            None => self.history.len(),
+        };
+        let visible = &self.history[..n];
+        self.samples.update(visible);
+        self.strategy.compute(visible);
+        self.trades.playhead = self.playhead.map(|_| visible[n - 1].timestamp);
+        self.custom_indicators.compute(visible);
+        self.volume_profile.compute(visible);
+        self.book_w.x_pos = n as f64;
+        self.heatmap.x_pos = n as f64;
+    }
+
+    /// Shows/hides the crosshair, starting at the last close.
+    pub fn toggle_crosshair(&mut self) {
+        self.alerts.crosshair = match self.alerts.crosshair {
+            Some(_) => None,
+            None => self.samples.data.last().map(|s| s.close),
+        };
+        self.alerts.crosshair_bar = self.samples.data.len().saturating_sub(1);
+    }
+
+    /// Moves the crosshair `bars` bars, backwards if negative.
+    pub fn step_crosshair(&mut self, bars: i64) {
+        let last = self.samples.data.len().saturating_sub(1) as i64;
+        self.alerts.crosshair_bar =
+            (self.alerts.crosshair_bar as i64 + bars).clamp(0, last) as usize;
+    }
+
+    /// Timestamp of the bar under the crosshair.
+    pub fn crosshair_timestamp(&self) -> Option<u64> {
+        self.alerts.crosshair?;
+        self.samples
+            .data
+            .get(self.alerts.crosshair_bar)
+            .map(|s| s.timestamp)
+    }
+
+    pub fn crosshair(&self) -> Option<f64> {
+        self.alerts.crosshair
+    }
+
+    /// Moves the crosshair by a fraction of the visible price range.
+    pub fn move_crosshair(&mut self, dy: f64) {
+        if let Some(price) = self.alerts.crosshair {
+            self.alerts.crosshair = Some(price + self.candle_w.size(1) * 0.01 * dy);
+        }
+    }
+
+    pub fn toggle_percent(&mut self) {
+        self.percent = !self.percent;
+    }
+
+    /// Switches between candles and renko bricks of the default size.
+    pub fn toggle_renko(&mut self) {
+        let size = match self.samples.renko() {
+            Some(_) => None,
+            None => Some(RENKO_BRICK),
+        };
+        self.set_renko(size);
+    }
+
+    /// Renko bricks of `size` replace the candles, None brings them back.
+    /// Overlays placed by bar are hidden while bricks are shown.
+    pub fn set_renko(&mut self, size: Option<BrickSize>) {
+        self.samples.set_renko(size);
+        if !self.samples.data.is_empty() {
+            self.update_bounds();
+        }
+    }
+
+    /// Close the percent view is relative to, None when prices are shown.
+    fn percent_base(&self) -> Option<f64> {
+        if !self.percent || self.samples.data.is_empty() {
+            return None;
+        }
+        let index = match self.alerts.crosshair {
+            Some(_) => self.alerts.crosshair_bar,
+            None => (self.candle_w.bounds[0][0] / self.candle_w.dx)
+                .ceil()
+                .max(0.0) as usize,
+        };
+        let last = self.samples.data.len() - 1;
+        Some(self.samples.data[index.min(last)].close).filter(|close| *close > 0.0)
+    }
+
+    pub fn is_scrubbing(&self) -> bool {
+        self.playhead.is_some()
+    }
+
+    /// Enters scrub mode at the last bar, or leaves it showing all data.
+    pub fn toggle_scrub(&mut self) {
+        self.playhead = match self.playhead {
+            Some(_) => None,
+            None => Some(self.history.len()),
+        };
+        if !self.history.is_empty() {
+            self.update_visible();
+        }
+    }
+
+    /// Moves the playhead `bars` bars, backwards if negative.
+    pub fn step_playhead(&mut self, bars: i64) {
+        if let Some(n) = self.playhead {
+            let n = (n as i64 + bars).clamp(1, self.history.len() as i64);
+            self.playhead = Some(n as usize);
+            self.update_visible();
+        }
     }
 
     pub fn add_indicator(&mut self, indicator: &Indicator) {
         self.custom_indicators.add_indicator(indicator);
     }
 
+    /// Removes the custom indicator at `index` or named `name` (see
+    /// IndicatorsGraph::position).
+    pub fn remove_indicator(&mut self, index_or_name: &str) -> Option<Indicator> {
+        let index = match index_or_name.parse::<usize>() {
+            Ok(index) => Some(index),
+            Err(_) => self.custom_indicators.position(index_or_name),
+        }?;
+        self.custom_indicators.remove_indicator(index)
+    }
+
+    /// Replaces a custom indicator, recomputing its curve.
+    pub fn set_indicator(&mut self, index: usize, indicator: &Indicator) {
+        self.custom_indicators.set_indicator(index, indicator);
+        if !self.samples.data.is_empty() {
+            self.custom_indicators.compute(&self.samples.data);
+        }
+    }
+
     pub fn set_strategy(&mut self, strategy: &Strategy) {
         self.strategy.set_strategy(strategy);
     }
 
-    pub fn set_backtest(&mut self, backtest: &Backtest) {
-        self.strategy.set_backtest(backtest);
+    /// Backtest orders of `token` are drawn with their round trips.
+    pub fn set_backtest(&mut self, backtest: &Backtest, token: &Token) {
+        self.trades.set_backtest(backtest, token);
     }
 
     pub fn reset_camera(&mut self) {
@@ -80,11 +241,14 @@ impl GraphView {
     fn update_bounds(&mut self) {
         self.candle_w.dx = 2.0;
         self.candle_w.bounds = self.samples.bounds();
+        // room for the series drawn ahead of the last bar
+        self.candle_w.bounds[0][1] += self.custom_indicators.lead() as f64;
         self.candle_w.bounds[0][0] *= self.candle_w.dx;
         self.candle_w.bounds[0][1] *= self.candle_w.dx;
 
         self.volume_w.dx = 2.0;
         self.volume_w.bounds = self.samples.bounds();
+        self.volume_w.bounds[0][1] += self.custom_indicators.lead() as f64;
         self.volume_w.bounds[0][0] *= self.volume_w.dx;
         self.volume_w.bounds[0][1] *= self.volume_w.dx;
         self.volume_w.bounds[1][0] = 0.0;
@@ -136,6 +300,30 @@ impl GraphView {
         title.push_str(self.candle_w.sample_count().to_string().as_str());
         title.push_str("@");
         title.push_str(self.samples.data[0].resolution.name().as_str());
+        if let (None, Some(last)) = (self.playhead, self.samples.data.last()) {
+            let now = (Date::now().timestamp() + self.clock_offset / 1000) as u64;
+            let remaining = last.resolution.time_to_close(last.seconds(), now);
+            title.push_str(format!(" closes in {}", countdown_string(remaining)).as_str());
+        }
+        if let (Some(n), Some(last)) = (self.playhead, self.samples.data.last()) {
+            title.push_str(
+                format!(
+                    " [scrub {}/{} {}]",
+                    n,
+                    self.history.len(),
+                    Date::from_timestamp(last.seconds()).pretty_string()
+                )
+                .as_str(),
+            );
+        }
+        let percent_base = self.percent_base();
+        if let Some(base) = percent_base {
+            title.push_str(format!(" [% from {}]", base).as_str());
+        }
+        let renko = self.samples.renko();
+        if let Some(size) = renko {
+            title.push_str(format!(" [renko {}]", size.name()).as_str());
+        }
         Canvas::default()
             .block(
                 common::block(title.as_str())
@@ -146,14 +334,39 @@ impl GraphView {
             .x_bounds(self.candle_w.bounds[0])
             .y_bounds(self.candle_w.bounds[1])
             .paint(|ctx| {
-                self.samples
+                self.liquidations
                     .draw(&self.candle_w, &IndicatorSource::Candle, ctx);
-                self.strategy
+                self.volume_profile
+                    .draw(&self.candle_w, &IndicatorSource::Candle, ctx);
+                if renko.is_none() {
+                    self.heatmap
+                        .draw(&self.candle_w, &IndicatorSource::Candle, ctx);
+                    for cloud in self.custom_indicators.clouds() {
+                        cloud.draw(&self.candle_w, &IndicatorSource::Candle, ctx);
+                    }
+                }
+                ctx.layer();
+                self.samples
                     .draw(&self.candle_w, &IndicatorSource::Candle, ctx);
-                self.custom_indicators
+                if renko.is_none() {
+                    self.strategy
+                        .draw(&self.candle_w, &IndicatorSource::Candle, ctx);
+                    self.trades
+                        .draw(&self.candle_w, &IndicatorSource::Candle, ctx);
+                    self.custom_indicators
+                        .draw(&self.candle_w, &IndicatorSource::Candle, ctx);
+                }
+                match percent_base {
+                    Some(base) => self.candle_w.draw_percent(ctx, base),
+                    None => self.candle_w.draw(ctx),
+                }
+                if renko.is_none() {
+                    self.book_w
+                        .draw(&self.candle_w, &IndicatorSource::Candle, ctx);
+                }
+                self.orders
                     .draw(&self.candle_w, &IndicatorSource::Candle, ctx);
-                self.candle_w.draw(ctx);
-                self.book_w
+                self.alerts
                     .draw(&self.candle_w, &IndicatorSource::Candle, ctx);
             })
             .render(candle_area, buf);
@@ -168,10 +381,12 @@ impl GraphView {
             .y_bounds(self.volume_w.bounds[1])
             .paint(|ctx| {
                 self.samples.draw_volume(&self.volume_w, ctx);
-                self.strategy
-                    .draw(&self.volume_w, &IndicatorSource::Volume, ctx);
-                self.custom_indicators
-                    .draw(&self.candle_w, &IndicatorSource::Volume, ctx);
+                if renko.is_none() {
+                    self.strategy
+                        .draw(&self.volume_w, &IndicatorSource::Volume, ctx);
+                    self.custom_indicators
+                        .draw(&self.candle_w, &IndicatorSource::Volume, ctx);
+                }
                 self.volume_w.draw(ctx);
             })
             .render(volume_area, buf);
@@ -18,16 +18,100 @@ impl HelpWindow {
             Line::from("o      : Open current oracle float window."),
             Line::from("ctrl+t : Iterate pairs."),
             Line::from("ctrl+o : Iterate pair oracles."),
+            Line::from("C      : Open/close correlation float window."),
+            Line::from("S      : Open/close seasonality float window."),
+            Line::from("G      : Open/close signal history float window."),
+            Line::from("I      : Edit chart indicators."),
+            Line::from("D      : Backtest A/B diff (pin the baseline with: pin)."),
+            Line::from("p      : Toggle chart scrub mode (left/right step bars)."),
+            Line::from("x      : Toggle chart crosshair (arrows move, A add alert)."),
+            Line::from("h      : Toggle chart order book heatmap."),
+            Line::from("%      : Toggle chart percent view (from first bar or crosshair)."),
+            Line::from("r      : Toggle chart renko bricks (atr 14, see renko)."),
+            Line::from("v      : Toggle chart volume profile (point of control highlighted)."),
+            Line::from("c      : Cycle wallet currency (USDT, BTC, EUR, BRL)."),
+            Line::from("N      : Open/close news headlines float window."),
+            Line::from("T      : Open/close daily trading statistics float window."),
+            Line::from(
+                "B      : Open/close the profit breakdown float window ('g' cycles the grouping).",
+            ),
+            Line::from(
+                "E      : Open/close stream diagnostics float window (rates, ages, reconnects).",
+            ),
+            Line::from("R      : Open/close recurring (DCA) purchases float window."),
+            Line::from("L      : Open/close the price ladder (up/down select, b/s limit order, x/X cancel rung/all, +/- step, c recenter)."),
+            Line::from("V      : Open/close the volatility cone of the current pair."),
+            Line::from("K      : Open/close the ranking of the last 'backtest all' ('g' cycles the order)."),
+            Line::from("O      : Open/close the open orders float window (up/down select, e edits the order)."),
             Line::from("a      : Enter command."),
+            Line::from("q      : Quit (lists open orders and positions first, c cancels them)."),
             Line::from(""),
             Line::from("COMMANDS".blue()),
             Line::from(""),
-            Line::from("load <symbol> <currency = usdt>"),
+            Line::from("load <symbol> <currency = usdt> [counselor...] [resolution [history]]"),
+            Line::from("loadres <symbol> <resolution> (another view, ctrl+o cycles)"),
+            Line::from("stock <symbol> (yahoo)"),
+            Line::from("adjust <raw|adjusted> (stock prices)"),
+            Line::from("sim <mu> <sigma> <start price> <resolution = 1h>"),
+            Line::from("sandbox <gbm|ou|jump> [speed = 60] (paper trades a synthetic copy of the pair)"),
+            Line::from("sandbox speed <minutes per second> (of the current sandbox)"),
             Line::from("graph <indicator> <indicator params>"),
+            Line::from("graph list | graph rm <index|name> | graph clear (chart indicators)"),
+            Line::from("renko <size> | atr <n> | off (bricks instead of candles in this tab)"),
+            Line::from("graph macd <fast> <slow> <signal> [ema|sma|wma|vwma]"),
+            Line::from("graph kama <n> [fast = 2] [slow = 30] (oracle kama-slope n fast slow)"),
+            Line::from("graph %b|bbw <n> [width = 2] (bollinger %B and bandwidth)"),
+            Line::from("graph atr <n> (average true range)"),
+            Line::from("graph adx <n> (average directional index with +DI and -DI)"),
+            Line::from("graph cci <n> (commodity channel index)"),
+            Line::from("graph %r|willr <n> (williams %R, -100 to 0)"),
+            Line::from("graph pivots 1d|1wk|1mo (floor pivots of the previous session)"),
+            Line::from("graph fib [<from> <to>] (retracement levels, of the last swing by default)"),
+            Line::from("graph ichimoku <tenkan> [kijun = 26] [senkou = 52] (cloud ahead of price)"),
+            Line::from("graph avwap|cret [timestamp] (anchored, defaults to the crosshair bar)"),
+            Line::from("graph expr <expression> (e.g. ema(20)-ema(50), (sma(1)-sma(20))/atr(14))"),
             Line::from("oracle <oracle>"),
+            Line::from("oracle dca every <bars> | dca at <hh:mm> (buys on a schedule, regardless of price)"),
+            Line::from("counselor <n> on|off (mute without removing, m in the oracle window)"),
             Line::from("res <resolution>"),
             Line::from("hist <size>"),
             Line::from("backtest"),
+            Line::from("backtest all [resolution [bars]] (current strategy on every loaded symbol)"),
+            Line::from("pin (baseline of the backtest A/B diff)"),
+            Line::from("preview (orders the current strategy would place now)"),
+            Line::from("activate (toggle forward test of current pair)"),
+            Line::from("report <file = report_<symbol>.html>"),
+            Line::from("export portfolio <file.csv> (balances, positions, open orders)"),
+            Line::from("export strategy <file.json> | import strategy <file.json>"),
+            Line::from("export trades <file.csv> (forward test profit by strategy and session)"),
+            Line::from(
+                "books replay [hours = 1] (recorded books in the heatmap, see config books)",
+            ),
+            Line::from("pnl (month over month P&L of the account snapshots)"),
+            Line::from("stats (today's P&L, fees, win rate of the forward tests)"),
+            Line::from("breakdown [strategy|counselor|hour|weekday] (forward test profit)"),
+            Line::from("volcone [cc|pk|gk] (volatility cone, close-to-close by default)"),
+            Line::from("allocate <risk aversion = 1>"),
+            Line::from("rebalance <symbol> <pct> ... [drift <pct>] [every <res>] | off"),
+            Line::from("dca <symbol> [currency] <notional> every <interval> | dca rm <id> | dca"),
+            Line::from("ladder buy|sell <price> [quantity] | ladder cancel <price>|all (current pair)"),
+            Line::from("amend <order id> <price> [quantity] (cancel and replace)"),
+            Line::from(
+                "bracket <buy|sell> <qty> <price> [stop] [take] (2% or 1.5atr, see config risk)",
+            ),
+            Line::from("bucket <pct> | bucket off (group order book levels by price)"),
+            Line::from("lock | unlock <phrase> (no orders in between, see config risk)"),
+            Line::from("partial <leave|amend|cancel> (remainder of partially filled orders)"),
+            Line::from("ttl <bars>b | <duration> | off (cancel unfilled limit orders)"),
+            Line::from("closed on|off (strategy decides only when bars close)"),
+            Line::from("heat <step%> [floor%] | off (size buys down after losses)"),
+            Line::from("trail <2%|1.5atr> | off (stop following the high of each buy)"),
+            Line::from("exits <stop atrs> <take atrs> [period = 14] | off (advice exits in ATRs)"),
+            Line::from("alias <name> = <command>; <command>... | alias <name> (remove)"),
+            Line::from("alert <price> | alert rm <id> | alert (list)"),
+            Line::from("perp <leverage> [funding.csv] | perp off (backtest as perpetual future)"),
+            Line::from("liq [leverage...] | liq off (estimated liquidation zones of perpetuals)"),
+            Line::from("source <file> (run commands, one per line)"),
             Line::from("save"),
         ];
         Paragraph::new(text)
@@ -0,0 +1,138 @@
+use crate::common;
+use dionysus::indicators::{match_indicator_from_text, Indicator};
+use dionysus::ERROR;
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    text::Line,
+    widgets::Paragraph,
+    Frame,
+};
+use slog::slog_error;
+use tui_prompts::prelude::*;
+
+/// Indicators of the current chart, the selected one can have its parameters
+/// edited in place.
+#[derive(Default)]
+pub struct IndicatorWindow {
+    midas_index: usize,
+    indicators: Vec<Indicator>,
+    selected: usize,
+    input: Option<TextState<'static>>,
+    edited: Option<(usize, Indicator)>,
+}
+
+impl IndicatorWindow {
+    pub fn open(&mut self, midas_index: usize, indicators: Vec<Indicator>) {
+        self.midas_index = midas_index;
+        self.indicators = indicators;
+        self.selected = 0;
+        self.input = None;
+        self.edited = None;
+    }
+
+    pub fn midas_index(&self) -> usize {
+        self.midas_index
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.input.is_some()
+    }
+
+    pub fn next(&mut self) {
+        if !self.indicators.is_empty() {
+            self.selected = (self.selected + 1) % self.indicators.len();
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if !self.indicators.is_empty() {
+            self.selected = (self.selected + self.indicators.len() - 1) % self.indicators.len();
+        }
+    }
+
+    /// Starts editing the parameters of the selected indicator.
+    pub fn edit(&mut self) {
+        if let Some(indicator) = self.indicators.get(self.selected) {
+            let mut state = TextState::default().with_value(indicator.to_text());
+            state.focus();
+            self.input = Some(state);
+        }
+    }
+
+    pub fn cancel_edit(&mut self) {
+        self.input = None;
+    }
+
+    pub fn input(&mut self) -> Option<&mut TextState<'static>> {
+        self.input.as_mut()
+    }
+
+    /// Parses the edited text. Returns true if the selected indicator changed.
+    pub fn submit(&mut self) -> bool {
+        let text = match &self.input {
+            Some(state) => state.value().to_string(),
+            None => return false,
+        };
+        let words: Vec<&str> = text.split_whitespace().collect();
+        match match_indicator_from_text(&words) {
+            Some(indicator) => {
+                self.input = None;
+                if self.indicators[self.selected] == indicator {
+                    return false;
+                }
+                self.indicators[self.selected] = indicator.clone();
+                self.edited = Some((self.selected, indicator));
+                true
+            }
+            None => {
+                ERROR!("invalid indicator {:?}", text);
+                false
+            }
+        }
+    }
+
+    /// Last submitted change as (indicator index, new indicator).
+    pub fn take_edited(&mut self) -> Option<(usize, Indicator)> {
+        self.edited.take()
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let block = common::block("INDICATORS");
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        let [list_area, input_area, hint_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+
+        let lines: Vec<Line> = if self.indicators.is_empty() {
+            vec![Line::from(
+                "no indicators, add one with: graph <indicator> <params>",
+            )]
+        } else {
+            self.indicators
+                .iter()
+                .enumerate()
+                .map(|(i, indicator)| {
+                    if i == self.selected {
+                        Line::from(format!("> {}", indicator.to_string()))
+                            .style(common::SELECTED_STYLE)
+                    } else {
+                        Line::from(format!("  {}", indicator.to_string()))
+                    }
+                })
+                .collect()
+        };
+        frame.render_widget(Paragraph::new(lines), list_area);
+
+        if let Some(state) = &mut self.input {
+            TextPrompt::from("params").draw(frame, input_area, state);
+        }
+        frame.render_widget(
+            Paragraph::new("enter: edit/apply  esc: cancel/close"),
+            hint_area,
+        );
+    }
+}
@@ -1,28 +1,66 @@
-use dionysus::binance::BinanceExchange;
+use dionysus::binance::ExchangeSymbolInfo;
 use dionysus::finance::Token;
+use dionysus::wallet::FeeSchedule;
+use dionysus::yahoo::YahooSymbolInfo;
 use ratatui::text::Line;
 
 use crate::common;
 use crate::common::ListWindow;
 
+fn format_value(value: Option<f64>) -> String {
+    match value {
+        Some(v) => format!("{:.2}", v),
+        None => String::from("-"),
+    }
+}
+
+/// 1234567890 -> 1.23B
+fn format_large(value: Option<f64>) -> String {
+    match value {
+        Some(v) if v.abs() >= 1e12 => format!("{:.2}T", v / 1e12),
+        Some(v) if v.abs() >= 1e9 => format!("{:.2}B", v / 1e9),
+        Some(v) if v.abs() >= 1e6 => format!("{:.2}M", v / 1e6),
+        _ => format_value(value),
+    }
+}
+
 #[derive(Default)]
 pub struct InfoWindow {
     list_window: ListWindow<String>,
 }
 
 impl InfoWindow {
-    pub fn update(&mut self, exchange: &mut BinanceExchange, token: &Token) {
+    /// `symbol` is None while the exchange info loads.
+    pub fn update(
+        &mut self,
+        server_time: u64,
+        symbol: Option<&ExchangeSymbolInfo>,
+        fees: &FeeSchedule,
+        token: &Token,
+    ) {
         self.list_window.items.clear();
         self.list_window
             .items
-            .push(format!("Server Time: {:?}", exchange.server_time));
+            .push(format!("Server Time: {:?}", server_time));
 
         self.list_window
             .items
             .push(format!("Current Token: {:?}", token.to_string()));
 
-        let symbol = exchange.get(token);
+        let default_symbol = ExchangeSymbolInfo::default();
+        let symbol = match symbol {
+            Some(symbol) => symbol,
+            None => {
+                self.list_window
+                    .items
+                    .push(String::from("Exchange info loading"));
+                &default_symbol
+            }
+        };
 
+        self.list_window
+            .items
+            .push(format!("Exchange: {}", symbol.exchange));
         self.list_window
             .items
             .push(format!("Status: {}", symbol.status));
@@ -42,6 +80,73 @@ impl InfoWindow {
         self.list_window
             .items
             .push(format!("    Min Quantity: {}", symbol.lot_min_qty));
+        self.list_window
+            .items
+            .push(format!("    Step: {}", symbol.step_size));
+        self.list_window
+            .items
+            .push(format!("Tick Size: {}", symbol.tick_size));
+        self.list_window
+            .items
+            .push(format!("Min Notional: {}", symbol.min_notional));
+        self.list_window.items.push(format!(
+            "Precision: base {} quote {}",
+            symbol.base_asset_precision, symbol.quote_precision
+        ));
+
+        self.list_window.items.push(format!("Fees:"));
+        self.list_window
+            .items
+            .push(format!("    Maker: {:.3}%", fees.maker * 100.0));
+        self.list_window
+            .items
+            .push(format!("    Taker: {:.3}%", fees.taker * 100.0));
+        self.list_window.items.push(format!(
+            "    BNB Discount: {}",
+            match fees.bnb_discount {
+                Some(true) => "on",
+                Some(false) => "off",
+                None => "unknown",
+            }
+        ));
+    }
+
+    pub fn update_stock(&mut self, info: &YahooSymbolInfo, token: &Token) {
+        self.list_window.items.clear();
+        self.list_window
+            .items
+            .push(format!("Current Token: {:?}", token.to_string()));
+        self.list_window.items.push(format!("Name: {}", info.name));
+        self.list_window
+            .items
+            .push(format!("Exchange: {}", info.exchange));
+        self.list_window
+            .items
+            .push(format!("Type: {}", info.quote_type));
+
+        let f = &info.fundamentals;
+        self.list_window.items.push(format!("Fundamentals:"));
+        self.list_window
+            .items
+            .push(format!("    Market Cap: {}", format_large(f.market_cap)));
+        self.list_window
+            .items
+            .push(format!("    P/E (ttm): {}", format_value(f.trailing_pe)));
+        self.list_window
+            .items
+            .push(format!("    P/E (fwd): {}", format_value(f.forward_pe)));
+        self.list_window.items.push(format!(
+            "    52wk Range: {} - {}",
+            format_value(f.week_52_low),
+            format_value(f.week_52_high)
+        ));
+        self.list_window.items.push(format!(
+            "    Last Quarter: {}",
+            match &f.last_quarter {
+                Some(date) => date.pretty_string(),
+                None => String::from("-"),
+            }
+        ));
     }
 
     pub fn render(&mut self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer)
@@ -51,7 +156,7 @@ impl InfoWindow {
         let block = common::block("INFO");
 
         self.list_window.render(area, buf, block, |info| {
-            Line::styled(format!(" {:?}", info), common::PROFIT_COLOR)
+            Line::styled(format!(" {:?}", info), common::theme().profit)
         });
     }
 }
@@ -1,7 +1,12 @@
 use crate::{
-    w_command::CommandInput, w_graph::GraphView, w_help::HelpWindow, w_info::InfoWindow,
-    w_log::LogWindow, w_market::MarketWindow, w_oracle::OracleWindow, w_order::OrderWindow,
-    w_order_book::OrderBookWindow, w_strategy::StrategyWindow, w_symbol_tabs::SymbolTabs,
+    w_backtest_diff::BacktestDiffWindow, w_backtest_rank::BacktestRankWindow,
+    w_breakdown::BreakdownWindow, w_command::CommandInput, w_correlation::CorrelationWindow,
+    w_dca::DcaWindow, w_graph::GraphView, w_help::HelpWindow, w_indicator::IndicatorWindow,
+    w_info::InfoWindow, w_ladder::LadderWindow, w_log::LogWindow, w_market::MarketWindow,
+    w_news::NewsWindow, w_oracle::OracleWindow, w_order::OrderWindow,
+    w_order_book::OrderBookWindow, w_seasonality::SeasonalityWindow, w_shutdown::ShutdownWindow,
+    w_signals::SignalsWindow, w_stats::StatsWindow, w_strategy::StrategyWindow,
+    w_streams::StreamsWindow, w_symbol_tabs::SymbolTabs, w_vol_cone::VolConeWindow,
     w_wallet::WalletWindow, w_window::WindowType,
 };
 use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
@@ -15,7 +20,13 @@ pub enum InteractionEvent {
     Escape,
     SymbolSelect(usize),
     WindowOpen(WindowType),
+    /// Exits, cancelling the open orders when true.
+    Quit(bool),
     UpdateStrategy,
+    UpdateIndicator,
+    AddAlert,
+    /// Amends the order submitted through the orders window form.
+    EditOrder,
 }
 
 impl InteractionEvent {
@@ -61,6 +72,29 @@ impl Interactible for GraphView {
                 _ => self.zooming = false,
             };
             match key_event.code {
+                KeyCode::Char('p') => self.toggle_scrub(),
+                KeyCode::Char('x') => self.toggle_crosshair(),
+                KeyCode::Char('h') => self.heatmap.toggle(),
+                KeyCode::Char('%') => self.toggle_percent(),
+                KeyCode::Char('r') => self.toggle_renko(),
+                KeyCode::Char('v') => self.volume_profile.toggle(),
+                KeyCode::Char('A') if self.crosshair().is_some() => {
+                    return InteractionEvent::AddAlert
+                }
+                KeyCode::Up if self.crosshair().is_some() && !self.zooming => {
+                    self.move_crosshair(1.0)
+                }
+                KeyCode::Down if self.crosshair().is_some() && !self.zooming => {
+                    self.move_crosshair(-1.0)
+                }
+                KeyCode::Left if self.is_scrubbing() && !self.zooming => self.step_playhead(-1),
+                KeyCode::Right if self.is_scrubbing() && !self.zooming => self.step_playhead(1),
+                KeyCode::Left if self.crosshair().is_some() && !self.zooming => {
+                    self.step_crosshair(-1)
+                }
+                KeyCode::Right if self.crosshair().is_some() && !self.zooming => {
+                    self.step_crosshair(1)
+                }
                 KeyCode::Left => {
                     if self.zooming {
                         self.zoom(-0.05, 0.0);
@@ -166,18 +200,31 @@ impl<'a> Interactible for OracleWindow<'a> {
         if global {
             InteractionEvent::None
         } else {
+            if let (KeyCode::Char('o'), KeyModifiers::CONTROL) =
+                (key_event.code, key_event.modifiers)
+            {
+                return InteractionEvent::UpdateStrategy;
+            }
+            if self.is_editing() {
+                match key_event.code {
+                    KeyCode::Enter => self.submit(),
+                    KeyCode::Esc => self.cancel_edit(),
+                    _ => self.current().handle_key_event(key_event.clone()),
+                };
+                return InteractionEvent::None;
+            }
             match (key_event.code, key_event.modifiers) {
-                (KeyCode::Enter, _) => self.submit(),
-                (KeyCode::Tab, KeyModifiers::NONE) => self.focus_next(),
-                (KeyCode::BackTab, KeyModifiers::SHIFT) => self.focus_prev(),
+                (KeyCode::Enter, _) => self.edit(),
+                (KeyCode::Tab, KeyModifiers::NONE) | (KeyCode::Down, _) => self.focus_next(),
+                (KeyCode::BackTab, KeyModifiers::SHIFT) | (KeyCode::Up, _) => self.focus_prev(),
+                (KeyCode::Char('+'), _) => self.add_counselor(),
+                (KeyCode::Char('d'), _) => self.remove_counselor(),
+                (KeyCode::Char('m'), _) => self.toggle_counselor(),
                 (KeyCode::Esc, _) => {
                     self.close();
                     return InteractionEvent::Escape;
                 }
-                (KeyCode::Char('o'), KeyModifiers::CONTROL) => {
-                    return InteractionEvent::UpdateStrategy
-                }
-                _ => self.current().handle_key_event(key_event.clone()),
+                _ => (),
             };
             InteractionEvent::None
         }
@@ -185,7 +232,13 @@ impl<'a> Interactible for OracleWindow<'a> {
 }
 
 impl Interactible for WalletWindow {
-    fn handle_key_event(&mut self, _key_event: &KeyEvent, _global: bool) -> InteractionEvent {
+    fn handle_key_event(&mut self, key_event: &KeyEvent, _global: bool) -> InteractionEvent {
+        if key_event.kind == KeyEventKind::Press {
+            if let KeyCode::Char('c') = key_event.code {
+                self.next_denomination();
+                return InteractionEvent::Consumed;
+            }
+        }
         InteractionEvent::None
     }
 }
@@ -203,11 +256,52 @@ impl Interactible for OrderBookWindow {
 }
 
 impl Interactible for OrderWindow {
+    fn handle_key_event(&mut self, key_event: &KeyEvent, global: bool) -> InteractionEvent {
+        if global {
+            return InteractionEvent::None;
+        }
+        if self.is_editing() {
+            match key_event.code {
+                KeyCode::Enter => {
+                    if self.submit() {
+                        return InteractionEvent::EditOrder;
+                    }
+                }
+                KeyCode::Esc => self.cancel_edit(),
+                _ => {
+                    if let Some(input) = self.input() {
+                        input.handle_key_event(key_event.clone());
+                    }
+                }
+            };
+            return InteractionEvent::None;
+        }
+        match (key_event.code, key_event.modifiers) {
+            (KeyCode::Esc, _) => InteractionEvent::Escape,
+            (KeyCode::Char('O'), _) => InteractionEvent::Escape,
+            (KeyCode::Down, _) | (KeyCode::Char('j'), _) => {
+                self.next();
+                InteractionEvent::Consumed
+            }
+            (KeyCode::Up, _) | (KeyCode::Char('k'), _) => {
+                self.previous();
+                InteractionEvent::Consumed
+            }
+            (KeyCode::Char('e'), _) => {
+                self.edit();
+                InteractionEvent::Consumed
+            }
+            _ => InteractionEvent::None,
+        }
+    }
+}
+
+impl Interactible for HelpWindow {
     fn handle_key_event(&mut self, key_event: &KeyEvent, global: bool) -> InteractionEvent {
         if !global {
             match (key_event.code, key_event.modifiers) {
                 (KeyCode::Esc, _) => InteractionEvent::Escape,
-                (KeyCode::Char('O'), _) => InteractionEvent::Escape,
+                (KeyCode::Char('?'), _) => InteractionEvent::Escape,
                 _ => InteractionEvent::None,
             }
         } else {
@@ -216,12 +310,12 @@ impl Interactible for OrderWindow {
     }
 }
 
-impl Interactible for HelpWindow {
+impl Interactible for InfoWindow {
     fn handle_key_event(&mut self, key_event: &KeyEvent, global: bool) -> InteractionEvent {
         if !global {
             match (key_event.code, key_event.modifiers) {
                 (KeyCode::Esc, _) => InteractionEvent::Escape,
-                (KeyCode::Char('?'), _) => InteractionEvent::Escape,
+                (KeyCode::Char('/'), _) => InteractionEvent::Escape,
                 _ => InteractionEvent::None,
             }
         } else {
@@ -230,12 +324,256 @@ impl Interactible for HelpWindow {
     }
 }
 
-impl Interactible for InfoWindow {
+impl Interactible for CorrelationWindow {
     fn handle_key_event(&mut self, key_event: &KeyEvent, global: bool) -> InteractionEvent {
         if !global {
             match (key_event.code, key_event.modifiers) {
                 (KeyCode::Esc, _) => InteractionEvent::Escape,
-                (KeyCode::Char('/'), _) => InteractionEvent::Escape,
+                (KeyCode::Char('C'), _) => InteractionEvent::Escape,
+                _ => InteractionEvent::None,
+            }
+        } else {
+            InteractionEvent::None
+        }
+    }
+}
+
+impl Interactible for SeasonalityWindow {
+    fn handle_key_event(&mut self, key_event: &KeyEvent, global: bool) -> InteractionEvent {
+        if !global {
+            match (key_event.code, key_event.modifiers) {
+                (KeyCode::Esc, _) => InteractionEvent::Escape,
+                (KeyCode::Char('S'), _) => InteractionEvent::Escape,
+                _ => InteractionEvent::None,
+            }
+        } else {
+            InteractionEvent::None
+        }
+    }
+}
+
+impl Interactible for SignalsWindow {
+    fn handle_key_event(&mut self, key_event: &KeyEvent, global: bool) -> InteractionEvent {
+        if !global {
+            match (key_event.code, key_event.modifiers) {
+                (KeyCode::Esc, _) => InteractionEvent::Escape,
+                (KeyCode::Char('G'), _) => InteractionEvent::Escape,
+                _ => InteractionEvent::None,
+            }
+        } else {
+            InteractionEvent::None
+        }
+    }
+}
+
+impl Interactible for IndicatorWindow {
+    fn handle_key_event(&mut self, key_event: &KeyEvent, global: bool) -> InteractionEvent {
+        if global {
+            return InteractionEvent::None;
+        }
+        if self.is_editing() {
+            match key_event.code {
+                KeyCode::Enter => {
+                    if self.submit() {
+                        return InteractionEvent::UpdateIndicator;
+                    }
+                }
+                KeyCode::Esc => self.cancel_edit(),
+                _ => {
+                    if let Some(input) = self.input() {
+                        input.handle_key_event(key_event.clone());
+                    }
+                }
+            };
+            return InteractionEvent::None;
+        }
+        match (key_event.code, key_event.modifiers) {
+            (KeyCode::Esc, _) => InteractionEvent::Escape,
+            (KeyCode::Char('I'), _) => InteractionEvent::Escape,
+            (KeyCode::Down, _) | (KeyCode::Char('j'), _) => {
+                self.next();
+                InteractionEvent::Consumed
+            }
+            (KeyCode::Up, _) | (KeyCode::Char('k'), _) => {
+                self.previous();
+                InteractionEvent::Consumed
+            }
+            (KeyCode::Enter, _) => {
+                self.edit();
+                InteractionEvent::Consumed
+            }
+            _ => InteractionEvent::None,
+        }
+    }
+}
+
+impl Interactible for BacktestDiffWindow {
+    fn handle_key_event(&mut self, key_event: &KeyEvent, global: bool) -> InteractionEvent {
+        if !global {
+            match (key_event.code, key_event.modifiers) {
+                (KeyCode::Esc, _) => InteractionEvent::Escape,
+                (KeyCode::Char('D'), _) => InteractionEvent::Escape,
+                _ => InteractionEvent::None,
+            }
+        } else {
+            InteractionEvent::None
+        }
+    }
+}
+
+impl Interactible for NewsWindow {
+    fn handle_key_event(&mut self, key_event: &KeyEvent, global: bool) -> InteractionEvent {
+        if !global {
+            match (key_event.code, key_event.modifiers) {
+                (KeyCode::Esc, _) => InteractionEvent::Escape,
+                (KeyCode::Char('N'), _) => InteractionEvent::Escape,
+                _ => InteractionEvent::None,
+            }
+        } else {
+            InteractionEvent::None
+        }
+    }
+}
+
+impl Interactible for StatsWindow {
+    fn handle_key_event(&mut self, key_event: &KeyEvent, global: bool) -> InteractionEvent {
+        if !global {
+            match (key_event.code, key_event.modifiers) {
+                (KeyCode::Esc, _) => InteractionEvent::Escape,
+                (KeyCode::Char('T'), _) => InteractionEvent::Escape,
+                _ => InteractionEvent::None,
+            }
+        } else {
+            InteractionEvent::None
+        }
+    }
+}
+
+impl Interactible for BreakdownWindow {
+    fn handle_key_event(&mut self, key_event: &KeyEvent, global: bool) -> InteractionEvent {
+        if !global {
+            match (key_event.code, key_event.modifiers) {
+                (KeyCode::Esc, _) => InteractionEvent::Escape,
+                (KeyCode::Char('B'), _) => InteractionEvent::Escape,
+                (KeyCode::Char('g'), _) => {
+                    self.next_key();
+                    InteractionEvent::Consumed
+                }
+                _ => InteractionEvent::None,
+            }
+        } else {
+            InteractionEvent::None
+        }
+    }
+}
+
+impl Interactible for ShutdownWindow {
+    fn handle_key_event(&mut self, key_event: &KeyEvent, global: bool) -> InteractionEvent {
+        if !global && key_event.kind == KeyEventKind::Press {
+            match key_event.code {
+                KeyCode::Esc | KeyCode::Char('n') => InteractionEvent::Escape,
+                KeyCode::Char('y') | KeyCode::Char('q') => InteractionEvent::Quit(false),
+                KeyCode::Char('c') => InteractionEvent::Quit(true),
+                _ => InteractionEvent::Consumed,
+            }
+        } else {
+            InteractionEvent::None
+        }
+    }
+}
+
+impl Interactible for StreamsWindow {
+    fn handle_key_event(&mut self, key_event: &KeyEvent, global: bool) -> InteractionEvent {
+        if !global {
+            match (key_event.code, key_event.modifiers) {
+                (KeyCode::Esc, _) => InteractionEvent::Escape,
+                (KeyCode::Char('E'), _) => InteractionEvent::Escape,
+                _ => InteractionEvent::None,
+            }
+        } else {
+            InteractionEvent::None
+        }
+    }
+}
+
+impl Interactible for DcaWindow {
+    fn handle_key_event(&mut self, key_event: &KeyEvent, global: bool) -> InteractionEvent {
+        if !global {
+            match (key_event.code, key_event.modifiers) {
+                (KeyCode::Esc, _) => InteractionEvent::Escape,
+                (KeyCode::Char('R'), _) => InteractionEvent::Escape,
+                _ => InteractionEvent::None,
+            }
+        } else {
+            InteractionEvent::None
+        }
+    }
+}
+
+impl Interactible for LadderWindow {
+    fn handle_key_event(&mut self, key_event: &KeyEvent, global: bool) -> InteractionEvent {
+        if global || key_event.kind != KeyEventKind::Press {
+            return InteractionEvent::None;
+        }
+        let order = |side: &str, price: Option<f64>| match price {
+            Some(price) => InteractionEvent::RunCommand(format!("ladder {} {}", side, price)),
+            None => InteractionEvent::Consumed,
+        };
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('L') => InteractionEvent::Escape,
+            KeyCode::Up => {
+                self.move_selection(1);
+                InteractionEvent::Consumed
+            }
+            KeyCode::Down => {
+                self.move_selection(-1);
+                InteractionEvent::Consumed
+            }
+            KeyCode::Char('+') => {
+                self.zoom(true);
+                InteractionEvent::Consumed
+            }
+            KeyCode::Char('-') => {
+                self.zoom(false);
+                InteractionEvent::Consumed
+            }
+            KeyCode::Char('c') => {
+                self.recenter();
+                InteractionEvent::Consumed
+            }
+            KeyCode::Char('b') => order("buy", self.selected_price()),
+            KeyCode::Char('s') => order("sell", self.selected_price()),
+            KeyCode::Char('x') => order("cancel", self.selected_price()),
+            KeyCode::Char('X') => InteractionEvent::RunCommand(String::from("ladder cancel all")),
+            _ => InteractionEvent::None,
+        }
+    }
+}
+
+impl Interactible for VolConeWindow {
+    fn handle_key_event(&mut self, key_event: &KeyEvent, global: bool) -> InteractionEvent {
+        if !global {
+            match (key_event.code, key_event.modifiers) {
+                (KeyCode::Esc, _) => InteractionEvent::Escape,
+                (KeyCode::Char('V'), _) => InteractionEvent::Escape,
+                _ => InteractionEvent::None,
+            }
+        } else {
+            InteractionEvent::None
+        }
+    }
+}
+
+impl Interactible for BacktestRankWindow {
+    fn handle_key_event(&mut self, key_event: &KeyEvent, global: bool) -> InteractionEvent {
+        if !global {
+            match (key_event.code, key_event.modifiers) {
+                (KeyCode::Esc, _) => InteractionEvent::Escape,
+                (KeyCode::Char('K'), _) => InteractionEvent::Escape,
+                (KeyCode::Char('g'), _) => {
+                    self.next_key();
+                    InteractionEvent::Consumed
+                }
                 _ => InteractionEvent::None,
             }
         } else {
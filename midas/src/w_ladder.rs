@@ -0,0 +1,154 @@
+use crate::common;
+use dionysus::finance::{Book, Order, Token};
+use dionysus::ladder::{ladder, Rung};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::Style,
+    widgets::{Cell, Row, StatefulWidget, Table, TableState},
+};
+
+/// Rungs listed on each side of the middle one.
+const DEPTH: usize = 30;
+/// Rung step multiples of the price tick, cycled with +/-.
+const ZOOMS: [f64; 8] = [1.0, 2.0, 5.0, 10.0, 25.0, 50.0, 100.0, 500.0];
+
+/// Depth-of-market ladder of the current pair. The ladder stays still around
+/// the selected rung, or follows the mid price until a rung is selected.
+#[derive(Default)]
+pub struct LadderWindow {
+    pub token: Token,
+    book: Book,
+    orders: Vec<Order>,
+    /// Exchange price tick, 0 while unknown.
+    tick: f64,
+    zoom: usize,
+    /// Price of the selected rung.
+    selected: Option<f64>,
+    rungs: Vec<Rung>,
+}
+
+impl LadderWindow {
+    pub fn update(&mut self, book: &Book, orders: &[Order], tick: f64) {
+        if book.token != self.token {
+            self.token = book.token.clone();
+            self.selected = None;
+        }
+        self.book = book.clone();
+        self.orders = orders.to_vec();
+        self.tick = tick;
+        self.rebuild();
+    }
+
+    fn mid(&self) -> Option<f64> {
+        match (self.book.best_bid(), self.book.best_ask()) {
+            (Some(bid), Some(ask)) => Some((bid + ask) / 2.0),
+            (Some(price), None) | (None, Some(price)) => Some(price),
+            (None, None) => None,
+        }
+    }
+
+    /// Price distance between rungs. Without the exchange tick, a ten
+    /// thousandth of the price magnitude.
+    pub fn step(&self) -> f64 {
+        let tick = match (self.tick, self.mid()) {
+            (tick, _) if tick > 0.0 => tick,
+            (_, Some(mid)) if mid > 0.0 => 10f64.powi(mid.log10().floor() as i32 - 4),
+            _ => return 0.0,
+        };
+        tick * ZOOMS[self.zoom]
+    }
+
+    /// Price of the selected rung, the rung of the mid price by default.
+    pub fn selected_price(&self) -> Option<f64> {
+        let step = self.step();
+        let price = self.selected.or(self.mid())?;
+        if step <= 0.0 {
+            return None;
+        }
+        Some((price / step).round() * step)
+    }
+
+    fn rebuild(&mut self) {
+        self.rungs = match self.selected_price() {
+            Some(center) => ladder(&self.book, &self.orders, center, self.step(), DEPTH),
+            None => Vec::new(),
+        };
+    }
+
+    /// Moves the selection `rungs` up (positive) or down.
+    pub fn move_selection(&mut self, rungs: i64) {
+        if let Some(price) = self.selected_price() {
+            self.selected = Some(price + rungs as f64 * self.step());
+            self.rebuild();
+        }
+    }
+
+    pub fn zoom(&mut self, out: bool) {
+        self.zoom = if out {
+            (self.zoom + 1).min(ZOOMS.len() - 1)
+        } else {
+            self.zoom.saturating_sub(1)
+        };
+        self.rebuild();
+    }
+
+    /// Follows the mid price again.
+    pub fn recenter(&mut self) {
+        self.selected = None;
+        self.rebuild();
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let theme = common::theme();
+        let token = &self.token;
+        let size = |quantity: f64| match quantity {
+            q if q > 0.0 => common::format_quantity(token, q),
+            _ => String::new(),
+        };
+        // rows fitting between the borders and the header, around the middle rung
+        let visible = (area.height as usize)
+            .saturating_sub(3)
+            .min(self.rungs.len());
+        let skip = (self.rungs.len() - visible) / 2;
+        let rows: Vec<Row> = self
+            .rungs
+            .iter()
+            .skip(skip)
+            .take(visible)
+            .map(|rung| {
+                Row::new(vec![
+                    Cell::from(size(rung.buys)).style(Style::default().fg(theme.profit)),
+                    Cell::from(size(rung.bid)).style(Style::default().fg(theme.profit)),
+                    Cell::from(common::format_price(token, rung.price)),
+                    Cell::from(size(rung.ask)).style(Style::default().fg(theme.loss)),
+                    Cell::from(size(rung.sells)).style(Style::default().fg(theme.loss)),
+                ])
+            })
+            .collect();
+        let title = format!(
+            "LADDER {} step {}",
+            token.name(),
+            common::format_price(token, self.step())
+        );
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(
+            Row::new(vec!["my buys", "bid", "price", "ask", "my sells"])
+                .style(Style::default().fg(common::NORMAL_FG)),
+        )
+        .block(common::block(title.as_str()))
+        .row_highlight_style(common::SELECTED_STYLE);
+        let selected = (!self.rungs.is_empty()).then_some(DEPTH - skip);
+        let mut state = TableState::default().with_selected(selected);
+        StatefulWidget::render(table, area, buf, &mut state);
+    }
+}
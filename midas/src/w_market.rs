@@ -9,6 +9,8 @@ use std::collections::HashMap;
 #[derive(Default)]
 pub struct MarketWindow {
     list_window: ListWindow<MarketTick>,
+    /// Recent price action of the tokens with cached 1m closes.
+    sparklines: HashMap<Token, String>,
 }
 
 impl MarketWindow {
@@ -22,24 +24,34 @@ impl MarketWindow {
         });
     }
 
+    pub fn set_sparklines(&mut self, sparklines: &HashMap<Token, String>) {
+        self.sparklines = sparklines.clone();
+    }
+
     pub fn render(&mut self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer)
     where
         Self: Sized,
     {
         let block = common::block("MARKET").title(Line::from("USDT").left_aligned());
 
+        let sparklines = &self.sparklines;
         self.list_window.render(area, buf, block, |market_tick| {
             Line::styled(
                 format!(
-                    " {:10} {: >12} ({:.2}%)",
+                    " {:10} {:w$} {: >12} ({})",
                     market_tick.token.get_symbol(),
-                    market_tick.price,
-                    market_tick.change_pct
+                    sparklines
+                        .get(&market_tick.token)
+                        .map(|s| s.as_str())
+                        .unwrap_or(""),
+                    common::format_price(&market_tick.token, market_tick.price),
+                    common::format_percent(market_tick.change_pct),
+                    w = common::SPARKLINE_WIDTH
                 ),
                 if market_tick.change_pct > 0.0 {
-                    common::PROFIT_COLOR
+                    common::theme().profit
                 } else {
-                    common::LOSS_COLOR
+                    common::theme().loss
                 },
             )
         });
@@ -0,0 +1,56 @@
+use crate::common;
+use crate::common::ListWindow;
+use dionysus::news::Headline;
+use ratatui::{
+    style::{Style, Stylize},
+    text::{Line, Span},
+};
+
+/// Number of headlines listed.
+const HEADLINES_SHOWN: usize = 100;
+
+#[derive(Default)]
+pub struct NewsWindow {
+    list_window: ListWindow<Headline>,
+    loading: bool,
+}
+
+impl NewsWindow {
+    pub fn set_loading(&mut self) {
+        self.loading = true;
+    }
+
+    /// Headlines mentioning loaded symbols are listed first.
+    pub fn update(&mut self, mut headlines: Vec<Headline>) {
+        headlines.sort_by_key(|h| h.symbols.is_empty());
+        headlines.truncate(HEADLINES_SHOWN);
+        self.list_window.items = headlines;
+        self.loading = false;
+    }
+
+    pub fn render(&mut self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
+        let title = match self.loading {
+            true => String::from("NEWS (loading)"),
+            false => format!("NEWS ({})", self.list_window.items.len()),
+        };
+        let block = common::block(title.as_str());
+        self.list_window.render(area, buf, block, |headline| {
+            let mut spans = Vec::new();
+            for symbol in &headline.symbols {
+                spans.push(Span::styled(
+                    format!("[{}] ", symbol),
+                    Style::new().yellow(),
+                ));
+            }
+            spans.push(Span::styled(
+                headline.title.clone(),
+                Style::new().fg(common::NORMAL_FG),
+            ));
+            spans.push(Span::styled(
+                format!("  {} {}", headline.source, headline.published),
+                Style::new().dark_gray(),
+            ));
+            Line::from(spans)
+        });
+    }
+}
@@ -1,15 +1,23 @@
-use dionysus::{strategy::Strategy, time::TimeUnit};
+use dionysus::ERROR;
+use dionysus::{counselor::match_oracle_from_text, strategy::Strategy, time::TimeUnit};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     widgets::{Block, Borders, Paragraph, Wrap},
     Frame,
 };
+use slog::slog_error;
 use tui_prompts::prelude::*;
 
+/// Fields before the counselor rows (oracle and resolution).
+const COUNSELOR_FIELD: usize = 2;
+const COUNSELOR_LABEL: &str = "Counselor:       ";
+const MUTED_COUNSELOR_LABEL: &str = "Counselor (off): ";
+
 #[derive(Default)]
 pub struct OracleWindow<'a> {
     pub strategy: Strategy,
     current_field: usize,
+    editing: bool,
     fields: Vec<(String, TextState<'a>)>,
 }
 
@@ -24,19 +32,103 @@ impl<'a> OracleWindow<'a> {
             String::from("Time Resolution: "),
             TextState::default().with_value(strategy.duration.resolution.name()),
         ));
-        for c in &strategy.counselors {
+        for (i, c) in strategy.counselors.iter().enumerate() {
+            let label = match strategy.is_enabled(i) {
+                true => COUNSELOR_LABEL,
+                false => MUTED_COUNSELOR_LABEL,
+            };
             self.fields.push((
-                String::from("Conselour: "),
-                TextState::default().with_value(c.name()),
+                String::from(label),
+                TextState::default().with_value(c.to_text()),
             ));
         }
     }
 
     pub fn close(&mut self) {
         self.current_field = 0;
+        self.editing = false;
         self.fields.clear();
     }
 
+    pub fn is_editing(&self) -> bool {
+        self.editing
+    }
+
+    /// Starts editing the current field.
+    pub fn edit(&mut self) {
+        if self.fields.is_empty() {
+            return;
+        }
+        self.editing = true;
+        self.current().focus();
+    }
+
+    /// Value of field `index` in the current strategy, None for counselor
+    /// rows that were not submitted yet.
+    fn field_value(&self, index: usize) -> Option<String> {
+        match index {
+            0 => Some(self.strategy.oracle.name()),
+            1 => Some(self.strategy.duration.resolution.name()),
+            _ => self
+                .strategy
+                .counselors
+                .get(index - COUNSELOR_FIELD)
+                .map(|c| c.to_text()),
+        }
+    }
+
+    /// Discards the edition of the current field.
+    pub fn cancel_edit(&mut self) {
+        self.editing = false;
+        match self.field_value(self.current_field) {
+            Some(value) => {
+                self.fields[self.current_field].1 = TextState::default().with_value(value);
+            }
+            None => self.remove_counselor(),
+        }
+    }
+
+    /// Appends an empty counselor row and starts editing it.
+    pub fn add_counselor(&mut self) {
+        let pending = COUNSELOR_FIELD + self.strategy.counselors.len();
+        if self.fields.len() > pending {
+            // a new row is already waiting to be submitted
+            self.current_field = pending;
+        } else {
+            self.fields
+                .push((String::from(COUNSELOR_LABEL), TextState::default()));
+            self.current_field = self.fields.len() - 1;
+        }
+        self.edit();
+    }
+
+    /// Removes the counselor of the current row.
+    pub fn remove_counselor(&mut self) {
+        if self.current_field < COUNSELOR_FIELD || self.current_field >= self.fields.len() {
+            return;
+        }
+        let index = self.current_field - COUNSELOR_FIELD;
+        self.strategy.remove_counselor(index);
+        self.fields.remove(self.current_field);
+        self.editing = false;
+        self.current_field = self.current_field.min(self.fields.len().saturating_sub(1));
+    }
+
+    /// Mutes/unmutes the counselor of the current row.
+    pub fn toggle_counselor(&mut self) {
+        if self.current_field < COUNSELOR_FIELD {
+            return;
+        }
+        let index = self.current_field - COUNSELOR_FIELD;
+        let enabled = !self.strategy.is_enabled(index);
+        if self.strategy.set_enabled(index, enabled).is_ok() {
+            self.fields[self.current_field].0 = String::from(match enabled {
+                true => COUNSELOR_LABEL,
+                false => MUTED_COUNSELOR_LABEL,
+            });
+        }
+    }
+
     pub fn render(&mut self, frame: &mut Frame, area: Rect) {
         let areas = Layout::default()
             .direction(Direction::Horizontal)
@@ -45,12 +137,23 @@ impl<'a> OracleWindow<'a> {
 
         let prompt_areas = Layout::default()
             .direction(Direction::Vertical)
-            .constraints(vec![Constraint::Length(1); self.fields.len()])
+            .constraints(vec![Constraint::Length(1); self.fields.len() + 2])
             .split(areas[0]);
 
         for (i, field) in self.fields.iter_mut().enumerate() {
-            TextPrompt::from(field.0.clone()).draw(frame, prompt_areas[i], &mut field.1);
+            let marker = if i == self.current_field { "> " } else { "  " };
+            TextPrompt::from(format!("{}{}", marker, field.0)).draw(
+                frame,
+                prompt_areas[i],
+                &mut field.1,
+            );
         }
+        frame.render_widget(
+            Paragraph::new(
+                "enter: edit/apply  +: add counselor  d: delete  m: mute/unmute  ctrl-o: save",
+            ),
+            prompt_areas[self.fields.len() + 1],
+        );
 
         let strategy = self.strategy.clone();
         let debug = format!("{strategy:#?}");
@@ -67,12 +170,20 @@ impl<'a> OracleWindow<'a> {
     }
 
     pub fn focus_next(&mut self) {
+        if self.fields.is_empty() {
+            return;
+        }
+        self.editing = false;
         self.fields[self.current_field].1.blur();
         self.current_field = (self.current_field + 1) % self.fields.len();
         self.fields[self.current_field].1.focus();
     }
 
     pub fn focus_prev(&mut self) {
+        if self.fields.is_empty() {
+            return;
+        }
+        self.editing = false;
         self.fields[self.current_field].1.blur();
         self.current_field = if self.current_field > 0 {
             self.current_field - 1
@@ -90,9 +201,26 @@ impl<'a> OracleWindow<'a> {
                 self.strategy.duration.resolution =
                     TimeUnit::from_name(self.current().value().into())
             }
-            2 => (),
-            _ => (),
+            field => {
+                let text = self.current().value().replace(['(', ')', ','], " ");
+                let words: Vec<&str> = text.split_whitespace().collect();
+                match match_oracle_from_text(&words) {
+                    Some(counselor) => {
+                        let index = field - COUNSELOR_FIELD;
+                        if index < self.strategy.counselors.len() {
+                            self.strategy.counselors[index] = counselor;
+                        } else {
+                            self.strategy.counselors.push(counselor);
+                        }
+                    }
+                    None => {
+                        ERROR!("invalid counselor {:?}", text);
+                        return;
+                    }
+                }
+            }
         }
+        self.editing = false;
         self.focus_next();
     }
 }
@@ -0,0 +1,153 @@
+use crate::common;
+use dionysus::finance::OrderStatus;
+use dionysus::trader::Trader;
+use dionysus::ERROR;
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    text::Line,
+    widgets::Paragraph,
+    Frame,
+};
+use slog::slog_error;
+use tui_prompts::prelude::*;
+
+/// Open orders of the wallet. The selected one can be amended through the
+/// order form, pre-filled with its price and unfilled quantity.
+#[derive(Default)]
+pub struct OrderWindow {
+    orders: Vec<OrderStatus>,
+    selected: usize,
+    input: Option<TextState<'static>>,
+    /// Submitted amend as (order id, price, quantity).
+    amend: Option<(u64, f64, f64)>,
+}
+
+impl OrderWindow {
+    pub fn update(&mut self, wallet: &impl Trader) {
+        match wallet.get_all_open_orders() {
+            Ok(orders) => self.orders = orders,
+            Err(e) => ERROR!("{:?}", e),
+        }
+        self.selected = self.selected.min(self.orders.len().saturating_sub(1));
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.input.is_some()
+    }
+
+    pub fn next(&mut self) {
+        if !self.orders.is_empty() {
+            self.selected = (self.selected + 1) % self.orders.len();
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if !self.orders.is_empty() {
+            self.selected = (self.selected + self.orders.len() - 1) % self.orders.len();
+        }
+    }
+
+    /// Opens the form with the selected order. The quantity is what is left
+    /// to fill, so a partially filled order isn't overfilled.
+    pub fn edit(&mut self) {
+        if let Some(status) = self.orders.get(self.selected) {
+            let order = &status.order;
+            let text = format!("{} {}", order.price, order.quantity - status.executed_qty);
+            let mut state = TextState::default().with_value(text);
+            state.focus();
+            self.input = Some(state);
+        }
+    }
+
+    pub fn cancel_edit(&mut self) {
+        self.input = None;
+    }
+
+    pub fn input(&mut self) -> Option<&mut TextState<'static>> {
+        self.input.as_mut()
+    }
+
+    /// Parses the form as "<price> <quantity>". Returns true if an amend of
+    /// the selected order was submitted.
+    pub fn submit(&mut self) -> bool {
+        let text = match &self.input {
+            Some(state) => state.value().to_string(),
+            None => return false,
+        };
+        let id = match self.orders.get(self.selected).and_then(|s| s.order.id) {
+            Some(id) => id as u64,
+            None => {
+                ERROR!("open order without id");
+                self.input = None;
+                return false;
+            }
+        };
+        let words: Vec<&str> = text.split_whitespace().collect();
+        match (
+            words.first().and_then(|w| w.parse::<f64>().ok()),
+            words.get(1).and_then(|w| w.parse::<f64>().ok()),
+        ) {
+            (Some(price), Some(quantity)) if words.len() == 2 => {
+                self.input = None;
+                self.amend = Some((id, price, quantity));
+                true
+            }
+            _ => {
+                ERROR!("invalid order form {:?}, expected <price> <quantity>", text);
+                false
+            }
+        }
+    }
+
+    /// Last submitted amend as (order id, price, quantity).
+    pub fn take_amend(&mut self) -> Option<(u64, f64, f64)> {
+        self.amend.take()
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let block = common::block("OPEN ORDERS");
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        let [list_area, input_area, hint_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+
+        let lines: Vec<Line> = if self.orders.is_empty() {
+            vec![Line::from("no open orders")]
+        } else {
+            self.orders
+                .iter()
+                .enumerate()
+                .map(|(i, status)| {
+                    let order = &status.order;
+                    let text = format!(
+                        "{:?} {} {}/{} @ {} #{}",
+                        order.side,
+                        order.token.name(),
+                        common::format_quantity(&order.token, status.executed_qty),
+                        common::format_quantity(&order.token, order.quantity),
+                        common::format_price(&order.token, order.price),
+                        order.id.unwrap_or_default()
+                    );
+                    if i == self.selected {
+                        Line::from(format!("> {}", text)).style(common::SELECTED_STYLE)
+                    } else {
+                        Line::from(format!("  {}", text))
+                    }
+                })
+                .collect()
+        };
+        frame.render_widget(Paragraph::new(lines), list_area);
+
+        if let Some(state) = &mut self.input {
+            TextPrompt::from("price quantity").draw(frame, input_area, state);
+        }
+        frame.render_widget(
+            Paragraph::new("e: edit  enter: amend  esc: cancel/close"),
+            hint_area,
+        );
+    }
+}
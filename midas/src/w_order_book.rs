@@ -46,17 +46,26 @@ impl OrderBookWindow {
             Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
                 .areas(area);
         let bids_block = Block::default().borders(Borders::RIGHT).title("SELL");
+        let token = &self.book.token;
         self.bids_window.render(bids_area, buf, bids_block, |item| {
             Line::styled(
-                format!("{} {}", item.price, item.quantity),
-                common::LOSS_COLOR,
+                format!(
+                    "{} {}",
+                    common::format_price(token, item.price),
+                    common::format_quantity(token, item.quantity)
+                ),
+                common::theme().loss,
             )
         });
         let asks_block = Block::default().borders(Borders::LEFT).title("BUY");
         self.asks_window.render(asks_area, buf, asks_block, |item| {
             Line::styled(
-                format!("{} {}", item.price, item.quantity),
-                common::PROFIT_COLOR,
+                format!(
+                    "{} {}",
+                    common::format_price(token, item.price),
+                    common::format_quantity(token, item.quantity)
+                ),
+                common::theme().profit,
             )
         });
     }
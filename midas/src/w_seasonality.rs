@@ -0,0 +1,84 @@
+use crate::common;
+use dionysus::analytics::{Seasonality, SeasonalityBucket};
+use dionysus::finance::Token;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::Style,
+    widgets::{Cell, Row, Table, Widget},
+};
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+#[derive(Default)]
+pub struct SeasonalityWindow {
+    token: Token,
+    seasonality: Seasonality,
+}
+
+impl SeasonalityWindow {
+    pub fn update(&mut self, token: &Token, seasonality: Seasonality) {
+        self.token = token.clone();
+        self.seasonality = seasonality;
+    }
+
+    fn max_abs(&self) -> f64 {
+        let mut m: f64 = 0.0;
+        for row in &self.seasonality.by_weekday_hour {
+            for bucket in row {
+                if let Some(v) = bucket.mean() {
+                    m = m.max(v.abs());
+                }
+            }
+        }
+        m
+    }
+
+    fn cell(bucket: &SeasonalityBucket, max: f64) -> Cell<'static> {
+        match bucket.mean() {
+            Some(v) => Cell::from(format!("{: .3}", v))
+                .style(Style::default().bg(common::heat_color(v, max))),
+            None => Cell::from("    -"),
+        }
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let title = format!("SEASONALITY {} (mean bar return %, UTC)", self.token.name());
+        let max = self.max_abs();
+        let header = Row::new(
+            std::iter::once("Hour")
+                .chain(WEEKDAYS.iter().copied())
+                .chain(std::iter::once("All"))
+                .map(Cell::from)
+                .collect::<Vec<Cell>>(),
+        )
+        .style(Style::default().fg(common::NORMAL_FG));
+        let mut rows: Vec<Row> = Vec::new();
+        for hour in 0..24 {
+            let mut cells = vec![Cell::from(format!("{:02}h", hour))];
+            for weekday in 0..7 {
+                cells.push(SeasonalityWindow::cell(
+                    &self.seasonality.by_weekday_hour[weekday][hour],
+                    max,
+                ));
+            }
+            cells.push(SeasonalityWindow::cell(
+                &self.seasonality.by_hour[hour],
+                max,
+            ));
+            rows.push(Row::new(cells));
+        }
+        let mut cells = vec![Cell::from("All")];
+        for weekday in 0..7 {
+            cells.push(SeasonalityWindow::cell(
+                &self.seasonality.by_weekday[weekday],
+                max,
+            ));
+        }
+        rows.push(Row::new(cells));
+        Table::new(rows, vec![Constraint::Length(7); 9])
+            .header(header)
+            .block(common::block(title.as_str()))
+            .render(area, buf);
+    }
+}
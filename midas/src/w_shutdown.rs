@@ -0,0 +1,57 @@
+use crate::common;
+use crate::common::ListWindow;
+use crate::midas::ExitSummary;
+use dionysus::finance::Order;
+use ratatui::text::Line;
+
+/// Shown on quit while orders or positions remain: 'c' cancels the open
+/// orders and quits, 'y' quits leaving them, Esc stays.
+#[derive(Default)]
+pub struct ShutdownWindow {
+    list_window: ListWindow<String>,
+    /// Orders cancelled by 'c'.
+    pub orders: Vec<Order>,
+}
+
+impl ShutdownWindow {
+    pub fn update(&mut self, summary: &ExitSummary) {
+        let mut lines = vec![format!("Open orders: {}", summary.orders.len())];
+        for order in &summary.orders {
+            lines.push(format!(
+                "  #{} {:?} {} {} @ {} ({})",
+                order.id.unwrap_or_default(),
+                order.side,
+                common::format_quantity(&order.token, order.quantity),
+                order.token.name(),
+                common::format_price(&order.token, order.price),
+                common::format_value(order.quantity * order.price)
+            ));
+        }
+        lines.push(format!("Positions: {}", summary.positions.len()));
+        for position in &summary.positions {
+            lines.push(format!(
+                "  {} {} @ {}",
+                common::format_quantity(&position.token, position.quantity),
+                position.token.name(),
+                common::format_price(&position.token, position.price)
+            ));
+        }
+        lines.push(format!(
+            "Estimated exposure: {}",
+            common::format_value(summary.exposure)
+        ));
+        lines.push(String::new());
+        lines.push(String::from(
+            "c: cancel open orders and quit   y: quit leaving them   Esc: stay",
+        ));
+        self.list_window.items = lines;
+        self.orders = summary.orders.clone();
+    }
+
+    pub fn render(&mut self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
+        let block = common::block("QUIT? (remains on the exchange)");
+        self.list_window.render(area, buf, block, |line| {
+            Line::styled(format!(" {}", line), common::NORMAL_FG)
+        });
+    }
+}
@@ -0,0 +1,84 @@
+use crate::common;
+use dionysus::counselor::Signal;
+use dionysus::finance::Token;
+use dionysus::signal_log::SignalRecord;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::Style,
+    widgets::{Cell, Row, Table, Widget},
+};
+
+/// Number of signals listed.
+const SIGNALS_SHOWN: usize = 50;
+
+#[derive(Default)]
+pub struct SignalsWindow {
+    token: Token,
+    source: String,
+    records: Vec<SignalRecord>,
+}
+
+impl SignalsWindow {
+    /// `records` must be sorted from the most recent.
+    pub fn update(&mut self, token: &Token, source: &str, records: Vec<&SignalRecord>) {
+        self.token = token.clone();
+        self.source = source.to_string();
+        self.records = records.into_iter().take(SIGNALS_SHOWN).cloned().collect();
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let title = format!("SIGNALS {} ({})", self.token.name(), self.source);
+        let header = Row::new(vec![
+            "date",
+            "signal",
+            "price",
+            "stop",
+            "take profit",
+            "order",
+            "indicators",
+        ]);
+        let rows: Vec<Row> = self
+            .records
+            .iter()
+            .map(|record| {
+                let color = match record.signal {
+                    Signal::Buy => common::theme().profit,
+                    Signal::Sell => common::theme().loss,
+                    Signal::None => common::NORMAL_FG,
+                };
+                let indicators: Vec<String> = record
+                    .indicators
+                    .iter()
+                    .map(|(name, value)| format!("{}={:.4}", name, value))
+                    .collect();
+                Row::new(vec![
+                    Cell::from(record.date.pretty_string()),
+                    Cell::from(format!("{:?}", record.signal)).style(Style::default().fg(color)),
+                    Cell::from(format!("{:.6}", record.price)),
+                    Cell::from(format!("{:.6}", record.stop_loss)),
+                    Cell::from(format!("{:.6}", record.take_profit)),
+                    Cell::from(if record.orders > 0 {
+                        format!("yes ({})", record.orders)
+                    } else {
+                        String::from("no")
+                    }),
+                    Cell::from(indicators.join(" ")),
+                ])
+            })
+            .collect();
+        let widths = [
+            Constraint::Length(17),
+            Constraint::Length(6),
+            Constraint::Length(14),
+            Constraint::Length(14),
+            Constraint::Length(14),
+            Constraint::Length(8),
+            Constraint::Fill(1),
+        ];
+        Table::new(rows, widths)
+            .header(header.style(Style::default().fg(common::NORMAL_FG)))
+            .block(common::block(title.as_str()))
+            .render(area, buf);
+    }
+}
@@ -0,0 +1,57 @@
+use crate::common;
+use crate::common::ListWindow;
+use dionysus::analytics::RiskMetrics;
+use dionysus::stats::TradeStats;
+use ratatui::text::Line;
+
+#[derive(Default)]
+pub struct StatsWindow {
+    list_window: ListWindow<String>,
+}
+
+impl StatsWindow {
+    /// `risk` holds the Sharpe, alpha and beta of each forward test since its
+    /// activation.
+    pub fn update(&mut self, stats: &TradeStats, risk: &[(String, RiskMetrics)]) {
+        let win_rate = match stats.win_rate() {
+            Some(rate) => format!("{:.1}%", rate),
+            None => String::from("-"),
+        };
+        self.list_window.items = vec![
+            format!("Orders:         {}", stats.orders),
+            format!("Round trips:    {}", stats.round_trips),
+            format!("Win rate:       {}", win_rate),
+            format!("Realized P&L:   {:+.4}", stats.realized),
+            format!("Unrealized P&L: {:+.4}", stats.unrealized),
+            format!("Fees:           {:.4}", stats.fees),
+            format!("Net:            {:+.4}", stats.net()),
+            format!("Largest win:    {:+.4}", stats.largest_win),
+            format!("Largest loss:   {:+.4}", stats.largest_loss),
+        ];
+        if !risk.is_empty() {
+            self.list_window
+                .items
+                .push(String::from("Since activation, sharpe / alpha % / beta:"));
+        }
+        let or_dash = |value: Option<f64>| match value {
+            Some(value) => format!("{:.2}", value),
+            None => String::from("-"),
+        };
+        for (name, metrics) in risk {
+            self.list_window.items.push(format!(
+                "  {:12} {} / {} / {}",
+                name,
+                or_dash(metrics.sharpe),
+                or_dash(metrics.alpha),
+                or_dash(metrics.beta)
+            ));
+        }
+    }
+
+    pub fn render(&mut self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
+        let block = common::block("TODAY (forward tests, UTC day)");
+        self.list_window.render(area, buf, block, |line| {
+            Line::styled(format!(" {}", line), common::NORMAL_FG)
+        });
+    }
+}
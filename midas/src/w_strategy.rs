@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 
 use dionysus::backtest::Backtest;
+use dionysus::finance::Side;
+use dionysus::time::Date;
 use ratatui::style::Color;
 use ratatui::text::Line;
 
@@ -30,7 +32,7 @@ impl StrategyWindow {
             let mut color = common::NORMAL_FG;
             if let Some(s) = selected {
                 if s == i {
-                    color = common::PROFIT_COLOR;
+                    color = common::theme().profit;
                 }
             }
             {
@@ -54,10 +56,44 @@ impl StrategyWindow {
 
                 self.list.items.push(StrategyItem { name: txt, color });
             }
+            if let Some(forward_test) = midas.forward_tests.get(&i) {
+                let now = Date::now();
+                let price = match midas.ticks.get(&chrysus.token) {
+                    Some(tick) => tick.price,
+                    None => forward_test.last_price,
+                };
+                let profit = forward_test.profit(price);
+                let mut txt = format!(
+                    "fwd {}m [{:.2}%] B{} S{}",
+                    forward_test.elapsed_seconds(&now) / 60,
+                    profit,
+                    forward_test.count(Side::Buy),
+                    forward_test.count(Side::Sell)
+                );
+                if let Some(backtest) = backtests.get(&i) {
+                    txt.push_str(
+                        format!(
+                            " exp [{:.2}%] drift [{:+.2}%]",
+                            forward_test.expected_profit(backtest, price, &now),
+                            forward_test.drift(backtest, price, &now)
+                        )
+                        .as_str(),
+                    );
+                }
+                let color = if profit >= 0.0 {
+                    common::theme().profit
+                } else {
+                    common::theme().loss
+                };
+                self.list.items.push(StrategyItem { name: txt, color });
+            }
 
-            for i in &chrysus.strategy.counselors {
+            for (index, i) in chrysus.strategy.counselors.iter().enumerate() {
                 self.list.items.push(StrategyItem {
-                    name: i.name(),
+                    name: match chrysus.strategy.is_enabled(index) {
+                        true => i.name(),
+                        false => format!("{} (off)", i.name()),
+                    },
                     color,
                 });
             }
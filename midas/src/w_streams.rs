@@ -0,0 +1,65 @@
+use crate::common;
+use crate::common::ListWindow;
+use dionysus::stream_stats::StreamStats;
+use dionysus::time::Date;
+use ratatui::text::Line;
+
+/// Connection changes listed under each stream.
+const CONNECTIONS_SHOWN: usize = 3;
+
+#[derive(Default)]
+pub struct StreamsWindow {
+    list_window: ListWindow<String>,
+}
+
+impl StreamsWindow {
+    /// `now` in ms.
+    pub fn update(&mut self, streams: &[(String, StreamStats)], now: i64) {
+        let mut lines = vec![format!(
+            "{:<28} {:>7} {:>8} {:>8} {:>6} {:>8} {:>5} {:>7}",
+            "stream", "msg/s", "age", "msgs", "drop", "coalesce", "recon", "restart"
+        )];
+        for (name, stats) in streams {
+            let age = match stats.age(now) {
+                Some(age) => format!("{:.1}s", age as f64 / 1000.0),
+                None => String::from("-"),
+            };
+            lines.push(format!(
+                "{:<28} {:>7.2} {:>8} {:>8} {:>6} {:>8} {:>5} {:>7}{}",
+                name,
+                stats.rate(now),
+                age,
+                stats.messages,
+                stats.dropped,
+                stats.coalesced,
+                stats.reconnects(),
+                stats.restarts,
+                if stats.is_connected() { "" } else { "  DOWN" }
+            ));
+            for connection in stats.connections.iter().rev().take(CONNECTIONS_SHOWN) {
+                lines.push(format!(
+                    "    {} {}{}",
+                    Date::from_timestamp((connection.time / 1000) as u64).pretty_string(),
+                    if connection.connected {
+                        "connected"
+                    } else {
+                        "disconnected"
+                    },
+                    connection
+                        .error
+                        .as_ref()
+                        .map(|e| format!(": {}", e))
+                        .unwrap_or_default()
+                ));
+            }
+        }
+        self.list_window.items = lines;
+    }
+
+    pub fn render(&mut self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
+        let block = common::block("STREAMS");
+        self.list_window.render(area, buf, block, |line| {
+            Line::styled(format!(" {}", line), common::NORMAL_FG)
+        });
+    }
+}
@@ -5,6 +5,7 @@ use ratatui::{
     style::{palette::tailwind, Color},
     widgets::{Tabs, Widget},
 };
+use std::collections::HashMap;
 use std::iter::Iterator;
 
 struct TabItem {
@@ -17,6 +18,9 @@ struct TabItem {
 pub struct SymbolTabs {
     selected_tab: usize,
     tabs: Vec<TabItem>,
+    sparklines: HashMap<Token, String>,
+    /// Bars missing from the cached history, by midas index.
+    staleness: HashMap<usize, i64>,
 }
 
 impl TabItem {
@@ -121,6 +125,24 @@ impl SymbolTabs {
         }
     }
 
+    pub fn set_sparklines(&mut self, sparklines: &HashMap<Token, String>) {
+        self.sparklines = sparklines.clone();
+    }
+
+    pub fn set_staleness(&mut self, staleness: HashMap<usize, i64>) {
+        self.staleness = staleness;
+    }
+
+    /// Most bars any view of the tab is behind.
+    fn bars_behind(&self, tab: &TabItem) -> i64 {
+        tab.midas_indices
+            .iter()
+            .filter_map(|index| self.staleness.get(index))
+            .cloned()
+            .max()
+            .unwrap_or(0)
+    }
+
     pub fn draw(&self, area: Rect, buf: &mut Buffer) {
         if self.tabs.is_empty() {
             return;
@@ -128,7 +150,16 @@ impl SymbolTabs {
         let tab_titles: Vec<String> = self
             .tabs
             .iter()
-            .map(|x| format!("{:?}", x.token.name()))
+            .map(|x| {
+                let mut title = format!("{:?}", x.token.name());
+                if let Some(sparkline) = self.sparklines.get(&x.token) {
+                    title.push_str(format!(" {}", sparkline).as_str());
+                }
+                match self.bars_behind(x) {
+                    0 => title,
+                    behind => format!("{} !{}", title, behind),
+                }
+            })
             .collect();
         let highlight_style = (Color::default(), tailwind::BLUE.c700);
         let selected_tab_index = self.selected_tab as usize;
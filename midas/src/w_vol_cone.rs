@@ -0,0 +1,146 @@
+use crate::common;
+use dionysus::analytics::{ConeLevel, VolatilityEstimator};
+use dionysus::finance::Token;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style},
+    symbols,
+    widgets::{Axis, Cell, Chart, Dataset, GraphType, Paragraph, Row, Table, Widget},
+};
+
+const RANGE_COLOR: Color = Color::DarkGray;
+const QUARTILE_COLOR: Color = Color::Gray;
+const MEDIAN_COLOR: Color = Color::Cyan;
+const CURRENT_COLOR: Color = Color::Yellow;
+
+/// Annualized volatility of the current pair over several lookbacks, against
+/// the percentiles of its history. Volatility above the 75th percentile is
+/// rich, below the 25th cheap.
+#[derive(Default)]
+pub struct VolConeWindow {
+    token: Token,
+    estimator: VolatilityEstimator,
+    cone: Vec<ConeLevel>,
+}
+
+impl VolConeWindow {
+    pub fn update(&mut self, token: &Token, estimator: VolatilityEstimator, cone: Vec<ConeLevel>) {
+        self.token = token.clone();
+        self.estimator = estimator;
+        self.cone = cone;
+    }
+
+    pub fn estimator(&self) -> VolatilityEstimator {
+        self.estimator
+    }
+
+    pub fn set_estimator(&mut self, estimator: VolatilityEstimator) {
+        self.estimator = estimator;
+    }
+
+    fn render_cone(&self, area: Rect, buf: &mut Buffer) {
+        let line = |value: fn(&ConeLevel) -> f64| -> Vec<(f64, f64)> {
+            self.cone
+                .iter()
+                .map(|level| (level.window as f64, value(level) * 100.0))
+                .collect()
+        };
+        let lines = [
+            ("max", RANGE_COLOR, line(|l| l.max)),
+            ("p75", QUARTILE_COLOR, line(|l| l.p75)),
+            ("median", MEDIAN_COLOR, line(|l| l.median)),
+            ("p25", QUARTILE_COLOR, line(|l| l.p25)),
+            ("min", RANGE_COLOR, line(|l| l.min)),
+            ("current", CURRENT_COLOR, line(|l| l.current)),
+        ];
+        let x_min = self.cone[0].window as f64;
+        let x_max = self.cone[self.cone.len() - 1].window as f64;
+        let y_min = self
+            .cone
+            .iter()
+            .map(|l| l.min * 100.0)
+            .fold(f64::MAX, f64::min);
+        let y_max = self
+            .cone
+            .iter()
+            .map(|l| l.max * 100.0)
+            .fold(f64::MIN, f64::max);
+        let datasets = lines
+            .iter()
+            .map(|(name, color, points)| {
+                Dataset::default()
+                    .name(*name)
+                    .marker(symbols::Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(Style::default().fg(*color))
+                    .data(points)
+            })
+            .collect();
+        Chart::new(datasets)
+            .x_axis(
+                Axis::default()
+                    .bounds([x_min, x_max])
+                    .labels([format!("{} bars", x_min), format!("{} bars", x_max)]),
+            )
+            .y_axis(
+                Axis::default()
+                    .bounds([y_min, y_max])
+                    .labels([format!("{:.1}%", y_min), format!("{:.1}%", y_max)]),
+            )
+            .render(area, buf);
+    }
+
+    fn render_levels(&self, area: Rect, buf: &mut Buffer) {
+        let theme = common::theme();
+        let header = Row::new(vec![
+            "bars", "current", "min", "p25", "median", "p75", "max", "rank",
+        ])
+        .style(Style::default().fg(common::NORMAL_FG));
+        let rows: Vec<Row> = self
+            .cone
+            .iter()
+            .map(|level| {
+                let style = match level.current {
+                    v if v > level.p75 => Style::default().fg(theme.loss),
+                    v if v < level.p25 => Style::default().fg(theme.profit),
+                    _ => Style::default(),
+                };
+                let pct = |v: f64| Cell::from(common::format_percent(v * 100.0));
+                Row::new(vec![
+                    Cell::from(level.window.to_string()),
+                    pct(level.current).style(style),
+                    pct(level.min),
+                    pct(level.p25),
+                    pct(level.median),
+                    pct(level.p75),
+                    pct(level.max),
+                    Cell::from(format!("{:.0}", level.rank)).style(style),
+                ])
+            })
+            .collect();
+        Table::new(rows, vec![Constraint::Fill(1); 8])
+            .header(header)
+            .render(area, buf);
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let title = format!(
+            "VOL CONE {} ({}, annualized)",
+            self.token.name(),
+            self.estimator.name()
+        );
+        let block = common::block(title.as_str());
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if self.cone.is_empty() {
+            Paragraph::new("not enough history").render(inner, buf);
+            return;
+        }
+        let rows = self.cone.len() as u16 + 1;
+        let [cone_area, levels_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(rows)]).areas(inner);
+        self.render_cone(cone_area, buf);
+        self.render_levels(levels_area, buf);
+    }
+}
@@ -4,11 +4,19 @@ use crate::common;
 use crate::common::ListWindow;
 use dionysus::finance::{MarketTick, Token};
 use dionysus::utils::compute_change_pct;
-use ratatui::text::Line;
+use ratatui::{
+    layout::{Constraint, Layout},
+    text::Line,
+    widgets::{Sparkline, Widget},
+};
+
+/// Currencies the wallet can be valued in, cycled with 'c'.
+const DENOMINATIONS: [&str; 4] = ["USDT", "BTC", "EUR", "BRL"];
 
 struct BalanceItem {
     asset: String,
     free: f64,
+    /// In USDT
     value: f64,
     change: f64,
 }
@@ -18,6 +26,33 @@ pub struct WalletWindow {
     list_window: ListWindow<BalanceItem>,
     total: f64,
     total_change: f64,
+    denomination: usize,
+    /// USDT per unit of the denomination, None if there is no quote.
+    rate: Option<f64>,
+    /// Account value (USDT) of each snapshot.
+    history: Vec<f64>,
+}
+
+/// Amount of `currency` with the decimals of its USDT pair quantities.
+fn format_amount(currency: &str, amount: f64) -> String {
+    match currency {
+        "USDT" => common::format_value(amount),
+        _ => common::format_quantity(&Token::pair(currency, "USDT"), amount),
+    }
+}
+
+/// Price of `currency` in USDT, from its USDT pair or the inverse one.
+fn usdt_rate(ticks: &HashMap<Token, MarketTick>, currency: &str) -> Option<f64> {
+    if currency == "USDT" {
+        return Some(1.0);
+    }
+    if let Some(tick) = ticks.get(&Token::pair(currency, "USDT")) {
+        return Some(tick.price);
+    }
+    ticks
+        .get(&Token::pair("USDT", currency))
+        .filter(|tick| tick.price > 0.0)
+        .map(|tick| 1.0 / tick.price)
 }
 
 impl WalletWindow {
@@ -57,23 +92,66 @@ impl WalletWindow {
         }
         self.total = current;
         self.total_change = compute_change_pct(initial, current);
+        self.rate = usdt_rate(ticks, DENOMINATIONS[self.denomination]);
+    }
+
+    pub fn set_history(&mut self, totals: Vec<f64>) {
+        self.history = totals;
+    }
+
+    /// Switches to the next denomination, values update with the next ticks.
+    pub fn next_denomination(&mut self) {
+        self.denomination = (self.denomination + 1) % DENOMINATIONS.len();
+        self.rate = None;
     }
 
     pub fn render(&mut self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer)
     where
         Self: Sized,
     {
-        let title = format!(
-            "WALLET (USDT)  {:.2}({:.2}%)",
-            self.total, self.total_change
-        );
+        let denomination = DENOMINATIONS[self.denomination];
+        let title = match self.rate {
+            Some(rate) => format!(
+                "WALLET ({})  {}({})",
+                denomination,
+                format_amount(denomination, self.total / rate),
+                common::format_percent(self.total_change)
+            ),
+            None => format!("WALLET ({} n/a)", denomination),
+        };
         let block = common::block(title.as_str());
+        let rate = self.rate;
+        let area = if self.history.len() > 1 {
+            let [list_area, history_area] =
+                Layout::vertical([Constraint::Fill(1), Constraint::Length(3)]).areas(area);
+            // sparklines start at zero, show the variation above the lowest value
+            let min = self.history.iter().cloned().fold(f64::MAX, f64::min);
+            let data: Vec<u64> = self
+                .history
+                .iter()
+                .map(|v| ((v - min) * 100.0) as u64 + 1)
+                .collect();
+            Sparkline::default()
+                .data(&data)
+                .style(common::NORMAL_FG)
+                .render(history_area, buf);
+            list_area
+        } else {
+            area
+        };
 
         self.list_window.render(area, buf, block, |value| {
+            let converted = match rate {
+                Some(rate) => format_amount(denomination, value.value / rate),
+                None => String::from("-"),
+            };
             Line::styled(
                 format!(
-                    " {:8} {: >12} {:.4} ({:.2}%)",
-                    value.asset, value.free, value.value, value.change
+                    " {:8} {: >12} {} ({})",
+                    value.asset,
+                    format_amount(&value.asset, value.free),
+                    converted,
+                    common::format_percent(value.change)
                 ),
                 common::NORMAL_FG,
             )
@@ -1,13 +1,27 @@
+use crate::w_backtest_diff::BacktestDiffWindow;
+use crate::w_backtest_rank::BacktestRankWindow;
+use crate::w_breakdown::BreakdownWindow;
+use crate::w_correlation::CorrelationWindow;
+use crate::w_dca::DcaWindow;
 use crate::w_graph::GraphView;
 use crate::w_help::HelpWindow;
+use crate::w_indicator::IndicatorWindow;
 use crate::w_info::InfoWindow;
 use crate::w_interactible::{Interactible, InteractionEvent};
+use crate::w_ladder::LadderWindow;
 use crate::w_log::LogWindow;
 use crate::w_market::MarketWindow;
+use crate::w_news::NewsWindow;
 use crate::w_order::OrderWindow;
 use crate::w_order_book::OrderBookWindow;
+use crate::w_seasonality::SeasonalityWindow;
+use crate::w_shutdown::ShutdownWindow;
+use crate::w_signals::SignalsWindow;
+use crate::w_stats::StatsWindow;
 use crate::w_strategy::StrategyWindow;
+use crate::w_streams::StreamsWindow;
 use crate::w_symbol_tabs::SymbolTabs;
+use crate::w_vol_cone::VolConeWindow;
 use crate::w_wallet::WalletWindow;
 use crate::{w_command::CommandInput, w_oracle::OracleWindow};
 use crossterm::event::KeyEvent;
@@ -72,7 +86,7 @@ impl WindowContent for OrderBookWindow {
 
 impl WindowContent for OrderWindow {
     fn render(&mut self, frame: &mut Frame, area: Rect, _focus: bool) {
-        self.render(area, frame.buffer_mut());
+        self.render(frame, area);
     }
 }
 
@@ -108,6 +122,90 @@ impl WindowContent for GraphView {
     }
 }
 
+impl WindowContent for CorrelationWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect, _focus: bool) {
+        self.render(area, frame.buffer_mut());
+    }
+}
+
+impl WindowContent for SeasonalityWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect, _focus: bool) {
+        self.render(area, frame.buffer_mut());
+    }
+}
+
+impl WindowContent for SignalsWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect, _focus: bool) {
+        self.render(area, frame.buffer_mut());
+    }
+}
+
+impl WindowContent for IndicatorWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect, _focus: bool) {
+        self.render(frame, area);
+    }
+}
+
+impl WindowContent for BacktestDiffWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect, _focus: bool) {
+        self.render(area, frame.buffer_mut());
+    }
+}
+
+impl WindowContent for NewsWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect, _focus: bool) {
+        self.render(area, frame.buffer_mut());
+    }
+}
+
+impl WindowContent for StatsWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect, _focus: bool) {
+        self.render(area, frame.buffer_mut());
+    }
+}
+
+impl WindowContent for BreakdownWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect, _focus: bool) {
+        self.render(area, frame.buffer_mut());
+    }
+}
+
+impl WindowContent for ShutdownWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect, _focus: bool) {
+        self.render(area, frame.buffer_mut());
+    }
+}
+
+impl WindowContent for StreamsWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect, _focus: bool) {
+        self.render(area, frame.buffer_mut());
+    }
+}
+
+impl WindowContent for DcaWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect, _focus: bool) {
+        self.render(area, frame.buffer_mut());
+    }
+}
+
+impl WindowContent for LadderWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect, _focus: bool) {
+        self.render(area, frame.buffer_mut());
+    }
+}
+
+impl WindowContent for VolConeWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect, _focus: bool) {
+        self.render(area, frame.buffer_mut());
+    }
+}
+
+impl WindowContent for BacktestRankWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect, _focus: bool) {
+        self.render(area, frame.buffer_mut());
+    }
+}
+
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub enum WindowType {
     LOG = 0,
@@ -121,8 +219,22 @@ pub enum WindowType {
     HELP = 8,
     INFO = 9,
     ORDER = 10,
+    CORRELATION = 11,
+    SEASONALITY = 12,
+    SIGNALS = 13,
+    INDICATOR = 14,
+    BACKTEST_DIFF = 15,
+    NEWS = 16,
+    STATS = 17,
+    BREAKDOWN = 18,
+    SHUTDOWN = 19,
+    STREAMS = 20,
+    DCA = 21,
+    LADDER = 22,
+    VOL_CONE = 23,
+    BACKTEST_RANK = 24,
     // CHART must be the last, window_manager concatenates charts after unique windows
-    CHART = 11,
+    CHART = 25,
 }
 
 pub struct MidasWindow {
@@ -202,6 +314,20 @@ impl MidasWindow {
             WindowType::ORDERBOOK => create_window!(window_type, OrderBookWindow),
             WindowType::TABS => create_window!(window_type, SymbolTabs),
             WindowType::CHART => create_window!(window_type, GraphView),
+            WindowType::BACKTEST_RANK => create_window!(window_type, BacktestRankWindow),
+            WindowType::VOL_CONE => create_window!(window_type, VolConeWindow),
+            WindowType::LADDER => create_window!(window_type, LadderWindow),
+            WindowType::DCA => create_window!(window_type, DcaWindow),
+            WindowType::STREAMS => create_window!(window_type, StreamsWindow),
+            WindowType::SHUTDOWN => create_window!(window_type, ShutdownWindow),
+            WindowType::BREAKDOWN => create_window!(window_type, BreakdownWindow),
+            WindowType::STATS => create_window!(window_type, StatsWindow),
+            WindowType::NEWS => create_window!(window_type, NewsWindow),
+            WindowType::BACKTEST_DIFF => create_window!(window_type, BacktestDiffWindow),
+            WindowType::INDICATOR => create_window!(window_type, IndicatorWindow),
+            WindowType::SIGNALS => create_window!(window_type, SignalsWindow),
+            WindowType::SEASONALITY => create_window!(window_type, SeasonalityWindow),
+            WindowType::CORRELATION => create_window!(window_type, CorrelationWindow),
             WindowType::HELP => create_window!(window_type, HelpWindow),
             WindowType::INFO => create_window!(window_type, InfoWindow),
             WindowType::ORDER => create_window!(window_type, OrderWindow),
@@ -225,6 +351,20 @@ impl MidasWindow {
                 WindowType::ORDERBOOK => render!(self, frame, OrderBookWindow, focus, area),
                 WindowType::ORACLE => render!(self, frame, OracleWindow, focus, area),
                 WindowType::CHART => render!(self, frame, GraphView, focus, area),
+                WindowType::BACKTEST_RANK => render!(self, frame, BacktestRankWindow, focus, area),
+                WindowType::VOL_CONE => render!(self, frame, VolConeWindow, focus, area),
+                WindowType::LADDER => render!(self, frame, LadderWindow, focus, area),
+                WindowType::DCA => render!(self, frame, DcaWindow, focus, area),
+                WindowType::STREAMS => render!(self, frame, StreamsWindow, focus, area),
+                WindowType::SHUTDOWN => render!(self, frame, ShutdownWindow, focus, area),
+                WindowType::BREAKDOWN => render!(self, frame, BreakdownWindow, focus, area),
+                WindowType::STATS => render!(self, frame, StatsWindow, focus, area),
+                WindowType::NEWS => render!(self, frame, NewsWindow, focus, area),
+                WindowType::BACKTEST_DIFF => render!(self, frame, BacktestDiffWindow, focus, area),
+                WindowType::INDICATOR => render!(self, frame, IndicatorWindow, focus, area),
+                WindowType::SIGNALS => render!(self, frame, SignalsWindow, focus, area),
+                WindowType::SEASONALITY => render!(self, frame, SeasonalityWindow, focus, area),
+                WindowType::CORRELATION => render!(self, frame, CorrelationWindow, focus, area),
                 WindowType::HELP => render!(self, frame, HelpWindow, focus, area),
                 WindowType::INFO => render!(self, frame, InfoWindow, focus, area),
                 WindowType::ORDER => render!(self, frame, OrderWindow, focus, area),
@@ -256,6 +396,44 @@ impl MidasWindow {
                     return handle_key_event!(self, key_event, OracleWindow, global)
                 }
                 WindowType::CHART => return handle_key_event!(self, key_event, GraphView, global),
+                WindowType::BACKTEST_RANK => {
+                    return handle_key_event!(self, key_event, BacktestRankWindow, global)
+                }
+                WindowType::VOL_CONE => {
+                    return handle_key_event!(self, key_event, VolConeWindow, global)
+                }
+                WindowType::LADDER => {
+                    return handle_key_event!(self, key_event, LadderWindow, global)
+                }
+                WindowType::DCA => return handle_key_event!(self, key_event, DcaWindow, global),
+                WindowType::STREAMS => {
+                    return handle_key_event!(self, key_event, StreamsWindow, global)
+                }
+                WindowType::SHUTDOWN => {
+                    return handle_key_event!(self, key_event, ShutdownWindow, global)
+                }
+                WindowType::BREAKDOWN => {
+                    return handle_key_event!(self, key_event, BreakdownWindow, global)
+                }
+                WindowType::STATS => {
+                    return handle_key_event!(self, key_event, StatsWindow, global)
+                }
+                WindowType::NEWS => return handle_key_event!(self, key_event, NewsWindow, global),
+                WindowType::BACKTEST_DIFF => {
+                    return handle_key_event!(self, key_event, BacktestDiffWindow, global)
+                }
+                WindowType::INDICATOR => {
+                    return handle_key_event!(self, key_event, IndicatorWindow, global)
+                }
+                WindowType::SIGNALS => {
+                    return handle_key_event!(self, key_event, SignalsWindow, global)
+                }
+                WindowType::SEASONALITY => {
+                    return handle_key_event!(self, key_event, SeasonalityWindow, global)
+                }
+                WindowType::CORRELATION => {
+                    return handle_key_event!(self, key_event, CorrelationWindow, global)
+                }
                 WindowType::HELP => return handle_key_event!(self, key_event, HelpWindow, global),
                 WindowType::INFO => return handle_key_event!(self, key_event, InfoWindow, global),
                 WindowType::ORDER => {
@@ -1,22 +1,62 @@
 use crate::{
     common::popup_area,
+    w_backtest_diff::BacktestDiffWindow,
+    w_backtest_rank::BacktestRankWindow,
+    w_breakdown::BreakdownWindow,
+    w_command::CommandInput,
+    w_correlation::CorrelationWindow,
+    w_dca::DcaWindow,
     w_graph::GraphView,
+    w_indicator::IndicatorWindow,
     w_info::InfoWindow,
     w_interactible::InteractionEvent,
+    w_ladder::LadderWindow,
     w_market::MarketWindow,
+    w_news::NewsWindow,
     w_oracle::OracleWindow,
     w_order::OrderWindow,
     w_order_book::OrderBookWindow,
+    w_seasonality::SeasonalityWindow,
+    w_shutdown::ShutdownWindow,
+    w_signals::SignalsWindow,
+    w_stats::StatsWindow,
     w_strategy::StrategyWindow,
+    w_streams::StreamsWindow,
     w_symbol_tabs::SymbolTabs,
+    w_vol_cone::VolConeWindow,
     w_wallet::WalletWindow,
     w_window::{MidasWindow, WindowType},
 };
 use crossterm::event::{KeyCode, KeyEvent};
 use dionysus::strategy::Strategy;
+use dionysus::ERROR;
 use ratatui::{layout::Rect, widgets::Clear, Frame};
+use slog::slog_error;
 use std::collections::HashMap;
 
+/// Key, window it opens and whether it opens as a float window.
+const DEFAULT_KEYS: [(char, WindowType, bool); 19] = [
+    ('a', WindowType::INPUT, false),
+    ('l', WindowType::LOG, true),
+    ('o', WindowType::ORACLE, true),
+    ('?', WindowType::HELP, true),
+    ('/', WindowType::INFO, true),
+    ('O', WindowType::ORDER, true),
+    ('C', WindowType::CORRELATION, true),
+    ('S', WindowType::SEASONALITY, true),
+    ('G', WindowType::SIGNALS, true),
+    ('I', WindowType::INDICATOR, true),
+    ('D', WindowType::BACKTEST_DIFF, true),
+    ('N', WindowType::NEWS, true),
+    ('T', WindowType::STATS, true),
+    ('B', WindowType::BREAKDOWN, true),
+    ('E', WindowType::STREAMS, true),
+    ('R', WindowType::DCA, true),
+    ('L', WindowType::LADDER, true),
+    ('V', WindowType::VOL_CONE, true),
+    ('K', WindowType::BACKTEST_RANK, true),
+];
+
 pub struct WindowManager {
     pub windows: Vec<MidasWindow>,
     pub selected_window: Option<usize>,
@@ -34,18 +74,7 @@ impl WindowManager {
             key_codes: HashMap::new(),
             float_window: None,
         };
-        wm.key_codes
-            .insert(KeyCode::Char('a'), (WindowType::INPUT, false));
-        wm.key_codes
-            .insert(KeyCode::Char('l'), (WindowType::LOG, true));
-        wm.key_codes
-            .insert(KeyCode::Char('o'), (WindowType::ORACLE, true));
-        wm.key_codes
-            .insert(KeyCode::Char('?'), (WindowType::HELP, true));
-        wm.key_codes
-            .insert(KeyCode::Char('/'), (WindowType::INFO, true));
-        wm.key_codes
-            .insert(KeyCode::Char('O'), (WindowType::ORDER, true));
+        wm.reset_keys();
 
         wm.open(WindowType::LOG);
         wm.open(WindowType::STRATEGY);
@@ -58,9 +87,74 @@ impl WindowManager {
         wm.open(WindowType::HELP);
         wm.open(WindowType::INFO);
         wm.open(WindowType::ORDER);
+        wm.open(WindowType::CORRELATION);
+        wm.open(WindowType::SEASONALITY);
+        wm.open(WindowType::SIGNALS);
+        wm.open(WindowType::INDICATOR);
+        wm.open(WindowType::BACKTEST_DIFF);
+        wm.open(WindowType::NEWS);
+        wm.open(WindowType::STATS);
+        wm.open(WindowType::BREAKDOWN);
+        wm.open(WindowType::SHUTDOWN);
+        wm.open(WindowType::STREAMS);
+        wm.open(WindowType::DCA);
+        wm.open(WindowType::LADDER);
+        wm.open(WindowType::VOL_CONE);
+        wm.open(WindowType::BACKTEST_RANK);
         wm
     }
 
+    /// Restores the default key bindings.
+    pub fn reset_keys(&mut self) {
+        self.key_codes = DEFAULT_KEYS
+            .iter()
+            .map(|(key, window_type, is_float)| {
+                (KeyCode::Char(*key), (window_type.clone(), *is_float))
+            })
+            .collect();
+    }
+
+    /// Binds `bindings` over the default keys. The rebound windows drop their
+    /// default key first, so they can swap keys. A key that still opens
+    /// another window is kept by it, the binding is skipped and the window
+    /// gets its default key back when free.
+    pub fn set_keys(&mut self, bindings: &[(WindowType, char)]) {
+        self.reset_keys();
+        let mut defaults: Vec<Option<KeyCode>> = Vec::new();
+        for (window_type, _) in bindings {
+            let old = self
+                .key_codes
+                .iter()
+                .find(|(_, (wt, _))| wt == window_type)
+                .map(|(code, _)| *code);
+            if let Some(code) = old {
+                self.key_codes.remove(&code);
+            }
+            defaults.push(old);
+        }
+        for ((window_type, key), default) in bindings.iter().zip(defaults) {
+            let is_float = DEFAULT_KEYS
+                .iter()
+                .find(|(_, wt, _)| wt == window_type)
+                .map(|(_, _, is_float)| *is_float)
+                .unwrap_or(true);
+            let mut code = KeyCode::Char(*key);
+            if let Some((bound, _)) = self.key_codes.get(&code) {
+                ERROR!(
+                    "key {:?} of {:?} already opens {:?}",
+                    key,
+                    window_type,
+                    bound
+                );
+                match default {
+                    Some(default) if !self.key_codes.contains_key(&default) => code = default,
+                    _ => continue,
+                }
+            }
+            self.key_codes.insert(code, (window_type.clone(), is_float));
+        }
+    }
+
     pub fn open(&mut self, window_type: WindowType) {
         self.windows.push(MidasWindow::new(window_type));
     }
@@ -95,6 +189,13 @@ impl WindowManager {
             .unwrap()
     }
 
+    pub fn command(&mut self) -> &mut CommandInput {
+        self.windows[WindowType::INPUT as usize]
+            .content
+            .downcast_mut::<CommandInput>()
+            .unwrap()
+    }
+
     pub fn wallet(&mut self) -> &mut WalletWindow {
         self.windows[WindowType::WALLET as usize]
             .content
@@ -137,6 +238,104 @@ impl WindowManager {
             .unwrap()
     }
 
+    pub fn correlation(&mut self) -> &mut CorrelationWindow {
+        self.windows[WindowType::CORRELATION as usize]
+            .content
+            .downcast_mut::<CorrelationWindow>()
+            .unwrap()
+    }
+
+    pub fn seasonality(&mut self) -> &mut SeasonalityWindow {
+        self.windows[WindowType::SEASONALITY as usize]
+            .content
+            .downcast_mut::<SeasonalityWindow>()
+            .unwrap()
+    }
+
+    pub fn signals(&mut self) -> &mut SignalsWindow {
+        self.windows[WindowType::SIGNALS as usize]
+            .content
+            .downcast_mut::<SignalsWindow>()
+            .unwrap()
+    }
+
+    pub fn indicator(&mut self) -> &mut IndicatorWindow {
+        self.windows[WindowType::INDICATOR as usize]
+            .content
+            .downcast_mut::<IndicatorWindow>()
+            .unwrap()
+    }
+
+    pub fn backtest_diff(&mut self) -> &mut BacktestDiffWindow {
+        self.windows[WindowType::BACKTEST_DIFF as usize]
+            .content
+            .downcast_mut::<BacktestDiffWindow>()
+            .unwrap()
+    }
+
+    pub fn news(&mut self) -> &mut NewsWindow {
+        self.windows[WindowType::NEWS as usize]
+            .content
+            .downcast_mut::<NewsWindow>()
+            .unwrap()
+    }
+
+    pub fn stats(&mut self) -> &mut StatsWindow {
+        self.windows[WindowType::STATS as usize]
+            .content
+            .downcast_mut::<StatsWindow>()
+            .unwrap()
+    }
+
+    pub fn breakdown(&mut self) -> &mut BreakdownWindow {
+        self.windows[WindowType::BREAKDOWN as usize]
+            .content
+            .downcast_mut::<BreakdownWindow>()
+            .unwrap()
+    }
+
+    pub fn shutdown(&mut self) -> &mut ShutdownWindow {
+        self.windows[WindowType::SHUTDOWN as usize]
+            .content
+            .downcast_mut::<ShutdownWindow>()
+            .unwrap()
+    }
+
+    pub fn streams(&mut self) -> &mut StreamsWindow {
+        self.windows[WindowType::STREAMS as usize]
+            .content
+            .downcast_mut::<StreamsWindow>()
+            .unwrap()
+    }
+
+    pub fn dca(&mut self) -> &mut DcaWindow {
+        self.windows[WindowType::DCA as usize]
+            .content
+            .downcast_mut::<DcaWindow>()
+            .unwrap()
+    }
+
+    pub fn ladder(&mut self) -> &mut LadderWindow {
+        self.windows[WindowType::LADDER as usize]
+            .content
+            .downcast_mut::<LadderWindow>()
+            .unwrap()
+    }
+
+    pub fn vol_cone(&mut self) -> &mut VolConeWindow {
+        self.windows[WindowType::VOL_CONE as usize]
+            .content
+            .downcast_mut::<VolConeWindow>()
+            .unwrap()
+    }
+
+    pub fn backtest_rank(&mut self) -> &mut BacktestRankWindow {
+        self.windows[WindowType::BACKTEST_RANK as usize]
+            .content
+            .downcast_mut::<BacktestRankWindow>()
+            .unwrap()
+    }
+
     pub fn open_oracle(&mut self, strategy: &Strategy) {
         self.windows[WindowType::ORACLE as usize]
             .content
@@ -169,6 +368,12 @@ impl WindowManager {
         }
     }
 
+    /// Opens a window floating, as its key would.
+    pub fn open_float(&mut self, window_type: WindowType) {
+        self.float_window = Some(self.window_index(window_type.clone()));
+        self.select_window(window_type);
+    }
+
     pub fn window_index(&self, window_type: WindowType) -> usize {
         match window_type {
             WindowType::CHART => 0,